@@ -0,0 +1,39 @@
+//! Run a ROM without any frontend and print what ends up on the screen.
+//!
+//! ```sh
+//! cargo run -p chip8_core --example headless -- ROMs/test/2-ibm-logo.ch8
+//! ```
+
+use chip8_core::{Chip8, StopReason, PIXELS_PER_COLUMN, PIXELS_PER_ROW};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/../ROMs/test/2-ibm-logo.ch8").to_string()
+    });
+    let rom = std::fs::read(&path)?;
+
+    let mut chip8 = Chip8::new();
+    chip8.load_rom(&rom);
+    chip8.apply_rom_platform();
+
+    // Run until the program settles into its final loop, or give up after a few seconds
+    for _ in 0..300 {
+        if chip8.run_until_event(chip8.clock().instructions_per_frame as usize)? == StopReason::Halt
+        {
+            break;
+        }
+        chip8.tick_timers();
+    }
+
+    for y in 0..PIXELS_PER_COLUMN {
+        let row = (0..PIXELS_PER_ROW)
+            .map(|x| if chip8.pixel(x, y) { '█' } else { ' ' })
+            .collect::<String>();
+        println!("{}", row.trim_end());
+    }
+
+    let lit = chip8.screen.iter().filter(|&&on| on).count();
+    assert!(lit > 0, "the ROM didn't draw anything");
+    println!("{} pixels lit", lit);
+    Ok(())
+}
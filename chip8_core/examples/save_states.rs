@@ -0,0 +1,31 @@
+//! Snapshot a running machine and rewind it to the snapshot later.
+
+use chip8_core::Chip8;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let rom = std::fs::read(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../ROMs/test/1-chip8-logo.ch8"
+    ))?;
+
+    let mut chip8 = Chip8::new();
+    chip8.load_rom(&rom);
+    for _ in 0..5 {
+        chip8.run_frame()?;
+    }
+    let snapshot = chip8.save_state();
+    let screen_at_snapshot = chip8.screen;
+
+    for _ in 0..60 {
+        chip8.run_frame()?;
+    }
+    chip8.load_state(&snapshot)?;
+    assert_eq!(chip8.screen, screen_at_snapshot);
+
+    // States only load into a machine running the same ROM
+    let mut other = Chip8::new();
+    other.load_rom(&[0x12, 0x00]);
+    let error = other.load_state(&snapshot).unwrap_err();
+    println!("restored the snapshot; another ROM refuses it: {}", error);
+    Ok(())
+}
@@ -0,0 +1,46 @@
+//! Drive a ROM with synthetic key presses and check how it reacts.
+//!
+//! The program below waits for a key, then draws the hex digit of that key.
+
+use chip8_core::{Chip8, Instruction};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let program = [
+        Instruction::WaitForKey { x: 0x0 },
+        Instruction::FontCharacter { x: 0x0 },
+        Instruction::Set { x: 0x1, nn: 0 },
+        Instruction::Draw {
+            x: 0x1,
+            y: 0x1,
+            n: 5,
+        },
+        Instruction::Jump { nnn: 0x208 },
+    ];
+    let rom = program
+        .iter()
+        .flat_map(|instruction| instruction.opcode().to_be_bytes())
+        .collect::<Vec<_>>();
+
+    let mut chip8 = Chip8::new();
+    chip8.load_rom(&rom);
+
+    // Nothing happens until a key is pressed
+    for _ in 0..10 {
+        chip8.run_frame()?;
+    }
+    assert!(chip8.screen.iter().all(|&on| !on));
+
+    chip8.press_key(0x7)?;
+    chip8.run_frame()?;
+    chip8.release_key(0x7)?;
+    chip8.run_frame()?;
+
+    // The font's 7 is a bar on top of a diagonal stroke
+    let top_row = (0..4).map(|x| chip8.pixel(x, 0)).collect::<Vec<_>>();
+    assert_eq!(top_row, [true, true, true, true]);
+    assert!(chip8.pixel(1, 4) && !chip8.pixel(0, 4));
+    assert_eq!(chip8.cpu_state().v[0], 0x7);
+
+    println!("the ROM drew the key that was pressed");
+    Ok(())
+}
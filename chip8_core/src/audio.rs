@@ -0,0 +1,176 @@
+//! The buzzer's tone as samples, for frontends to hand to whatever audio output they have.
+//! A square wave switched on and off mid-cycle clicks, so the tone fades in and out over a
+//! short [`Envelope`] instead.
+
+use std::time::Duration;
+
+/// How long the tone takes to reach full volume when a beep starts, and to fall silent
+/// when it stops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Envelope {
+    pub attack: Duration,
+    pub release: Duration,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            attack: Duration::from_millis(2),
+            release: Duration::from_millis(5),
+        }
+    }
+}
+
+/// Square wave generator clocked by the samples asked of it, so its pitch depends on the
+/// sample rate only and not on how fast frames are emulated
+#[derive(Debug, Clone)]
+pub struct ToneSynth {
+    sample_rate: f32,
+    volume: f32,
+    phase: f32,
+    phase_increment: f32,
+    /// The sound timer is running
+    on: bool,
+    /// Shortest a beep lasts, in samples
+    min_samples: u32,
+    /// Samples left before the tone may stop, counted from the start of each beep
+    latched_samples: u32,
+    /// Where the envelope is, from 0 for silence to 1 for full volume
+    level: f32,
+    attack_step: f32,
+    release_step: f32,
+}
+
+impl ToneSynth {
+    /// A silent synth for `sample_rate` samples a second, beeping at `frequency` Hz with
+    /// samples between `-volume` and `volume`
+    pub fn new(sample_rate: u32, frequency: f32, volume: f32) -> Self {
+        let mut synth = Self {
+            sample_rate: sample_rate as f32,
+            volume,
+            phase: 0.0,
+            phase_increment: 0.0,
+            on: false,
+            min_samples: 0,
+            latched_samples: 0,
+            level: 0.0,
+            attack_step: 1.0,
+            release_step: 1.0,
+        };
+        synth.set_frequency(frequency);
+        synth.set_envelope(Envelope::default());
+        synth
+    }
+
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.phase_increment = frequency / self.sample_rate;
+    }
+
+    /// Fade beeps in and out over `envelope`; zero durations switch the tone instantly
+    pub fn set_envelope(&mut self, envelope: Envelope) {
+        self.attack_step = self.level_step(envelope.attack);
+        self.release_step = self.level_step(envelope.release);
+    }
+
+    /// Make beeps last at least `duration`, so a sound timer of a tick or two is still heard
+    pub fn set_min_beep(&mut self, duration: Duration) {
+        self.min_samples = (duration.as_secs_f64() * self.sample_rate as f64) as u32;
+    }
+
+    /// Start or stop the tone. Starting it lasts at least the minimum beep, so call this
+    /// with `true` as soon as the machine starts a beep.
+    pub fn set_on(&mut self, on: bool) {
+        if on && !self.on {
+            self.latched_samples = self.min_samples;
+        }
+        self.on = on;
+    }
+
+    /// Whether the next samples aren't silence, including a beep fading out
+    pub fn is_sounding(&self) -> bool {
+        self.on || self.latched_samples > 0 || self.level > 0.0
+    }
+
+    /// Write the next samples of the tone to `out`
+    pub fn fill(&mut self, out: &mut [f32]) {
+        out.fill(0.0);
+        self.mix(out);
+    }
+
+    /// Add the next samples of the tone to those in `out`, to play it over other sounds
+    pub fn mix(&mut self, out: &mut [f32]) {
+        for sample in out.iter_mut() {
+            self.level = if self.on || self.latched_samples > 0 {
+                (self.level + self.attack_step).min(1.0)
+            } else {
+                (self.level - self.release_step).max(0.0)
+            };
+            self.latched_samples = self.latched_samples.saturating_sub(1);
+            if self.level == 0.0 {
+                continue;
+            }
+            let square = if self.phase < 0.5 { 1.0 } else { -1.0 };
+            *sample += square * self.volume * self.level;
+            self.phase = (self.phase + self.phase_increment) % 1.0;
+        }
+    }
+
+    /// How much the level changes each sample to go all the way in `duration`
+    fn level_step(&self, duration: Duration) -> f32 {
+        let samples = duration.as_secs_f32() * self.sample_rate;
+        if samples < 1.0 {
+            1.0
+        } else {
+            1.0 / samples
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beeps_fade_in_and_out() {
+        // 8 samples of attack and 16 of release, with the phase flipping every 4 samples
+        let mut synth = ToneSynth::new(1024, 128.0, 0.5);
+        synth.set_envelope(Envelope {
+            attack: Duration::from_secs(1) / 128,
+            release: Duration::from_secs(1) / 64,
+        });
+        let mut samples = [1.0; 32];
+        synth.fill(&mut samples);
+        assert_eq!(samples, [0.0; 32]);
+        assert!(!synth.is_sounding());
+
+        synth.set_on(true);
+        synth.fill(&mut samples);
+        let levels: Vec<_> = samples.iter().map(|sample| sample.abs()).collect();
+        assert_eq!(levels[0], 0.0625);
+        assert!(levels[..8].windows(2).all(|pair| pair[1] > pair[0]));
+        assert!(levels[7..].iter().all(|&level| level == 0.5));
+        assert_eq!(
+            &samples[..8],
+            [1.0, 2.0, 3.0, 4.0, -5.0, -6.0, -7.0, -8.0].map(|n| n / 16.0)
+        );
+
+        synth.set_on(false);
+        synth.fill(&mut samples);
+        assert!(samples[..15]
+            .iter()
+            .all(|sample| sample.abs() > 0.0 && sample.abs() < 0.5));
+        assert_eq!(samples[15..], [0.0; 17]);
+        assert!(!synth.is_sounding());
+
+        synth.set_envelope(Envelope {
+            attack: Duration::ZERO,
+            release: Duration::ZERO,
+        });
+        synth.set_min_beep(Duration::from_secs(1) / 256);
+        synth.set_on(true);
+        synth.set_on(false);
+        synth.fill(&mut samples);
+        assert!(samples[..4].iter().all(|sample| sample.abs() == 0.5));
+        assert_eq!(samples[4..], [0.0; 28]);
+    }
+}
@@ -0,0 +1,130 @@
+//! Bank switching, an extension for homebrew larger than memory. The upper half of memory
+//! is a window onto one of several banks; FXB0 maps bank VX into it. Bank 0 is what the
+//! window holds when the ROM is loaded, and the ROM's bytes past the end of memory fill
+//! banks 1 and up. What the program writes to a bank stays there while others are mapped.
+
+use crate::{MAX_ROM_SIZE, RAM_SIZE};
+
+/// First address of the window banks are mapped into
+pub const BANK_WINDOW_START: usize = 0x800;
+pub const BANK_SIZE: usize = RAM_SIZE - BANK_WINDOW_START;
+/// Banks FXB0 can reach with its 4-bit register value
+pub const MAX_BANKS: usize = 16;
+/// Largest ROM that fits in memory and the banks after it
+pub const MAX_BANKED_ROM_SIZE: usize = MAX_ROM_SIZE + (MAX_BANKS - 1) * BANK_SIZE;
+
+/// The banks of a machine with bank switching enabled, see [`crate::Chip8::enable_banking`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Banks {
+    /// Contents of every bank. The mapped bank's lives in memory, so its entry here is
+    /// only brought up to date when another bank is mapped.
+    banks: Vec<[u8; BANK_SIZE]>,
+    selected: usize,
+}
+
+impl Banks {
+    pub(crate) fn new() -> Self {
+        Self {
+            banks: vec![[0; BANK_SIZE]],
+            selected: 0,
+        }
+    }
+
+    /// Banks holding `data` after bank 0, `selected` of them mapped, as save states
+    /// store them
+    pub(crate) fn restore(selected: usize, data: &[u8]) -> Self {
+        Self {
+            banks: data
+                .chunks_exact(BANK_SIZE)
+                .map(|bank| bank.try_into().unwrap())
+                .collect(),
+            selected,
+        }
+    }
+
+    /// Put the part of a ROM that doesn't fit in memory in banks 1 and up, with bank 0
+    /// mapped
+    pub(crate) fn load(&mut self, overflow: &[u8]) {
+        self.banks.truncate(1);
+        for chunk in overflow.chunks(BANK_SIZE) {
+            let mut bank = [0; BANK_SIZE];
+            bank[..chunk.len()].copy_from_slice(chunk);
+            self.banks.push(bank);
+        }
+        self.selected = 0;
+    }
+
+    pub fn count(&self) -> usize {
+        self.banks.len()
+    }
+
+    /// The bank mapped into the window
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Map `bank` into the window of `memory`, keeping the one mapped before as the
+    /// program left it. Returns `false`, changing nothing, when there is no such bank.
+    pub(crate) fn select(&mut self, bank: usize, memory: &mut [u8; RAM_SIZE]) -> bool {
+        if bank >= self.banks.len() {
+            return false;
+        }
+        let window = &mut memory[BANK_WINDOW_START..];
+        self.banks[self.selected].copy_from_slice(window);
+        window.copy_from_slice(&self.banks[bank]);
+        self.selected = bank;
+        true
+    }
+
+    /// Contents of `bank`, taking the mapped one from `memory`
+    pub fn bank<'a>(&'a self, bank: usize, memory: &'a [u8]) -> Option<&'a [u8]> {
+        match bank == self.selected {
+            true => Some(&memory[BANK_WINDOW_START..]),
+            false => self.banks.get(bank).map(|bank| &bank[..]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Chip8, Chip8Error};
+
+    #[test]
+    fn fxb0_maps_banks_past_the_end_of_memory() {
+        let mut rom = vec![0; MAX_ROM_SIZE + BANK_SIZE];
+        rom[..8].copy_from_slice(&[
+            0x60, 0x01, // V0 = 1
+            0xF0, 0xB0, // map bank 1
+            0x60, 0x05, // V0 = 5
+            0xF0, 0xB0, // map bank 5, which doesn't exist
+        ]);
+        rom[MAX_ROM_SIZE..MAX_ROM_SIZE + 2].copy_from_slice(&[0xAB, 0xCD]);
+        let mut chip8 = Chip8::new();
+        chip8.enable_banking();
+        chip8.load_rom(&rom);
+        assert_eq!(chip8.banks().map(Banks::count), Some(2));
+
+        chip8.memory[BANK_WINDOW_START] = 0x42;
+        chip8.tick().unwrap();
+        chip8.tick().unwrap();
+        assert_eq!(chip8.memory()[BANK_WINDOW_START..][..2], [0xAB, 0xCD]);
+        let banks = chip8.banks().unwrap();
+        assert_eq!(banks.selected(), 1);
+        assert_eq!(banks.bank(0, chip8.memory()).unwrap()[0], 0x42);
+
+        chip8.tick().unwrap();
+        assert_eq!(
+            chip8.tick(),
+            Err(Chip8Error::NoSuchBank { pc: 0x206, bank: 5 })
+        );
+
+        let mut plain = Chip8::new();
+        plain.load_rom(&rom[..8]);
+        plain.tick().unwrap();
+        assert!(matches!(
+            plain.tick(),
+            Err(Chip8Error::UnknownOpcode { opcode: 0xF0B0, .. })
+        ));
+    }
+}
@@ -0,0 +1,427 @@
+//! Reading images drawn for CHIP-8, as PNG or BMP files, into sprites
+
+use std::fmt;
+
+use crate::patch::crc32;
+use crate::rom_loader::{inflate, RomLoadError};
+
+const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n";
+const BMP_MAGIC: &[u8] = b"BM";
+/// Widest and tallest image accepted, far beyond anything that fits on the screen
+const MAX_SIDE: usize = 4096;
+/// Width of a sprite in pixels: DXYN draws one byte per row
+pub const SPRITE_WIDTH: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageError {
+    /// The data is neither a PNG nor a BMP file
+    UnknownFormat,
+    /// The image uses a feature of its format this reader doesn't handle
+    Unsupported(&'static str),
+    /// The file is damaged or truncated; the message says where
+    Corrupted(&'static str),
+    /// The image is wider or taller than [`MAX_SIDE`] pixels, or empty
+    BadSize { width: usize, height: usize },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::UnknownFormat => write!(f, "the image is neither a PNG nor a BMP file"),
+            ImageError::Unsupported(feature) => write!(f, "{} are not supported", feature),
+            ImageError::Corrupted(reason) => write!(f, "the image is corrupted: {}", reason),
+            ImageError::BadSize { width, height } => write!(
+                f,
+                "the image is {}x{} pixels; it must be 1 to {} pixels on each side",
+                width, height, MAX_SIDE
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// A picture with each pixel on or off
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    /// Row by row from the top left, `true` for lit pixels
+    pub pixels: Vec<bool>,
+}
+
+impl Bitmap {
+    /// Read a PNG or BMP file. Opaque pixels lighter than mid-grey are lit, so white on
+    /// black art comes out as drawn; [`Bitmap::invert`] suits black on white art.
+    pub fn decode(data: &[u8]) -> Result<Self, ImageError> {
+        let (width, height, luma) = if data.starts_with(PNG_MAGIC) {
+            decode_png(data)?
+        } else if data.starts_with(BMP_MAGIC) {
+            decode_bmp(data)?
+        } else {
+            return Err(ImageError::UnknownFormat);
+        };
+        Ok(Self {
+            width,
+            height,
+            pixels: luma.into_iter().map(|luma| luma >= 0x80).collect(),
+        })
+    }
+
+    pub fn invert(&mut self) {
+        for pixel in &mut self.pixels {
+            *pixel = !*pixel;
+        }
+    }
+
+    /// The image as sprites 8 pixels wide, one per column of it from the left, each a
+    /// byte per row with the leftmost pixel in the high bit. A last column narrower than
+    /// 8 pixels is padded with unlit ones.
+    pub fn sprite_columns(&self) -> Vec<Vec<u8>> {
+        (0..self.width.div_ceil(SPRITE_WIDTH))
+            .map(|column| {
+                (0..self.height)
+                    .map(|y| {
+                        (0..SPRITE_WIDTH).fold(0, |byte, bit| {
+                            let x = column * SPRITE_WIDTH + bit;
+                            let lit = x < self.width && self.pixels[y * self.width + x];
+                            byte | (lit as u8) << (7 - bit)
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Width, height and brightness of every pixel, with transparent ones black
+type Luma = (usize, usize, Vec<u8>);
+
+fn check_size(width: usize, height: usize) -> Result<(), ImageError> {
+    if width == 0 || height == 0 || width > MAX_SIDE || height > MAX_SIDE {
+        return Err(ImageError::BadSize { width, height });
+    }
+    Ok(())
+}
+
+fn luma(r: u8, g: u8, b: u8) -> u8 {
+    ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8
+}
+
+fn read_u16_le(data: &[u8], offset: usize) -> Result<u16, ImageError> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or(ImageError::Corrupted("the header is truncated"))
+}
+
+fn read_u32_le(data: &[u8], offset: usize) -> Result<u32, ImageError> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ImageError::Corrupted("the header is truncated"))
+}
+
+fn read_u32_be(data: &[u8], offset: usize) -> Result<u32, ImageError> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+        .ok_or(ImageError::Corrupted("a chunk is truncated"))
+}
+
+/// The `index`th value of `depth` bits in `row`, packed from the high bits down; of 16
+/// bit values only the high byte is kept
+fn packed(row: &[u8], index: usize, depth: u16) -> u8 {
+    match depth {
+        8 => row[index],
+        16 => row[index * 2],
+        _ => {
+            let bit = index * depth as usize;
+            (row[bit / 8] >> (8 - depth as usize - bit % 8)) & ((1 << depth) - 1) as u8
+        }
+    }
+}
+
+/// A value of `depth` bits, as read by [`packed`], scaled to 8 bits
+fn scaled(value: u8, depth: u16) -> u8 {
+    match depth {
+        8 | 16 => value,
+        _ => value * (255 / ((1 << depth) - 1)) as u8,
+    }
+}
+
+fn decode_png(data: &[u8]) -> Result<Luma, ImageError> {
+    const GRAY: u8 = 0;
+    const RGB: u8 = 2;
+    const PALETTE: u8 = 3;
+    const GRAY_ALPHA: u8 = 4;
+    const RGBA: u8 = 6;
+
+    let mut offset = PNG_MAGIC.len();
+    let mut header = None;
+    let mut palette: Vec<[u8; 4]> = Vec::new();
+    let mut compressed = Vec::new();
+    loop {
+        let len = read_u32_be(data, offset)? as usize;
+        let chunk = data
+            .get(offset + 4..offset + 8 + len)
+            .ok_or(ImageError::Corrupted("a chunk is truncated"))?;
+        if read_u32_be(data, offset + 8 + len)? != crc32(chunk) {
+            return Err(ImageError::Corrupted("a chunk's checksum doesn't match"));
+        }
+        let (kind, body) = chunk.split_at(4);
+        offset += 12 + len;
+        match kind {
+            b"IHDR" if body.len() == 13 => header = Some(body.to_vec()),
+            b"PLTE" => {
+                palette = body
+                    .chunks_exact(3)
+                    .map(|rgb| [rgb[0], rgb[1], rgb[2], 0xFF])
+                    .collect()
+            }
+            b"tRNS" => {
+                for (entry, &alpha) in palette.iter_mut().zip(body) {
+                    entry[3] = alpha;
+                }
+            }
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or(ImageError::Corrupted("the IHDR chunk is missing"))?;
+    let width = u32::from_be_bytes(header[0..4].try_into().unwrap()) as usize;
+    let height = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+    check_size(width, height)?;
+    let (depth, color_type) = (header[8] as u16, header[9]);
+    let channels = match (color_type, depth) {
+        (GRAY, 1 | 2 | 4 | 8 | 16) | (PALETTE, 1 | 2 | 4 | 8) => 1,
+        (GRAY_ALPHA, 8 | 16) => 2,
+        (RGB, 8 | 16) => 3,
+        (RGBA, 8 | 16) => 4,
+        _ => {
+            return Err(ImageError::Corrupted(
+                "the bit depth or colour type is invalid",
+            ))
+        }
+    };
+    if header[12] != 0 {
+        return Err(ImageError::Unsupported("interlaced PNGs"));
+    }
+
+    // A zlib stream: a two byte header, DEFLATE data and a checksum
+    if compressed.len() < 2 || compressed[0] & 0x0F != 8 || compressed[1] & 0x20 != 0 {
+        return Err(ImageError::Corrupted("the image data isn't a zlib stream"));
+    }
+    let bits_per_pixel = channels * depth as usize;
+    let stride = (width * bits_per_pixel).div_ceil(8);
+    let (filtered, _) =
+        inflate(&compressed[2..], (stride + 1) * height).map_err(|err| match err {
+            RomLoadError::Corrupted(reason) => ImageError::Corrupted(reason),
+            _ => ImageError::Corrupted("the image data is longer than its size says"),
+        })?;
+    let rows = unfilter(&filtered, stride, height, bits_per_pixel.div_ceil(8))?;
+
+    let mut luma_values = Vec::with_capacity(width * height);
+    for row in rows.chunks_exact(stride) {
+        for x in 0..width {
+            let value = |channel| scaled(packed(row, x * channels + channel, depth), depth);
+            let (brightness, alpha) = match color_type {
+                GRAY => (value(0), 0xFF),
+                GRAY_ALPHA => (value(0), value(1)),
+                RGB => (luma(value(0), value(1), value(2)), 0xFF),
+                RGBA => (luma(value(0), value(1), value(2)), value(3)),
+                _ => {
+                    let [r, g, b, a] = *palette
+                        .get(packed(row, x, depth) as usize)
+                        .ok_or(ImageError::Corrupted("a pixel is outside the palette"))?;
+                    (luma(r, g, b), a)
+                }
+            };
+            luma_values.push(if alpha >= 0x80 { brightness } else { 0 });
+        }
+    }
+    Ok((width, height, luma_values))
+}
+
+/// Undo the filter each PNG row starts with, `bpp` being the bytes per pixel rounded up
+fn unfilter(data: &[u8], stride: usize, height: usize, bpp: usize) -> Result<Vec<u8>, ImageError> {
+    if data.len() != (stride + 1) * height {
+        return Err(ImageError::Corrupted("the image data is truncated"));
+    }
+    let mut out = vec![0; stride * height];
+    for (y, line) in data.chunks_exact(stride + 1).enumerate() {
+        let (filter, line) = (line[0], &line[1..]);
+        let (done, rest) = out.split_at_mut(y * stride);
+        // empty on the first row, which has nothing above it
+        let above = &done[done.len().saturating_sub(stride)..];
+        let current = &mut rest[..stride];
+        for x in 0..stride {
+            let up = above.get(x).copied().unwrap_or(0);
+            let (left, up_left) = match x.checked_sub(bpp) {
+                Some(x) => (current[x], above.get(x).copied().unwrap_or(0)),
+                None => (0, 0),
+            };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(ImageError::Corrupted("a row has an unknown filter")),
+            };
+            current[x] = line[x].wrapping_add(predicted);
+        }
+    }
+    Ok(out)
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distance = |value: u8| (estimate - value as i16).abs();
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
+
+fn decode_bmp(data: &[u8]) -> Result<Luma, ImageError> {
+    let pixels_offset = read_u32_le(data, 10)? as usize;
+    let info_size = read_u32_le(data, 14)? as usize;
+    if info_size < 40 {
+        return Err(ImageError::Unsupported("OS/2 bitmaps"));
+    }
+    let width = read_u32_le(data, 18)? as i32;
+    let height = read_u32_le(data, 22)? as i32;
+    let depth = read_u16_le(data, 28)?;
+    if read_u32_le(data, 30)? != 0 {
+        return Err(ImageError::Unsupported("compressed bitmaps"));
+    }
+    // Rows are stored bottom up, unless the height is negative
+    let top_down = height < 0;
+    let (width, height) = (
+        width.unsigned_abs() as usize,
+        height.unsigned_abs() as usize,
+    );
+    check_size(width, height)?;
+
+    let palette = match depth {
+        1 | 4 | 8 => {
+            let colors = match read_u32_le(data, 46)? {
+                0 => 1 << depth,
+                colors => colors as usize,
+            };
+            let start = 14 + info_size;
+            data.get(start..start + colors * 4)
+                .ok_or(ImageError::Corrupted("the palette is truncated"))?
+                .chunks_exact(4)
+                .map(|bgr| luma(bgr[2], bgr[1], bgr[0]))
+                .collect()
+        }
+        24 | 32 => Vec::new(),
+        _ => return Err(ImageError::Unsupported("bitmaps of this bit depth")),
+    };
+
+    let stride = (width * depth as usize).div_ceil(32) * 4;
+    let pixels = data
+        .get(pixels_offset..pixels_offset + stride * height)
+        .ok_or(ImageError::Corrupted("the pixel data is truncated"))?;
+    let mut luma_values = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let stored = if top_down { y } else { height - 1 - y };
+        let row = &pixels[stored * stride..][..stride];
+        for x in 0..width {
+            let value = match depth {
+                24 | 32 => {
+                    let pixel = &row[x * depth as usize / 8..];
+                    luma(pixel[2], pixel[1], pixel[0])
+                }
+                _ => *palette
+                    .get(packed(row, x, depth) as usize)
+                    .ok_or(ImageError::Corrupted("a pixel is outside the palette"))?,
+            };
+            luma_values.push(value);
+        }
+    }
+    Ok((width, height, luma_values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 10x2 image: an 8 pixel bar on the top row, and one pixel at x = 9 below it
+    const ROWS: [[bool; 10]; 2] = [
+        [true, true, true, true, true, true, true, true, false, false],
+        [
+            false, false, false, false, false, false, false, false, false, true,
+        ],
+    ];
+    const SPRITES: [[u8; 2]; 2] = [[0xFF, 0x00], [0x00, 0x40]];
+
+    fn bmp() -> Vec<u8> {
+        let mut bmp = b"BM".to_vec();
+        bmp.extend_from_slice(&[0; 8]);
+        bmp.extend_from_slice(&(14u32 + 40 + 8).to_le_bytes());
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+        bmp.extend_from_slice(&10u32.to_le_bytes());
+        bmp.extend_from_slice(&2u32.to_le_bytes());
+        bmp.extend_from_slice(&[1, 0, 1, 0]);
+        bmp.extend_from_slice(&[0; 24]);
+        bmp.extend_from_slice(&[0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0]);
+        // bottom row first, each padded to 4 bytes
+        bmp.extend_from_slice(&[0b0000_0000, 0b0100_0000, 0, 0]);
+        bmp.extend_from_slice(&[0b1111_1111, 0b0000_0000, 0, 0]);
+        bmp
+    }
+
+    fn png() -> Vec<u8> {
+        let chunk = |png: &mut Vec<u8>, kind: &[u8], body: &[u8]| {
+            let mut chunk = kind.to_vec();
+            chunk.extend_from_slice(body);
+            png.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            png.extend_from_slice(&chunk);
+            png.extend_from_slice(&crc32(&chunk).to_be_bytes());
+        };
+        let mut png = PNG_MAGIC.to_vec();
+        // 10x2, 8-bit greyscale
+        chunk(&mut png, b"IHDR", &[0, 0, 0, 10, 0, 0, 0, 2, 8, 0, 0, 0, 0]);
+        let mut raw = Vec::new();
+        let value = |lit| if lit { 0xFFu8 } else { 0x00 };
+        // the first row unfiltered, the second as its difference from the first
+        raw.push(0);
+        raw.extend(ROWS[0].map(value));
+        raw.push(2);
+        raw.extend((0..10).map(|x| value(ROWS[1][x]).wrapping_sub(value(ROWS[0][x]))));
+        // a zlib stream holding one stored block, without the checksum it isn't read for
+        let mut zlib = vec![0x78, 0x01, 0x01];
+        zlib.extend_from_slice(&(raw.len() as u16).to_le_bytes());
+        zlib.extend_from_slice(&(!(raw.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(&raw);
+        chunk(&mut png, b"IDAT", &zlib);
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    #[test]
+    fn images_become_columns_of_sprites() {
+        for image in [bmp(), png()] {
+            let bitmap = Bitmap::decode(&image).unwrap();
+            assert_eq!((bitmap.width, bitmap.height), (10, 2));
+            assert_eq!(bitmap.pixels, ROWS.concat());
+            assert_eq!(
+                bitmap.sprite_columns(),
+                SPRITES.map(|sprite| sprite.to_vec())
+            );
+        }
+
+        let mut damaged = png();
+        damaged[40] ^= 0xFF;
+        assert!(matches!(
+            Bitmap::decode(&damaged),
+            Err(ImageError::Corrupted(_))
+        ));
+        assert_eq!(Bitmap::decode(b"GIF89a"), Err(ImageError::UnknownFormat));
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use crate::PIXELS_PER_SCREEN;
+
+/// How [`crate::Chip8::blended_frame`] combines the recent frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// A pixel is fully lit if it was lit in any of the frames
+    Or,
+    /// A pixel is as bright as the share of frames it was lit in, like a slow phosphor
+    #[default]
+    Average,
+}
+
+impl FromStr for BlendMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "or" => Ok(BlendMode::Or),
+            "average" => Ok(BlendMode::Average),
+            _ => Err(format!(
+                "unknown blend mode '{}', expected or or average",
+                s
+            )),
+        }
+    }
+}
+
+/// Frame blending hides the flicker of games that erase and redraw their sprites every frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlendConfig {
+    pub mode: BlendMode,
+    /// Frames blended together, including the one being drawn
+    pub frames: usize,
+}
+
+impl Default for BlendConfig {
+    fn default() -> Self {
+        Self {
+            mode: BlendMode::default(),
+            frames: 2,
+        }
+    }
+}
+
+/// The screens of the last few completed frames
+#[derive(Debug, Clone)]
+pub(crate) struct FrameHistory {
+    config: BlendConfig,
+    screens: VecDeque<[bool; PIXELS_PER_SCREEN]>,
+}
+
+impl FrameHistory {
+    pub fn new(config: BlendConfig) -> Self {
+        Self {
+            config,
+            screens: VecDeque::with_capacity(config.frames),
+        }
+    }
+
+    pub fn config(&self) -> BlendConfig {
+        self.config
+    }
+
+    pub fn record(&mut self, screen: &[bool; PIXELS_PER_SCREEN]) {
+        // The screen being drawn takes one of the slots
+        let capacity = self.config.frames.saturating_sub(1);
+        if capacity == 0 {
+            return;
+        }
+        if self.screens.len() == capacity {
+            self.screens.pop_front();
+        }
+        self.screens.push_back(*screen);
+    }
+
+    /// Brightness of each pixel from 0 to 255, blending `screen` with the recorded frames
+    pub fn blend(&self, screen: &[bool; PIXELS_PER_SCREEN]) -> [u8; PIXELS_PER_SCREEN] {
+        let frames = 1 + self.screens.len();
+        let mut blended = [0; PIXELS_PER_SCREEN];
+        for (index, brightness) in blended.iter_mut().enumerate() {
+            let lit =
+                screen[index] as usize + self.screens.iter().filter(|screen| screen[index]).count();
+            *brightness = match self.config.mode {
+                BlendMode::Or if lit > 0 => u8::MAX,
+                BlendMode::Or => 0,
+                BlendMode::Average => (lit * u8::MAX as usize / frames) as u8,
+            };
+        }
+        blended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BlendConfig, BlendMode, Chip8};
+
+    #[test]
+    fn flickering_sprites_are_blended() {
+        // Draws the font's 0 at the top left, then erases it, every frame
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0xA0, 0x50, 0xD0, 0x05, 0xD0, 0x05, 0x12, 0x02]);
+        chip8.set_frame_blending(Some(BlendConfig {
+            mode: BlendMode::Average,
+            frames: 2,
+        }));
+
+        // Stop each frame with the sprite drawn, then with it erased
+        chip8.tick().unwrap();
+        chip8.tick().unwrap();
+        chip8.tick_timers();
+        chip8.tick().unwrap();
+        assert!(!chip8.pixel(0, 0));
+        assert_eq!(chip8.blended_frame()[0], 127);
+
+        chip8.set_frame_blending(Some(BlendConfig {
+            mode: BlendMode::Or,
+            frames: 2,
+        }));
+        chip8.tick().unwrap();
+        chip8.tick().unwrap();
+        chip8.tick_timers();
+        chip8.tick().unwrap();
+        assert_eq!(chip8.blended_frame()[0], 255);
+        assert_eq!(chip8.blended_frame()[4], 0);
+
+        chip8.set_frame_blending(None);
+        assert_eq!(chip8.blended_frame()[0], 0);
+    }
+}
@@ -0,0 +1,208 @@
+//! What this build of the core can do, for frontends and launchers that would otherwise
+//! have to know which crate version added what
+
+use crate::json::Value;
+use crate::{
+    Instruction, Platform, Preset, Quirks, WriteProtection, ZeroHeightSprite, PIXELS_PER_COLUMN,
+    PIXELS_PER_ROW, STATE_VERSION,
+};
+
+/// Features frontends may rely on, each with a version bumped whenever it changes in a way
+/// they'd notice
+pub const FEATURES: &[(&str, u32)] = &[
+    ("save-states", STATE_VERSION as u32),
+    ("step-back", 1),
+    ("battery-ram", 1),
+    ("write-protection", 1),
+    ("frame-budget", 1),
+    ("speed-zones", 1),
+    ("trace-export", 1),
+    ("octo-options", 1),
+    ("patches", 1),
+    ("keypad-ghosting", 1),
+    ("ram-patterns", 1),
+    ("bank-switching", 1),
+];
+
+/// The core's version, platforms with their instructions and quirks, display modes,
+/// presets and [`FEATURES`] as one JSON document
+pub fn capabilities() -> Value {
+    let platforms = Platform::ALL.map(|platform| {
+        Value::Object(vec![
+            ("name".to_string(), platform.name().into()),
+            (
+                "instructions".to_string(),
+                Value::Array(instruction_patterns(platform)),
+            ),
+            (
+                "quirks".to_string(),
+                quirks_json(Quirks::for_platform(platform)),
+            ),
+            (
+                "second_keypad".to_string(),
+                platform.has_second_keypad().into(),
+            ),
+        ])
+    });
+    let presets = Preset::ALL.map(|preset| {
+        Value::Object(vec![
+            ("name".to_string(), preset.name().into()),
+            (
+                "instructions_per_frame".to_string(),
+                preset.instructions_per_frame().into(),
+            ),
+            (
+                "write_protection".to_string(),
+                write_protection_name(preset.write_protection()).into(),
+            ),
+        ])
+    });
+    let display_mode = Value::Object(vec![
+        ("width".to_string(), (PIXELS_PER_ROW as u32).into()),
+        ("height".to_string(), (PIXELS_PER_COLUMN as u32).into()),
+    ]);
+    let zero_height_sprites = [
+        ZeroHeightSprite::Nothing,
+        ZeroHeightSprite::Tall,
+        ZeroHeightSprite::Large,
+    ]
+    .map(|sprite| zero_height_sprite_name(sprite).into());
+    let quirk_options = QUIRK_NAMES
+        .iter()
+        .map(|&name| (name.to_string(), Value::String("bool".to_string())))
+        .chain([(
+            "zero_height_sprite".to_string(),
+            Value::Array(zero_height_sprites.to_vec()),
+        )])
+        .collect();
+    let write_protection = [
+        WriteProtection::Off,
+        WriteProtection::Warn,
+        WriteProtection::Strict,
+    ]
+    .map(|protection| write_protection_name(protection).into());
+    let features = FEATURES
+        .iter()
+        .map(|&(name, version)| (name.to_string(), version.into()))
+        .collect();
+
+    Value::Object(vec![
+        ("version".to_string(), env!("CARGO_PKG_VERSION").into()),
+        ("platforms".to_string(), Value::Array(platforms.to_vec())),
+        ("quirk_options".to_string(), Value::Object(quirk_options)),
+        (
+            "display_modes".to_string(),
+            Value::Array(vec![display_mode]),
+        ),
+        ("presets".to_string(), Value::Array(presets.to_vec())),
+        (
+            "write_protection".to_string(),
+            Value::Array(write_protection.to_vec()),
+        ),
+        ("features".to_string(), Value::Object(features)),
+    ])
+}
+
+/// The boolean quirks, named like the fields of [`Quirks`]
+const QUIRK_NAMES: [&str; 7] = [
+    "shift_in_place",
+    "vf_reset",
+    "increment_index",
+    "jump_with_vx",
+    "wrap_horizontally",
+    "wrap_vertically",
+    "clipped_rows_collide",
+];
+
+fn quirks_json(quirks: Quirks) -> Value {
+    let flags = [
+        quirks.shift_in_place,
+        quirks.vf_reset,
+        quirks.increment_index,
+        quirks.jump_with_vx,
+        quirks.wrap_horizontally,
+        quirks.wrap_vertically,
+        quirks.clipped_rows_collide,
+    ];
+    let zero_height_sprite = zero_height_sprite_name(quirks.zero_height_sprite);
+    Value::Object(
+        QUIRK_NAMES
+            .iter()
+            .zip(flags)
+            .map(|(name, flag)| (name.to_string(), flag.into()))
+            .chain([("zero_height_sprite".to_string(), zero_height_sprite.into())])
+            .collect(),
+    )
+}
+
+/// Patterns like `6XNN` of every instruction `platform` implements, in opcode order
+fn instruction_patterns(platform: Platform) -> Vec<Value> {
+    let mut patterns: Vec<&str> = Vec::new();
+    for opcode in 0..=u16::MAX {
+        let Some(instruction) = Instruction::decode_for(opcode, platform) else {
+            continue;
+        };
+        if platform.supports(&instruction) && !patterns.contains(&instruction.pattern()) {
+            patterns.push(instruction.pattern());
+        }
+    }
+    patterns.into_iter().map(Value::from).collect()
+}
+
+fn zero_height_sprite_name(sprite: ZeroHeightSprite) -> &'static str {
+    match sprite {
+        ZeroHeightSprite::Nothing => "nothing",
+        ZeroHeightSprite::Tall => "tall",
+        ZeroHeightSprite::Large => "large",
+    }
+}
+
+fn write_protection_name(protection: WriteProtection) -> &'static str {
+    match protection {
+        WriteProtection::Off => "off",
+        WriteProtection::Warn => "warn",
+        WriteProtection::Strict => "strict",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities_list_each_platforms_instructions() {
+        let capabilities = crate::json::parse(&capabilities().to_string()).unwrap();
+        let Some(Value::Array(platforms)) = capabilities.get("platforms") else {
+            panic!("no platforms in {:?}", capabilities);
+        };
+        let instructions = |name: &str| {
+            let platform = platforms
+                .iter()
+                .find(|platform| platform.get("name").and_then(Value::as_str) == Some(name))
+                .unwrap();
+            match platform.get("instructions") {
+                Some(Value::Array(patterns)) => patterns
+                    .iter()
+                    .filter_map(|pattern| pattern.as_str().map(str::to_string))
+                    .collect::<Vec<_>>(),
+                _ => Vec::new(),
+            }
+        };
+
+        let vip = instructions("cosmac-vip");
+        assert_eq!(vip.len(), 34);
+        assert!(vip.contains(&"BNNN".to_string()));
+        assert!(!vip.contains(&"00FB".to_string()));
+        assert!(instructions("super-chip").contains(&"00FB".to_string()));
+        let chip8x = instructions("chip-8x");
+        assert!(chip8x.contains(&"BXYN".to_string()));
+        assert!(!chip8x.contains(&"BNNN".to_string()));
+        assert_eq!(
+            capabilities
+                .get("features")
+                .and_then(|features| features.get("save-states"))
+                .and_then(Value::as_f64),
+            Some(STATE_VERSION as f64)
+        );
+    }
+}
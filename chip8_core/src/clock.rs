@@ -0,0 +1,74 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// How fast the machine runs relative to its 60 Hz frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockConfig {
+    /// Instructions executed by each [`crate::Chip8::run_frame`], and the budget of
+    /// [`crate::Chip8::run_frame_within`] before any deficit
+    pub instructions_per_frame: u32,
+    /// Timer decrements per second, independent of the frame rate; 50 on PAL machines
+    pub timer_frequency: u32,
+    /// Whether a frame's timer ticks come before or after its instructions
+    pub timer_order: TimerOrder,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            instructions_per_frame: 10,
+            timer_frequency: 60,
+            timer_order: TimerOrder::default(),
+        }
+    }
+}
+
+/// When a frame counts the timers down. Interpreters differ, and a ROM that sets a timer
+/// and polls it within one frame sees one tick more or less depending on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimerOrder {
+    /// Run the instructions, then tick the timers, as the COSMAC VIP's interrupt did at
+    /// the end of each frame
+    #[default]
+    AfterInstructions,
+    /// Tick the timers, then run the instructions
+    BeforeInstructions,
+}
+
+impl TimerOrder {
+    pub const ALL: [TimerOrder; 2] = [
+        TimerOrder::AfterInstructions,
+        TimerOrder::BeforeInstructions,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TimerOrder::AfterInstructions => "after",
+            TimerOrder::BeforeInstructions => "before",
+        }
+    }
+}
+
+impl fmt::Display for TimerOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for TimerOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TimerOrder::ALL
+            .into_iter()
+            .find(|order| order.name() == s)
+            .ok_or_else(|| {
+                let names = TimerOrder::ALL.map(TimerOrder::name);
+                format!(
+                    "unknown timer order '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
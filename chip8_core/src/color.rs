@@ -0,0 +1,105 @@
+use std::ops::Range;
+
+use crate::{PIXELS_PER_COLUMN, PIXELS_PER_ROW};
+
+/// Width in pixels of the strips the CHIP-8X colour board colours independently
+pub const COLOR_ZONE_WIDTH: usize = 8;
+const ZONES_PER_ROW: usize = PIXELS_PER_ROW / COLOR_ZONE_WIDTH;
+
+/// Background colours of the CHIP-8X colour board, in the order `02A0` cycles through them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackgroundColor {
+    #[default]
+    Blue,
+    Black,
+    Green,
+    Red,
+}
+
+impl BackgroundColor {
+    fn next(self) -> Self {
+        match self {
+            BackgroundColor::Blue => BackgroundColor::Black,
+            BackgroundColor::Black => BackgroundColor::Green,
+            BackgroundColor::Green => BackgroundColor::Red,
+            BackgroundColor::Red => BackgroundColor::Blue,
+        }
+    }
+}
+
+/// Foreground colours of the CHIP-8X colour board, numbered as `BXYN` takes them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ForegroundColor {
+    Black,
+    #[default]
+    Red,
+    Blue,
+    Violet,
+    Green,
+    Yellow,
+    Aqua,
+    White,
+}
+
+impl ForegroundColor {
+    /// The colour numbered by the low three bits of `value`
+    pub fn from_bits(value: u8) -> Self {
+        match value & 0b111 {
+            0 => ForegroundColor::Black,
+            1 => ForegroundColor::Red,
+            2 => ForegroundColor::Blue,
+            3 => ForegroundColor::Violet,
+            4 => ForegroundColor::Green,
+            5 => ForegroundColor::Yellow,
+            6 => ForegroundColor::Aqua,
+            _ => ForegroundColor::White,
+        }
+    }
+}
+
+/// Colours of a CHIP-8X display: one background, and a foreground colour for lit pixels
+/// in each 8x1 zone of the screen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorAttributes {
+    background: BackgroundColor,
+    zones: [ForegroundColor; ZONES_PER_ROW * PIXELS_PER_COLUMN],
+}
+
+impl Default for ColorAttributes {
+    fn default() -> Self {
+        Self {
+            background: BackgroundColor::default(),
+            zones: [ForegroundColor::default(); ZONES_PER_ROW * PIXELS_PER_COLUMN],
+        }
+    }
+}
+
+impl ColorAttributes {
+    pub fn background(&self) -> BackgroundColor {
+        self.background
+    }
+
+    /// Colour of the pixel at column `x` and row `y` when it's lit
+    pub fn foreground_at(&self, x: usize, y: usize) -> ForegroundColor {
+        self.zones[y * ZONES_PER_ROW + x / COLOR_ZONE_WIDTH]
+    }
+
+    pub(crate) fn cycle_background(&mut self) {
+        self.background = self.background.next();
+    }
+
+    /// Colour the zones in `columns` (counted in zones) and `rows` (counted in pixels),
+    /// clipping at the screen edges
+    pub(crate) fn set_foreground(
+        &mut self,
+        columns: Range<usize>,
+        rows: Range<usize>,
+        color: ForegroundColor,
+    ) {
+        for row in rows.start..rows.end.min(PIXELS_PER_COLUMN) {
+            for column in columns.start..columns.end.min(ZONES_PER_ROW) {
+                self.zones[row * ZONES_PER_ROW + column] = color;
+            }
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Control-flow graphs of ROMs for reverse engineering: the code reachable from the entry
+//! point split into basic blocks at jumps, calls, skips and returns, with Graphviz output.
+//!
+//! Computed jumps (`BNNN`) can't be followed, so they get an edge to nowhere and code only
+//! reachable through them is left out.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::disasm::{disassemble_at, DisassembledInstruction, SymbolTable};
+use crate::lint::successors;
+use crate::{Instruction, Platform, ROM_INITIAL_POSITION};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// On to the next instruction, including after a call returns
+    Next,
+    Jump,
+    Call,
+    /// Over the next instruction
+    Skip,
+    /// `BNNN` to its address plus V0, which is only known when it runs
+    Computed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    /// Start of the block the edge leaves
+    pub from: u16,
+    /// `None` for computed jumps
+    pub to: Option<u16>,
+    pub kind: EdgeKind,
+}
+
+/// Instructions that always run one after the other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    pub instructions: Vec<DisassembledInstruction>,
+}
+
+impl BasicBlock {
+    pub fn start(&self) -> u16 {
+        self.instructions[0].addr
+    }
+
+    /// Address of the block's last instruction
+    pub fn last(&self) -> u16 {
+        self.instructions[self.instructions.len() - 1].addr
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    /// By start address
+    blocks: BTreeMap<u16, BasicBlock>,
+    edges: Vec<Edge>,
+}
+
+impl ControlFlowGraph {
+    /// Graph of the code in `rom` reachable from its entry point, as decoded for `platform`.
+    /// Opcodes the platform doesn't run end their block with no way out.
+    pub fn build(rom: &[u8], platform: Platform) -> Self {
+        let origin = ROM_INITIAL_POSITION as u16;
+        let rom_end = origin + rom.len() as u16;
+        let decode = |addr: u16| {
+            disassemble_at(rom, origin, addr).map(|line| DisassembledInstruction {
+                instruction: Instruction::decode_for(line.opcode, platform)
+                    .filter(|instruction| platform.supports(instruction)),
+                ..line
+            })
+        };
+
+        // Find the reachable instructions, and the leaders that start blocks: the entry
+        // point and anywhere execution gets to other than from the instruction before
+        let mut reachable = BTreeMap::new();
+        let mut leaders = BTreeSet::from([origin]);
+        let mut pending = vec![origin];
+        while let Some(addr) = pending.pop() {
+            if addr >= rom_end || reachable.contains_key(&addr) {
+                continue;
+            }
+            let Some(line) = decode(addr) else {
+                continue;
+            };
+            reachable.insert(addr, line);
+            let Some(instruction) = line.instruction else {
+                continue;
+            };
+            let next = successors(addr, instruction, true);
+            if next != [addr + 2] {
+                leaders.extend(&next);
+            }
+            pending.extend(next);
+        }
+
+        let mut graph = Self::default();
+        for &leader in leaders.iter().filter(|addr| reachable.contains_key(addr)) {
+            let mut instructions = vec![reachable[&leader]];
+            loop {
+                let line = instructions[instructions.len() - 1];
+                let Some(instruction) = line.instruction else {
+                    break;
+                };
+                let next = successors(line.addr, instruction, true);
+                let falls_through = next == [line.addr + 2];
+                match reachable.get(&(line.addr + 2)) {
+                    Some(&following) if falls_through && !leaders.contains(&following.addr) => {
+                        instructions.push(following);
+                    }
+                    _ => {
+                        graph.add_edges(leader, line.addr, instruction);
+                        break;
+                    }
+                }
+            }
+            graph.blocks.insert(leader, BasicBlock { instructions });
+        }
+        graph
+    }
+
+    fn add_edges(&mut self, from: u16, addr: u16, instruction: Instruction) {
+        let mut edge = |to, kind| self.edges.push(Edge { from, to, kind });
+        match instruction {
+            Instruction::Jump { nnn } => edge(Some(nnn), EdgeKind::Jump),
+            Instruction::Call { nnn } => {
+                edge(Some(nnn), EdgeKind::Call);
+                edge(Some(addr + 2), EdgeKind::Next);
+            }
+            Instruction::JumpWithOffset { .. } => edge(None, EdgeKind::Computed),
+            Instruction::Return => {}
+            instruction => {
+                for to in successors(addr, instruction, true) {
+                    let kind = match to == addr + 2 {
+                        true => EdgeKind::Next,
+                        false => EdgeKind::Skip,
+                    };
+                    edge(Some(to), kind);
+                }
+            }
+        }
+    }
+
+    pub fn blocks(&self) -> impl Iterator<Item = &BasicBlock> {
+        self.blocks.values()
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// The block starting at `addr`
+    pub fn block_at(&self, addr: u16) -> Option<&BasicBlock> {
+        self.blocks.get(&addr)
+    }
+
+    /// The graph in Graphviz's dot language, with the names from `symbols` if given
+    pub fn to_dot(&self, symbols: Option<&SymbolTable>) -> String {
+        let mut dot = String::from("digraph cfg {\n");
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        for block in self.blocks() {
+            let mut label = String::new();
+            if let Some(name) = symbols.and_then(|symbols| symbols.label(block.start())) {
+                let _ = write!(label, "{}:\\l", escape(name));
+            }
+            for line in &block.instructions {
+                let text = match symbols {
+                    Some(symbols) => line.with_symbols(symbols),
+                    None => line.to_string(),
+                };
+                let _ = write!(label, "{}\\l", escape(&text));
+            }
+            let _ = writeln!(
+                dot,
+                "    \"0x{:03X}\" [label=\"{}\"];",
+                block.start(),
+                label
+            );
+        }
+
+        let mut outside = BTreeSet::new();
+        for edge in &self.edges {
+            let from = format!("0x{:03X}", edge.from);
+            let to = match edge.to {
+                Some(to) => {
+                    if !self.blocks.contains_key(&to) {
+                        outside.insert(to);
+                    }
+                    format!("0x{:03X}", to)
+                }
+                None => {
+                    let node = format!("computed {}", from);
+                    let _ = writeln!(
+                        dot,
+                        "    \"{}\" [label=\"?\", shape=circle, style=dashed];",
+                        node
+                    );
+                    node
+                }
+            };
+            let style = match edge.kind {
+                EdgeKind::Next => "",
+                EdgeKind::Jump => " [label=\"jump\"]",
+                EdgeKind::Call => " [label=\"call\", style=bold]",
+                EdgeKind::Skip => " [label=\"skip\"]",
+                EdgeKind::Computed => " [label=\"+ V0\", style=dashed]",
+            };
+            let _ = writeln!(dot, "    \"{}\" -> \"{}\"{};", from, to, style);
+        }
+        for addr in outside {
+            let _ = writeln!(
+                dot,
+                "    \"0x{:03X}\" [label=\"0x{:03X}\\lnot in the ROM\\l\", style=dashed];",
+                addr, addr
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// `text` inside a quoted dot string
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_split_at_jumps_calls_and_skips() {
+        let graph = ControlFlowGraph::build(
+            &[
+                0x60, 0x00, // 200: V0 = 0
+                0x22, 0x0A, // 202: call 20A
+                0x30, 0x01, // 204: skip if V0 == 1
+                0x12, 0x02, // 206: jump to 202
+                0xB3, 0x00, // 208: jump to 300 + V0
+                0x70, 0x01, // 20A: V0 += 1
+                0x00, 0xEE, // 20C: return
+            ],
+            Platform::CosmacVip,
+        );
+
+        let starts = graph.blocks().map(BasicBlock::start).collect::<Vec<_>>();
+        assert_eq!(starts, [0x200, 0x202, 0x204, 0x206, 0x208, 0x20A]);
+        assert_eq!(graph.block_at(0x20A).unwrap().last(), 0x20C);
+        let edges_from = |from| {
+            graph
+                .edges()
+                .iter()
+                .filter(|edge| edge.from == from)
+                .map(|edge| (edge.to, edge.kind))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(edges_from(0x200), [(Some(0x202), EdgeKind::Next)]);
+        assert_eq!(
+            edges_from(0x202),
+            [(Some(0x20A), EdgeKind::Call), (Some(0x204), EdgeKind::Next)]
+        );
+        assert_eq!(
+            edges_from(0x204),
+            [(Some(0x206), EdgeKind::Next), (Some(0x208), EdgeKind::Skip)]
+        );
+        assert_eq!(edges_from(0x208), [(None, EdgeKind::Computed)]);
+        assert_eq!(edges_from(0x20A), []);
+
+        let dot = graph.to_dot(None);
+        assert!(dot.starts_with("digraph cfg {\n"));
+        assert!(
+            dot.contains("\"0x20A\" [label=\"0x20A  7001  ADD V0, 0x01\\l0x20C  00EE  RET\\l\"];")
+        );
+        assert!(dot.contains("\"0x206\" -> \"0x202\" [label=\"jump\"];"));
+        assert!(dot.contains("\"0x208\" -> \"computed 0x208\" [label=\"+ V0\", style=dashed];"));
+    }
+}
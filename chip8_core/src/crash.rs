@@ -0,0 +1,114 @@
+use std::fmt;
+
+use crate::disasm::{disassemble_at, SymbolTable};
+use crate::trace::TraceEntry;
+use crate::{Chip8, Chip8Error, CpuState, DisassembledInstruction, RomInfo};
+
+/// Instructions shown before and after the faulting one
+const LISTING_RADIUS: u16 = 8;
+
+/// Everything needed to investigate a fault after the fact
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    pub error: Chip8Error,
+    pub rom: RomInfo,
+    pub state: CpuState,
+    /// Disassembly around the program counter
+    pub listing: Vec<DisassembledInstruction>,
+    /// Last traced instructions, oldest first; empty when the tracer is disabled
+    pub history: Vec<TraceEntry>,
+    /// Labels of the ROM, used to name addresses in the report
+    pub symbols: Option<SymbolTable>,
+}
+
+impl CrashReport {
+    pub fn new(chip8: &Chip8, error: Chip8Error) -> Self {
+        let state = chip8.cpu_state();
+        let first = state.pc.saturating_sub(2 * LISTING_RADIUS);
+        let listing = (0..2 * LISTING_RADIUS)
+            .filter_map(|index| disassemble_at(chip8.memory(), 0, first + 2 * index))
+            .collect();
+        let history = chip8.instruction_history(usize::MAX).copied().collect();
+
+        Self {
+            error,
+            rom: chip8.rom_info().clone(),
+            state,
+            listing,
+            history,
+            symbols: chip8.symbols().cloned(),
+        }
+    }
+
+    /// One line suitable for an on-screen message
+    pub fn summary(&self) -> String {
+        format!("Crashed at 0x{:03X}: {}", self.state.pc, self.error)
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "CHIP-8 crash report")?;
+        writeln!(f, "error: {}", self.error)?;
+        if let Some(symbols) = &self.symbols {
+            write!(f, "at: {}", symbols.describe(self.state.pc))?;
+            match symbols.source_line(self.state.pc) {
+                Some(line) => writeln!(f, " ({})", line)?,
+                None => writeln!(f)?,
+            }
+            for (register, addr) in [("PC", self.state.pc), ("I", self.state.i)] {
+                if let Some((range, note)) = symbols.note(addr) {
+                    let start = range.start();
+                    writeln!(f, "note on {} (0x{:03X}): {}", register, start, note)?;
+                }
+            }
+        }
+        writeln!(
+            f,
+            "rom: {:016x} ({} bytes, running as {})",
+            self.rom.hash, self.rom.size, self.rom.platform
+        )?;
+
+        writeln!(f, "\nregisters:")?;
+        for (x, value) in self.state.v.iter().enumerate() {
+            write!(f, "  V{:X}={:02X}", x, value)?;
+            if x % 8 == 7 {
+                writeln!(f)?;
+            }
+        }
+        writeln!(
+            f,
+            "  PC={:03X}  I={:03X}  DT={:02X}  ST={:02X}",
+            self.state.pc, self.state.i, self.state.delay_timer, self.state.sound_timer
+        )?;
+
+        writeln!(f, "\nstack ({} entries):", self.state.stack.len())?;
+        for (depth, addr) in self.state.stack.iter().enumerate().rev() {
+            writeln!(f, "  {:2}: 0x{:03X}", depth, addr)?;
+        }
+
+        writeln!(f, "\ndisassembly:")?;
+        for line in &self.listing {
+            let marker = if line.addr == self.state.pc { ">" } else { " " };
+            match &self.symbols {
+                Some(symbols) => writeln!(f, "  {} {}", marker, line.with_symbols(symbols))?,
+                None => writeln!(f, "  {} {}", marker, line)?,
+            }
+        }
+
+        writeln!(f, "\nlast {} instructions:", self.history.len())?;
+        for entry in &self.history {
+            match &self.symbols {
+                Some(symbols) => writeln!(
+                    f,
+                    "    0x{:03X}  {:<24} {}",
+                    entry.pc,
+                    symbols.instruction(&entry.instruction),
+                    symbols.describe(entry.pc)
+                )?,
+                None => writeln!(f, "    0x{:03X}  {}", entry.pc, entry.instruction)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,298 @@
+//! Errors and warnings of the tools, pointing at where in their input they are, for
+//! people as annotated snippets and for scripts as JSON
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::disasm::SymbolError;
+use crate::json::{JsonError, Value};
+use crate::lint::LintWarning;
+use crate::ROM_INITIAL_POSITION;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How tools print their diagnostics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Annotated snippets, like rustc's
+    #[default]
+    Human,
+    /// One JSON object per line
+    Json,
+}
+
+impl ErrorFormat {
+    pub const ALL: [ErrorFormat; 2] = [ErrorFormat::Human, ErrorFormat::Json];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorFormat::Human => "human",
+            ErrorFormat::Json => "json",
+        }
+    }
+}
+
+impl fmt::Display for ErrorFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ErrorFormat::ALL
+            .into_iter()
+            .find(|format| format.name() == s)
+            .ok_or_else(|| {
+                let names = ErrorFormat::ALL.map(ErrorFormat::name);
+                format!(
+                    "unknown error format '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Name of the input, as given on the command line
+    pub file: String,
+    /// Bytes of the input the diagnostic is about
+    pub span: Option<Range<usize>>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            file: String::new(),
+            span: None,
+        }
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(message)
+        }
+    }
+
+    pub fn in_file(self, file: impl Into<String>) -> Self {
+        Self {
+            file: file.into(),
+            ..self
+        }
+    }
+
+    pub fn with_span(self, span: Range<usize>) -> Self {
+        Self {
+            span: Some(span),
+            ..self
+        }
+    }
+
+    /// The diagnostic as text. With the `source` text it was found in, the span is shown
+    /// underlined in its line; otherwise, as for ROMs, it is given as a byte offset.
+    pub fn render(&self, source: Option<&str>) -> String {
+        let mut out = format!("{}: {}\n", self.severity, self.message);
+        let Some(span) = &self.span else {
+            if !self.file.is_empty() {
+                out += &format!(" --> {}\n", self.file);
+            }
+            return out;
+        };
+        let Some(source) = source else {
+            out += &format!(" --> {}+0x{:03X}\n", self.file, span.start);
+            return out;
+        };
+        // Spans of errors found byte by byte may start or end inside a character
+        let start = floor_char_boundary(source, span.start);
+        let position = Position::of(source, start);
+        let text = source[position.line_start..]
+            .lines()
+            .next()
+            .unwrap_or_default();
+        let underline_start = source[position.line_start..start].chars().count();
+        let underline_end = ceil_char_boundary(source, span.end)
+            .min(position.line_start + text.len())
+            .max(start);
+        let underline = source[start..underline_end].chars().count().max(1);
+        let number = position.line.to_string();
+        let gutter = " ".repeat(number.len());
+        out += &format!(
+            "{}--> {}:{}:{}\n",
+            gutter, self.file, position.line, position.column
+        );
+        out += &format!("{} |\n", gutter);
+        out += &format!("{} | {}\n", number, text);
+        out += &format!(
+            "{} | {}{}\n",
+            gutter,
+            " ".repeat(underline_start),
+            "^".repeat(underline)
+        );
+        out
+    }
+
+    /// The diagnostic as a JSON object, with the line and column of the span when the
+    /// `source` text is given
+    pub fn to_json(&self, source: Option<&str>) -> Value {
+        let mut members = vec![
+            ("severity".to_string(), self.severity.name().into()),
+            ("message".to_string(), self.message.as_str().into()),
+            ("file".to_string(), self.file.as_str().into()),
+        ];
+        let span = match &self.span {
+            Some(span) => Value::Object(vec![
+                ("start".to_string(), (span.start as u32).into()),
+                ("end".to_string(), (span.end as u32).into()),
+            ]),
+            None => Value::Null,
+        };
+        members.push(("span".to_string(), span));
+        if let (Some(span), Some(source)) = (&self.span, source) {
+            let position = Position::of(source, span.start);
+            members.push(("line".to_string(), (position.line as u32).into()));
+            members.push(("column".to_string(), (position.column as u32).into()));
+        }
+        Value::Object(members)
+    }
+
+    /// Print the diagnostic to stderr in `format`
+    pub fn emit(&self, format: ErrorFormat, source: Option<&str>) {
+        match format {
+            ErrorFormat::Human => eprint!("{}", self.render(source)),
+            ErrorFormat::Json => eprintln!("{}", self.to_json(source)),
+        }
+    }
+}
+
+impl From<&SymbolError> for Diagnostic {
+    fn from(err: &SymbolError) -> Self {
+        Diagnostic::error(err.message.clone()).with_span(err.span.clone())
+    }
+}
+
+impl From<&JsonError> for Diagnostic {
+    fn from(err: &JsonError) -> Self {
+        Diagnostic::error(err.message).with_span(err.offset..err.offset + 1)
+    }
+}
+
+/// A warning spanning the instruction in the ROM file
+impl From<&LintWarning> for Diagnostic {
+    fn from(warning: &LintWarning) -> Self {
+        let offset = (warning.addr as usize).saturating_sub(ROM_INITIAL_POSITION);
+        Diagnostic::warning(format!("0x{:03X}: {}", warning.addr, warning.kind))
+            .with_span(offset..offset + 2)
+    }
+}
+
+/// Line and column, both from 1, of a byte offset in some text
+struct Position {
+    line: usize,
+    column: usize,
+    /// Byte offset of the start of the line
+    line_start: usize,
+}
+
+impl Position {
+    fn of(source: &str, offset: usize) -> Self {
+        let offset = floor_char_boundary(source, offset);
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            line_start,
+        }
+    }
+}
+
+/// `offset`, or the start of the character it's in
+fn floor_char_boundary(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// `offset`, or the end of the character it's in
+fn ceil_char_boundary(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset += 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disasm::SymbolTable;
+
+    #[test]
+    fn errors_point_at_their_span_in_the_source() {
+        let source = "label start 0x200\nlabel loop 0x2G0\n";
+        let err = SymbolTable::parse(source).unwrap_err();
+        let diagnostic = Diagnostic::from(&err).in_file("game.sym");
+        assert_eq!(
+            diagnostic.render(Some(source)),
+            "error: '0x2G0' is not an address\n \
+             --> game.sym:2:12\n  \
+             |\n\
+             2 | label loop 0x2G0\n  \
+             |            ^^^^^\n"
+        );
+
+        let json = diagnostic.to_json(Some(source));
+        assert_eq!(json.get("line").and_then(Value::as_f64), Some(2.0));
+        assert_eq!(json.get("column").and_then(Value::as_f64), Some(12.0));
+        let span = json.get("span").unwrap();
+        assert_eq!(span.get("start").and_then(Value::as_f64), Some(29.0));
+
+        let warning = Diagnostic::warning("odd")
+            .in_file("game.ch8")
+            .with_span(4..6);
+        assert_eq!(warning.render(None), "warning: odd\n --> game.ch8+0x004\n");
+
+        let source = "{\"a\": é}";
+        let err = crate::json::parse(source).unwrap_err();
+        assert_eq!(
+            Diagnostic::from(&err)
+                .in_file("a.json")
+                .render(Some(source)),
+            "error: expected a value\n \
+             --> a.json:1:7\n  \
+             |\n\
+             1 | {\"a\": é}\n  \
+             |       ^\n"
+        );
+    }
+}
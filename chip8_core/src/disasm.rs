@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+
+use crate::Instruction;
+
+/// An opcode read from memory, decoded when the core knows it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisassembledInstruction {
+    pub addr: u16,
+    pub opcode: u16,
+    pub instruction: Option<Instruction>,
+}
+
+impl fmt::Display for DisassembledInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.instruction {
+            Some(instruction) => write!(
+                f,
+                "0x{:03X}  {:04X}  {}",
+                self.addr, self.opcode, instruction
+            ),
+            None => write!(f, "0x{:03X}  {:04X}  ???", self.addr, self.opcode),
+        }
+    }
+}
+
+impl DisassembledInstruction {
+    /// Like the [`fmt::Display`] output, with label names for addresses and the source line
+    /// of the instruction when `symbols` knows them
+    pub fn with_symbols(&self, symbols: &SymbolTable) -> String {
+        let mut text = match self.instruction {
+            Some(instruction) => format!(
+                "0x{:03X}  {:04X}  {}",
+                self.addr,
+                self.opcode,
+                symbols.instruction(&instruction)
+            ),
+            None => self.to_string(),
+        };
+        if let Some(line) = symbols.source_line(self.addr) {
+            text = format!("{}  ; {}", text, line);
+        }
+        if let Some((_, note)) = symbols
+            .note(self.addr)
+            .filter(|(range, _)| *range.start() == self.addr)
+        {
+            text = format!("{}  ; {}", text, note);
+        }
+        text
+    }
+}
+
+/// Labels and source lines of a ROM, like one built with Octo, to show its own names in
+/// listings, and notes on addresses or ranges of them left while reverse engineering one.
+///
+/// Symbol files are in a plain text format of this emulator's own, not one Octo writes,
+/// so they're easy to write by hand or generate from any assembler's output. There is one
+/// entry per line; `#` starts a comment:
+///
+/// ```text
+/// label draw_paddle 0x2A4
+/// line 0x2A4 pong.8o:42
+/// note 0x300-0x30F paddle sprite, one row per byte
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolTable {
+    labels: BTreeMap<u16, String>,
+    lines: BTreeMap<u16, SourceLine>,
+    /// By first address, with the last one
+    notes: BTreeMap<u16, (u16, String)>,
+}
+
+/// Where an instruction came from in the program's source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLine {
+    pub file: String,
+    pub line: u32,
+}
+
+impl fmt::Display for SourceLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// Why a symbol file couldn't be read, with the 1-based line it happened on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolError {
+    pub line: usize,
+    pub message: String,
+    /// Bytes of the file at fault: the bad field, or the whole line
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for SymbolError {}
+
+impl SymbolTable {
+    pub fn parse(text: &str) -> Result<Self, SymbolError> {
+        let mut symbols = Self::default();
+        let mut line_start = 0;
+        for (index, line) in text.split_inclusive('\n').enumerate() {
+            let offset = line_start;
+            line_start += line.len();
+            let error = |message: String, span: Range<usize>| SymbolError {
+                line: index + 1,
+                message,
+                span: offset + span.start..offset + span.end,
+            };
+            let line = line.split('#').next().unwrap_or_default();
+            let fields = fields(line);
+            let names = fields.iter().map(|(_, field)| *field).collect::<Vec<_>>();
+            let parse_field = |n: usize| {
+                let (span, field) = fields[n].clone();
+                parse_address(field).map_err(|message| error(message, span))
+            };
+            match names.as_slice() {
+                [] => {}
+                ["label", name, _] => {
+                    let addr = parse_field(2)?;
+                    symbols.labels.insert(addr, name.to_string());
+                }
+                ["line", _, location] => {
+                    let addr = parse_field(1)?;
+                    let (file, line) = location
+                        .rsplit_once(':')
+                        .and_then(|(file, line)| Some((file, line.parse().ok()?)))
+                        .ok_or_else(|| {
+                            let message = format!("'{}' is not like file.8o:12", location);
+                            error(message, fields[2].0.clone())
+                        })?;
+                    let file = file.to_string();
+                    symbols.lines.insert(addr, SourceLine { file, line });
+                }
+                ["note", range, _, ..] => {
+                    let span = fields[1].0.clone();
+                    let (start, end) = match range.split_once('-') {
+                        Some((start, end)) => (parse_address(start), parse_address(end)),
+                        None => (parse_address(range), parse_address(range)),
+                    };
+                    let (start, end) = start
+                        .and_then(|start| Ok((start, end?)))
+                        .map_err(|message| error(message, span.clone()))?;
+                    if end < start {
+                        let message = format!("'{}' ends before it starts", range);
+                        return Err(error(message, span));
+                    }
+                    let text = line[span.end..].trim().to_string();
+                    symbols.notes.insert(start, (end, text));
+                }
+                _ => {
+                    let trimmed = line.trim_end();
+                    let start = trimmed.len() - trimmed.trim_start().len();
+                    return Err(error(
+                        format!(
+                            "expected 'label NAME ADDR', 'line ADDR FILE:LINE' or \
+                             'note ADDR[-END] TEXT', found '{}'",
+                            trimmed.trim_start()
+                        ),
+                        start..trimmed.len(),
+                    ));
+                }
+            }
+        }
+        Ok(symbols)
+    }
+
+    /// Name of the label at exactly `addr`
+    pub fn label(&self, addr: u16) -> Option<&str> {
+        self.labels.get(&addr).map(String::as_str)
+    }
+
+    /// `instruction` in assembly, with its address operand named after its label
+    pub fn instruction(&self, instruction: &Instruction) -> String {
+        let text = instruction.to_string();
+        match instruction
+            .address_operand()
+            .and_then(|target| Some((target, self.label(target)?)))
+        {
+            Some((target, label)) => text.replace(&format!("0x{:03X}", target), label),
+            None => text,
+        }
+    }
+
+    pub fn source_line(&self, addr: u16) -> Option<&SourceLine> {
+        self.lines.get(&addr)
+    }
+
+    /// The note on the addresses including `addr` that starts closest before it, with
+    /// those addresses
+    pub fn note(&self, addr: u16) -> Option<(RangeInclusive<u16>, &str)> {
+        self.notes
+            .range(..=addr)
+            .rev()
+            .find(|(_, (end, _))| addr <= *end)
+            .map(|(&start, (end, text))| (start..=*end, text.as_str()))
+    }
+
+    /// Add the entries of `other`, replacing ours at the same addresses
+    pub fn merge(&mut self, other: SymbolTable) {
+        self.labels.extend(other.labels);
+        self.lines.extend(other.lines);
+        self.notes.extend(other.notes);
+    }
+
+    /// `addr` relative to the closest label at or before it, like `draw_paddle+4`, or as a
+    /// plain address when there is no such label
+    pub fn describe(&self, addr: u16) -> String {
+        match self.labels.range(..=addr).next_back() {
+            Some((&start, name)) if start == addr => name.clone(),
+            Some((&start, name)) => format!("{}+{}", name, addr - start),
+            None => format!("0x{:03X}", addr),
+        }
+    }
+}
+
+/// The whitespace-separated fields of `line` with their byte ranges in it
+fn fields(line: &str) -> Vec<(Range<usize>, &str)> {
+    let mut fields = Vec::new();
+    let mut start = None;
+    for (index, character) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (start, character.is_whitespace()) {
+            (None, false) => start = Some(index),
+            (Some(field_start), true) => {
+                fields.push((field_start..index, &line[field_start..index]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    fields
+}
+
+fn parse_address(s: &str) -> Result<u16, String> {
+    let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(digits) => u16::from_str_radix(digits, 16),
+        None => s.parse(),
+    };
+    parsed.map_err(|_| format!("'{}' is not an address", s))
+}
+
+/// Decode the opcode at `addr`, where `bytes[0]` lives at `origin`.
+///
+/// Returns `None` if the opcode doesn't fit inside `bytes`.
+pub fn disassemble_at(bytes: &[u8], origin: u16, addr: u16) -> Option<DisassembledInstruction> {
+    let offset = addr.checked_sub(origin)? as usize;
+    let opcode = u16::from_be_bytes([*bytes.get(offset)?, *bytes.get(offset + 1)?]);
+    Some(DisassembledInstruction {
+        addr,
+        opcode,
+        instruction: Instruction::decode(opcode),
+    })
+}
+
+/// Linear disassembly of every opcode in `bytes`, where `bytes[0]` lives at `origin`
+pub fn disassemble(bytes: &[u8], origin: u16) -> Vec<DisassembledInstruction> {
+    (0..bytes.len() / 2)
+        .filter_map(|index| disassemble_at(bytes, origin, origin + 2 * index as u16))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roms_are_disassembled_linearly() {
+        let listing = disassemble(&[0x00, 0xE0, 0xA2, 0x2A, 0x01, 0x23, 0xFF], 0x200)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            listing,
+            [
+                "0x200  00E0  CLS",
+                "0x202  A22A  LD I, 0x22A",
+                "0x204  0123  SYS 0x123",
+            ]
+        );
+    }
+
+    #[test]
+    fn symbols_name_addresses_and_source_lines() {
+        let symbols = SymbolTable::parse(
+            "# pong's labels\n\
+             label main 0x200\n\
+             label draw_paddle 0x2A4\n\
+             line 0x202 pong.8o:12\n",
+        )
+        .unwrap();
+        let call = disassemble_at(&[0x00, 0xE0, 0x22, 0xA4], 0x200, 0x202).unwrap();
+        assert_eq!(
+            call.with_symbols(&symbols),
+            "0x202  22A4  CALL draw_paddle  ; pong.8o:12"
+        );
+        let clear = disassemble_at(&[0x00, 0xE0], 0x200, 0x200).unwrap();
+        assert_eq!(clear.with_symbols(&symbols), clear.to_string());
+        assert_eq!(symbols.describe(0x2A8), "draw_paddle+4");
+        assert_eq!(symbols.describe(0x100), "0x100");
+
+        let error = SymbolTable::parse("label main\n").unwrap_err();
+        assert_eq!(error.line, 1);
+    }
+
+    #[test]
+    fn notes_annotate_addresses_and_ranges() {
+        let mut symbols = SymbolTable::parse("label main 0x200\n").unwrap();
+        symbols.merge(
+            SymbolTable::parse(
+                "note 0x202 waits for the start key\n\
+                 note 0x300-0x30F paddle sprite, one row per byte\n",
+            )
+            .unwrap(),
+        );
+        assert_eq!(symbols.label(0x200), Some("main"));
+        assert_eq!(
+            symbols.note(0x305),
+            Some((0x300..=0x30F, "paddle sprite, one row per byte"))
+        );
+        assert_eq!(symbols.note(0x310), None);
+        let wait = disassemble_at(&[0x00, 0xE0, 0xF0, 0x0A], 0x200, 0x202).unwrap();
+        assert_eq!(
+            wait.with_symbols(&symbols),
+            "0x202  F00A  LD V0, K  ; waits for the start key"
+        );
+
+        let error = SymbolTable::parse("note 0x30F-0x300 backwards\n").unwrap_err();
+        assert_eq!(error.span, 5..16);
+    }
+}
@@ -0,0 +1,110 @@
+//! When what instructions draw becomes visible to frontends. Drawing straight to the
+//! visible screen is accurate, but a game that erases and redraws its sprites over many
+//! instructions can be shown between the two, with sprites missing.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Frames the visible screen waits for the program to sync before showing it anyway, see
+/// [`DisplayBuffering::Sync`]
+pub(crate) const MAX_UNSYNCED_FRAMES: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayBuffering {
+    /// Frontends see the screen instructions draw on, as it is at any moment; for accuracy
+    /// testing
+    #[default]
+    Immediate,
+    /// Instructions draw on a back buffer shown at the end of each frame
+    Frame,
+    /// Instructions draw on a back buffer shown when the program sets the delay timer
+    /// (FX15), which most games do once per pass of their main loop, however many frames
+    /// it takes. Programs that don't are shown every few frames.
+    Sync,
+}
+
+impl DisplayBuffering {
+    pub const ALL: [DisplayBuffering; 3] = [
+        DisplayBuffering::Immediate,
+        DisplayBuffering::Frame,
+        DisplayBuffering::Sync,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DisplayBuffering::Immediate => "immediate",
+            DisplayBuffering::Frame => "frame",
+            DisplayBuffering::Sync => "sync",
+        }
+    }
+}
+
+impl fmt::Display for DisplayBuffering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for DisplayBuffering {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DisplayBuffering::ALL
+            .into_iter()
+            .find(|buffering| buffering.name() == s)
+            .ok_or_else(|| {
+                let names = DisplayBuffering::ALL.map(DisplayBuffering::name);
+                format!(
+                    "unknown display buffering '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Chip8, DisplayBuffering};
+
+    #[test]
+    fn buffered_screens_show_finished_drawings() {
+        // Draw the font's 0 and 8 a frame apart, then set the delay timer
+        let rom = [
+            0xA0, 0x50, // 200: I = font character 0
+            0xD0, 0x05, // 202: draw it
+            0x60, 0x08, // 204: V0 = 8
+            0xF0, 0x29, // 206: I = font character V0
+            0xD1, 0x05, // 208: draw it below
+            0xF0, 0x15, // 20A: delay timer = V0
+            0x12, 0x0C, // 20C: loop
+        ];
+        let lit = |screen: &[bool]| screen.iter().filter(|&&on| on).count();
+        let run = |buffering| {
+            let mut chip8 = Chip8::new();
+            chip8.set_display_buffering(buffering);
+            chip8.load_rom(&rom);
+            let mut shown = vec![];
+            for _ in 0..4 {
+                chip8.step().unwrap();
+                shown.push(lit(chip8.displayed_screen()));
+            }
+            chip8.end_frame();
+            shown.push(lit(chip8.displayed_screen()));
+            for _ in 0..2 {
+                chip8.step().unwrap();
+                shown.push(lit(chip8.displayed_screen()));
+            }
+            shown
+        };
+
+        assert_eq!(
+            run(DisplayBuffering::Immediate),
+            [0, 14, 14, 14, 14, 30, 30]
+        );
+        assert_eq!(run(DisplayBuffering::Frame), [0, 0, 0, 0, 14, 14, 14]);
+        assert_eq!(run(DisplayBuffering::Sync), [0, 0, 0, 0, 0, 0, 30]);
+        assert_eq!("sync".parse(), Ok(DisplayBuffering::Sync));
+        assert!("triple".parse::<DisplayBuffering>().is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Random numbers for CXNN, from the system or from a seed the frontend supplies
+
+use std::fmt;
+
+/// Seed used until the frontend supplies one, when there's no system entropy to draw from
+const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+/// State of the shift register when seeded with zero, which it would never leave
+const DEFAULT_LFSR_STATE: u16 = 0xACE1;
+/// Taps of the shift register, which make it maximal-length
+const LFSR_TAPS: u16 = 0xB400;
+
+/// Generator CXNN draws its numbers from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomSource {
+    /// Numbers as random as the operating system's, different on every run
+    #[cfg(feature = "native")]
+    System,
+    /// A fast xorshift generator seeded with the number, the same on every run
+    Xorshift(u64),
+    /// A 16-bit Galois linear feedback shift register seeded with the number: cheap and
+    /// far less random than xorshift, like the generators of early interpreters. It isn't
+    /// the COSMAC VIP interpreter's own routine.
+    Lfsr(u64),
+}
+
+impl Default for RandomSource {
+    #[cfg(feature = "native")]
+    fn default() -> Self {
+        RandomSource::System
+    }
+
+    #[cfg(not(feature = "native"))]
+    fn default() -> Self {
+        RandomSource::Xorshift(DEFAULT_SEED)
+    }
+}
+
+impl fmt::Display for RandomSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "native")]
+            RandomSource::System => write!(f, "system"),
+            RandomSource::Xorshift(seed) => write!(f, "xorshift:{}", seed),
+            RandomSource::Lfsr(seed) => write!(f, "lfsr:{}", seed),
+        }
+    }
+}
+
+impl std::str::FromStr for RandomSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, seed) = match s.split_once(':') {
+            Some((name, seed)) => {
+                let seed = seed
+                    .parse()
+                    .map_err(|_| format!("'{}' is not a seed", seed))?;
+                (name, Some(seed))
+            }
+            None => (s, None),
+        };
+        match (name, seed) {
+            #[cfg(feature = "native")]
+            ("system", None) => Ok(RandomSource::System),
+            ("xorshift", seed) => Ok(RandomSource::Xorshift(seed.unwrap_or(0))),
+            ("lfsr", seed) => Ok(RandomSource::Lfsr(seed.unwrap_or(0))),
+            _ => Err(format!(
+                "unknown random source '{}', expected one of: system, xorshift, xorshift:SEED, \
+                 lfsr, lfsr:SEED",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Entropy {
+    /// The thread-local generator of `rand`, seeded by the operating system
+    #[cfg(feature = "native")]
+    System,
+    /// An xorshift64* generator, which needs nothing from the platform
+    Seeded(u64),
+    /// A 16-bit linear feedback shift register, stepped a byte at a time
+    Lfsr(u16),
+}
+
+impl Entropy {
+    pub fn seeded(seed: u64) -> Self {
+        // Xorshift never leaves zero
+        Entropy::Seeded(if seed == 0 { DEFAULT_SEED } else { seed })
+    }
+
+    pub fn new(source: RandomSource) -> Self {
+        match source {
+            #[cfg(feature = "native")]
+            RandomSource::System => Entropy::System,
+            RandomSource::Xorshift(seed) => Entropy::seeded(seed),
+            RandomSource::Lfsr(seed) => match (seed ^ (seed >> 16) ^ (seed >> 32)) as u16 {
+                0 => Entropy::Lfsr(DEFAULT_LFSR_STATE),
+                state => Entropy::Lfsr(state),
+            },
+        }
+    }
+
+    pub fn next_u8(&mut self) -> u8 {
+        match self {
+            #[cfg(feature = "native")]
+            Entropy::System => rand::Rng::gen(&mut rand::thread_rng()),
+            Entropy::Seeded(state) => {
+                *state ^= *state >> 12;
+                *state ^= *state << 25;
+                *state ^= *state >> 27;
+                (state.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 56) as u8
+            }
+            Entropy::Lfsr(state) => {
+                for _ in 0..8 {
+                    let carry = *state & 1;
+                    *state >>= 1;
+                    if carry == 1 {
+                        *state ^= LFSR_TAPS;
+                    }
+                }
+                *state as u8
+            }
+        }
+    }
+}
+
+impl Default for Entropy {
+    #[cfg(feature = "native")]
+    fn default() -> Self {
+        Entropy::System
+    }
+
+    #[cfg(not(feature = "native"))]
+    fn default() -> Self {
+        Entropy::seeded(DEFAULT_SEED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Chip8, RandomSource};
+
+    #[test]
+    fn seeded_machines_draw_the_same_numbers() {
+        let draws = |seed| {
+            let mut chip8 = Chip8::new();
+            chip8.seed_random(seed);
+            chip8.load_rom(&[0xC0, 0xFF, 0xC1, 0xFF, 0xC2, 0xFF, 0xC3, 0xFF]);
+            for _ in 0..4 {
+                chip8.step().unwrap();
+            }
+            chip8.cpu_state().v
+        };
+        assert_eq!(draws(7), draws(7));
+        assert_ne!(draws(7), draws(8));
+        // Zero is a valid seed too
+        assert_ne!(draws(0)[..4], [0; 4]);
+    }
+
+    #[test]
+    fn sources_are_selectable() {
+        let draws = |source| {
+            let mut chip8 = Chip8::new();
+            chip8.set_random_source(source);
+            chip8.load_rom(&[0xC0, 0xFF, 0xC1, 0xFF, 0xC2, 0xFF, 0xC3, 0xFF]);
+            for _ in 0..4 {
+                chip8.step().unwrap();
+            }
+            chip8.cpu_state().v
+        };
+        assert_eq!(draws(RandomSource::Lfsr(1)), draws(RandomSource::Lfsr(1)));
+        assert_ne!(
+            draws(RandomSource::Lfsr(1)),
+            draws(RandomSource::Xorshift(1))
+        );
+        assert_ne!(draws(RandomSource::Lfsr(0))[..4], [0; 4]);
+
+        assert_eq!("lfsr".parse(), Ok(RandomSource::Lfsr(0)));
+        assert_eq!("xorshift:7".parse(), Ok(RandomSource::Xorshift(7)));
+        assert_eq!(RandomSource::Lfsr(3).to_string(), "lfsr:3");
+        assert!("lcg".parse::<RandomSource>().is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Chip8Error {
+    /// The opcode at `pc` isn't an instruction of the emulated platform
+    UnknownOpcode { pc: u16, opcode: u16 },
+    /// A subroutine call at `pc` found the stack full
+    StackOverflow { pc: u16 },
+    /// A return at `pc` found the stack empty
+    StackUnderflow { pc: u16 },
+    /// The program counter left memory
+    PcOutOfBounds { pc: u16 },
+    /// An access to `len` bytes at `addr` doesn't fit in memory
+    OutOfBounds { addr: usize, len: usize },
+    /// Data doesn't have the size the machine expects
+    SizeMismatch { expected: usize, found: usize },
+    /// There is no V register with this index
+    NoSuchRegister { x: usize },
+    /// There is no key with this index on the hex keypad
+    NoSuchKey { key: usize },
+    /// The instruction at `pc` wrote to protected memory at `addr`, see
+    /// [`crate::WriteProtection`]
+    IllegalWrite { pc: u16, addr: u16 },
+    /// Execution reached an odd `pc` or the interpreter's area below the program, see
+    /// [`crate::WriteProtection`]
+    StrayExecution { pc: u16 },
+    /// FXB0 at `pc` asked for a bank the ROM doesn't have
+    NoSuchBank { pc: u16, bank: u8 },
+    /// The 0NNN at `pc` called the machine-language routine at `addr`, which has no
+    /// stand-in, see [`crate::MachineRoutine`]
+    MachineCode { pc: u16, addr: u16 },
+}
+
+impl fmt::Display for Chip8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Chip8Error::UnknownOpcode { pc, opcode } => {
+                write!(f, "unknown opcode {:04X} at 0x{:03X}", opcode, pc)
+            }
+            Chip8Error::StackOverflow { pc } => {
+                write!(f, "stack overflow calling a subroutine at 0x{:03X}", pc)
+            }
+            Chip8Error::StackUnderflow { pc } => {
+                write!(f, "return with an empty stack at 0x{:03X}", pc)
+            }
+            Chip8Error::PcOutOfBounds { pc } => {
+                write!(f, "program counter 0x{:03X} is outside memory", pc)
+            }
+            Chip8Error::OutOfBounds { addr, len } => write!(
+                f,
+                "{} bytes at 0x{:03X} don't fit in memory",
+                len, addr
+            ),
+            Chip8Error::SizeMismatch { expected, found } => {
+                write!(f, "expected {} bytes, found {}", expected, found)
+            }
+            Chip8Error::NoSuchRegister { x } => write!(f, "there is no register V{:X}", x),
+            Chip8Error::NoSuchKey { key } => write!(f, "there is no key {}", key),
+            Chip8Error::IllegalWrite { pc, addr } => write!(
+                f,
+                "write to protected address 0x{:03X} at 0x{:03X}",
+                addr, pc
+            ),
+            Chip8Error::StrayExecution { pc } if pc % 2 == 1 => {
+                write!(f, "executing at odd address 0x{:03X}", pc)
+            }
+            Chip8Error::StrayExecution { pc } => {
+                write!(f, "executing at 0x{:03X}, below the program", pc)
+            }
+            Chip8Error::NoSuchBank { pc, bank } => {
+                write!(f, "there is no memory bank {} to map at 0x{:03X}", bank, pc)
+            }
+            Chip8Error::MachineCode { pc, addr } => write!(
+                f,
+                "call to the machine-language routine at 0x{:03X} at 0x{:03X}, which isn't \
+                 emulated",
+                addr, pc
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Chip8Error {}
@@ -0,0 +1,56 @@
+use crate::screen_trigger::ScreenTrigger;
+
+/// Something that happened inside the machine which frontends may want to react to.
+///
+/// Events are only collected after [`crate::Chip8::enable_events`] is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chip8Event {
+    /// The whole screen moved by `dx` pixels right and `dy` pixels down
+    Scrolled { dx: i8, dy: i8 },
+    /// The instruction at `pc` cleared the screen, for transitions, flashes or full
+    /// refreshes of e-paper displays exactly then
+    ScreenCleared { pc: u16 },
+    /// The FX0A at `addr` started waiting for a key to put in `register`; see
+    /// [`crate::Chip8::key_prompt`] for the keys it likely expects
+    WaitingForKey { addr: u16, register: u8 },
+    /// The instruction at `pc` wrote to protected memory at `addr`, see
+    /// [`crate::WriteProtection`]
+    IllegalWrite { pc: u16, addr: u16 },
+    /// The instruction at `pc` read memory at `addr` that nothing wrote since power-on,
+    /// reported while the write protection is strict; see [`crate::RamPattern`]
+    UninitializedRead { pc: u16, addr: u16 },
+    /// An instruction is executing at an odd `pc` or below the program, where only
+    /// corrupted jumps and returns lead; reported while the write protection is on, see
+    /// [`crate::WriteProtection`]
+    StrayExecution { pc: u16 },
+    /// The sound timer was set while silent, to sound for `ticks` timer ticks. Frontends
+    /// can start the buzzer on this edge to hear beeps too short to show up in any frame.
+    SoundStarted { ticks: u8 },
+    /// The sound timer ran out or was cleared
+    SoundStopped,
+    /// DXYN drew the `width` by `height` sprite at `addr` with its top left corner at
+    /// (`x`, `y`), before wrapping or clipping. It `collided` when it turned some lit pixels
+    /// off but not all of its own: drawing a sprite over itself to erase it, as games move
+    /// things, doesn't count, even though it sets VF.
+    SpriteDrawn {
+        addr: u16,
+        x: u8,
+        y: u8,
+        width: u8,
+        height: u8,
+        collided: bool,
+    },
+    /// The instruction at `pc` changed the screen to show what `trigger` waits for, see
+    /// [`crate::Chip8::add_screen_trigger`]
+    ScreenTriggered { pc: u16, trigger: ScreenTrigger },
+}
+
+/// A pixel turning on or off, for frontends that update displays pixel by pixel.
+///
+/// Changes are only collected after [`crate::Chip8::enable_pixel_changes`] is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelChange {
+    pub x: u8,
+    pub y: u8,
+    pub on: bool,
+}
@@ -0,0 +1,163 @@
+//! Instruction sets added on top of CHIP-8. Each is an [`OpcodeExtension`] decoding the
+//! opcodes it claims; a machine tries the ones of its platform before plain CHIP-8, so an
+//! extension can also take an opcode over, like CHIP-8X's colour instructions in place of
+//! BNNN. Crates with instructions of their own register them with
+//! [`crate::Chip8::add_extension`].
+
+use crate::{decode_instruction_into_nibbles, Chip8, Chip8Error, Instruction, Platform};
+
+pub trait OpcodeExtension: Sync {
+    /// Name to tell the extension apart in listings, like `super-chip`
+    fn name(&self) -> &'static str;
+
+    /// The instruction `opcode` is in this extension, or `None` to leave it to the others
+    /// and to plain CHIP-8
+    fn decode(&self, opcode: u16) -> Option<Instruction>;
+
+    /// Run an [`Instruction::Extension`] this extension decoded, with the program counter
+    /// already on the next instruction. The built-in extensions decode to the core's own
+    /// instructions and never get here.
+    fn execute(&self, opcode: u16, chip8: &mut Chip8) -> Result<(), Chip8Error> {
+        let pc = chip8.cpu_state().pc - 2;
+        Err(Chip8Error::UnknownOpcode { pc, opcode })
+    }
+}
+
+/// SUPER-CHIP's scrolling
+pub struct SuperChipExtension;
+
+impl OpcodeExtension for SuperChipExtension {
+    fn name(&self) -> &'static str {
+        "super-chip"
+    }
+
+    fn decode(&self, opcode: u16) -> Option<Instruction> {
+        match decode_instruction_into_nibbles(opcode) {
+            [0x0, 0x0, 0xC, n] => Some(Instruction::ScrollDown { n }),
+            [0x0, 0x0, 0xF, 0xB] => Some(Instruction::ScrollRight),
+            [0x0, 0x0, 0xF, 0xC] => Some(Instruction::ScrollLeft),
+            _ => None,
+        }
+    }
+}
+
+/// CHIP-8X's colours and second keypad. It gave up BNNN for its colour instructions.
+pub struct Chip8XExtension;
+
+impl OpcodeExtension for Chip8XExtension {
+    fn name(&self) -> &'static str {
+        "chip-8x"
+    }
+
+    fn decode(&self, opcode: u16) -> Option<Instruction> {
+        match decode_instruction_into_nibbles(opcode) {
+            [0x0, 0x2, 0xA, 0x0] => Some(Instruction::CycleBackground),
+            [0xB, x, y, 0x0] => Some(Instruction::SetZoneColors { x, y }),
+            [0xB, x, y, n] => Some(Instruction::SetRowColors { x, y, n }),
+            [0xE, x, 0xF, 0x2] => Some(Instruction::SkipIfSecondKey { x }),
+            [0xE, x, 0xF, 0x5] => Some(Instruction::SkipIfNotSecondKey { x }),
+            _ => None,
+        }
+    }
+}
+
+/// FXB0, this emulator's bank switching, see [`crate::Chip8::enable_banking`]
+pub struct BankSwitchingExtension;
+
+impl OpcodeExtension for BankSwitchingExtension {
+    fn name(&self) -> &'static str {
+        "bank-switching"
+    }
+
+    fn decode(&self, opcode: u16) -> Option<Instruction> {
+        match decode_instruction_into_nibbles(opcode) {
+            [0xF, x, 0xB, 0x0] => Some(Instruction::SelectBank { x }),
+            _ => None,
+        }
+    }
+}
+
+/// Every built-in extension
+pub const BUILT_IN: [&dyn OpcodeExtension; 3] = [
+    &SuperChipExtension,
+    &Chip8XExtension,
+    &BankSwitchingExtension,
+];
+
+/// The extensions `platform`'s interpreter has, in the order they're tried
+pub fn for_platform(platform: Platform) -> &'static [&'static dyn OpcodeExtension] {
+    match platform {
+        Platform::CosmacVip | Platform::Chip48 => &[],
+        Platform::SuperChip => &[&SuperChipExtension],
+        Platform::Chip8X => &[&Chip8XExtension],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// XORs VX with the byte at I, as 5XY1 on some interpreters
+    struct XorWithMemory;
+
+    impl OpcodeExtension for XorWithMemory {
+        fn name(&self) -> &'static str {
+            "xor-with-memory"
+        }
+
+        fn decode(&self, opcode: u16) -> Option<Instruction> {
+            match decode_instruction_into_nibbles(opcode) {
+                [0x5, _, _, 0x1] => Some(Instruction::Extension { opcode }),
+                _ => None,
+            }
+        }
+
+        fn execute(&self, opcode: u16, chip8: &mut Chip8) -> Result<(), Chip8Error> {
+            let x = (opcode >> 8 & 0xF) as usize;
+            let state = chip8.cpu_state();
+            let byte = chip8.memory()[state.i as usize];
+            chip8.set_v(x, state.v[x] ^ byte)
+        }
+    }
+
+    static XOR_WITH_MEMORY: XorWithMemory = XorWithMemory;
+
+    #[test]
+    fn platforms_decode_with_their_extensions() {
+        assert_eq!(
+            Instruction::decode_for(0xB123, Platform::Chip8X),
+            Some(Instruction::SetRowColors { x: 1, y: 2, n: 3 })
+        );
+        assert_eq!(
+            Instruction::decode_for(0xB123, Platform::SuperChip),
+            Some(Instruction::JumpWithOffset { nnn: 0x123 })
+        );
+        assert!(Platform::SuperChip.supports(&Instruction::ScrollLeft));
+        assert!(!Platform::CosmacVip.supports(&Instruction::ScrollLeft));
+        assert!(!Platform::Chip8X.supports(&Instruction::JumpWithOffset { nnn: 0x123 }));
+        assert!(!Platform::SuperChip.supports(&Instruction::SelectBank { x: 0 }));
+
+        let rom = [
+            0xA2, 0x06, // 200: I = 0x206
+            0x63, 0x0F, // 202: V3 = 0x0F
+            0x53, 0x01, // 204: V3 ^= the byte at I
+            0xFF, 0x00, // 206: data
+        ];
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&rom);
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+        assert!(chip8.step().is_err());
+
+        let mut chip8 = Chip8::new();
+        chip8.add_extension(&XOR_WITH_MEMORY);
+        chip8.load_rom(&rom);
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+        assert_eq!(chip8.step(), Ok(Instruction::Extension { opcode: 0x5301 }));
+        assert_eq!(chip8.cpu_state().v[3], 0xF0);
+        assert_eq!(chip8.cpu_state().pc, 0x206);
+        let names: Vec<_> = chip8.extensions().iter().map(|e| e.name()).collect();
+        assert_eq!(names, ["xor-with-memory"]);
+    }
+}
@@ -0,0 +1,93 @@
+//! Sparing players sensitive to flashing lights: games that strobe the screen, like by
+//! clearing it and drawing it full again every few frames, are shown flashing at most a
+//! few times a second. Frames that would flash sooner are held back, and the screen catches
+//! up with the game once another flash is allowed.
+
+use crate::runner::FRAME_RATE;
+use crate::PIXELS_PER_SCREEN;
+
+/// Flashes a second that accessibility guidelines like WCAG's consider safe
+pub const SAFE_FLASHES_PER_SECOND: u32 = 3;
+
+/// A change between two frames is a flash when it adds up to this share of the screen
+/// going from unlit to fully lit or back
+const FLASH_SHARE: f64 = 0.25;
+
+/// Decides which frames are shown so the screen doesn't flash too often
+#[derive(Debug, Clone)]
+pub struct FlashLimiter {
+    /// Frames between flashes
+    interval: u64,
+    /// Number of the frame that last flashed
+    last_flash: Option<u64>,
+    /// Brightness of the pixels of the frame shown
+    shown: [u8; PIXELS_PER_SCREEN],
+}
+
+impl FlashLimiter {
+    /// Flash at most `max_flashes_per_second` times a second of emulated time
+    pub fn new(max_flashes_per_second: u32) -> Self {
+        let interval = (FRAME_RATE / max_flashes_per_second.max(1) as f64).ceil() as u64;
+        Self {
+            interval: interval.max(1),
+            last_flash: None,
+            shown: [0; PIXELS_PER_SCREEN],
+        }
+    }
+
+    /// Whether to show the frame numbered `frame` with pixels of `brightness`, instead of
+    /// going on showing the last frame this allowed. Only frames that would flash too soon
+    /// after the last flash aren't.
+    pub fn allow(&mut self, brightness: &[u8; PIXELS_PER_SCREEN], frame: u64) -> bool {
+        let change: u32 = self
+            .shown
+            .iter()
+            .zip(brightness)
+            .map(|(&shown, &next)| shown.abs_diff(next) as u32)
+            .sum();
+        let full_change = u8::MAX as f64 * PIXELS_PER_SCREEN as f64;
+        if change as f64 >= FLASH_SHARE * full_change {
+            let due = self
+                .last_flash
+                .is_none_or(|last| frame < last || frame - last >= self.interval);
+            if !due {
+                return false;
+            }
+            self.last_flash = Some(frame);
+        }
+        self.shown = *brightness;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strobing_is_slowed_down_to_safe_flashes() {
+        let mut limiter = FlashLimiter::new(SAFE_FLASHES_PER_SECOND);
+        let (dark, lit) = ([0; PIXELS_PER_SCREEN], [255; PIXELS_PER_SCREEN]);
+
+        // A game flipping the whole screen every frame
+        let mut showing = dark;
+        let mut flashes = vec![];
+        for frame in 0..60 {
+            let next = if frame % 2 == 0 { lit } else { dark };
+            if limiter.allow(&next, frame) && next != showing {
+                showing = next;
+                flashes.push(frame);
+            }
+        }
+        assert_eq!(flashes, [0, 21, 42]);
+
+        // Smaller changes are always shown, and flashes once they're due
+        let mut limiter = FlashLimiter::new(SAFE_FLASHES_PER_SECOND);
+        assert!(limiter.allow(&lit, 0));
+        let mut erased = lit;
+        erased[..64].fill(0);
+        assert!(limiter.allow(&erased, 1));
+        assert!(!limiter.allow(&dark, 2));
+        assert!(limiter.allow(&dark, 20));
+    }
+}
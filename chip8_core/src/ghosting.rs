@@ -0,0 +1,71 @@
+/// Keys of the COSMAC VIP's hex keypad as wired in its 4x4 matrix, row by row
+const MATRIX: [[u8; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xC],
+    [0x4, 0x5, 0x6, 0xD],
+    [0x7, 0x8, 0x9, 0xE],
+    [0xA, 0x0, 0xB, 0xF],
+];
+
+/// The keys a scan of the matrix finds with `keys` held. Without diodes, current flows
+/// from a row to a column through any path of held keys, so holding three corners of a
+/// rectangle also reads its fourth corner as held.
+pub(crate) fn ghost_keys(keys: [bool; 16]) -> [bool; 16] {
+    let mut connected = MATRIX.map(|row| row.map(|key| keys[key as usize]));
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (row, column) in (0..4).flat_map(|row| (0..4).map(move |column| (row, column))) {
+            if connected[row][column] {
+                continue;
+            }
+            let ghost = (0..4).any(|other_row| {
+                (0..4).any(|other_column| {
+                    connected[row][other_column]
+                        && connected[other_row][other_column]
+                        && connected[other_row][column]
+                })
+            });
+            if ghost {
+                connected[row][column] = true;
+                changed = true;
+            }
+        }
+    }
+    let mut ghosted = [false; 16];
+    for (row, keys) in MATRIX.iter().enumerate() {
+        for (column, &key) in keys.iter().enumerate() {
+            ghosted[key as usize] = connected[row][column];
+        }
+    }
+    ghosted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ghost_keys;
+
+    fn held(keys: &[usize]) -> [bool; 16] {
+        let mut held = [false; 16];
+        for &key in keys {
+            held[key] = true;
+        }
+        held
+    }
+
+    #[test]
+    fn three_corners_of_a_rectangle_ghost_the_fourth() {
+        // 1, 2 and 4 span the rectangle closed by 5
+        assert_eq!(
+            ghost_keys(held(&[0x1, 0x2, 0x4])),
+            held(&[0x1, 0x2, 0x4, 0x5])
+        );
+        // Two keys on a row, or a diagonal pair, read as they are
+        assert_eq!(ghost_keys(held(&[0x4, 0x6])), held(&[0x4, 0x6]));
+        assert_eq!(ghost_keys(held(&[0x1, 0x5])), held(&[0x1, 0x5]));
+        // Ghosts only appear between rows and columns joined by held keys
+        assert_eq!(
+            ghost_keys(held(&[0x1, 0x2, 0x5, 0x9])),
+            held(&[0x1, 0x2, 0x4, 0x5, 0x9])
+        );
+    }
+}
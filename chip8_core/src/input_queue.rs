@@ -0,0 +1,166 @@
+//! Keypad input made up by the frontend instead of typed: a queue of presses played back
+//! a frame at a time, and encodings turning text into presses for ROMs that read text
+//! from the keypad
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+/// Presses waiting to be played back, each held for a while and then released for a while,
+/// so programs waiting for a key with FX0A see every one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputQueue {
+    presses: VecDeque<u8>,
+    hold_frames: u32,
+    release_frames: u32,
+    /// Key being played back, with the frames left of its press and the release after it
+    current: Option<(u8, u32)>,
+}
+
+impl InputQueue {
+    pub fn new(hold_frames: u32, release_frames: u32) -> Self {
+        Self {
+            presses: VecDeque::new(),
+            hold_frames: hold_frames.max(1),
+            release_frames: release_frames.max(1),
+            current: None,
+        }
+    }
+
+    /// Queue presses of `keys`, one after the other, after those already queued
+    pub fn push(&mut self, keys: impl IntoIterator<Item = u8>) {
+        self.presses.extend(keys.into_iter().map(|key| key & 0xF));
+    }
+
+    /// Forget the presses not played back yet
+    pub fn clear(&mut self) {
+        self.presses.clear();
+        self.current = None;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current.is_none() && self.presses.is_empty()
+    }
+
+    /// Keys the queue holds down this frame, moving it on by a frame
+    pub fn next_frame(&mut self) -> [bool; 16] {
+        let mut keys = [false; 16];
+        if self.current.is_none() {
+            self.current = self
+                .presses
+                .pop_front()
+                .map(|key| (key, self.hold_frames + self.release_frames));
+        }
+        if let Some((key, frames_left)) = self.current {
+            keys[key as usize] = frames_left > self.release_frames;
+            self.current = (frames_left > 1).then_some((key, frames_left - 1));
+        }
+        keys
+    }
+}
+
+/// How text becomes keypad presses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyEncoding {
+    /// Hex digits press their key; everything else is skipped
+    Digits,
+    /// Each ASCII character presses the keys of its two hex digits, high one first
+    Ascii,
+    /// Characters press the keys given for them; those without keys are skipped
+    Map(Vec<(char, Vec<u8>)>),
+}
+
+impl KeyEncoding {
+    /// Read a map of characters to keys, a line each like `A = 4 1`; `space` stands for
+    /// the space character and `#` starts a comment
+    pub fn parse_map(text: &str) -> Result<Self, String> {
+        let mut map = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: &str| format!("line {}: {}", index + 1, message);
+            let (character, keys) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected a character, '=' and keys"))?;
+            let character = match character.trim() {
+                "space" => ' ',
+                character => {
+                    let mut chars = character.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(character), None) => character,
+                        _ => return Err(error("expected a single character before '='")),
+                    }
+                }
+            };
+            let keys = keys
+                .split_whitespace()
+                .map(|key| match u8::from_str_radix(key, 16) {
+                    Ok(key) if key < 16 => Ok(key),
+                    _ => Err(error(&format!("'{}' is not a key from 0 to F", key))),
+                })
+                .collect::<Result<Vec<u8>, String>>()?;
+            map.push((character, keys));
+        }
+        Ok(KeyEncoding::Map(map))
+    }
+
+    /// Presses typing `text`
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        text.chars()
+            .flat_map(|character| match self {
+                KeyEncoding::Digits => character
+                    .to_digit(16)
+                    .map(|digit| vec![digit as u8])
+                    .unwrap_or_default(),
+                KeyEncoding::Ascii if character.is_ascii() => {
+                    let code = character as u8;
+                    vec![code >> 4, code & 0xF]
+                }
+                KeyEncoding::Ascii => Vec::new(),
+                KeyEncoding::Map(map) => map
+                    .iter()
+                    .find(|(mapped, _)| *mapped == character)
+                    .map(|(_, keys)| keys.clone())
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for KeyEncoding {
+    type Err = String;
+
+    /// `digits` or `ascii`; maps are read with [`KeyEncoding::parse_map`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "digits" => Ok(KeyEncoding::Digits),
+            "ascii" => Ok(KeyEncoding::Ascii),
+            _ => Err(format!(
+                "unknown key encoding '{}', expected one of: digits, ascii",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_is_typed_a_key_at_a_time() {
+        assert_eq!(KeyEncoding::Digits.encode("1f, x0"), [0x1, 0xF, 0x0]);
+        assert_eq!(KeyEncoding::Ascii.encode("Hi"), [0x4, 0x8, 0x6, 0x9]);
+        let map = KeyEncoding::parse_map("a = 1 2  # first\nspace = 0\n").unwrap();
+        assert_eq!(map.encode("a b"), [0x1, 0x2, 0x0]);
+        assert!(KeyEncoding::parse_map("ab = 1").is_err());
+        assert!(KeyEncoding::parse_map("a = 10").is_err());
+
+        let mut queue = InputQueue::new(2, 1);
+        queue.push([0x3, 0x3]);
+        let held: Vec<bool> = (0..7).map(|_| queue.next_frame()[0x3]).collect();
+        assert_eq!(held, [true, true, false, true, true, false, false]);
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,631 @@
+use std::fmt;
+
+use crate::extension::{self, OpcodeExtension};
+use crate::{decode_instruction_into_nibbles, CpuState, Platform};
+
+/// A decoded CHIP-8 instruction. Register operands are register numbers (0x0..=0xF).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// 00CN (SUPER-CHIP)
+    ScrollDown { n: u8 },
+    /// 00E0
+    ClearScreen,
+    /// 00EE
+    Return,
+    /// 00FB (SUPER-CHIP)
+    ScrollRight,
+    /// 00FC (SUPER-CHIP)
+    ScrollLeft,
+    /// 02A0 (CHIP-8X)
+    CycleBackground,
+    /// 0NNN, a call to a machine-language routine, see [`crate::MachineRoutine`]
+    CallMachineCode { nnn: u16 },
+    /// 1NNN
+    Jump { nnn: u16 },
+    /// 2NNN
+    Call { nnn: u16 },
+    /// 3XNN
+    SkipIfEqual { x: u8, nn: u8 },
+    /// 4XNN
+    SkipIfNotEqual { x: u8, nn: u8 },
+    /// 5XY0
+    SkipIfRegistersEqual { x: u8, y: u8 },
+    /// 6XNN
+    Set { x: u8, nn: u8 },
+    /// 7XNN
+    Add { x: u8, nn: u8 },
+    /// 8XY0
+    Copy { x: u8, y: u8 },
+    /// 8XY1
+    Or { x: u8, y: u8 },
+    /// 8XY2
+    And { x: u8, y: u8 },
+    /// 8XY3
+    Xor { x: u8, y: u8 },
+    /// 8XY4
+    AddRegisters { x: u8, y: u8 },
+    /// 8XY5
+    Sub { x: u8, y: u8 },
+    /// 8XY6
+    ShiftRight { x: u8, y: u8 },
+    /// 8XY7
+    SubReversed { x: u8, y: u8 },
+    /// 8XYE
+    ShiftLeft { x: u8, y: u8 },
+    /// 9XY0
+    SkipIfRegistersNotEqual { x: u8, y: u8 },
+    /// ANNN
+    SetIndex { nnn: u16 },
+    /// BNNN
+    JumpWithOffset { nnn: u16 },
+    /// BXY0 (CHIP-8X): colour zones 8 pixels wide and 4 tall with VY. VX's low nibble
+    /// is the first column of zones and its high nibble how many more to colour;
+    /// V(X+1) picks the rows the same way
+    SetZoneColors { x: u8, y: u8 },
+    /// BXYN (CHIP-8X): colour N rows of the 8 pixel wide zone at (VX, V(X+1)) with VY
+    SetRowColors { x: u8, y: u8, n: u8 },
+    /// CXNN
+    Random { x: u8, nn: u8 },
+    /// DXYN
+    Draw { x: u8, y: u8, n: u8 },
+    /// EX9E
+    SkipIfKey { x: u8 },
+    /// EXA1
+    SkipIfNotKey { x: u8 },
+    /// EXF2 (CHIP-8X): like EX9E, on the second keypad
+    SkipIfSecondKey { x: u8 },
+    /// EXF5 (CHIP-8X): like EXA1, on the second keypad
+    SkipIfNotSecondKey { x: u8 },
+    /// FX07
+    ReadDelayTimer { x: u8 },
+    /// FX0A
+    WaitForKey { x: u8 },
+    /// FX15
+    SetDelayTimer { x: u8 },
+    /// FX18
+    SetSoundTimer { x: u8 },
+    /// FX1E
+    AddToIndex { x: u8 },
+    /// FX29
+    FontCharacter { x: u8 },
+    /// FX33
+    BinaryCodedDecimal { x: u8 },
+    /// FX55
+    StoreRegisters { x: u8 },
+    /// FX65
+    LoadRegisters { x: u8 },
+    /// FXB0 (bank switching extension): map bank VX into the upper half of memory
+    SelectBank { x: u8 },
+    /// An opcode of an extension added with [`crate::Chip8::add_extension`], which runs it
+    Extension { opcode: u16 },
+}
+
+impl Instruction {
+    /// Decode an opcode, returning `None` for opcodes the core doesn't implement.
+    ///
+    /// Opcodes that mean different things on different platforms decode to their most
+    /// common meaning; use [`Instruction::decode_for`] to decode them for a given platform.
+    pub fn decode(opcode: u16) -> Option<Self> {
+        // Only CHIP-8X reads BNNN as colour instructions
+        let extended = match opcode >> 12 {
+            0xB => None,
+            _ => Self::decode_extended(opcode, &extension::BUILT_IN),
+        };
+        extended.or_else(|| Self::decode_chip8(opcode))
+    }
+
+    /// Decode an opcode the way `platform`'s interpreter reads it
+    pub fn decode_for(opcode: u16, platform: Platform) -> Option<Self> {
+        Self::decode_extended(opcode, extension::for_platform(platform))
+            .or_else(|| Self::decode(opcode))
+    }
+
+    /// Decode an opcode with the first of `extensions` that has it, or as plain CHIP-8
+    pub fn decode_with(opcode: u16, extensions: &[&dyn OpcodeExtension]) -> Option<Self> {
+        Self::decode_extended(opcode, extensions).or_else(|| Self::decode_chip8(opcode))
+    }
+
+    fn decode_extended(opcode: u16, extensions: &[&dyn OpcodeExtension]) -> Option<Self> {
+        extensions
+            .iter()
+            .find_map(|extension| extension.decode(opcode))
+    }
+
+    /// Decode an opcode of the original COSMAC VIP instruction set, without extensions
+    pub fn decode_chip8(opcode: u16) -> Option<Self> {
+        let nibbles = decode_instruction_into_nibbles(opcode);
+        let [_, x, y, n] = nibbles;
+        let nn = (opcode & 0x00FF) as u8;
+        let nnn = opcode & 0x0FFF;
+
+        let instruction = match nibbles {
+            [0x0, 0x0, 0xE, 0x0] => Instruction::ClearScreen,
+            [0x0, 0x0, 0xE, 0xE] => Instruction::Return,
+            // 0000 is what running into empty memory reads, not a call
+            [0x0, _, _, _] if nnn != 0 => Instruction::CallMachineCode { nnn },
+            [0x1, _, _, _] => Instruction::Jump { nnn },
+            [0x2, _, _, _] => Instruction::Call { nnn },
+            [0x3, _, _, _] => Instruction::SkipIfEqual { x, nn },
+            [0x4, _, _, _] => Instruction::SkipIfNotEqual { x, nn },
+            [0x5, _, _, 0x0] => Instruction::SkipIfRegistersEqual { x, y },
+            [0x6, _, _, _] => Instruction::Set { x, nn },
+            [0x7, _, _, _] => Instruction::Add { x, nn },
+            [0x8, _, _, 0x0] => Instruction::Copy { x, y },
+            [0x8, _, _, 0x1] => Instruction::Or { x, y },
+            [0x8, _, _, 0x2] => Instruction::And { x, y },
+            [0x8, _, _, 0x3] => Instruction::Xor { x, y },
+            [0x8, _, _, 0x4] => Instruction::AddRegisters { x, y },
+            [0x8, _, _, 0x5] => Instruction::Sub { x, y },
+            [0x8, _, _, 0x6] => Instruction::ShiftRight { x, y },
+            [0x8, _, _, 0x7] => Instruction::SubReversed { x, y },
+            [0x8, _, _, 0xE] => Instruction::ShiftLeft { x, y },
+            [0x9, _, _, 0x0] => Instruction::SkipIfRegistersNotEqual { x, y },
+            [0xA, _, _, _] => Instruction::SetIndex { nnn },
+            [0xB, _, _, _] => Instruction::JumpWithOffset { nnn },
+            [0xC, _, _, _] => Instruction::Random { x, nn },
+            [0xD, _, _, _] => Instruction::Draw { x, y, n },
+            [0xE, _, 0x9, 0xE] => Instruction::SkipIfKey { x },
+            [0xE, _, 0xA, 0x1] => Instruction::SkipIfNotKey { x },
+            [0xF, _, 0x0, 0x7] => Instruction::ReadDelayTimer { x },
+            [0xF, _, 0x0, 0xA] => Instruction::WaitForKey { x },
+            [0xF, _, 0x1, 0x5] => Instruction::SetDelayTimer { x },
+            [0xF, _, 0x1, 0x8] => Instruction::SetSoundTimer { x },
+            [0xF, _, 0x1, 0xE] => Instruction::AddToIndex { x },
+            [0xF, _, 0x2, 0x9] => Instruction::FontCharacter { x },
+            [0xF, _, 0x3, 0x3] => Instruction::BinaryCodedDecimal { x },
+            [0xF, _, 0x5, 0x5] => Instruction::StoreRegisters { x },
+            [0xF, _, 0x6, 0x5] => Instruction::LoadRegisters { x },
+            _ => return None,
+        };
+        Some(instruction)
+    }
+
+    /// Encode the instruction back into its opcode
+    pub fn opcode(&self) -> u16 {
+        let xy = |prefix: u16, x: u8, y: u8, n: u16| prefix << 12 | (x as u16) << 8 | (y as u16) << 4 | n;
+        let xnn = |prefix: u16, x: u8, nn: u8| prefix << 12 | (x as u16) << 8 | nn as u16;
+        let fx = |x: u8, nn: u16| 0xF000 | (x as u16) << 8 | nn;
+
+        match *self {
+            Instruction::ScrollDown { n } => 0x00C0 | n as u16,
+            Instruction::ClearScreen => 0x00E0,
+            Instruction::Return => 0x00EE,
+            Instruction::ScrollRight => 0x00FB,
+            Instruction::ScrollLeft => 0x00FC,
+            Instruction::CycleBackground => 0x02A0,
+            Instruction::CallMachineCode { nnn } => nnn,
+            Instruction::Jump { nnn } => 0x1000 | nnn,
+            Instruction::Call { nnn } => 0x2000 | nnn,
+            Instruction::SkipIfEqual { x, nn } => xnn(0x3, x, nn),
+            Instruction::SkipIfNotEqual { x, nn } => xnn(0x4, x, nn),
+            Instruction::SkipIfRegistersEqual { x, y } => xy(0x5, x, y, 0x0),
+            Instruction::Set { x, nn } => xnn(0x6, x, nn),
+            Instruction::Add { x, nn } => xnn(0x7, x, nn),
+            Instruction::Copy { x, y } => xy(0x8, x, y, 0x0),
+            Instruction::Or { x, y } => xy(0x8, x, y, 0x1),
+            Instruction::And { x, y } => xy(0x8, x, y, 0x2),
+            Instruction::Xor { x, y } => xy(0x8, x, y, 0x3),
+            Instruction::AddRegisters { x, y } => xy(0x8, x, y, 0x4),
+            Instruction::Sub { x, y } => xy(0x8, x, y, 0x5),
+            Instruction::ShiftRight { x, y } => xy(0x8, x, y, 0x6),
+            Instruction::SubReversed { x, y } => xy(0x8, x, y, 0x7),
+            Instruction::ShiftLeft { x, y } => xy(0x8, x, y, 0xE),
+            Instruction::SkipIfRegistersNotEqual { x, y } => xy(0x9, x, y, 0x0),
+            Instruction::SetIndex { nnn } => 0xA000 | nnn,
+            Instruction::JumpWithOffset { nnn } => 0xB000 | nnn,
+            Instruction::SetZoneColors { x, y } => xy(0xB, x, y, 0x0),
+            Instruction::SetRowColors { x, y, n } => xy(0xB, x, y, n as u16),
+            Instruction::Random { x, nn } => xnn(0xC, x, nn),
+            Instruction::Draw { x, y, n } => xy(0xD, x, y, n as u16),
+            Instruction::SkipIfKey { x } => xnn(0xE, x, 0x9E),
+            Instruction::SkipIfNotKey { x } => xnn(0xE, x, 0xA1),
+            Instruction::SkipIfSecondKey { x } => xnn(0xE, x, 0xF2),
+            Instruction::SkipIfNotSecondKey { x } => xnn(0xE, x, 0xF5),
+            Instruction::ReadDelayTimer { x } => fx(x, 0x07),
+            Instruction::WaitForKey { x } => fx(x, 0x0A),
+            Instruction::SetDelayTimer { x } => fx(x, 0x15),
+            Instruction::SetSoundTimer { x } => fx(x, 0x18),
+            Instruction::AddToIndex { x } => fx(x, 0x1E),
+            Instruction::FontCharacter { x } => fx(x, 0x29),
+            Instruction::BinaryCodedDecimal { x } => fx(x, 0x33),
+            Instruction::StoreRegisters { x } => fx(x, 0x55),
+            Instruction::LoadRegisters { x } => fx(x, 0x65),
+            Instruction::SelectBank { x } => fx(x, 0xB0),
+            Instruction::Extension { opcode } => opcode,
+        }
+    }
+
+    /// The opcode with its operands as letters, like `6XNN`
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            Instruction::ScrollDown { .. } => "00CN",
+            Instruction::ClearScreen => "00E0",
+            Instruction::Return => "00EE",
+            Instruction::ScrollRight => "00FB",
+            Instruction::ScrollLeft => "00FC",
+            Instruction::CycleBackground => "02A0",
+            Instruction::CallMachineCode { .. } => "0NNN",
+            Instruction::Jump { .. } => "1NNN",
+            Instruction::Call { .. } => "2NNN",
+            Instruction::SkipIfEqual { .. } => "3XNN",
+            Instruction::SkipIfNotEqual { .. } => "4XNN",
+            Instruction::SkipIfRegistersEqual { .. } => "5XY0",
+            Instruction::Set { .. } => "6XNN",
+            Instruction::Add { .. } => "7XNN",
+            Instruction::Copy { .. } => "8XY0",
+            Instruction::Or { .. } => "8XY1",
+            Instruction::And { .. } => "8XY2",
+            Instruction::Xor { .. } => "8XY3",
+            Instruction::AddRegisters { .. } => "8XY4",
+            Instruction::Sub { .. } => "8XY5",
+            Instruction::ShiftRight { .. } => "8XY6",
+            Instruction::SubReversed { .. } => "8XY7",
+            Instruction::ShiftLeft { .. } => "8XYE",
+            Instruction::SkipIfRegistersNotEqual { .. } => "9XY0",
+            Instruction::SetIndex { .. } => "ANNN",
+            Instruction::JumpWithOffset { .. } => "BNNN",
+            Instruction::SetZoneColors { .. } => "BXY0",
+            Instruction::SetRowColors { .. } => "BXYN",
+            Instruction::Random { .. } => "CXNN",
+            Instruction::Draw { .. } => "DXYN",
+            Instruction::SkipIfKey { .. } => "EX9E",
+            Instruction::SkipIfNotKey { .. } => "EXA1",
+            Instruction::SkipIfSecondKey { .. } => "EXF2",
+            Instruction::SkipIfNotSecondKey { .. } => "EXF5",
+            Instruction::ReadDelayTimer { .. } => "FX07",
+            Instruction::WaitForKey { .. } => "FX0A",
+            Instruction::SetDelayTimer { .. } => "FX15",
+            Instruction::SetSoundTimer { .. } => "FX18",
+            Instruction::AddToIndex { .. } => "FX1E",
+            Instruction::FontCharacter { .. } => "FX29",
+            Instruction::BinaryCodedDecimal { .. } => "FX33",
+            Instruction::StoreRegisters { .. } => "FX55",
+            Instruction::LoadRegisters { .. } => "FX65",
+            Instruction::SelectBank { .. } => "FXB0",
+            Instruction::Extension { .. } => "EXT",
+        }
+    }
+
+    /// The memory address the instruction jumps to, calls or points I at
+    pub fn address_operand(&self) -> Option<u16> {
+        match *self {
+            Instruction::Jump { nnn }
+            | Instruction::Call { nnn }
+            | Instruction::SetIndex { nnn }
+            | Instruction::JumpWithOffset { nnn } => Some(nnn),
+            _ => None,
+        }
+    }
+
+    /// V registers the instruction reads or writes, VF included when it's used as a flag
+    pub fn registers(&self) -> Vec<u8> {
+        match *self {
+            Instruction::ScrollDown { .. }
+            | Instruction::ScrollRight
+            | Instruction::ScrollLeft
+            | Instruction::CycleBackground
+            | Instruction::CallMachineCode { .. }
+            | Instruction::ClearScreen
+            | Instruction::Return
+            | Instruction::Jump { .. }
+            | Instruction::Call { .. }
+            | Instruction::SetIndex { .. }
+            | Instruction::Extension { .. } => vec![],
+            Instruction::JumpWithOffset { .. } => vec![0x0],
+            Instruction::SkipIfEqual { x, .. }
+            | Instruction::SkipIfNotEqual { x, .. }
+            | Instruction::Set { x, .. }
+            | Instruction::Add { x, .. }
+            | Instruction::Random { x, .. }
+            | Instruction::SkipIfKey { x }
+            | Instruction::SkipIfNotKey { x }
+            | Instruction::SkipIfSecondKey { x }
+            | Instruction::SkipIfNotSecondKey { x }
+            | Instruction::ReadDelayTimer { x }
+            | Instruction::WaitForKey { x }
+            | Instruction::SetDelayTimer { x }
+            | Instruction::SetSoundTimer { x }
+            | Instruction::AddToIndex { x }
+            | Instruction::FontCharacter { x }
+            | Instruction::BinaryCodedDecimal { x }
+            | Instruction::SelectBank { x } => vec![x],
+            Instruction::SkipIfRegistersEqual { x, y }
+            | Instruction::SkipIfRegistersNotEqual { x, y }
+            | Instruction::Copy { x, y } => vec![x, y],
+            Instruction::Or { x, y }
+            | Instruction::And { x, y }
+            | Instruction::Xor { x, y }
+            | Instruction::AddRegisters { x, y }
+            | Instruction::Sub { x, y }
+            | Instruction::ShiftRight { x, y }
+            | Instruction::SubReversed { x, y }
+            | Instruction::ShiftLeft { x, y }
+            | Instruction::Draw { x, y, .. } => vec![x, y, 0xF],
+            Instruction::SetZoneColors { x, y } | Instruction::SetRowColors { x, y, .. } => {
+                vec![x, (x + 1) % 16, y]
+            }
+            Instruction::StoreRegisters { x } | Instruction::LoadRegisters { x } => (0..=x).collect(),
+        }
+    }
+
+    /// Describe in plain English what the instruction is about to do on a machine in `state`
+    pub fn explain(&self, state: &CpuState) -> String {
+        let v = |x: u8| state.v[x as usize];
+        match *self {
+            Instruction::ScrollDown { n } => format!("Scroll the screen down by {} pixels", n),
+            Instruction::ScrollRight => "Scroll the screen right by 4 pixels".to_string(),
+            Instruction::ScrollLeft => "Scroll the screen left by 4 pixels".to_string(),
+            Instruction::CycleBackground => "Switch to the next background colour".to_string(),
+            Instruction::CallMachineCode { nnn } => {
+                format!("Call the machine-language routine at 0x{:03X}", nnn)
+            }
+            Instruction::ClearScreen => "Clear the screen".to_string(),
+            Instruction::Return => match state.stack.last() {
+                Some(addr) => format!("Return from the subroutine to 0x{:03X}", addr),
+                None => "Return from a subroutine, but the stack is empty".to_string(),
+            },
+            Instruction::Jump { nnn } => format!("Jump to 0x{:03X}", nnn),
+            Instruction::Call { nnn } => format!(
+                "Call the subroutine at 0x{:03X}, remembering to come back to 0x{:03X}",
+                nnn,
+                state.pc + 2
+            ),
+            Instruction::SkipIfEqual { x, nn } => format!(
+                "Skip the next instruction if V{:X} ({}) equals {}",
+                x,
+                v(x),
+                nn
+            ),
+            Instruction::SkipIfNotEqual { x, nn } => format!(
+                "Skip the next instruction if V{:X} ({}) is not {}",
+                x,
+                v(x),
+                nn
+            ),
+            Instruction::SkipIfRegistersEqual { x, y } => format!(
+                "Skip the next instruction if V{:X} ({}) equals V{:X} ({})",
+                x,
+                v(x),
+                y,
+                v(y)
+            ),
+            Instruction::SkipIfRegistersNotEqual { x, y } => format!(
+                "Skip the next instruction if V{:X} ({}) is not V{:X} ({})",
+                x,
+                v(x),
+                y,
+                v(y)
+            ),
+            Instruction::Set { x, nn } => format!("Set V{:X} to {}", x, nn),
+            Instruction::Add { x, nn } => format!(
+                "Add {} to V{:X} ({}), wrapping around without touching VF",
+                nn,
+                x,
+                v(x)
+            ),
+            Instruction::Copy { x, y } => format!("Copy V{:X} ({}) into V{:X}", y, v(y), x),
+            Instruction::Or { x, y } => format!("Set V{:X} to V{:X} OR V{:X}", x, x, y),
+            Instruction::And { x, y } => format!("Set V{:X} to V{:X} AND V{:X}", x, x, y),
+            Instruction::Xor { x, y } => format!("Set V{:X} to V{:X} XOR V{:X}", x, x, y),
+            Instruction::AddRegisters { x, y } => format!(
+                "Add V{:X} ({}) to V{:X} ({}), setting VF to 1 on overflow",
+                y,
+                v(y),
+                x,
+                v(x)
+            ),
+            Instruction::Sub { x, y } => format!(
+                "Subtract V{:X} ({}) from V{:X} ({}), setting VF to 0 on borrow",
+                y,
+                v(y),
+                x,
+                v(x)
+            ),
+            Instruction::SubReversed { x, y } => format!(
+                "Set V{:X} to V{:X} ({}) minus V{:X} ({}), setting VF to 0 on borrow",
+                x,
+                y,
+                v(y),
+                x,
+                v(x)
+            ),
+            Instruction::ShiftRight { x, y } => format!(
+                "Shift V{:X} (or V{:X}, depending on quirks) right by one bit into V{:X}, keeping the lost bit in VF",
+                x, y, x
+            ),
+            Instruction::ShiftLeft { x, y } => format!(
+                "Shift V{:X} (or V{:X}, depending on quirks) left by one bit into V{:X}, keeping the lost bit in VF",
+                x, y, x
+            ),
+            Instruction::SetIndex { nnn } => format!("Point I at 0x{:03X}", nnn),
+            Instruction::JumpWithOffset { nnn } => format!(
+                "Jump to 0x{:03X} plus V0 ({}), or plus V{:X} under the CHIP-48 quirk",
+                nnn,
+                v(0x0),
+                nnn >> 8
+            ),
+            Instruction::SetZoneColors { x, y } => format!(
+                "Colour the 8x4 zones picked by V{:X} ({:02X}) and V{:X} ({:02X}) with colour {} (from V{:X})",
+                x,
+                v(x),
+                (x + 1) % 16,
+                v((x + 1) % 16),
+                v(y) & 0b111,
+                y
+            ),
+            Instruction::SetRowColors { x, y, n } => format!(
+                "Colour {} rows of the 8 pixel zone at ({}, {}) with colour {} (from V{:X})",
+                n,
+                v(x),
+                v((x + 1) % 16),
+                v(y) & 0b111,
+                y
+            ),
+            Instruction::Random { x, nn } => format!(
+                "Set V{:X} to a random number masked with 0x{:02X}",
+                x, nn
+            ),
+            Instruction::Draw { x, y, n } => format!(
+                "Draw an 8x{} sprite from I=0x{:03X} at (V{:X}, V{:X}) = ({}, {}), setting VF if any pixel is erased",
+                n,
+                state.i,
+                x,
+                y,
+                v(x),
+                v(y)
+            ),
+            Instruction::SkipIfKey { x } => format!(
+                "Skip the next instruction if key {:X} (from V{:X}) is pressed",
+                v(x),
+                x
+            ),
+            Instruction::SkipIfNotKey { x } => format!(
+                "Skip the next instruction if key {:X} (from V{:X}) is not pressed",
+                v(x),
+                x
+            ),
+            Instruction::SkipIfSecondKey { x } => format!(
+                "Skip the next instruction if key {:X} (from V{:X}) is pressed on the second keypad",
+                v(x),
+                x
+            ),
+            Instruction::SkipIfNotSecondKey { x } => format!(
+                "Skip the next instruction if key {:X} (from V{:X}) is not pressed on the second keypad",
+                v(x),
+                x
+            ),
+            Instruction::ReadDelayTimer { x } => format!(
+                "Copy the delay timer ({}) into V{:X}",
+                state.delay_timer, x
+            ),
+            Instruction::WaitForKey { x } => {
+                format!("Wait until a key is pressed and store it in V{:X}", x)
+            }
+            Instruction::SetDelayTimer { x } => {
+                format!("Set the delay timer to V{:X} ({})", x, v(x))
+            }
+            Instruction::SetSoundTimer { x } => format!(
+                "Set the sound timer to V{:X} ({}), beeping until it reaches zero",
+                x,
+                v(x)
+            ),
+            Instruction::AddToIndex { x } => format!(
+                "Add V{:X} ({}) to I (0x{:03X})",
+                x,
+                v(x),
+                state.i
+            ),
+            Instruction::FontCharacter { x } => format!(
+                "Point I at the font sprite for the hex digit {:X} (from V{:X})",
+                v(x) & 0xF,
+                x
+            ),
+            Instruction::BinaryCodedDecimal { x } => format!(
+                "Write the decimal digits of V{:X} ({}) to memory at I=0x{:03X}",
+                x,
+                v(x),
+                state.i
+            ),
+            Instruction::StoreRegisters { x } => format!(
+                "Store V0 to V{:X} in memory starting at I=0x{:03X}",
+                x, state.i
+            ),
+            Instruction::LoadRegisters { x } => format!(
+                "Load V0 to V{:X} from memory starting at I=0x{:03X}",
+                x, state.i
+            ),
+            Instruction::SelectBank { x } => format!(
+                "Map memory bank {} (from V{:X}) into 0x800 to 0xFFF",
+                v(x),
+                x
+            ),
+            Instruction::Extension { opcode } => {
+                format!("Run {:04X}, an instruction of an added extension", opcode)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Instruction::ScrollDown { n } => write!(f, "SCD {}", n),
+            Instruction::ClearScreen => write!(f, "CLS"),
+            Instruction::Return => write!(f, "RET"),
+            Instruction::ScrollRight => write!(f, "SCR"),
+            Instruction::ScrollLeft => write!(f, "SCL"),
+            Instruction::CycleBackground => write!(f, "BGC"),
+            Instruction::CallMachineCode { nnn } => write!(f, "SYS 0x{:03X}", nnn),
+            Instruction::Jump { nnn } => write!(f, "JP 0x{:03X}", nnn),
+            Instruction::Call { nnn } => write!(f, "CALL 0x{:03X}", nnn),
+            Instruction::SkipIfEqual { x, nn } => write!(f, "SE V{:X}, 0x{:02X}", x, nn),
+            Instruction::SkipIfNotEqual { x, nn } => write!(f, "SNE V{:X}, 0x{:02X}", x, nn),
+            Instruction::SkipIfRegistersEqual { x, y } => write!(f, "SE V{:X}, V{:X}", x, y),
+            Instruction::Set { x, nn } => write!(f, "LD V{:X}, 0x{:02X}", x, nn),
+            Instruction::Add { x, nn } => write!(f, "ADD V{:X}, 0x{:02X}", x, nn),
+            Instruction::Copy { x, y } => write!(f, "LD V{:X}, V{:X}", x, y),
+            Instruction::Or { x, y } => write!(f, "OR V{:X}, V{:X}", x, y),
+            Instruction::And { x, y } => write!(f, "AND V{:X}, V{:X}", x, y),
+            Instruction::Xor { x, y } => write!(f, "XOR V{:X}, V{:X}", x, y),
+            Instruction::AddRegisters { x, y } => write!(f, "ADD V{:X}, V{:X}", x, y),
+            Instruction::Sub { x, y } => write!(f, "SUB V{:X}, V{:X}", x, y),
+            Instruction::ShiftRight { x, y } => write!(f, "SHR V{:X}, V{:X}", x, y),
+            Instruction::SubReversed { x, y } => write!(f, "SUBN V{:X}, V{:X}", x, y),
+            Instruction::ShiftLeft { x, y } => write!(f, "SHL V{:X}, V{:X}", x, y),
+            Instruction::SkipIfRegistersNotEqual { x, y } => write!(f, "SNE V{:X}, V{:X}", x, y),
+            Instruction::SetIndex { nnn } => write!(f, "LD I, 0x{:03X}", nnn),
+            Instruction::JumpWithOffset { nnn } => write!(f, "JP V0, 0x{:03X}", nnn),
+            Instruction::SetZoneColors { x, y } => write!(f, "COL V{:X}, V{:X}", x, y),
+            Instruction::SetRowColors { x, y, n } => write!(f, "COL V{:X}, V{:X}, {}", x, y, n),
+            Instruction::Random { x, nn } => write!(f, "RND V{:X}, 0x{:02X}", x, nn),
+            Instruction::Draw { x, y, n } => write!(f, "DRW V{:X}, V{:X}, {}", x, y, n),
+            Instruction::SkipIfKey { x } => write!(f, "SKP V{:X}", x),
+            Instruction::SkipIfNotKey { x } => write!(f, "SKNP V{:X}", x),
+            Instruction::SkipIfSecondKey { x } => write!(f, "SKP2 V{:X}", x),
+            Instruction::SkipIfNotSecondKey { x } => write!(f, "SKNP2 V{:X}", x),
+            Instruction::ReadDelayTimer { x } => write!(f, "LD V{:X}, DT", x),
+            Instruction::WaitForKey { x } => write!(f, "LD V{:X}, K", x),
+            Instruction::SetDelayTimer { x } => write!(f, "LD DT, V{:X}", x),
+            Instruction::SetSoundTimer { x } => write!(f, "LD ST, V{:X}", x),
+            Instruction::AddToIndex { x } => write!(f, "ADD I, V{:X}", x),
+            Instruction::FontCharacter { x } => write!(f, "LD F, V{:X}", x),
+            Instruction::BinaryCodedDecimal { x } => write!(f, "LD B, V{:X}", x),
+            Instruction::StoreRegisters { x } => write!(f, "LD [I], V{:X}", x),
+            Instruction::LoadRegisters { x } => write!(f, "LD V{:X}, [I]", x),
+            Instruction::SelectBank { x } => write!(f, "BANK V{:X}", x),
+            Instruction::Extension { opcode } => write!(f, "EXT 0x{:04X}", opcode),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_implemented_opcode_round_trips() {
+        for opcode in 0..=u16::MAX {
+            for platform in Platform::ALL {
+                if let Some(instruction) = Instruction::decode_for(opcode, platform) {
+                    assert_eq!(instruction.opcode(), opcode, "{}", instruction);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn instructions_are_explained_with_machine_values() {
+        let mut state = CpuState {
+            i: 0x20A,
+            ..CpuState::default()
+        };
+        state.v[2] = 10;
+        state.v[3] = 4;
+
+        assert_eq!(
+            Instruction::decode(0xD235).unwrap().explain(&state),
+            "Draw an 8x5 sprite from I=0x20A at (V2, V3) = (10, 4), setting VF if any pixel is erased"
+        );
+        assert_eq!(Instruction::decode(0xD235).unwrap().to_string(), "DRW V2, V3, 5");
+    }
+}
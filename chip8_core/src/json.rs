@@ -0,0 +1,329 @@
+//! Just enough JSON to exchange files with other CHIP-8 tools
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Members in document order
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Member `key` of an object, `None` for missing members and non-objects
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Number(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Self {
+        Value::Number(value as f64)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Number(value) => write!(f, "{}", value),
+            Value::String(value) => write_string(f, value),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(members) => {
+                write!(f, "{{")?;
+                for (index, (name, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for character in value.chars() {
+        match character {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            character if (character as u32) < 0x20 => write!(f, "\\u{:04x}", character as u32)?,
+            character => write!(f, "{}", character)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Where and why a document couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    /// Byte offset of the problem
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid JSON at byte {}: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+pub fn parse(text: &str) -> Result<Value, JsonError> {
+    let mut parser = Parser { text, offset: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.offset != text.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> JsonError {
+        JsonError {
+            offset: self.offset,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.offset..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let character = self.peek()?;
+        self.offset += character.len_utf8();
+        Some(character)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|character| character.is_ascii_whitespace())
+        {
+            self.offset += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char, message: &'static str) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(character) if character == expected => Ok(()),
+            _ => Err(self.error(message)),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Value) -> Result<Value, JsonError> {
+        if self.text[self.offset..].starts_with(keyword) {
+            self.offset += keyword.len();
+            Ok(value)
+        } else {
+            Err(self.error("unknown keyword"))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('n') => self.keyword("null", Value::Null),
+            Some('t') => self.keyword("true", Value::Bool(true)),
+            Some('f') => self.keyword("false", Value::Bool(false)),
+            Some('"') => self.string().map(Value::String),
+            Some('[') => {
+                self.offset += 1;
+                let mut values = vec![];
+                self.skip_whitespace();
+                if self.peek() == Some(']') {
+                    self.offset += 1;
+                    return Ok(Value::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(',') => continue,
+                        Some(']') => return Ok(Value::Array(values)),
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some('{') => {
+                self.offset += 1;
+                let mut members = vec![];
+                self.skip_whitespace();
+                if self.peek() == Some('}') {
+                    self.offset += 1;
+                    return Ok(Value::Object(members));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let name = self.string()?;
+                    self.expect(':', "expected ':'")?;
+                    members.push((name, self.value()?));
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(',') => continue,
+                        Some('}') => return Ok(Value::Object(members)),
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            Some(character) if character == '-' || character.is_ascii_digit() => self.number(),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, JsonError> {
+        let start = self.offset;
+        while self
+            .peek()
+            .is_some_and(|character| matches!(character, '-' | '+' | '.' | 'e' | 'E' | '0'..='9'))
+        {
+            self.offset += 1;
+        }
+        self.text[start..self.offset]
+            .parse()
+            .map(Value::Number)
+            .map_err(|_| JsonError {
+                offset: start,
+                message: "invalid number",
+            })
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        if self.next() != Some('"') {
+            return Err(self.error("expected a string"));
+        }
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let escaped = match self.next() {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let digits = self
+                                .text
+                                .get(self.offset..self.offset + 4)
+                                .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+                                .ok_or_else(|| self.error("expected four hex digits"))?;
+                            let code = u32::from_str_radix(digits, 16).ok();
+                            self.offset += 4;
+                            // Surrogate pairs aren't combined; they are rare in CHIP-8 tooling
+                            code.and_then(char::from_u32)
+                                .unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        Some(character @ ('"' | '\\' | '/')) => character,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    string.push(escaped);
+                }
+                Some(character) => string.push(character),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documents_round_trip() {
+        let text = r##"{"program":"# hi\n: main","options":{"tickrate":20,"shiftQuirks":true,"colors":[null,-1.5]}}"##;
+        let value = parse(text).unwrap();
+
+        assert_eq!(
+            value
+                .get("options")
+                .and_then(|options| options.get("tickrate")),
+            Some(&Value::Number(20.0))
+        );
+        assert_eq!(
+            value.get("program").and_then(Value::as_str),
+            Some("# hi\n: main")
+        );
+        assert_eq!(value.to_string(), text);
+        assert_eq!(parse("{\"a\": }").unwrap_err().offset, 6);
+        assert_eq!(parse("\"\\u00e9\""), Ok(Value::String("é".to_string())));
+        assert_eq!(parse("\"\\u1").unwrap_err().offset, 3);
+        assert_eq!(parse("\"\\u+1ab\"").unwrap_err().offset, 3);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{disasm, Instruction};
+
+/// Instructions after an FX0A searched for comparisons against the key
+const LOOKAHEAD: usize = 16;
+
+/// What a program waiting on FX0A does with the key, so frontends can hint at the choices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyPrompt {
+    /// Address of the FX0A
+    pub addr: u16,
+    /// Register receiving the key
+    pub register: u8,
+    /// Keys the following instructions compare the register against, in program order.
+    /// Empty when the program does something less obvious with the key.
+    pub choices: Vec<u8>,
+}
+
+impl KeyPrompt {
+    /// Look at the instructions after the FX0A at `addr` for the keys it expects
+    pub(crate) fn analyze(memory: &[u8], addr: u16, register: u8) -> Self {
+        let mut choices = Vec::new();
+        let mut conditional = false;
+        let mut next = addr + 2;
+        for _ in 0..LOOKAHEAD {
+            let Some(instruction) =
+                disasm::disassemble_at(memory, 0, next).and_then(|found| found.instruction)
+            else {
+                break;
+            };
+            match instruction {
+                // Only values below 16 can be keys
+                Instruction::SkipIfEqual { x, nn } | Instruction::SkipIfNotEqual { x, nn }
+                    if x == register && nn < 16 && !choices.contains(&nn) =>
+                {
+                    choices.push(nn)
+                }
+                _ if overwrites(&instruction, register) => break,
+                // Leaving for good, unless a skip before it makes it a branch
+                Instruction::Jump { .. }
+                | Instruction::JumpWithOffset { .. }
+                | Instruction::Call { .. }
+                | Instruction::Return
+                    if !conditional =>
+                {
+                    break
+                }
+                _ => {}
+            }
+            conditional = is_skip(&instruction);
+            next += 2;
+        }
+        Self {
+            addr,
+            register,
+            choices,
+        }
+    }
+}
+
+fn is_skip(instruction: &Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::SkipIfEqual { .. }
+            | Instruction::SkipIfNotEqual { .. }
+            | Instruction::SkipIfRegistersEqual { .. }
+            | Instruction::SkipIfRegistersNotEqual { .. }
+            | Instruction::SkipIfKey { .. }
+            | Instruction::SkipIfNotKey { .. }
+            | Instruction::SkipIfSecondKey { .. }
+            | Instruction::SkipIfNotSecondKey { .. }
+    )
+}
+
+/// Whether `instruction` may change `register`, after which comparisons say nothing about
+/// the key
+fn overwrites(instruction: &Instruction, register: u8) -> bool {
+    match *instruction {
+        Instruction::Set { x, .. }
+        | Instruction::Add { x, .. }
+        | Instruction::Copy { x, .. }
+        | Instruction::Random { x, .. }
+        | Instruction::ReadDelayTimer { x }
+        | Instruction::WaitForKey { x } => x == register,
+        // These also set VF
+        Instruction::Or { x, .. }
+        | Instruction::And { x, .. }
+        | Instruction::Xor { x, .. }
+        | Instruction::AddRegisters { x, .. }
+        | Instruction::Sub { x, .. }
+        | Instruction::ShiftRight { x, .. }
+        | Instruction::SubReversed { x, .. }
+        | Instruction::ShiftLeft { x, .. } => x == register || register == 0xF,
+        Instruction::LoadRegisters { x } => register <= x,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Chip8, Chip8Event, KeyPrompt};
+
+    #[test]
+    fn menu_choices_are_found_after_the_wait() {
+        let mut chip8 = Chip8::new();
+        chip8.enable_events();
+        chip8.load_rom(&[
+            0xF3, 0x0A, // wait for a key in V3
+            0x43, 0x01, // skip unless it's 1
+            0x12, 0x40, // jump to the first game
+            0x43, 0x02, // skip unless it's 2
+            0x12, 0x80, // jump to the second game
+            0x33, 0x0F, // skip if it's F
+            0x12, 0x00, // otherwise ask again
+            0x63, 0x07, // V3 = 7, so the comparison after it doesn't count
+            0x33, 0x07,
+        ]);
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+        assert_eq!(
+            chip8.key_prompt(),
+            Some(KeyPrompt {
+                addr: 0x200,
+                register: 3,
+                choices: vec![1, 2, 0xF],
+            })
+        );
+        // The wait is announced once
+        assert_eq!(
+            chip8.take_events(),
+            [Chip8Event::WaitingForKey {
+                addr: 0x200,
+                register: 3
+            }]
+        );
+
+        chip8.press_key(2).unwrap();
+        chip8.step().unwrap();
+        assert_eq!(chip8.key_prompt(), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! Several ROMs bundled behind a menu ROM that lets the player pick one with the keypad.
+//!
+//! The menu numbers the games with the built-in font and waits for a key. Once a valid
+//! one is pressed it leaves the game's index in V0 and halts by jumping to itself, which
+//! the frontend notices through [`Launcher::poll`] to swap the game in.
+//!
+//! A bundle is the menu ROM followed by each game, a big-endian `u16` length before its
+//! bytes, and a trailer of the menu's length (`u16`), the game count (`u8`) and
+//! [`BUNDLE_MAGIC`].
+
+use std::fmt;
+
+use crate::{rom_hash, Chip8, MAX_ROM_SIZE, ROM_INITIAL_POSITION};
+
+/// The most games a menu can offer, one per keypad key besides 0
+pub const MAX_GAMES: usize = 15;
+
+/// Last bytes of a launcher bundle
+pub const BUNDLE_MAGIC: &[u8; 8] = b"C8LAUNCH";
+
+const TRAILER_LEN: usize = 3 + BUNDLE_MAGIC.len();
+
+/// Entries per row of the menu
+const MENU_COLUMNS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LauncherError {
+    NoGames,
+    TooManyGames(usize),
+    /// The game at this index doesn't fit in memory
+    GameTooLarge(usize),
+}
+
+impl fmt::Display for LauncherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LauncherError::NoGames => write!(f, "a launcher needs at least one game"),
+            LauncherError::TooManyGames(count) => {
+                write!(
+                    f,
+                    "{} games given, a launcher holds at most {}",
+                    count, MAX_GAMES
+                )
+            }
+            LauncherError::GameTooLarge(index) => write!(
+                f,
+                "game {} is larger than the {} bytes available",
+                index + 1,
+                MAX_ROM_SIZE
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LauncherError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Launcher {
+    menu: Vec<u8>,
+    games: Vec<Vec<u8>>,
+}
+
+impl Launcher {
+    /// A launcher offering `games`, numbered from 1 in the given order
+    pub fn new(games: Vec<Vec<u8>>) -> Result<Self, LauncherError> {
+        if games.is_empty() {
+            return Err(LauncherError::NoGames);
+        }
+        if games.len() > MAX_GAMES {
+            return Err(LauncherError::TooManyGames(games.len()));
+        }
+        if let Some(index) = games.iter().position(|game| game.len() > MAX_ROM_SIZE) {
+            return Err(LauncherError::GameTooLarge(index));
+        }
+        Ok(Self {
+            menu: menu_rom(games.len()),
+            games,
+        })
+    }
+
+    /// The launcher in `data`, or `None` if it isn't a well-formed bundle
+    pub fn from_bundle(data: &[u8]) -> Option<Self> {
+        let trailer = data.len().checked_sub(TRAILER_LEN)?;
+        if &data[trailer + 3..] != BUNDLE_MAGIC {
+            return None;
+        }
+        let menu_len = u16::from_be_bytes([data[trailer], data[trailer + 1]]) as usize;
+        let count = data[trailer + 2] as usize;
+        let menu = data.get(..menu_len)?.to_vec();
+
+        let mut games = Vec::with_capacity(count);
+        let mut offset = menu_len;
+        for _ in 0..count {
+            let len = data.get(offset..offset + 2)?;
+            let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+            games.push(data.get(offset + 2..offset + 2 + len)?.to_vec());
+            offset += 2 + len;
+        }
+        if offset != trailer || menu != menu_rom(count) {
+            return None;
+        }
+        Self::new(games).ok()
+    }
+
+    pub fn to_bundle(&self) -> Vec<u8> {
+        let mut bundle = self.menu.clone();
+        for game in &self.games {
+            bundle.extend_from_slice(&(game.len() as u16).to_be_bytes());
+            bundle.extend_from_slice(game);
+        }
+        bundle.extend_from_slice(&(self.menu.len() as u16).to_be_bytes());
+        bundle.push(self.games.len() as u8);
+        bundle.extend_from_slice(BUNDLE_MAGIC);
+        bundle
+    }
+
+    /// The menu ROM, to load into the machine first
+    pub fn menu(&self) -> &[u8] {
+        &self.menu
+    }
+
+    pub fn games(&self) -> &[Vec<u8>] {
+        &self.games
+    }
+
+    /// If `chip8` is running the menu and a game was picked, swap the game in with
+    /// [`Chip8::swap_rom`] and return its index. Call once per frame.
+    pub fn poll(&self, chip8: &mut Chip8) -> Option<usize> {
+        let select_addr = (ROM_INITIAL_POSITION + self.menu.len() - 2) as u16;
+        let state = chip8.cpu_state();
+        if chip8.rom_hash() != rom_hash(&self.menu) || state.pc != select_addr {
+            return None;
+        }
+        let index = state.v[0] as usize;
+        chip8.swap_rom(self.games.get(index)?);
+        Some(index)
+    }
+}
+
+/// Program listing entries 1 to `count` and halting at its last instruction with the
+/// zero-based index of the chosen one in V0
+fn menu_rom(count: usize) -> Vec<u8> {
+    let mut program: Vec<u16> = vec![0x00E0];
+    for entry in 0..count {
+        let x = 4 + (entry % MENU_COLUMNS) * 8;
+        let y = 10 + (entry / MENU_COLUMNS) * 10;
+        program.extend([
+            0x6000 | (entry as u16 + 1), // V0 = entry number
+            0xF029,                      // I = font digit of V0
+            0x6100 | x as u16,
+            0x6200 | y as u16,
+            0xD125, // draw it at (V1, V2)
+        ]);
+    }
+    let wait = 0x1000 | (ROM_INITIAL_POSITION + 2 * program.len()) as u16;
+    program.extend([
+        0xF00A, // V0 = key
+        0x4000, // skip unless key 0 was pressed
+        wait,   // and wait again
+        0x6100 | (count as u16 + 1),
+        0x8300, // V3 = V0
+        0x8315, // V3 -= count + 1, clearing VF if V0 is a listed entry
+        0x3F00, // skip if it is
+        wait,   // wait again otherwise
+        0x70FF, // entry numbers start at 1, indices at 0
+    ]);
+    let select = 0x1000 | (ROM_INITIAL_POSITION + 2 * program.len()) as u16;
+    program.push(select);
+    program
+        .iter()
+        .flat_map(|opcode| opcode.to_be_bytes())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_menu_swaps_in_the_chosen_game() {
+        let games = vec![vec![0x60, 0x11, 0x12, 0x02], vec![0x60, 0x22, 0x12, 0x02]];
+        let launcher = Launcher::new(games.clone()).unwrap();
+        let bundle = launcher.to_bundle();
+        assert!(bundle.ends_with(BUNDLE_MAGIC));
+        assert_eq!(Launcher::from_bundle(&bundle), Some(launcher.clone()));
+        assert_eq!(Launcher::from_bundle(&bundle[..bundle.len() - 1]), None);
+        assert_eq!(Launcher::from_bundle(&games[0]), None);
+
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(launcher.menu());
+        for _ in 0..3 {
+            chip8.run_frame().unwrap();
+        }
+        assert_eq!(launcher.poll(&mut chip8), None);
+
+        // Keys without an entry are ignored
+        for key in [0x3, 0x2] {
+            chip8.keypad.update_keys(std::array::from_fn(|k| k == key));
+            chip8.run_frame().unwrap();
+            chip8.keypad.update_keys([false; 16]);
+            chip8.run_frame().unwrap();
+        }
+        assert_eq!(launcher.poll(&mut chip8), Some(1));
+        assert_eq!(chip8.rom_hash(), rom_hash(&games[1]));
+        assert_eq!(chip8.cpu_state().pc, 0x200);
+        assert!(chip8.screen.iter().all(|&on| !on));
+
+        chip8.run_frame().unwrap();
+        assert_eq!(chip8.cpu_state().v[0], 0x22);
+        // The game isn't mistaken for the menu
+        assert_eq!(launcher.poll(&mut chip8), None);
+    }
+}
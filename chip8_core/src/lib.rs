@@ -1,7 +1,98 @@
 use std::cmp;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+use std::sync::Arc;
 
 use arrayvec::ArrayVec;
-use rand::Rng;
+
+use banking::Banks;
+use blend::FrameHistory;
+use entropy::Entropy;
+use extension::{BankSwitchingExtension, OpcodeExtension};
+use step_history::StepHistory;
+use disasm::SymbolTable;
+use screen_trigger::ScreenTrigger;
+
+pub mod audio;
+pub mod banking;
+pub mod bitmap;
+mod blend;
+pub mod capabilities;
+mod clock;
+mod color;
+pub mod control_flow;
+mod crash;
+pub mod diagnostic;
+pub mod disasm;
+mod display_buffer;
+mod entropy;
+mod error;
+mod event;
+pub mod extension;
+pub mod flash_limit;
+mod ghosting;
+pub mod input_queue;
+mod instruction;
+pub mod json;
+mod key_prompt;
+pub mod launcher;
+#[cfg(feature = "native")]
+pub mod limits;
+mod machine_code;
+pub mod memory_layout;
+pub mod memory_map;
+pub mod matrix_protocol;
+pub mod memory_search;
+#[cfg(test)]
+mod opcode_tests;
+pub mod lint;
+pub mod octo;
+pub mod patch;
+#[cfg(feature = "profiling")]
+pub mod profiling;
+mod preset;
+mod protection;
+mod quirks;
+mod ram_init;
+pub mod refresh;
+mod rom_db;
+pub mod rom_dump;
+mod rom_info;
+pub mod rom_loader;
+pub mod runner;
+pub mod script;
+pub mod screen_trigger;
+pub mod snapshot;
+mod state;
+mod step_history;
+#[cfg(test)]
+mod test_machine;
+pub mod text;
+pub mod timing;
+mod trace;
+pub mod watch;
+mod wide_glyphs;
+
+pub use blend::{BlendConfig, BlendMode};
+pub use clock::{ClockConfig, TimerOrder};
+pub use color::{BackgroundColor, ColorAttributes, ForegroundColor, COLOR_ZONE_WIDTH};
+pub use crash::CrashReport;
+pub use disasm::DisassembledInstruction;
+pub use display_buffer::DisplayBuffering;
+pub use entropy::RandomSource;
+pub use error::Chip8Error;
+pub use event::{Chip8Event, PixelChange};
+pub use instruction::Instruction;
+pub use key_prompt::KeyPrompt;
+pub use machine_code::MachineRoutine;
+pub use preset::Preset;
+pub use protection::WriteProtection;
+pub use quirks::{Platform, Quirks, ZeroHeightSprite};
+pub use ram_init::RamPattern;
+pub use rom_db::{DisplayColors, RomProfile, SpeedZone};
+pub use rom_info::{detect_platform, Detection, PlatformSource, RomInfo};
+pub use state::{rom_hash, StateError, STATE_VERSION};
+pub use trace::{Register, Registers, TraceEntry, Tracer, VcdWriter};
 
 pub const PIXELS_PER_ROW: usize = 64;
 pub const PIXELS_PER_COLUMN: usize = 32;
@@ -9,7 +100,15 @@ pub const PIXELS_PER_SCREEN: usize = PIXELS_PER_COLUMN * PIXELS_PER_ROW;
 pub const STACK_SIZE: usize = 16;
 pub const RAM_SIZE: usize = 4_096;
 pub const ROM_INITIAL_POSITION: usize = 0x200;
+/// Largest ROM that fits in memory after the interpreter area
+pub const MAX_ROM_SIZE: usize = RAM_SIZE - ROM_INITIAL_POSITION;
 pub const FONT_INITIAL_POSITION: usize = 0x50;
+/// Instructions [`Chip8::run_frame_within`] runs between looking at the time
+const TIME_CHECK_INTERVAL: u32 = 16;
+/// Frames' worth of instructions a late frame may leave for later ones to catch up on
+const MAX_DEFICIT_FRAMES: u32 = 2;
+/// Instructions a new machine's tracer remembers, for [`Chip8::instruction_history`]
+pub const DEFAULT_TRACE_LENGTH: usize = 64;
 
 const FONT_SET: &[u8] = &[
     0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
@@ -30,32 +129,78 @@ const FONT_SET: &[u8] = &[
     0xF0, 0x80, 0xF0, 0x80, 0x80, // F
 ];
 
+/// One of the hex keypads a machine can have
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyBank {
+    First,
+    /// Player 2's keypad, see [`Keypad::second_bank`]
+    Second,
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Keypad {
     pub previous_frame_keys: [bool; 16],
     pub current_frame_keys: [bool; 16],
+    /// Keys of the second keypad, on machines that have one
+    pub second_bank: Option<[bool; 16]>,
+    /// Read the keys through the VIP's key matrix, where some combinations of held keys
+    /// make others appear held too, to check that a ROM copes with real hardware
+    pub ghosting: bool,
 }
 
 impl Keypad {
+    /// A keypad with a second, initially released, bank of 16 keys
+    pub fn with_second_bank() -> Self {
+        Self {
+            second_bank: Some([false; 16]),
+            ..Self::default()
+        }
+    }
+
+    /// Set the keys of the second bank; ignored when there is none
+    pub fn update_second_bank(&mut self, keys: [bool; 16]) {
+        if let Some(second_bank) = &mut self.second_bank {
+            *second_bank = keys;
+        }
+    }
+
+    /// Whether `key` is held on `bank`. Only the low nibble of `key` is used, like on the VIP.
+    pub fn is_pressed(&self, bank: KeyBank, key: u8) -> bool {
+        let key = (key & 0xF) as usize;
+        match bank {
+            KeyBank::First => self.scan(self.current_frame_keys)[key],
+            KeyBank::Second => self.second_bank.is_some_and(|keys| self.scan(keys)[key]),
+        }
+    }
+
+    /// The keys the program sees while `keys` are held
+    fn scan(&self, keys: [bool; 16]) -> [bool; 16] {
+        match self.ghosting {
+            true => ghosting::ghost_keys(keys),
+            false => keys,
+        }
+    }
+
     pub fn update_keys(&mut self, current_frame_keys: [bool; 16]) {
         self.previous_frame_keys = self.current_frame_keys;
         self.current_frame_keys = current_frame_keys;
     }
 
-    fn first_released_keypress(&self) -> Option<usize> {
-        self.previous_frame_keys
+    pub fn first_released_keypress(&self) -> Option<usize> {
+        self.scan(self.previous_frame_keys)
             .into_iter()
-            .zip(self.current_frame_keys.into_iter())
+            .zip(self.scan(self.current_frame_keys))
             .position(|(was_pressed, is_pressed)| was_pressed && !is_pressed)
     }
 
     fn first_pressed_keypress(&self) -> Option<usize> {
-        self.current_frame_keys
+        self.scan(self.current_frame_keys)
             .into_iter()
             .position(|is_pressed| is_pressed)
     }
 }
 
+#[derive(Clone)]
 pub struct Chip8 {
     memory: [u8; RAM_SIZE],
     pub screen: [bool; PIXELS_PER_SCREEN],
@@ -69,11 +214,104 @@ pub struct Chip8 {
     v: [u8; 16],
     pub should_redraw: bool,
     pub keypad: Keypad,
+    /// Hash, size and likely platform of the loaded ROM
+    rom_info: RomInfo,
+    breakpoints: BTreeSet<u16>,
+    platform: Platform,
+    quirks: Quirks,
+    clock: ClockConfig,
+    /// Recent frames, kept while frame blending is enabled
+    frame_history: Option<FrameHistory>,
+    /// Colour board state, only present on platforms that have one
+    colors: Option<ColorAttributes>,
+    /// Memory emulating battery-backed RAM, which frontends persist between runs
+    battery_ram: Option<Range<usize>>,
+    tracer: Option<Tracer>,
+    /// Events not yet taken by the frontend, `None` while nobody listens
+    events: Option<Vec<Chip8Event>>,
+    /// Address of the FX0A being waited on, so each wait is announced once
+    key_wait: Option<u16>,
+    write_protection: WriteProtection,
+    /// Timer frequency accumulated over frames; every `FRAME_RATE` of it is due as a tick
+    timer_phase: u32,
+    /// Instructions earlier frames ran out of time for, see [`Chip8::run_frame_within`]
+    instruction_deficit: u32,
+    /// Labels of the loaded ROM, shared with the frames showing it
+    symbols: Option<Arc<SymbolTable>>,
+    /// Source of CXNN's random numbers
+    entropy: Entropy,
+    /// Pixel changes not yet taken by the frontend, `None` while nobody listens
+    pixel_changes: Option<Vec<PixelChange>>,
+    /// States before the latest instructions, kept while stepping back is enabled
+    step_history: Option<StepHistory>,
+    /// Data placed with [`Chip8::load_segment`], guarded by the write protection like the ROM
+    segments: Vec<Range<usize>>,
+    /// What memory and the V registers hold until the program writes them
+    ram_pattern: RamPattern,
+    /// Noise for the RAM pattern, drawn from by every fill
+    ram_entropy: Entropy,
+    /// Bytes of memory written since power-on, a bit each
+    written: [u64; RAM_SIZE / 64],
+    /// Memory banks FXB0 switches between, while bank switching is enabled
+    banks: Option<Banks>,
+    /// Instructions executed since the machine was created
+    instruction_count: u64,
+    /// Frames ended since the machine was created
+    frame_count: u64,
+    /// Bytes instructions wrote to memory since the machine was created
+    bytes_written: u64,
+    /// Address and instruction of the latest instruction executed
+    last_instruction: Option<(u16, Instruction)>,
+    display_buffering: DisplayBuffering,
+    /// The screen frontends see, unless drawing is immediate
+    front_screen: [bool; PIXELS_PER_SCREEN],
+    /// Frames ended since the front screen was last updated
+    unsynced_frames: u32,
+    /// Time spent on each family of opcodes since the machine was created
+    #[cfg(feature = "profiling")]
+    opcode_profile: profiling::OpcodeProfile,
+    /// Triggers reported by [`Chip8Event::ScreenTriggered`], each with whether the screen
+    /// matched it after the latest instruction that changed it
+    screen_triggers: Vec<(ScreenTrigger, bool)>,
+    /// Whether a screen trigger fired since [`Chip8::run_until_event`] last looked
+    screen_triggered: bool,
+    /// Stand-ins for the machine-language routines 0NNN calls, by address
+    machine_routines: BTreeMap<u16, MachineRoutine>,
+    /// Extensions added with [`Chip8::add_extension`]
+    added_extensions: Vec<&'static dyn OpcodeExtension>,
+    /// Extensions opcodes are decoded with before plain CHIP-8: the added ones, then the
+    /// platform's and bank switching
+    extensions: Vec<&'static dyn OpcodeExtension>,
+}
+
+/// Snapshot of the registers, for debuggers and other tools
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CpuState {
+    pub pc: u16,
+    pub i: u16,
+    pub v: [u8; 16],
+    pub stack: ArrayVec<u16, STACK_SIZE>,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
 }
 
-enum Platforms {
-    CosmacVip,
-    Amiga,
+/// Why [`Chip8::run_until_event`] returned control to the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The screen was cleared or drawn to
+    Draw,
+    /// The sound timer was started while silent
+    SoundStart,
+    /// The program is stuck jumping to itself
+    Halt,
+    /// The program counter reached a breakpoint
+    Breakpoint,
+    /// The screen started showing what a [`ScreenTrigger`] waits for
+    ScreenTrigger,
+    /// The instruction budget ran out
+    Budget,
+    /// A [`Chip8::step_over`] or [`Chip8::step_out`] got where it was going
+    Stepped,
 }
 
 enum NextInstruction {
@@ -93,6 +331,12 @@ impl NextInstruction {
     }
 }
 
+impl Default for Chip8 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Chip8 {
     pub fn new() -> Self {
         let memory = {
@@ -112,85 +356,1224 @@ impl Chip8 {
             v: [0; 16],
             should_redraw: false,
             keypad: Keypad::default(),
+            rom_info: RomInfo::analyze(&[]),
+            breakpoints: BTreeSet::new(),
+            platform: Platform::default(),
+            quirks: Quirks::default(),
+            clock: ClockConfig::default(),
+            frame_history: None,
+            colors: None,
+            battery_ram: None,
+            tracer: Some(Tracer::new(DEFAULT_TRACE_LENGTH)),
+            events: None,
+            key_wait: None,
+            write_protection: WriteProtection::default(),
+            timer_phase: 0,
+            instruction_deficit: 0,
+            symbols: None,
+            entropy: Entropy::default(),
+            pixel_changes: None,
+            step_history: None,
+            segments: Vec::new(),
+            ram_pattern: RamPattern::Zero,
+            ram_entropy: RamPattern::Zero.entropy(),
+            written: [0; RAM_SIZE / 64],
+            banks: None,
+            instruction_count: 0,
+            frame_count: 0,
+            bytes_written: 0,
+            last_instruction: None,
+            display_buffering: DisplayBuffering::Immediate,
+            front_screen: [false; PIXELS_PER_SCREEN],
+            unsynced_frames: 0,
+            #[cfg(feature = "profiling")]
+            opcode_profile: profiling::OpcodeProfile::default(),
+            screen_triggers: Vec::new(),
+            screen_triggered: false,
+            machine_routines: BTreeMap::new(),
+            added_extensions: Vec::new(),
+            extensions: Vec::new(),
+        }
+        .with_written(FONT_INITIAL_POSITION..FONT_INITIAL_POSITION + FONT_SET.len())
+    }
+
+    /// A machine implementing `platform`'s instructions with its quirks
+    pub fn with_platform(platform: Platform) -> Self {
+        let mut chip8 = Self::new();
+        chip8.use_platform(platform);
+        chip8
+    }
+
+    pub fn platform(&self) -> Platform {
+        self.platform
+    }
+
+    pub fn with_quirks(quirks: Quirks) -> Self {
+        Self {
+            quirks,
+            ..Self::new()
         }
     }
 
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    pub fn clock(&self) -> ClockConfig {
+        self.clock
+    }
+
+    pub fn set_clock(&mut self, clock: ClockConfig) {
+        self.clock = clock;
+    }
+
+    /// Load `rom` at 0x200. With bank switching enabled, the part that doesn't fit in
+    /// memory goes to the banks; otherwise the ROM must fit.
     pub fn load_rom(&mut self, rom: &'_ [u8]) {
+        let (code, overflow) = match &self.banks {
+            Some(_) => rom.split_at(rom.len().min(MAX_ROM_SIZE)),
+            None => (rom, &[][..]),
+        };
         let start = 0x200;
-        let end = 0x200 + rom.len();
-        self.memory[start..end].copy_from_slice(rom);
+        let end = 0x200 + code.len();
+        self.memory[start..end].copy_from_slice(code);
+        self.mark_written(start..end);
+        if let Some(banks) = &mut self.banks {
+            banks.load(overflow);
+        }
+        // The banks are data the program pages in, not code to analyze
+        self.rom_info = RomInfo::analyze(code);
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            size = rom.len(),
+            hash = %format_args!("{:016x}", self.rom_info.hash),
+            platform = %self.rom_info.platform,
+            "loaded ROM"
+        );
+        self.symbols = None;
+        if let Some(history) = &mut self.step_history {
+            history.clear();
+        }
     }
 
-    pub fn tick(&mut self) {
-        // fetch instruction from memory
+    /// Place `data` at `addr`, apart from the ROM: fonts, level data and other blobs that
+    /// tools assemble separately. Segments stay when a ROM is loaded after them and are
+    /// guarded by the write protection like the ROM itself.
+    pub fn load_segment(&mut self, addr: usize, data: &[u8]) -> Result<(), Chip8Error> {
+        self.write_memory(addr, data)?;
+        if !data.is_empty() {
+            self.segments.push(addr..addr + data.len());
+        }
+        Ok(())
+    }
+
+    /// Memory ranges placed with [`Chip8::load_segment`], in the order they were loaded
+    pub fn segments(&self) -> &[Range<usize>] {
+        &self.segments
+    }
+
+    /// Fill memory, except the font, and the V registers with `pattern` instead of zeros,
+    /// as the machine powers on. Call it before loading the ROM; [`Chip8::swap_rom`] fills
+    /// memory with it again.
+    pub fn set_ram_pattern(&mut self, pattern: RamPattern) {
+        self.ram_pattern = pattern;
+        self.ram_entropy = pattern.entropy();
+        self.fill_with_ram_pattern();
+    }
+
+    pub fn ram_pattern(&self) -> RamPattern {
+        self.ram_pattern
+    }
+
+    /// Let ROMs up to [`banking::MAX_BANKED_ROM_SIZE`] bytes switch banks of memory with
+    /// FXB0, see [`banking`]. Call it before loading the ROM.
+    pub fn enable_banking(&mut self) {
+        self.banks = Some(Banks::new());
+        self.resolve_extensions();
+    }
+
+    /// The banks, while bank switching is enabled
+    pub fn banks(&self) -> Option<&Banks> {
+        self.banks.as_ref()
+    }
+
+    fn fill_with_ram_pattern(&mut self) {
+        let font = FONT_INITIAL_POSITION..FONT_INITIAL_POSITION + FONT_SET.len();
+        let entropy = &mut self.ram_entropy;
+        self.ram_pattern.fill(&mut self.memory[..font.start], entropy);
+        self.ram_pattern.fill(&mut self.memory[font.end..], entropy);
+        self.ram_pattern.fill(&mut self.v, entropy);
+    }
+
+    fn with_written(mut self, range: Range<usize>) -> Self {
+        self.mark_written(range);
+        self
+    }
+
+    fn mark_written(&mut self, range: Range<usize>) {
+        for addr in range {
+            self.written[addr / 64] |= 1 << (addr % 64);
+        }
+    }
+
+    /// Report the first byte of `range` nothing wrote since power-on, when the write
+    /// protection is strict, as a read by the instruction at `pc`
+    fn check_read(&mut self, pc: u16, range: Range<usize>) {
+        if self.write_protection != WriteProtection::Strict {
+            return;
+        }
+        let unwritten = range
+            .into_iter()
+            .find(|&addr| self.written[addr / 64] & 1 << (addr % 64) == 0);
+        if let Some(addr) = unwritten {
+            self.emit(Chip8Event::UninitializedRead {
+                pc,
+                addr: addr as u16,
+            });
+        }
+    }
+
+    /// Replace the running program with `rom` and start it afresh: memory, registers,
+    /// screen, timers and breakpoints are reset, while the quirks, clock and everything
+    /// enabled on the machine carry over. Like [`Chip8::load_rom`], it only detects the new
+    /// ROM's platform.
+    pub fn swap_rom(&mut self, rom: &[u8]) {
+        let fresh = Self::new();
+        let previous = self.screen;
+        self.memory = fresh.memory;
+        self.screen = fresh.screen;
+        self.record_screen_changes(&previous);
+        self.pc = fresh.pc;
+        self.i = fresh.i;
+        self.stack = fresh.stack;
+        self.delay_timer = fresh.delay_timer;
+        self.sound_timer = fresh.sound_timer;
+        self.v = fresh.v;
+        self.breakpoints = fresh.breakpoints;
+        self.frame_history = self
+            .frame_history
+            .as_ref()
+            .map(|history| FrameHistory::new(history.config()));
+        self.colors = self.colors.as_ref().map(|_| ColorAttributes::default());
+        self.banks = self.banks.as_ref().map(|_| Banks::new());
+        self.battery_ram = None;
+        self.segments.clear();
+        self.written = fresh.written;
+        self.fill_with_ram_pattern();
+        self.key_wait = None;
+        self.timer_phase = 0;
+        self.instruction_deficit = 0;
+        self.last_instruction = None;
+        self.show_screen();
+        for (_, matched) in &mut self.screen_triggers {
+            *matched = false;
+        }
+        self.should_redraw = true;
+        self.load_rom(rom);
+    }
+
+    /// Symbols of the loaded ROM, for listings to use its label names.
+    ///
+    /// Loading a ROM forgets them, so set them after [`Chip8::load_rom`].
+    pub fn set_symbols(&mut self, symbols: Option<SymbolTable>) {
+        self.symbols = symbols.map(Arc::new);
+    }
+
+    pub fn symbols(&self) -> Option<&SymbolTable> {
+        self.symbols.as_deref()
+    }
+
+    pub fn rom_hash(&self) -> u64 {
+        self.rom_info.hash
+    }
+
+    /// Information about the loaded ROM, including the platform it most likely targets.
+    ///
+    /// Loading a ROM only detects the platform; frontends apply it with
+    /// [`Chip8::apply_rom_platform`] or override it with [`Chip8::select_platform`].
+    pub fn rom_info(&self) -> &RomInfo {
+        &self.rom_info
+    }
+
+    /// Use the quirks of the platform reported by [`Chip8::rom_info`]
+    pub fn apply_rom_platform(&mut self) {
+        self.use_platform(self.rom_info.platform);
+    }
+
+    /// Run the ROM with the quirks of `platform`, whatever was detected
+    pub fn select_platform(&mut self, platform: Platform) {
+        self.rom_info.platform = platform;
+        self.rom_info.platform_source = PlatformSource::Selected;
+        self.rom_info.platform_reasons = vec!["selected by the user".to_string()];
+        self.use_platform(platform);
+    }
+
+    fn use_platform(&mut self, platform: Platform) {
+        self.platform = platform;
+        self.quirks = Quirks::for_platform(platform);
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            %platform,
+            source = ?self.rom_info.platform_source,
+            quirks = ?self.quirks,
+            "using the platform's quirks"
+        );
+        self.colors = (platform == Platform::Chip8X).then(ColorAttributes::default);
+        self.keypad.second_bank = match platform.has_second_keypad() {
+            true => Some(self.keypad.second_bank.unwrap_or_default()),
+            false => None,
+        };
+        self.resolve_extensions();
+    }
+
+    /// Decode opcodes with `extension` before the platform's instructions, for instructions
+    /// of interpreters or homebrew the core doesn't know. Extensions added first are tried
+    /// first.
+    pub fn add_extension(&mut self, extension: &'static dyn OpcodeExtension) {
+        self.added_extensions.push(extension);
+        self.resolve_extensions();
+    }
+
+    /// Extensions opcodes are decoded with, in the order they're tried
+    pub fn extensions(&self) -> &[&'static dyn OpcodeExtension] {
+        &self.extensions
+    }
+
+    fn resolve_extensions(&mut self) {
+        self.extensions = self.added_extensions.clone();
+        self.extensions
+            .extend_from_slice(extension::for_platform(self.platform));
+        if self.banks.is_some() {
+            self.extensions.push(&BankSwitchingExtension);
+        }
+    }
+
+    /// Keypad `instruction` reads keys from on the active platform
+    pub fn key_bank(&self, instruction: &Instruction) -> KeyBank {
+        self.platform.key_bank(instruction).unwrap_or(KeyBank::First)
+    }
+
+    /// Colours of the screen, on platforms with a colour board (CHIP-8X)
+    pub fn colors(&self) -> Option<&ColorAttributes> {
+        self.colors.as_ref()
+    }
+
+    /// Draw CXNN's random numbers from a generator seeded with `seed` instead of the
+    /// system's. Without the `native` feature there is no system entropy, so frontends
+    /// should seed every machine they create, for example from `crypto.getRandomValues`
+    /// on the web.
+    pub fn seed_random(&mut self, seed: u64) {
+        self.entropy = Entropy::seeded(seed);
+    }
+
+    /// Draw CXNN's random numbers from `source`, like the VIP's generator for ROMs that
+    /// expect its sequences
+    pub fn set_random_source(&mut self, source: RandomSource) {
+        self.entropy = Entropy::new(source);
+    }
+
+    pub fn write_protection(&self) -> WriteProtection {
+        self.write_protection
+    }
+
+    /// Guard the interpreter area, the loaded ROM and its segments against writes by the
+    /// program
+    pub fn set_write_protection(&mut self, write_protection: WriteProtection) {
+        self.write_protection = write_protection;
+    }
+
+    /// The `len` bytes at `addr`, when they're all inside memory. I can point anywhere up
+    /// to 0xFFFF, so instructions reading or writing through it check first.
+    fn memory_range(&self, addr: usize, len: usize) -> Result<Range<usize>, Chip8Error> {
+        if addr + len > RAM_SIZE {
+            return Err(Chip8Error::OutOfBounds { addr, len });
+        }
+        Ok(addr..addr + len)
+    }
+
+    /// Apply the write protection to a write of `len` bytes at `addr` by the current
+    /// instruction
+    fn check_write(&mut self, addr: usize, len: usize) -> Result<(), Chip8Error> {
+        let protected_end = ROM_INITIAL_POSITION + self.rom_info.size;
+        let in_segment = self
+            .segments
+            .iter()
+            .any(|segment| addr < segment.end && segment.start < addr + len);
+        if len == 0 || (addr >= protected_end && !in_segment) {
+            return Ok(());
+        }
+        let pc = self.pc - 2;
+        let addr = addr as u16;
+        match self.write_protection {
+            WriteProtection::Off => Ok(()),
+            WriteProtection::Warn => {
+                self.emit(Chip8Event::IllegalWrite { pc, addr });
+                Ok(())
+            }
+            WriteProtection::Strict => Err(Chip8Error::IllegalWrite { pc, addr }),
+        }
+    }
+
+    /// Apply the write protection to executing at `pc`: only corrupted jumps and returns
+    /// lead to odd addresses or into the interpreter's area below the program, font included
+    fn check_pc(&mut self, pc: u16) -> Result<(), Chip8Error> {
+        if pc.is_multiple_of(2) && pc as usize >= ROM_INITIAL_POSITION {
+            return Ok(());
+        }
+        match self.write_protection {
+            WriteProtection::Off => Ok(()),
+            WriteProtection::Warn => {
+                self.emit(Chip8Event::StrayExecution { pc });
+                Ok(())
+            }
+            WriteProtection::Strict => Err(Chip8Error::StrayExecution { pc }),
+        }
+    }
+
+    /// Mark a memory range as battery-backed so its contents can be saved and restored
+    pub fn set_battery_ram(&mut self, range: Option<Range<usize>>) -> Result<(), Chip8Error> {
+        if let Some(range) = &range {
+            if range.is_empty() || range.end > RAM_SIZE {
+                return Err(Chip8Error::OutOfBounds {
+                    addr: range.start,
+                    len: range.len(),
+                });
+            }
+        }
+        self.battery_ram = range;
+        Ok(())
+    }
+
+    /// Current contents of the battery-backed memory, if any is configured
+    pub fn battery_ram(&self) -> Option<&[u8]> {
+        self.battery_ram.clone().map(|range| &self.memory[range])
+    }
+
+    /// Restore battery-backed memory saved from a previous run. Call after `load_rom`.
+    pub fn restore_battery_ram(&mut self, data: &[u8]) -> Result<(), Chip8Error> {
+        let Some(range) = self.battery_ram.clone() else {
+            return Err(Chip8Error::SizeMismatch {
+                expected: 0,
+                found: data.len(),
+            });
+        };
+        if data.len() != range.len() {
+            return Err(Chip8Error::SizeMismatch {
+                expected: range.len(),
+                found: data.len(),
+            });
+        }
+        self.memory[range.clone()].copy_from_slice(data);
+        self.mark_written(range);
+        Ok(())
+    }
+
+    pub fn add_breakpoint(&mut self, addr: u16) {
+        self.breakpoints.insert(addr);
+    }
+
+    pub fn remove_breakpoint(&mut self, addr: u16) {
+        self.breakpoints.remove(&addr);
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = u16> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Run `routine` for 0NNN calls to `addr` instead of stopping with
+    /// [`Chip8Error::MachineCode`]
+    pub fn set_machine_routine(&mut self, addr: u16, routine: MachineRoutine) {
+        self.machine_routines.insert(addr, routine);
+    }
+
+    pub fn machine_routines(&self) -> impl Iterator<Item = (u16, MachineRoutine)> + '_ {
+        self.machine_routines
+            .iter()
+            .map(|(&addr, &routine)| (addr, routine))
+    }
+
+    /// Run until something a frontend may want to react to happens, executing at most
+    /// `max_instructions` instructions.
+    ///
+    /// A breakpoint stops execution before the instruction at its address runs, unless
+    /// the machine is already sitting on it when called, so calling again resumes.
+    pub fn run_until_event(&mut self, max_instructions: usize) -> Result<StopReason, Chip8Error> {
+        for executed in 0..max_instructions {
+            if executed > 0 && self.breakpoints.contains(&self.pc) {
+                return Ok(StopReason::Breakpoint);
+            }
+
+            let instruction = self.fetch()?;
+            if instruction == 0x1000 | self.pc {
+                return Ok(StopReason::Halt);
+            }
+
+            let was_silent = self.sound_timer == 0;
+            self.screen_triggered = false;
+            self.tick()?;
+
+            if self.screen_triggered {
+                return Ok(StopReason::ScreenTrigger);
+            }
+            if instruction == 0x00E0 || instruction & 0xF000 == 0xD000 {
+                return Ok(StopReason::Draw);
+            }
+            if was_silent && self.sound_timer > 0 {
+                return Ok(StopReason::SoundStart);
+            }
+        }
+        Ok(StopReason::Budget)
+    }
+
+    /// How many subroutine calls deep the program is
+    pub fn stack_depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Execute the instruction at the program counter like [`Chip8::step`], except that a
+    /// 2NNN call runs until the subroutine returns to the instruction after it, as if a
+    /// breakpoint were there. Other breakpoints still stop it, and so does running
+    /// `max_instructions` instructions. The timers count down once per frame's worth of
+    /// instructions, as in [`Chip8::run_frame`], so delay loops in the subroutine end.
+    pub fn step_over(&mut self, max_instructions: usize) -> Result<StopReason, Chip8Error> {
+        if !matches!(self.current_instruction(), Some(Instruction::Call { .. })) {
+            self.step()?;
+            return Ok(StopReason::Stepped);
+        }
+        let depth = self.stack.len();
+        let returned_to = self.pc + 2;
+        self.run_until(max_instructions, |chip8| {
+            chip8.stack.len() == depth && chip8.pc == returned_to
+        })
+    }
+
+    /// Run until the current subroutine returns, stopping like [`Chip8::step_over`]. Outside
+    /// any subroutine this is a single step.
+    pub fn step_out(&mut self, max_instructions: usize) -> Result<StopReason, Chip8Error> {
+        let depth = self.stack.len();
+        if depth == 0 {
+            self.step()?;
+            return Ok(StopReason::Stepped);
+        }
+        self.run_until(max_instructions, |chip8| chip8.stack.len() < depth)
+    }
+
+    fn run_until(
+        &mut self,
+        max_instructions: usize,
+        done: impl Fn(&Self) -> bool,
+    ) -> Result<StopReason, Chip8Error> {
+        let mut executed_this_frame = 0;
+        for executed in 0..max_instructions {
+            if executed > 0 && self.breakpoints.contains(&self.pc) {
+                return Ok(StopReason::Breakpoint);
+            }
+            self.tick()?;
+            executed_this_frame += 1;
+            if executed_this_frame >= self.instructions_per_frame() {
+                self.tick_due_timers();
+                self.end_frame();
+                executed_this_frame = 0;
+            }
+            if done(self) {
+                return Ok(StopReason::Stepped);
+            }
+        }
+        Ok(StopReason::Budget)
+    }
+
+    fn fetch(&self) -> Result<u16, Chip8Error> {
         let pc = self.pc as usize;
-        let instruction = u16::from_be_bytes([self.memory[pc], self.memory[pc + 1]]);
+        if pc + 1 >= RAM_SIZE {
+            return Err(Chip8Error::PcOutOfBounds { pc: self.pc });
+        }
+        Ok(u16::from_be_bytes([self.memory[pc], self.memory[pc + 1]]))
+    }
+
+    /// Run one 60 Hz frame: the configured number of instructions and as many timer ticks
+    /// as the timer frequency makes due (one at 60 Hz, none every sixth frame at 50 Hz),
+    /// in the clock's [`TimerOrder`]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, err(level = "warn"))
+    )]
+    pub fn run_frame(&mut self) -> Result<(), Chip8Error> {
+        if self.clock.timer_order == TimerOrder::BeforeInstructions {
+            self.tick_due_timers();
+        }
+        for _ in 0..self.instructions_per_frame() {
+            self.tick()?;
+        }
+        if self.clock.timer_order == TimerOrder::AfterInstructions {
+            self.tick_due_timers();
+        }
+        self.end_frame();
+        Ok(())
+    }
+
+    /// Run a frame like [`Chip8::run_frame`], but stop early once `out_of_time` says the
+    /// frame's wall-clock deadline is near; it's asked every few instructions. Instructions
+    /// left over are added to the next frames' budgets, up to a couple of frames' worth, so
+    /// a slow frame delays the game for a moment instead of slowing it down. Timers tick
+    /// once per frame either way, in the clock's [`TimerOrder`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, err(level = "warn"))
+    )]
+    pub fn run_frame_within(
+        &mut self,
+        mut out_of_time: impl FnMut() -> bool,
+    ) -> Result<(), Chip8Error> {
+        if self.clock.timer_order == TimerOrder::BeforeInstructions {
+            self.tick_due_timers();
+        }
+        let instructions_per_frame = self.instructions_per_frame();
+        let budget = instructions_per_frame + self.instruction_deficit;
+        // A fault leaves the rest of the budget to nobody
+        self.instruction_deficit = 0;
+        let mut executed = 0;
+        while executed < budget {
+            if executed > 0 && executed % TIME_CHECK_INTERVAL == 0 && out_of_time() {
+                break;
+            }
+            self.tick()?;
+            executed += 1;
+        }
+        let max_deficit = instructions_per_frame * MAX_DEFICIT_FRAMES;
+        self.instruction_deficit = (budget - executed).min(max_deficit);
+        if self.clock.timer_order == TimerOrder::AfterInstructions {
+            self.tick_due_timers();
+        }
+        self.end_frame();
+        Ok(())
+    }
+
+    /// Instructions the next frame runs: those of the ROM profile's speed zone the program
+    /// counter is in, the clock's everywhere else
+    pub fn instructions_per_frame(&self) -> u32 {
+        let zones = self
+            .rom_info
+            .profile
+            .map_or(&[][..], |profile| profile.speed_zones);
+        zones
+            .iter()
+            .find(|zone| zone.contains(self.pc))
+            .map_or(self.clock.instructions_per_frame, |zone| {
+                zone.instructions_per_frame
+            })
+    }
+
+    /// Tick the timers as often as their frequency makes due in a frame
+    fn tick_due_timers(&mut self) {
+        let frame_rate = runner::FRAME_RATE as u32;
+        self.timer_phase += self.clock.timer_frequency;
+        for _ in 0..self.timer_phase / frame_rate {
+            self.count_timers_down();
+        }
+        self.timer_phase %= frame_rate;
+    }
+
+    /// Count the delay and sound timers down once and end the frame for
+    /// [`Chip8::blended_frame`], for frontends pacing the timers themselves
+    pub fn tick_timers(&mut self) {
+        self.count_timers_down();
+        self.end_frame();
+    }
+
+    fn count_timers_down(&mut self) {
+        self.delay_timer = self.delay_timer.saturating_sub(1);
+        if self.sound_timer == 1 {
+            self.emit(Chip8Event::SoundStopped);
+        }
+        self.sound_timer = self.sound_timer.saturating_sub(1);
+    }
+
+    fn end_frame(&mut self) {
+        match self.display_buffering {
+            DisplayBuffering::Immediate => {}
+            DisplayBuffering::Frame => self.show_screen(),
+            DisplayBuffering::Sync => {
+                self.unsynced_frames += 1;
+                if self.unsynced_frames >= display_buffer::MAX_UNSYNCED_FRAMES {
+                    self.show_screen();
+                }
+            }
+        }
+        let screen = *self.displayed_screen();
+        if let Some(history) = &mut self.frame_history {
+            history.record(&screen);
+        }
+        self.frame_count += 1;
+    }
+
+    /// Frames ended since the machine was created
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn display_buffering(&self) -> DisplayBuffering {
+        self.display_buffering
+    }
+
+    /// Choose when what instructions draw becomes visible in [`Chip8::displayed_screen`].
+    /// The screen as it is now is shown right away.
+    pub fn set_display_buffering(&mut self, buffering: DisplayBuffering) {
+        self.display_buffering = buffering;
+        self.show_screen();
+    }
+
+    /// The screen as frontends should show it: [`Chip8::screen`] while drawing is
+    /// immediate, otherwise the last finished picture, see [`DisplayBuffering`]
+    pub fn displayed_screen(&self) -> &[bool; PIXELS_PER_SCREEN] {
+        match self.display_buffering {
+            DisplayBuffering::Immediate => &self.screen,
+            DisplayBuffering::Frame | DisplayBuffering::Sync => &self.front_screen,
+        }
+    }
+
+    /// Make the screen instructions drew on visible
+    fn show_screen(&mut self) {
+        self.front_screen = self.screen;
+        self.unsynced_frames = 0;
+    }
+
+    /// Blend the last few frames for [`Chip8::blended_frame`], or stop with `None`
+    pub fn set_frame_blending(&mut self, config: Option<BlendConfig>) {
+        self.frame_history = config.map(FrameHistory::new);
+    }
+
+    pub fn frame_blending(&self) -> Option<BlendConfig> {
+        self.frame_history.as_ref().map(FrameHistory::config)
+    }
+
+    /// Brightness of each pixel from 0 to 255, in screen order.
+    ///
+    /// With frame blending enabled this combines the screen with the last frames, so every
+    /// frontend can show flicker-free output the same way; otherwise lit pixels are 255.
+    pub fn blended_frame(&self) -> [u8; PIXELS_PER_SCREEN] {
+        match &self.frame_history {
+            Some(history) => history.blend(self.displayed_screen()),
+            None => self.displayed_screen().map(|on| if on { u8::MAX } else { 0 }),
+        }
+    }
+
+    /// Whether the buzzer should be sounding
+    pub fn is_beeping(&self) -> bool {
+        self.sound_timer > 0
+    }
+
+    /// Whether nothing will change until a key is pressed: the program waits in `FX0A` or is
+    /// stuck jumping to itself, and both timers have stopped. Frontends can then wait for input
+    /// events instead of running 60 frames per second.
+    pub fn is_idle(&self) -> bool {
+        if self.delay_timer > 0 || self.sound_timer > 0 {
+            return false;
+        }
+        match self.fetch() {
+            Ok(opcode) if opcode == 0x1000 | self.pc => true,
+            Ok(_) => {
+                matches!(self.current_instruction(), Some(Instruction::WaitForKey { .. }))
+                    && self.keypad.first_pressed_keypress().is_none()
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// The register and likely choices of the FX0A the machine is waiting on, if it is
+    pub fn key_prompt(&self) -> Option<KeyPrompt> {
+        match self.current_instruction()? {
+            Instruction::WaitForKey { x } => Some(KeyPrompt::analyze(&self.memory, self.pc, x)),
+            _ => None,
+        }
+    }
+
+    /// Hold down `key`, from 0 to F, until [`Chip8::release_key`], for embedders driving
+    /// the keypad themselves
+    pub fn press_key(&mut self, key: usize) -> Result<(), Chip8Error> {
+        self.set_key(key, true)
+    }
+
+    /// Let go of `key`, from 0 to F
+    pub fn release_key(&mut self, key: usize) -> Result<(), Chip8Error> {
+        self.set_key(key, false)
+    }
+
+    fn set_key(&mut self, key: usize, down: bool) -> Result<(), Chip8Error> {
+        let held = self.keypad.current_frame_keys.get_mut(key);
+        *held.ok_or(Chip8Error::NoSuchKey { key })? = down;
+        Ok(())
+    }
+
+    /// Whether the pixel at column `x` and row `y` is lit
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        self.screen[index_from_point((y, x))]
+    }
+
+    /// Turn the pixel at column `x` and row `y` on or off, for debuggers and experiments
+    pub fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+        let index = index_from_point((y, x));
+        if self.screen[index] != on {
+            self.record_pixel(index, on);
+        }
+        self.screen[index] = on;
+        self.should_redraw = true;
+    }
+
+    /// Start collecting [`PixelChange`]s for [`Chip8::take_pixel_changes`]
+    pub fn enable_pixel_changes(&mut self) {
+        self.pixel_changes.get_or_insert_with(Vec::new);
+    }
+
+    /// Pixels that changed since the last call, in the order they changed. A pixel may
+    /// appear more than once; its last change is its current state.
+    pub fn take_pixel_changes(&mut self) -> Vec<PixelChange> {
+        self.pixel_changes
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    fn record_pixel(&mut self, index: usize, on: bool) {
+        if let Some(changes) = &mut self.pixel_changes {
+            let (y, x) = point_from_index(index);
+            changes.push(PixelChange {
+                x: x as u8,
+                y: y as u8,
+                on,
+            });
+        }
+    }
+
+    /// Record every pixel that differs between `previous` and the current screen
+    fn record_screen_changes(&mut self, previous: &[bool; PIXELS_PER_SCREEN]) {
+        if self.pixel_changes.is_none() {
+            return;
+        }
+        for (index, &was_on) in previous.iter().enumerate() {
+            let on = self.screen[index];
+            if was_on != on {
+                self.record_pixel(index, on);
+            }
+        }
+    }
+
+    /// Start collecting [`Chip8Event`]s for [`Chip8::take_events`]
+    pub fn enable_events(&mut self) {
+        self.events.get_or_insert_with(Vec::new);
+    }
+
+    /// Events that happened since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<Chip8Event> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    fn emit(&mut self, event: Chip8Event) {
+        if let Some(events) = &mut self.events {
+            events.push(event);
+        }
+    }
+
+    /// Remember the last `capacity` executed instructions, instead of the
+    /// [`DEFAULT_TRACE_LENGTH`] a new machine remembers
+    pub fn enable_tracer(&mut self, capacity: usize) {
+        self.tracer = Some(Tracer::new(capacity));
+    }
+
+    pub fn disable_tracer(&mut self) {
+        self.tracer = None;
+    }
+
+    pub fn tracer(&self) -> Option<&Tracer> {
+        self.tracer.as_ref()
+    }
+
+    pub fn tracer_mut(&mut self) -> Option<&mut Tracer> {
+        self.tracer.as_mut()
+    }
+
+    /// The last `n` executed instructions the tracer remembers, oldest first, with the
+    /// frame each ran in and the registers it changed; none once the tracer is disabled
+    pub fn instruction_history(&self, n: usize) -> impl DoubleEndedIterator<Item = &TraceEntry> {
+        let entries = self.tracer.as_ref().map(|tracer| {
+            let entries = tracer.entries();
+            let skipped = entries.len().saturating_sub(n);
+            entries.skip(skipped)
+        });
+        entries.into_iter().flatten()
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    /// The `height` rows of the 8 pixel wide sprite at `addr`, as DXYN would draw them
+    pub fn decode_sprite(&self, addr: u16, height: usize) -> Vec<[bool; 8]> {
+        decode_sprite(&self.memory, addr, height)
+    }
+
+    /// Write `data` to memory starting at `addr`, to set a machine up before running it or
+    /// for debuggers and cheats to change it while it runs
+    pub fn write_memory(&mut self, addr: usize, data: &[u8]) -> Result<(), Chip8Error> {
+        let end = addr
+            .checked_add(data.len())
+            .filter(|&end| end <= RAM_SIZE)
+            .ok_or(Chip8Error::OutOfBounds {
+                addr,
+                len: data.len(),
+            })?;
+        self.memory[addr..end].copy_from_slice(data);
+        self.mark_written(addr..end);
+        Ok(())
+    }
+
+    /// Set the register VX
+    pub fn set_v(&mut self, x: usize, value: u8) -> Result<(), Chip8Error> {
+        let register = self.v.get_mut(x).ok_or(Chip8Error::NoSuchRegister { x })?;
+        *register = value;
+        Ok(())
+    }
+
+    /// Point I at `addr`, which must be in memory
+    pub fn set_i(&mut self, addr: u16) -> Result<(), Chip8Error> {
+        if addr as usize >= RAM_SIZE {
+            return Err(Chip8Error::OutOfBounds {
+                addr: addr as usize,
+                len: 1,
+            });
+        }
+        self.i = addr;
+        Ok(())
+    }
+
+    /// Continue execution at `addr`, which must leave room for a whole instruction
+    pub fn set_pc(&mut self, addr: u16) -> Result<(), Chip8Error> {
+        if addr as usize + 1 >= RAM_SIZE {
+            return Err(Chip8Error::PcOutOfBounds { pc: addr });
+        }
+        self.pc = addr;
+        self.key_wait = None;
+        Ok(())
+    }
+
+    pub fn cpu_state(&self) -> CpuState {
+        CpuState {
+            pc: self.pc,
+            i: self.i,
+            v: self.v,
+            stack: self.stack.clone(),
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+        }
+    }
+
+    fn registers(&self) -> Registers {
+        Registers {
+            v: self.v,
+            i: self.i,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+        }
+    }
+
+    /// The instruction at the program counter, which the next tick will execute
+    pub fn current_instruction(&self) -> Option<Instruction> {
+        let opcode = self.fetch().ok()?;
+        let added = self.added_extensions.iter().find_map(|extension| extension.decode(opcode));
+        added.or_else(|| Instruction::decode_for(opcode, self.platform))
+    }
+
+    /// Execute a single instruction and return it.
+    ///
+    /// On error the machine is left as it was before the faulting instruction.
+    pub fn step(&mut self) -> Result<Instruction, Chip8Error> {
+        // fetch instruction from memory
+        let pc = self.pc;
+        let opcode = self.fetch()?;
+        self.check_pc(pc)?;
+        self.check_read(pc, pc as usize..pc as usize + 2);
         // decode instruction
-        let nibbles = decode_instruction_into_nibbles(instruction);
-        let (x, y, n) = {
-            let [_, x, y, n] = nibbles;
-            (x as usize, y as usize, n)
+        let decoded = Instruction::decode_with(opcode, &self.extensions);
+        let Some(instruction) = decoded.filter(|instruction| self.supports(instruction)) else {
+            return Err(match Instruction::decode_for(opcode, self.platform) {
+                // SUPER-CHIP and later extensions took some of these opcodes
+                Some(Instruction::CallMachineCode { nnn })
+                    if matches!(self.platform, Platform::CosmacVip | Platform::Chip8X) =>
+                {
+                    Chip8Error::MachineCode { pc, addr: nnn }
+                }
+                _ => Chip8Error::UnknownOpcode { pc, opcode },
+            });
         };
-        let nn = (instruction & 0x00FF) as u8;
-        let nnn = instruction & 0x0FFF;
 
+        let snapshot = self.step_history.is_some().then(|| self.save_state());
+        let before = self.tracer.is_some().then(|| self.registers());
         self.pc += 2;
         // execute instruction
-        let next_instruction = match nibbles {
-            [0x0, 0x0, 0xE, 0x0] => self.execute_00e0(),
-            [0x0, 0x0, 0xE, 0xE] => self.execute_00ee(),
-            [0x1, _, _, _] => self.execute_1nnn(nnn),
-            [0x2, _, _, _] => self.execute_2nnn(nnn),
-            [0x3, _, _, _] => self.execute_3xnn(x, nn),
-            [0x4, _, _, _] => self.execute_4xnn(x, nn),
-            [0x5, _, _, 0x0] => self.execute_5xy0(x, y),
-            [0x6, _, _, _] => self.execute_6xnn(x, nn),
-            [0x7, _, _, _] => self.execute_7xnn(x, nn),
-            [0x8, _, _, 0x0] => self.execute_8xy0(x, y),
-            [0x8, _, _, 0x1] => self.execute_8xy1(x, y),
-            [0x8, _, _, 0x2] => self.execute_8xy2(x, y),
-            [0x8, _, _, 0x3] => self.execute_8xy3(x, y),
-            [0x8, _, _, 0x4] => self.execute_8xy4(x, y),
-            [0x8, _, _, 0x5] => self.execute_8xy5(x, y),
-            [0x8, _, _, 0x6] => self.execute_8xy6(x, y),
-            [0x8, _, _, 0x7] => self.execute_8xy7(x, y),
-            [0x8, _, _, 0xE] => self.execute_8xye(x, y),
-            [0xA, _, _, _] => self.execute_annn(nnn),
-            [0xB, _, _, _] => self.execute_bnnn(nnn),
-            [0xC, _, _, _] => self.execute_cxnn(x, nn),
-            [0xD, _, _, _] => self.execute_dxyn(x, y, n),
-            [0xE, _, 0x9, 0xE] => self.execute_ex9e(x),
-            [0xE, _, 0xA, 0x1] => self.execute_exa1(x),
-            [0xF, _, 0x0, 0x7] => self.execute_fx07(x),
-            [0xF, _, 0x1, 0x5] => self.execute_fx15(x),
-            [0xF, _, 0x1, 0x8] => self.execute_fx18(x),
-            [0xF, _, 0x1, 0xE] => self.execute_fx1e(x),
-            [0xF, _, 0x0, 0xA] => self.execute_fx0a(x),
-            [0xF, _, 0x2, 0x9] => self.execute_fx29(x),
-            [0xF, _, 0x3, 0x3] => self.execute_fx33(x),
-            [0xF, _, 0x5, 0x5] => self.execute_fx55(x),
-            [0xF, _, 0x6, 0x5] => self.execute_fx65(x),
-            [0x9, _, _, 0x0] => self.execute_9xy0(x, y),
-            _ => todo!(),
+        #[cfg(feature = "profiling")]
+        let started = std::time::Instant::now();
+        let next_instruction = match self.execute(instruction) {
+            Ok(next_instruction) => next_instruction,
+            Err(err) => {
+                self.pc = pc;
+                return Err(err);
+            }
         };
+        #[cfg(feature = "profiling")]
+        self.opcode_profile.record(opcode, started.elapsed());
+        if let Some(before) = before {
+            let after = self.registers();
+            if let Some(tracer) = &mut self.tracer {
+                tracer.record(TraceEntry {
+                    frame: self.frame_count,
+                    pc,
+                    instruction,
+                    before,
+                    after,
+                });
+            }
+        }
+        // Waiting for a key changes nothing worth stepping back over
+        if let (Some(history), Some(snapshot)) = (&mut self.step_history, snapshot) {
+            if !matches!(next_instruction, NextInstruction::Stay) {
+                history.push(snapshot);
+            }
+        }
 
         self.pc = match next_instruction {
             NextInstruction::Next => self.pc,
             NextInstruction::Skip => self.pc + 2,
             NextInstruction::Jump(addr) => addr,
             NextInstruction::Stay => self.pc - 2,
+        };
+        if matches!(
+            instruction,
+            Instruction::ClearScreen
+                | Instruction::CallMachineCode { .. }
+                | Instruction::Draw { .. }
+                | Instruction::ScrollDown { .. }
+                | Instruction::ScrollRight
+                | Instruction::ScrollLeft
+        ) {
+            self.check_screen_triggers(pc);
         }
+        self.instruction_count += 1;
+        self.last_instruction = Some((pc, instruction));
+        Ok(instruction)
+    }
+
+    /// Report [`Chip8Event::ScreenTriggered`] when the screen starts showing what
+    /// `trigger` waits for, like debuggers break on
+    pub fn add_screen_trigger(&mut self, trigger: ScreenTrigger) {
+        let matched = trigger.matches(&self.screen);
+        self.screen_triggers.push((trigger, matched));
+    }
+
+    pub fn clear_screen_triggers(&mut self) {
+        self.screen_triggers.clear();
+    }
+
+    pub fn screen_triggers(&self) -> impl Iterator<Item = &ScreenTrigger> {
+        self.screen_triggers.iter().map(|(trigger, _)| trigger)
+    }
+
+    fn check_screen_triggers(&mut self, pc: u16) {
+        let mut fired = vec![];
+        for (trigger, matched) in &mut self.screen_triggers {
+            let matches = trigger.matches(&self.screen);
+            if matches && !*matched {
+                fired.push(*trigger);
+            }
+            *matched = matches;
+        }
+        self.screen_triggered |= !fired.is_empty();
+        for trigger in fired {
+            self.emit(Chip8Event::ScreenTriggered { pc, trigger });
+        }
+    }
+
+    /// Remember the state before each of the last `capacity` instructions, for
+    /// [`Chip8::step_back`]
+    pub fn enable_step_history(&mut self, capacity: usize) {
+        self.step_history = Some(StepHistory::new(capacity));
+    }
+
+    /// Stop remembering states, and forget those remembered, so instructions run without
+    /// taking a snapshot each
+    pub fn disable_step_history(&mut self) {
+        self.step_history = None;
+    }
+
+    /// Go back to the state before the last executed instruction. Returns `false` when
+    /// there is no earlier state to go back to.
+    ///
+    /// Only what save states hold is restored; the tracer and collected events keep
+    /// what they saw.
+    pub fn step_back(&mut self) -> bool {
+        let Some(mut history) = self.step_history.take() else {
+            return false;
+        };
+        let restored = history
+            .pop()
+            .is_some_and(|snapshot| self.load_state(&snapshot).is_ok());
+        self.step_history = Some(history);
+        restored
+    }
+
+    /// Whether the machine executes `instruction`, decoded with its extensions: all but
+    /// calls to machine-language routines without a stand-in
+    fn supports(&self, instruction: &Instruction) -> bool {
+        match instruction {
+            Instruction::CallMachineCode { nnn } => self.machine_routines.contains_key(nnn),
+            _ => true,
+        }
+    }
+
+    /// Instructions executed since the machine was created, waits for a key included
+    pub fn instruction_count(&self) -> u64 {
+        self.instruction_count
+    }
+
+    /// Bytes the program's instructions wrote to memory since the machine was created
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Address and instruction of the latest instruction executed, for a "now executing"
+    /// readout without a tracer; `None` until the program's first instruction
+    pub fn last_instruction(&self) -> Option<(u16, Instruction)> {
+        self.last_instruction
+    }
+
+    /// Time spent executing each family of opcodes since the machine was created
+    #[cfg(feature = "profiling")]
+    pub fn opcode_profile(&self) -> &profiling::OpcodeProfile {
+        &self.opcode_profile
+    }
+
+    /// Execute a single instruction, see [`Chip8::step`]
+    pub fn tick(&mut self) -> Result<(), Chip8Error> {
+        self.step().map(|_| ())
+    }
+
+    fn execute(&mut self, instruction: Instruction) -> Result<NextInstruction, Chip8Error> {
+        let next_instruction = match instruction {
+            Instruction::ScrollDown { n } => self.execute_00cn(n),
+            Instruction::ScrollRight => self.execute_00fb(),
+            Instruction::ScrollLeft => self.execute_00fc(),
+            Instruction::CycleBackground => self.execute_02a0(),
+            Instruction::CallMachineCode { nnn } => match self.machine_routines[&nnn] {
+                MachineRoutine::Ignore => NextInstruction::Next,
+                MachineRoutine::ClearScreen => self.execute_00e0(),
+                MachineRoutine::Halt => NextInstruction::Stay,
+            },
+            Instruction::ClearScreen => self.execute_00e0(),
+            Instruction::Return => self.execute_00ee()?,
+            Instruction::Jump { nnn } => self.execute_1nnn(nnn),
+            Instruction::Call { nnn } => self.execute_2nnn(nnn)?,
+            Instruction::SkipIfEqual { x, nn } => self.execute_3xnn(x as usize, nn),
+            Instruction::SkipIfNotEqual { x, nn } => self.execute_4xnn(x as usize, nn),
+            Instruction::SkipIfRegistersEqual { x, y } => self.execute_5xy0(x as usize, y as usize),
+            Instruction::Set { x, nn } => self.execute_6xnn(x as usize, nn),
+            Instruction::Add { x, nn } => self.execute_7xnn(x as usize, nn),
+            Instruction::Copy { x, y } => self.execute_8xy0(x as usize, y as usize),
+            Instruction::Or { x, y } => self.execute_8xy1(x as usize, y as usize),
+            Instruction::And { x, y } => self.execute_8xy2(x as usize, y as usize),
+            Instruction::Xor { x, y } => self.execute_8xy3(x as usize, y as usize),
+            Instruction::AddRegisters { x, y } => self.execute_8xy4(x as usize, y as usize),
+            Instruction::Sub { x, y } => self.execute_8xy5(x as usize, y as usize),
+            Instruction::ShiftRight { x, y } => self.execute_8xy6(x as usize, y as usize),
+            Instruction::SubReversed { x, y } => self.execute_8xy7(x as usize, y as usize),
+            Instruction::ShiftLeft { x, y } => self.execute_8xye(x as usize, y as usize),
+            Instruction::SkipIfRegistersNotEqual { x, y } => self.execute_9xy0(x as usize, y as usize),
+            Instruction::SetIndex { nnn } => self.execute_annn(nnn),
+            Instruction::JumpWithOffset { nnn } => self.execute_bnnn(nnn),
+            Instruction::SetZoneColors { x, y } => self.execute_bxy0(x as usize, y as usize),
+            Instruction::SetRowColors { x, y, n } => self.execute_bxyn(x as usize, y as usize, n),
+            Instruction::Random { x, nn } => self.execute_cxnn(x as usize, nn),
+            Instruction::Draw { x, y, n } => self.execute_dxyn(x as usize, y as usize, n)?,
+            Instruction::SkipIfKey { x } | Instruction::SkipIfSecondKey { x } => {
+                self.execute_ex9e(x as usize, self.key_bank(&instruction))
+            }
+            Instruction::SkipIfNotKey { x } | Instruction::SkipIfNotSecondKey { x } => {
+                self.execute_exa1(x as usize, self.key_bank(&instruction))
+            }
+            Instruction::ReadDelayTimer { x } => self.execute_fx07(x as usize),
+            Instruction::WaitForKey { x } => self.execute_fx0a(x as usize),
+            Instruction::SetDelayTimer { x } => self.execute_fx15(x as usize),
+            Instruction::SetSoundTimer { x } => self.execute_fx18(x as usize),
+            Instruction::AddToIndex { x } => self.execute_fx1e(x as usize),
+            Instruction::FontCharacter { x } => self.execute_fx29(x as usize),
+            Instruction::BinaryCodedDecimal { x } => self.execute_fx33(x as usize)?,
+            Instruction::StoreRegisters { x } => self.execute_fx55(x as usize)?,
+            Instruction::LoadRegisters { x } => self.execute_fx65(x as usize)?,
+            Instruction::SelectBank { x } => self.execute_fxb0(x as usize)?,
+            Instruction::Extension { opcode } => self.execute_extension(opcode)?,
+        };
+        Ok(next_instruction)
+    }
+
+    // 00CN - Scroll down N pixels (SUPER-CHIP)
+    fn execute_00cn(&mut self, n: u8) -> NextInstruction {
+        self.scroll(0, n as i8);
+        NextInstruction::Next
+    }
+
+    // 00FB - Scroll right 4 pixels (SUPER-CHIP)
+    fn execute_00fb(&mut self) -> NextInstruction {
+        self.scroll(4, 0);
+        NextInstruction::Next
+    }
+
+    // 00FC - Scroll left 4 pixels (SUPER-CHIP)
+    fn execute_00fc(&mut self) -> NextInstruction {
+        self.scroll(-4, 0);
+        NextInstruction::Next
+    }
+
+    // 02A0 - Cycle the background colour (CHIP-8X)
+    fn execute_02a0(&mut self) -> NextInstruction {
+        if let Some(colors) = &mut self.colors {
+            colors.cycle_background();
+        }
+        self.should_redraw = true;
+        NextInstruction::Next
+    }
+
+    /// Move the screen contents, filling uncovered pixels with black
+    fn scroll(&mut self, dx: i8, dy: i8) {
+        let previous = self.screen;
+        for (index, pixel) in self.screen.iter_mut().enumerate() {
+            let (i, j) = point_from_index(index);
+            let source_i = i as isize - dy as isize;
+            let source_j = j as isize - dx as isize;
+            let inside = (0..PIXELS_PER_COLUMN as isize).contains(&source_i)
+                && (0..PIXELS_PER_ROW as isize).contains(&source_j);
+            *pixel = inside && previous[index_from_point((source_i as usize, source_j as usize))];
+        }
+        self.record_screen_changes(&previous);
+        self.should_redraw = true;
+        self.emit(Chip8Event::Scrolled { dx, dy });
     }
 
     // 00E0 - Clear screen
     fn execute_00e0(&mut self) -> NextInstruction {
+        let previous = self.screen;
         self.screen = [false; PIXELS_PER_SCREEN];
+        self.record_screen_changes(&previous);
         self.should_redraw = true;
+        self.emit(Chip8Event::ScreenCleared { pc: self.pc - 2 });
         NextInstruction::Next
     }
 
-    fn execute_00ee(&mut self) -> NextInstruction {
-        NextInstruction::Jump(self.stack.pop().unwrap())
+    fn execute_00ee(&mut self) -> Result<NextInstruction, Chip8Error> {
+        match self.stack.pop() {
+            Some(addr) => Ok(NextInstruction::Jump(addr)),
+            None => Err(Chip8Error::StackUnderflow { pc: self.pc - 2 }),
+        }
     }
 
     // 1NNN - Jump
@@ -198,9 +1581,11 @@ impl Chip8 {
         NextInstruction::Jump(nnn)
     }
 
-    fn execute_2nnn(&mut self, nnn: u16) -> NextInstruction {
-        self.stack.push(self.pc);
-        NextInstruction::Jump(nnn)
+    fn execute_2nnn(&mut self, nnn: u16) -> Result<NextInstruction, Chip8Error> {
+        if self.stack.try_push(self.pc).is_err() {
+            return Err(Chip8Error::StackOverflow { pc: self.pc - 2 });
+        }
+        Ok(NextInstruction::Jump(nnn))
     }
 
     fn execute_3xnn(&mut self, x: usize, nn: u8) -> NextInstruction {
@@ -234,19 +1619,25 @@ impl Chip8 {
 
     fn execute_8xy1(&mut self, x: usize, y: usize) -> NextInstruction {
         self.v[x] |= self.v[y];
-        self.v[0xF] = 0;
+        if self.quirks.vf_reset {
+            self.v[0xF] = 0;
+        }
         NextInstruction::Next
     }
 
     fn execute_8xy2(&mut self, x: usize, y: usize) -> NextInstruction {
         self.v[x] &= self.v[y];
-        self.v[0xF] = 0;
+        if self.quirks.vf_reset {
+            self.v[0xF] = 0;
+        }
         NextInstruction::Next
     }
 
     fn execute_8xy3(&mut self, x: usize, y: usize) -> NextInstruction {
         self.v[x] ^= self.v[y];
-        self.v[0xF] = 0;
+        if self.quirks.vf_reset {
+            self.v[0xF] = 0;
+        }
         NextInstruction::Next
     }
 
@@ -265,10 +1656,12 @@ impl Chip8 {
     }
 
     fn execute_8xy6(&mut self, x: usize, y: usize) -> NextInstruction {
-        // Put the value of VY into VX
+        // Put the value of VY into VX (unless shifting in place)
         // Shift VX 1 bit to the right
         // Set VF to the bit that was shifted out
-        self.v[x] = self.v[y];
+        if !self.quirks.shift_in_place {
+            self.v[x] = self.v[y];
+        }
         let rotated_bit = self.v[x] & 0x1;
         self.v[x] >>= 1;
         self.v[0xF] = rotated_bit;
@@ -283,7 +1676,9 @@ impl Chip8 {
     }
 
     fn execute_8xye(&mut self, x: usize, y: usize) -> NextInstruction {
-        self.v[x] = self.v[y];
+        if !self.quirks.shift_in_place {
+            self.v[x] = self.v[y];
+        }
         let rotated_bit = (self.v[x] >> 7) & 0b1;
         self.v[x] <<= 1;
         self.v[0xF] = rotated_bit;
@@ -297,55 +1692,117 @@ impl Chip8 {
     }
 
     fn execute_bnnn(&mut self, nnn: u16) -> NextInstruction {
-        NextInstruction::Jump(nnn + self.v[0x0] as u16)
+        let x = if self.quirks.jump_with_vx {
+            (nnn >> 8) as usize
+        } else {
+            0x0
+        };
+        NextInstruction::Jump(nnn + self.v[x] as u16)
+    }
+
+    // BXY0 - Colour zones of 8x4 pixels (CHIP-8X)
+    fn execute_bxy0(&mut self, x: usize, y: usize) -> NextInstruction {
+        let horizontal = self.v[x] as usize;
+        let vertical = self.v[(x + 1) % 16] as usize;
+        let color = ForegroundColor::from_bits(self.v[y]);
+        if let Some(colors) = &mut self.colors {
+            // low nibble: first zone, high nibble: how many more zones
+            let columns = (horizontal & 0xF)..(horizontal & 0xF) + (horizontal >> 4) + 1;
+            let rows = (vertical & 0xF)..(vertical & 0xF) + (vertical >> 4) + 1;
+            colors.set_foreground(columns, rows.start * 4..rows.end * 4, color);
+        }
+        self.should_redraw = true;
+        NextInstruction::Next
+    }
+
+    // BXYN - Colour N rows of one 8 pixel wide zone (CHIP-8X)
+    fn execute_bxyn(&mut self, x: usize, y: usize, n: u8) -> NextInstruction {
+        let column = (self.v[x] as usize % PIXELS_PER_ROW) / COLOR_ZONE_WIDTH;
+        let row = self.v[(x + 1) % 16] as usize % PIXELS_PER_COLUMN;
+        let color = ForegroundColor::from_bits(self.v[y]);
+        if let Some(colors) = &mut self.colors {
+            colors.set_foreground(column..column + 1, row..row + n as usize, color);
+        }
+        self.should_redraw = true;
+        NextInstruction::Next
     }
 
     fn execute_cxnn(&mut self, x: usize, nn: u8) -> NextInstruction {
-        let random: u8 = rand::thread_rng().gen();
-        self.v[x] = random & nn;
+        self.v[x] = self.entropy.next_u8() & nn;
         NextInstruction::Next
     }
 
     // DXYN - Display and draw
-    fn execute_dxyn(&mut self, x: usize, y: usize, n: u8) -> NextInstruction {
+    fn execute_dxyn(&mut self, x: usize, y: usize, n: u8) -> Result<NextInstruction, Chip8Error> {
         // get X and Y coordinates
-        println!("x = {}, y = {}, n = {}", x, y, n);
         let i = (self.v[y] % 32) as usize;
         let j = (self.v[x] % 64) as usize;
         self.v[0xF] = 0;
 
-        println!("i = {}, j = {}", i, j);
-
-        let end_downwards = cmp::min(i + n as usize, 32);
-        let end_to_right = cmp::min(j + 8, 64);
-
-        println!("end_downwards = {}, end_to_right = {}", end_downwards, end_to_right);
+        // Rows and columns of the sprite that are drawn; past an edge they either wrap
+        // around to the other side or are clipped
+        let (width, height) = self.quirks.sprite_size(n);
+        let rows = match self.quirks.wrap_vertically {
+            true => height,
+            false => cmp::min(height, PIXELS_PER_COLUMN - i),
+        };
+        let columns = match self.quirks.wrap_horizontally {
+            true => width,
+            false => cmp::min(width, PIXELS_PER_ROW - j),
+        };
 
-        for (column_iter, column_index) in (i..end_downwards).enumerate() {
-            let sprite_byte = self.memory[self.i as usize + column_iter];
-            for (row_iter, row_index) in (j..end_to_right).enumerate() {
-                let sprite_pixel = (sprite_byte >> (7 - row_iter)) & 0b1;
+        let bytes_per_row = width / 8;
+        let sprite = self.memory_range(self.i as usize, rows * bytes_per_row)?;
+        let (mut drawn, mut erased) = (0, 0);
+        self.check_read(self.pc - 2, sprite.clone());
+        for column_iter in 0..rows {
+            let column_index = (i + column_iter) % PIXELS_PER_COLUMN;
+            // The row's pixels from the most significant bit on
+            let start = sprite.start + column_iter * bytes_per_row;
+            let sprite_row = self.memory[start..start + bytes_per_row]
+                .iter()
+                .fold(0u16, |bits, &byte| bits << 8 | byte as u16)
+                << (16 - width);
+            for row_iter in 0..columns {
+                let row_index = (j + row_iter) % PIXELS_PER_ROW;
+                let sprite_pixel = (sprite_row >> (15 - row_iter)) & 0b1;
                 let pixel_index = column_index * PIXELS_PER_ROW + row_index;
                 let screen_pixel = self.screen[pixel_index];
                 if sprite_pixel == 1 {
-                    if screen_pixel == true {
+                    drawn += 1;
+                    if screen_pixel {
                         self.v[0xF] = 1;
+                        erased += 1;
                     }
                     self.screen[pixel_index] ^= true;
+                    self.record_pixel(pixel_index, !screen_pixel);
                 }
             }
         }
+        if self.quirks.clipped_rows_collide && rows < height {
+            self.v[0xF] = 1;
+        }
 
+        self.emit(Chip8Event::SpriteDrawn {
+            addr: self.i,
+            x: j as u8,
+            y: i as u8,
+            width: width as u8,
+            height: height as u8,
+            collided: erased > 0 && erased < drawn,
+        });
         self.should_redraw = true;
-        NextInstruction::Next
+        Ok(NextInstruction::Next)
     }
 
-    fn execute_ex9e(&mut self, x: usize) -> NextInstruction {
-        NextInstruction::skip_if(self.keypad.current_frame_keys[self.v[x] as usize])
+    // EX9E (and CHIP-8X's EXF2) - Skip if key VX is pressed on `bank`
+    fn execute_ex9e(&mut self, x: usize, bank: KeyBank) -> NextInstruction {
+        NextInstruction::skip_if(self.keypad.is_pressed(bank, self.v[x]))
     }
 
-    fn execute_exa1(&mut self, x: usize) -> NextInstruction {
-        NextInstruction::skip_if(!self.keypad.current_frame_keys[self.v[x] as usize])
+    // EXA1 (and CHIP-8X's EXF5) - Skip if key VX is not pressed on `bank`
+    fn execute_exa1(&mut self, x: usize, bank: KeyBank) -> NextInstruction {
+        NextInstruction::skip_if(!self.keypad.is_pressed(bank, self.v[x]))
     }
 
     fn execute_fx07(&mut self, x: usize) -> NextInstruction {
@@ -355,66 +1812,126 @@ impl Chip8 {
 
     fn execute_fx15(&mut self, x: usize) -> NextInstruction {
         self.delay_timer = self.v[x];
+        if self.display_buffering == DisplayBuffering::Sync {
+            self.show_screen();
+        }
         NextInstruction::Next
     }
 
     fn execute_fx18(&mut self, x: usize) -> NextInstruction {
+        let was_silent = self.sound_timer == 0;
         self.sound_timer = self.v[x];
+        match (was_silent, self.sound_timer) {
+            (true, 0) | (false, 1..) => {}
+            (true, ticks) => self.emit(Chip8Event::SoundStarted { ticks }),
+            (false, 0) => self.emit(Chip8Event::SoundStopped),
+        }
         NextInstruction::Next
     }
 
     // TODO: implement altering VF on overflow above 0FFF
     fn execute_fx1e(&mut self, x: usize) -> NextInstruction {
-        self.i += self.v[x] as u16;
+        self.i = self.i.wrapping_add(self.v[x] as u16);
         NextInstruction::Next
     }
 
     fn execute_fx0a(&mut self, x: usize) -> NextInstruction {
         if let Some(key) = self.keypad.first_pressed_keypress() {
             self.v[x] = key as u8;
+            self.key_wait = None;
             NextInstruction::Next
         } else {
+            let addr = self.pc - 2;
+            if self.key_wait != Some(addr) {
+                self.key_wait = Some(addr);
+                self.emit(Chip8Event::WaitingForKey {
+                    addr,
+                    register: x as u8,
+                });
+            }
             NextInstruction::Stay
         }
     }
 
     fn execute_fx29(&mut self, x: usize) -> NextInstruction {
-        let vx = self.v[x];
-        let offset = vx * 5;
-        self.i = FONT_INITIAL_POSITION as u16 + offset as u16;
+        // Only the low nibble selects a character
+        let offset = (self.v[x] & 0xF) as u16 * 5;
+        self.i = FONT_INITIAL_POSITION as u16 + offset;
         NextInstruction::Next
     }
 
-    fn execute_fx33(&mut self, x: usize) -> NextInstruction {
+    fn execute_fx33(&mut self, x: usize) -> Result<NextInstruction, Chip8Error> {
         let numbers = convert_to_binary_coded_decimal(self.v[x]);
 
         // set
-        let i = self.i as usize;
-        self.memory[i..i + 3].copy_from_slice(&numbers);
-        self.i = self.i + x as u16 + 1;
-        NextInstruction::Next
+        let range = self.memory_range(self.i as usize, numbers.len())?;
+        self.check_write(range.start, range.len())?;
+        self.memory[range.clone()].copy_from_slice(&numbers);
+        self.bytes_written += range.len() as u64;
+        self.mark_written(range);
+        Ok(NextInstruction::Next)
     }
 
-    fn execute_fx65(&mut self, x: usize) -> NextInstruction {
-        let i = self.i as usize;
-        let memory_range = i..i + x + 1;
+    fn execute_fx65(&mut self, x: usize) -> Result<NextInstruction, Chip8Error> {
+        let memory_range = self.memory_range(self.i as usize, x + 1)?;
+        self.check_read(self.pc - 2, memory_range.clone());
         self.v[0..=x].copy_from_slice(&self.memory[memory_range]);
-        self.i = self.i + x as u16 + 1;
-        NextInstruction::Next
+        if self.quirks.increment_index {
+            self.i = self.i + x as u16 + 1;
+        }
+        Ok(NextInstruction::Next)
     }
 
     // Store V0 to VX (inclusive) in memory
-    fn execute_fx55(&mut self, x: usize) -> NextInstruction {
-        let i = self.i as usize;
-        let memory_range = i..i + x + 1;
-        self.memory[memory_range].copy_from_slice(&self.v[0..=x]);
-        self.i = self.i + x as u16 + 1;
-        NextInstruction::Next
+    fn execute_fx55(&mut self, x: usize) -> Result<NextInstruction, Chip8Error> {
+        let memory_range = self.memory_range(self.i as usize, x + 1)?;
+        self.check_write(memory_range.start, memory_range.len())?;
+        self.memory[memory_range.clone()].copy_from_slice(&self.v[0..=x]);
+        self.bytes_written += memory_range.len() as u64;
+        self.mark_written(memory_range);
+        if self.quirks.increment_index {
+            self.i = self.i + x as u16 + 1;
+        }
+        Ok(NextInstruction::Next)
     }
 
     fn execute_9xy0(&mut self, x: usize, y: usize) -> NextInstruction {
         NextInstruction::skip_if(self.v[x] != self.v[y])
     }
+
+    // FXB0 - Map bank VX into the upper half of memory (bank switching)
+    fn execute_fxb0(&mut self, x: usize) -> Result<NextInstruction, Chip8Error> {
+        let bank = self.v[x];
+        let selected = self
+            .banks
+            .as_mut()
+            .is_some_and(|banks| banks.select(bank as usize, &mut self.memory));
+        if !selected {
+            return Err(Chip8Error::NoSuchBank {
+                pc: self.pc - 2,
+                bank,
+            });
+        }
+        Ok(NextInstruction::Next)
+    }
+
+    // An opcode of an added extension, run by the extension that decoded it
+    fn execute_extension(&mut self, opcode: u16) -> Result<NextInstruction, Chip8Error> {
+        let instruction = Instruction::Extension { opcode };
+        let extension = self
+            .extensions
+            .iter()
+            .copied()
+            .find(|extension| extension.decode(opcode) == Some(instruction));
+        match extension {
+            Some(extension) => extension.execute(opcode, self)?,
+            None => {
+                let pc = self.pc - 2;
+                return Err(Chip8Error::UnknownOpcode { pc, opcode });
+            }
+        }
+        Ok(NextInstruction::Next)
+    }
 }
 
 pub fn decode_instruction_into_nibbles(instruction: u16) -> [u8; 4] {
@@ -434,6 +1951,20 @@ pub fn convert_to_binary_coded_decimal(num: u8) -> [u8; 3] {
     [hundreds, decimals, units]
 }
 
+/// The `height` rows of the 8 pixel wide sprite at `addr` in `memory`, leftmost pixel
+/// first. Rows past the end of memory wrap around to the start; empty memory has no rows.
+pub fn decode_sprite(memory: &[u8], addr: u16, height: usize) -> Vec<[bool; 8]> {
+    if memory.is_empty() {
+        return vec![];
+    }
+    (0..height)
+        .map(|row| {
+            let byte = memory[(addr as usize + row) % memory.len()];
+            std::array::from_fn(|column| byte & (0x80 >> column) != 0)
+        })
+        .collect()
+}
+
 pub fn point_from_index(index: usize) -> (usize, usize) {
     (index / PIXELS_PER_ROW, index % PIXELS_PER_ROW)
 }
@@ -481,6 +2012,634 @@ mod tests {
         }
     }
 
+    fn machine_with_rom(rom: &[u8]) -> Chip8 {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(rom);
+        chip8
+    }
+
+    #[test]
+    fn run_until_event_stops_on_draw_sound_and_halt() {
+        let mut chip8 = machine_with_rom(&[
+            0x60, 0x05, // 200: V0 = 5
+            0xD0, 0x05, // 202: draw
+            0xF0, 0x18, // 204: sound timer = V0
+            0x12, 0x06, // 206: jump to self
+        ]);
+
+        assert_eq!(chip8.run_until_event(100), Ok(StopReason::Draw));
+        assert_eq!(chip8.run_until_event(100), Ok(StopReason::SoundStart));
+        assert_eq!(chip8.run_until_event(100), Ok(StopReason::Halt));
+        assert_eq!(chip8.run_until_event(100), Ok(StopReason::Halt));
+    }
+
+    #[test]
+    fn machines_idle_waiting_for_keys_or_halted() {
+        let mut chip8 = machine_with_rom(&[
+            0xF1, 0x15, // 200: delay timer = V1
+            0xF0, 0x0A, // 202: wait for a key
+            0x12, 0x04, // 204: jump to self
+        ]);
+        chip8.v[1] = 1;
+        chip8.tick().unwrap();
+        assert!(!chip8.is_idle());
+
+        chip8.tick_timers();
+        assert!(chip8.is_idle());
+        chip8.press_key(7).unwrap();
+        assert!(!chip8.is_idle());
+
+        chip8.tick().unwrap();
+        assert_eq!(chip8.pc, 0x204);
+        assert!(chip8.is_idle());
+    }
+
+    #[test]
+    fn run_until_event_stops_on_breakpoints_and_budget() {
+        let mut chip8 = machine_with_rom(&[
+            0x70, 0x01, // 200: V0 += 1
+            0x70, 0x01, // 202: V0 += 1
+            0x12, 0x00, // 204: jump to 200
+        ]);
+        chip8.add_breakpoint(0x202);
+
+        assert_eq!(chip8.run_until_event(100), Ok(StopReason::Breakpoint));
+        assert_eq!(chip8.pc, 0x202);
+        assert_eq!(chip8.run_until_event(2), Ok(StopReason::Budget));
+        assert_eq!(chip8.run_until_event(100), Ok(StopReason::Breakpoint));
+        assert_eq!(chip8.v[0], 3);
+
+        chip8.remove_breakpoint(0x202);
+        assert_eq!(chip8.run_until_event(9), Ok(StopReason::Budget));
+    }
+
+    #[test]
+    fn step_over_and_out_run_whole_subroutines() {
+        let rom = [
+            0x22, 0x06, // 200: call 206
+            0x60, 0x01, // 202: V0 = 1
+            0x12, 0x04, // 204: jump to self
+            0x22, 0x0A, // 206: call 20A
+            0x00, 0xEE, // 208: return
+            0x61, 0x02, // 20A: V1 = 2
+            0x00, 0xEE, // 20C: return
+        ];
+        let mut chip8 = machine_with_rom(&rom);
+        assert_eq!(chip8.step_over(100), Ok(StopReason::Stepped));
+        assert_eq!((chip8.pc, chip8.stack_depth(), chip8.v[1]), (0x202, 0, 2));
+        assert_eq!(chip8.step_over(100), Ok(StopReason::Stepped));
+        assert_eq!(chip8.pc, 0x204);
+
+        let mut chip8 = machine_with_rom(&rom);
+        chip8.add_breakpoint(0x20A);
+        assert_eq!(chip8.step_over(100), Ok(StopReason::Breakpoint));
+        assert_eq!((chip8.pc, chip8.stack_depth()), (0x20A, 2));
+        assert_eq!(chip8.step_out(100), Ok(StopReason::Stepped));
+        assert_eq!((chip8.pc, chip8.stack_depth()), (0x208, 1));
+        assert_eq!(chip8.step_out(100), Ok(StopReason::Stepped));
+        assert_eq!((chip8.pc, chip8.stack_depth()), (0x202, 0));
+        assert_eq!(chip8.step_out(100), Ok(StopReason::Stepped));
+        assert_eq!(chip8.pc, 0x204);
+
+        let mut chip8 = machine_with_rom(&rom);
+        assert_eq!(chip8.step_over(2), Ok(StopReason::Budget));
+        assert_eq!(chip8.pc, 0x20A);
+
+        // A subroutine waiting on the delay timer returns once it runs out
+        let mut chip8 = machine_with_rom(&[
+            0x22, 0x04, // 200: call 204
+            0x12, 0x02, // 202: jump to self
+            0x60, 0x05, // 204: V0 = 5
+            0xF0, 0x15, // 206: delay = V0
+            0xF0, 0x07, // 208: V0 = delay
+            0x30, 0x00, // 20A: skip if V0 == 0
+            0x12, 0x08, // 20C: jump to 208
+            0x00, 0xEE, // 20E: return
+        ]);
+        assert_eq!(chip8.step_over(10_000), Ok(StopReason::Stepped));
+        assert_eq!((chip8.pc, chip8.stack_depth(), chip8.delay_timer), (0x202, 0, 0));
+    }
+
+    #[test]
+    fn shifts_copy_vy_on_cosmac_vip() {
+        let mut chip8 = machine_with_rom(&[
+            0x60, 0x01, // V0 = 0x01
+            0x61, 0x81, // V1 = 0x81
+            0x80, 0x16, // V0 = V1 >> 1
+            0x62, 0x01, // V2 = 0x01
+            0x82, 0x1E, // V2 = V1 << 1
+        ]);
+        for _ in 0..5 {
+            chip8.tick().unwrap();
+        }
+
+        assert_eq!(chip8.v[0], 0x40);
+        assert_eq!(chip8.v[2], 0x02);
+        assert_eq!(chip8.v[0xF], 1);
+    }
+
+    #[test]
+    fn shifts_happen_in_place_on_chip48() {
+        let mut chip8 = Chip8::with_quirks(Quirks::for_platform(Platform::Chip48));
+        chip8.load_rom(&[
+            0x60, 0x06, // V0 = 0x06
+            0x61, 0xFF, // V1 = 0xFF
+            0x80, 0x16, // V0 = V0 >> 1
+            0x62, 0x41, // V2 = 0x41
+            0x82, 0x1E, // V2 = V2 << 1
+        ]);
+        for _ in 0..3 {
+            chip8.tick().unwrap();
+        }
+        assert_eq!(chip8.v[0], 0x03);
+        assert_eq!(chip8.v[0xF], 0);
+
+        for _ in 0..2 {
+            chip8.tick().unwrap();
+        }
+        assert_eq!(chip8.v[2], 0x82);
+        assert_eq!(chip8.v[0xF], 0);
+    }
+
+    #[test]
+    fn battery_ram_is_saved_and_restored() {
+        let rom = [0x60, 0x2A, 0xAE, 0x00, 0xF0, 0x55, 0x12, 0x06];
+        let mut chip8 = machine_with_rom(&rom);
+        chip8.set_battery_ram(Some(0xE00..0xE04)).unwrap();
+        for _ in 0..3 {
+            chip8.tick().unwrap();
+        }
+        let saved = chip8.battery_ram().unwrap().to_vec();
+        assert_eq!(saved, [0x2A, 0, 0, 0]);
+
+        let mut next_run = machine_with_rom(&rom);
+        next_run.set_battery_ram(Some(0xE00..0xE04)).unwrap();
+        next_run.restore_battery_ram(&saved).unwrap();
+        assert_eq!(next_run.memory()[0xE00], 0x2A);
+        assert!(next_run.restore_battery_ram(&[0; 2]).is_err());
+        assert!(next_run.set_battery_ram(Some(0xF00..0x1001)).is_err());
+    }
+
+    #[test]
+    fn faults_leave_the_machine_on_the_faulting_instruction() {
+        let mut chip8 = machine_with_rom(&[0x00, 0xEE]);
+        assert_eq!(chip8.tick(), Err(Chip8Error::StackUnderflow { pc: 0x200 }));
+        assert_eq!(chip8.pc, 0x200);
+
+        let mut chip8 = machine_with_rom(&[0x22, 0x00]);
+        for _ in 0..STACK_SIZE {
+            chip8.tick().unwrap();
+        }
+        assert_eq!(chip8.tick(), Err(Chip8Error::StackOverflow { pc: 0x200 }));
+        assert_eq!(chip8.stack.len(), STACK_SIZE);
+
+        let mut chip8 = machine_with_rom(&[0x01, 0x23]);
+        assert_eq!(
+            chip8.tick(),
+            Err(Chip8Error::MachineCode {
+                pc: 0x200,
+                addr: 0x123
+            })
+        );
+        assert_eq!(chip8.pc, 0x200);
+    }
+
+    #[test]
+    fn crash_reports_include_recent_history() {
+        let mut chip8 = machine_with_rom(&[0x60, 0x01, 0x61, 0x02, 0xFF, 0xFF]);
+        chip8.enable_tracer(64);
+        let error = chip8.run_until_event(10).unwrap_err();
+        let report = CrashReport::new(&chip8, error);
+
+        assert_eq!(report.summary(), "Crashed at 0x204: unknown opcode FFFF at 0x204");
+        assert_eq!(report.history.len(), 2);
+        assert!(report.to_string().contains("> 0x204  FFFF  ???"));
+    }
+
+    #[test]
+    fn instruction_history_keeps_the_latest_instructions_by_frame() {
+        let mut chip8 = machine_with_rom(&[0x60, 0x01, 0x61, 0x02, 0x12, 0x04]);
+        chip8.set_clock(ClockConfig {
+            instructions_per_frame: 2,
+            ..chip8.clock()
+        });
+        chip8.run_frame().unwrap();
+        chip8.run_frame().unwrap();
+        assert_eq!(chip8.frame_count(), 2);
+
+        let history: Vec<_> = chip8.instruction_history(3).collect();
+        let ran: Vec<_> = history
+            .iter()
+            .map(|entry| (entry.frame, entry.pc))
+            .collect();
+        assert_eq!(ran, [(0, 0x202), (1, 0x204), (1, 0x204)]);
+        let changes: Vec<_> = history[0].changes().collect();
+        assert_eq!(changes, [(Register::V(1), 0, 2)]);
+        assert_eq!(chip8.instruction_history(100).count(), 4);
+
+        chip8.disable_tracer();
+        assert_eq!(chip8.instruction_history(3).count(), 0);
+    }
+
+    #[test]
+    fn super_chip_scrolls_and_reports_it() {
+        let mut chip8 = Chip8::with_platform(Platform::SuperChip);
+        chip8.load_rom(&[0x00, 0xC2, 0x00, 0xFB, 0x00, 0xFC, 0x00, 0xFC]);
+        chip8.enable_events();
+        chip8.screen[index_from_point((0, 4))] = true;
+
+        chip8.tick().unwrap();
+        assert!(chip8.screen[index_from_point((2, 4))]);
+        chip8.tick().unwrap();
+        assert!(chip8.screen[index_from_point((2, 8))]);
+        chip8.tick().unwrap();
+        chip8.tick().unwrap();
+        assert!(chip8.screen[index_from_point((2, 0))]);
+        assert_eq!(chip8.screen.iter().filter(|&&on| on).count(), 1);
+
+        assert_eq!(
+            chip8.take_events(),
+            [
+                Chip8Event::Scrolled { dx: 0, dy: 2 },
+                Chip8Event::Scrolled { dx: 4, dy: 0 },
+                Chip8Event::Scrolled { dx: -4, dy: 0 },
+                Chip8Event::Scrolled { dx: -4, dy: 0 },
+            ]
+        );
+        assert_eq!(chip8.take_events(), []);
+    }
+
+    #[test]
+    fn screen_clears_are_reported() {
+        let mut chip8 = machine_with_rom(&[0x60, 0x01, 0x00, 0xE0]);
+        chip8.enable_events();
+        chip8.tick().unwrap();
+        assert_eq!(chip8.take_events(), []);
+        chip8.tick().unwrap();
+        assert_eq!(
+            chip8.take_events(),
+            [Chip8Event::ScreenCleared { pc: 0x202 }]
+        );
+    }
+
+    #[test]
+    fn sprite_draws_are_reported() {
+        // Draws the font's 0 at (70, 3), which wraps to (6, 3)
+        let mut chip8 = machine_with_rom(&[0x60, 0x46, 0x61, 0x03, 0xA0, 0x50, 0xD0, 0x15]);
+        chip8.enable_events();
+        chip8.run_until_event(4).unwrap();
+        assert_eq!(
+            chip8.take_events(),
+            [Chip8Event::SpriteDrawn {
+                addr: 0x050,
+                x: 6,
+                y: 3,
+                width: 8,
+                height: 5,
+                collided: false,
+            }]
+        );
+
+        // Erasing the 0 by drawing it again isn't a collision, but a 1 over it is
+        let mut chip8 = machine_with_rom(&[
+            0xA0, 0x50, // 200: I = the font's 0
+            0xD0, 0x15, // 202: draw it at (0, 0)
+            0xD0, 0x15, // 204: erase it
+            0xD0, 0x15, // 206: draw it again
+            0xA0, 0x55, // 208: I = the font's 1
+            0xD0, 0x15, // 20A: draw it over the 0
+        ]);
+        chip8.enable_events();
+        let collisions: Vec<_> = (0..6)
+            .map(|_| {
+                chip8.tick().unwrap();
+                chip8.take_events()
+            })
+            .filter_map(|events| match events[..] {
+                [Chip8Event::SpriteDrawn { collided, .. }] => Some(collided),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(collisions, [false, false, false, true]);
+    }
+
+    #[test]
+    fn pokes_change_the_machine_within_bounds() {
+        let mut chip8 = machine_with_rom(&[0x12, 0x00]);
+        chip8.write_memory(0x300, &[0xD0, 0x15]).unwrap();
+        chip8.set_v(0xF, 7).unwrap();
+        chip8.set_i(0xFFF).unwrap();
+        chip8.set_pc(0x300).unwrap();
+        let state = chip8.cpu_state();
+        assert_eq!((state.v[0xF], state.i, state.pc), (7, 0xFFF, 0x300));
+        assert_eq!(
+            chip8.current_instruction(),
+            Some(Instruction::Draw { x: 0, y: 1, n: 5 })
+        );
+
+        assert_eq!(
+            chip8.write_memory(0xFFF, &[1, 2]),
+            Err(Chip8Error::OutOfBounds {
+                addr: 0xFFF,
+                len: 2
+            })
+        );
+        assert_eq!(
+            chip8.write_memory(usize::MAX, &[1]),
+            Err(Chip8Error::OutOfBounds {
+                addr: usize::MAX,
+                len: 1
+            })
+        );
+        assert_eq!(
+            chip8.set_v(16, 1),
+            Err(Chip8Error::NoSuchRegister { x: 16 })
+        );
+        assert_eq!(chip8.press_key(16), Err(Chip8Error::NoSuchKey { key: 16 }));
+        assert!(chip8.set_i(0x1000).is_err());
+        assert_eq!(
+            chip8.set_pc(0xFFF),
+            Err(Chip8Error::PcOutOfBounds { pc: 0xFFF })
+        );
+        assert_eq!(chip8.cpu_state(), state);
+    }
+
+    #[test]
+    fn sound_reports_its_edges_even_within_a_frame() {
+        let mut chip8 = machine_with_rom(&[
+            0x60, 0x01, // V0 = 1
+            0xF0, 0x18, // sound timer = V0
+            0xF0, 0x18, // again, while it's sounding
+            0x12, 0x06, // loop forever
+        ]);
+        chip8.enable_events();
+        chip8.run_frame().unwrap();
+        // The single tick is over before anyone could look at the timer
+        assert!(!chip8.is_beeping());
+        assert_eq!(
+            chip8.take_events(),
+            [
+                Chip8Event::SoundStarted { ticks: 1 },
+                Chip8Event::SoundStopped
+            ]
+        );
+    }
+
+    #[test]
+    fn scrolling_is_unknown_on_cosmac_vip() {
+        let mut chip8 = machine_with_rom(&[0x00, 0xFB]);
+        assert_eq!(
+            chip8.tick(),
+            Err(Chip8Error::UnknownOpcode {
+                pc: 0x200,
+                opcode: 0x00FB
+            })
+        );
+    }
+
+    #[test]
+    fn super_chip_quirks_keep_vf_and_i_and_jump_with_vx() {
+        let rom = [
+            0x6F, 0x07, // VF = 7
+            0x81, 0x01, // V1 |= V0
+            0xA3, 0x00, // I = 300
+            0xF1, 0x55, // store V0..V1
+            0x62, 0x04, // V2 = 4
+            0xB2, 0x20, // jump to 220 + V2 (or V0)
+        ];
+        let mut vip = machine_with_rom(&rom);
+        let mut super_chip = Chip8::with_platform(Platform::SuperChip);
+        super_chip.load_rom(&rom);
+        for _ in 0..6 {
+            vip.tick().unwrap();
+            super_chip.tick().unwrap();
+        }
+
+        assert_eq!((vip.v[0xF], vip.i, vip.pc), (0, 0x302, 0x220));
+        assert_eq!((super_chip.v[0xF], super_chip.i, super_chip.pc), (7, 0x300, 0x224));
+    }
+
+    #[test]
+    fn chip8x_colours_zones_and_reads_the_second_keypad() {
+        let mut chip8 = Chip8::with_platform(Platform::Chip8X);
+        chip8.load_rom(&[
+            0x02, 0xA0, // next background colour
+            0x60, 0x11, // V0 = zones 1 and 2
+            0x61, 0x02, // V1 = rows 8 to 11
+            0x62, 0x05, // V2 = yellow
+            0xB0, 0x20, // colour the zones
+            0x60, 0x00, // V0 = column 0
+            0xB0, 0x23, // colour 3 rows from V1 = 2
+            0xE2, 0xF2, // skip if key 5 is pressed on the second keypad
+        ]);
+        chip8.keypad.update_second_bank({
+            let mut keys = [false; 16];
+            keys[5] = true;
+            keys
+        });
+        for _ in 0..8 {
+            chip8.tick().unwrap();
+        }
+
+        let colors = chip8.colors().unwrap();
+        assert_eq!(colors.background(), BackgroundColor::Black);
+        assert_eq!(colors.foreground_at(8, 8), ForegroundColor::Yellow);
+        assert_eq!(colors.foreground_at(23, 11), ForegroundColor::Yellow);
+        assert_eq!(colors.foreground_at(24, 11), ForegroundColor::Red);
+        assert_eq!(colors.foreground_at(8, 12), ForegroundColor::Red);
+        assert_eq!(colors.foreground_at(7, 4), ForegroundColor::Yellow);
+        assert_eq!(colors.foreground_at(7, 5), ForegroundColor::Red);
+        assert_eq!(chip8.pc, 0x212);
+        assert!(machine_with_rom(&[]).colors().is_none());
+        assert_eq!(machine_with_rom(&[]).keypad.second_bank, None);
+    }
+
+    #[test]
+    fn frames_run_the_configured_instructions_and_count_timers_down() {
+        let mut chip8 = machine_with_rom(&[
+            0x60, 0x03, // V0 = 3
+            0xF0, 0x15, // delay timer = V0
+            0x70, 0x01, // V0 += 1
+            0x12, 0x04, // jump back to the addition
+        ]);
+        chip8.set_clock(ClockConfig {
+            instructions_per_frame: 4,
+            ..ClockConfig::default()
+        });
+
+        chip8.run_frame().unwrap();
+        assert_eq!(chip8.delay_timer, 2);
+        assert_eq!(chip8.v[0], 4);
+        chip8.run_frame().unwrap();
+        chip8.run_frame().unwrap();
+        chip8.run_frame().unwrap();
+        assert_eq!(chip8.delay_timer, 0);
+        assert_eq!(chip8.v[0], 10);
+    }
+
+    #[test]
+    fn late_frames_leave_their_instructions_to_the_next() {
+        let mut chip8 = machine_with_rom(&[
+            0x70, 0x01, // V0 += 1
+            0x12, 0x00, // jump back to the addition
+        ]);
+        chip8.set_clock(ClockConfig {
+            instructions_per_frame: 40,
+            ..ClockConfig::default()
+        });
+
+        // Out of time at the first look
+        chip8.run_frame_within(|| true).unwrap();
+        assert_eq!(chip8.v[0], 8);
+        chip8.run_frame_within(|| false).unwrap();
+        assert_eq!(chip8.v[0], 40);
+
+        // The deficit stops growing after a couple of frames
+        for _ in 0..10 {
+            chip8.run_frame_within(|| true).unwrap();
+        }
+        chip8.run_frame_within(|| false).unwrap();
+        assert_eq!(chip8.v[0], 40 + 10 * 8 + 60);
+    }
+
+    #[test]
+    fn speed_zones_of_the_profile_set_the_frame_budget() {
+        static PROFILE: RomProfile = RomProfile {
+            name: "menu at 0x204",
+            hash: 0,
+            colors: None,
+            cycle_palette_on_sound: false,
+            speed_zones: &[SpeedZone {
+                start: 0x204,
+                end: 0x208,
+                instructions_per_frame: 2,
+            }],
+        };
+        let mut chip8 = machine_with_rom(&[
+            0x70, 0x01, // V0 += 1
+            0x12, 0x00, // jump back to the addition
+            0x71, 0x01, // menu: V1 += 1
+            0x12, 0x04, // jump back to the menu's addition
+        ]);
+        chip8.rom_info.profile = Some(&PROFILE);
+
+        chip8.run_frame().unwrap();
+        assert_eq!(chip8.v[0], 5);
+        chip8.set_pc(0x204).unwrap();
+        chip8.run_frame().unwrap();
+        assert_eq!(chip8.v[1], 1);
+    }
+
+    #[test]
+    fn timers_run_at_the_configured_frequency() {
+        let mut chip8 = machine_with_rom(&[
+            0x60, 0x64, // V0 = 100
+            0xF0, 0x15, // delay timer = V0
+            0x12, 0x04, // loop forever
+        ]);
+        chip8.set_clock(ClockConfig {
+            instructions_per_frame: 2,
+            timer_frequency: 50,
+            ..ClockConfig::default()
+        });
+        for _ in 0..60 {
+            chip8.run_frame().unwrap();
+        }
+        assert_eq!(chip8.delay_timer, 50);
+
+        chip8.set_clock(ClockConfig {
+            instructions_per_frame: 1,
+            timer_frequency: 120,
+            ..ClockConfig::default()
+        });
+        chip8.run_frame().unwrap();
+        assert_eq!(chip8.delay_timer, 48);
+    }
+
+    #[test]
+    fn timers_tick_before_or_after_the_instructions() {
+        let delay_seen = |timer_order| {
+            let mut chip8 = machine_with_rom(&[
+                0xF0, 0x07, // V0 = delay timer
+                0x12, 0x00, // jump back
+            ]);
+            chip8.set_clock(ClockConfig {
+                instructions_per_frame: 1,
+                timer_order,
+                ..ClockConfig::default()
+            });
+            chip8.delay_timer = 5;
+            chip8.run_frame().unwrap();
+            assert_eq!(chip8.delay_timer, 4);
+            chip8.v[0]
+        };
+        assert_eq!(delay_seen(TimerOrder::AfterInstructions), 5);
+        assert_eq!(delay_seen(TimerOrder::BeforeInstructions), 4);
+    }
+
+    #[test]
+    fn pixel_changes_follow_draws_and_clears() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[
+            0xA2, 0x0A, // I = sprite
+            0xD0, 0x01, // draw it at (0, 0)
+            0xD0, 0x01, // and again, erasing it
+            0xD0, 0x01, // draw it a third time
+            0x00, 0xE0, // clear the screen
+            0xC0, 0x00, // the sprite, two pixels wide
+        ]);
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+        // Nothing is collected until someone asks
+        assert_eq!(chip8.take_pixel_changes(), []);
+
+        chip8.enable_pixel_changes();
+        chip8.step().unwrap();
+        let change = |x, on| PixelChange { x, y: 0, on };
+        assert_eq!(
+            chip8.take_pixel_changes(),
+            [change(0, false), change(1, false)]
+        );
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+        assert_eq!(
+            chip8.take_pixel_changes(),
+            [change(0, true), change(1, true), change(0, false), change(1, false)]
+        );
+    }
+
+    #[test]
+    fn last_instruction_is_the_latest_one_executed() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0x61, 0x23, 0x12, 0x00]);
+        assert_eq!(chip8.last_instruction(), None);
+        chip8.step().unwrap();
+        assert_eq!(
+            chip8.last_instruction(),
+            Some((0x200, Instruction::Set { x: 1, nn: 0x23 }))
+        );
+        chip8.step().unwrap();
+        assert_eq!(chip8.last_instruction().map(|(pc, _)| pc), Some(0x202));
+        chip8.swap_rom(&[0x00, 0xE0]);
+        assert_eq!(chip8.last_instruction(), None);
+    }
+
+    #[test]
+    fn sprites_decode_from_memory() {
+        let chip8 = Chip8::new();
+        let zero = chip8.decode_sprite(FONT_INITIAL_POSITION as u16, 5);
+        let rows = zero
+            .iter()
+            .map(|row| row.map(|on| if on { '#' } else { '.' }).iter().collect())
+            .collect::<Vec<String>>();
+        assert_eq!(rows, ["####....", "#..#....", "#..#....", "#..#....", "####...."]);
+
+        let memory = [0x81, 0x00, 0x00, 0xFF];
+        let wrapped = decode_sprite(&memory, 3, 2);
+        assert_eq!(wrapped, [[true; 8], [true, false, false, false, false, false, false, true]]);
+        assert!(decode_sprite(&[], 0, 5).is_empty());
+    }
+
     #[test]
     fn point_is_correctly_converted_to_index() {
         let test_cases = [(0, (0, 0)), (1, (0, 1)), (66, (1, 2)), (2047, (31, 63))];
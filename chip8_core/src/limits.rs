@@ -0,0 +1,131 @@
+//! Hard limits for running ROMs nobody watches, like in CI or while fuzzing, so that none
+//! can hang the run however it misbehaves
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::Chip8;
+
+/// Limits left at `None` don't apply
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Real time the whole run may take
+    pub wall_clock: Option<Duration>,
+    /// Instructions the run may execute
+    pub instructions: Option<u64>,
+    /// Bytes the program may write to memory in a single frame
+    pub writes_per_frame: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitExceeded {
+    WallClock(Duration),
+    Instructions(u64),
+    WritesPerFrame { writes: u64, limit: u64 },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::WallClock(limit) => {
+                write!(f, "ran for longer than {:.1}s", limit.as_secs_f64())
+            }
+            LimitExceeded::Instructions(limit) => {
+                write!(f, "executed more than {} instructions", limit)
+            }
+            LimitExceeded::WritesPerFrame { writes, limit } => write!(
+                f,
+                "wrote {} bytes in a frame, more than the {} allowed",
+                writes, limit
+            ),
+        }
+    }
+}
+
+/// Keeps track of one run against its limits, checked between frames
+#[derive(Debug, Clone)]
+pub struct LimitGuard {
+    limits: Limits,
+    started: Instant,
+    first_instruction: u64,
+    /// Bytes written when the frame being run started
+    frame_writes: u64,
+}
+
+impl LimitGuard {
+    /// Start timing a run of `chip8` from its state now
+    pub fn start(limits: Limits, chip8: &Chip8) -> Self {
+        Self {
+            limits,
+            started: Instant::now(),
+            first_instruction: chip8.instruction_count(),
+            frame_writes: chip8.bytes_written(),
+        }
+    }
+
+    /// Check the run after each frame of `chip8`
+    pub fn check(&mut self, chip8: &Chip8) -> Result<(), LimitExceeded> {
+        let writes = chip8.bytes_written() - self.frame_writes;
+        self.frame_writes = chip8.bytes_written();
+        if let Some(limit) = self.limits.writes_per_frame.filter(|&limit| writes > limit) {
+            return Err(LimitExceeded::WritesPerFrame { writes, limit });
+        }
+        let instructions = chip8.instruction_count() - self.first_instruction;
+        if let Some(limit) = self
+            .limits
+            .instructions
+            .filter(|&limit| instructions > limit)
+        {
+            return Err(LimitExceeded::Instructions(limit));
+        }
+        match self.limits.wall_clock {
+            Some(limit) if self.started.elapsed() > limit => Err(LimitExceeded::WallClock(limit)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_stop_at_their_limits() {
+        // Stores V0-VF over and over
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0xA3, 0x00, 0xFF, 0x55, 0x12, 0x00]);
+        let limits = Limits {
+            instructions: Some(1000),
+            ..Limits::default()
+        };
+        let mut guard = LimitGuard::start(limits, &chip8);
+        let stopped = (0..1000).find_map(|_| {
+            chip8.run_frame().unwrap();
+            guard.check(&chip8).err()
+        });
+        assert_eq!(stopped, Some(LimitExceeded::Instructions(1000)));
+        assert!(chip8.instruction_count() <= 1000 + chip8.clock().instructions_per_frame as u64);
+
+        let limits = Limits {
+            writes_per_frame: Some(16),
+            ..Limits::default()
+        };
+        let mut guard = LimitGuard::start(limits, &chip8);
+        chip8.run_frame().unwrap();
+        assert!(matches!(
+            guard.check(&chip8),
+            Err(LimitExceeded::WritesPerFrame { limit: 16, .. })
+        ));
+
+        let limits = Limits {
+            wall_clock: Some(Duration::ZERO),
+            ..Limits::default()
+        };
+        let mut guard = LimitGuard::start(limits, &chip8);
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(
+            guard.check(&chip8),
+            Err(LimitExceeded::WallClock(Duration::ZERO))
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::disasm::disassemble_at;
+use crate::{Instruction, Platform, Quirks, RAM_SIZE, ROM_INITIAL_POSITION, STACK_SIZE};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    /// A jump or call lands on an odd address
+    OddTarget { target: u16 },
+    /// A jump or call lands outside the loaded ROM
+    TargetOutsideRom { target: u16 },
+    /// A reachable opcode the platform doesn't implement
+    UnsupportedOpcode { opcode: u16, platform: Platform },
+    /// A sprite or register load reads memory after the end of the ROM
+    ReadPastRomEnd { start: u16, end: u16 },
+    /// Execution continues past the last instruction of the ROM
+    RunsOffRomEnd,
+    /// Subroutine calls can nest deeper than the stack allows
+    StackOverflow { depth: usize },
+    /// A subroutine can call itself, so the stack depth can't be bounded
+    Recursion { subroutine: u16 },
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            LintKind::OddTarget { target } => {
+                write!(f, "jump or call to odd address 0x{:03X}", target)
+            }
+            LintKind::TargetOutsideRom { target } => {
+                write!(f, "jump or call to 0x{:03X}, outside the ROM", target)
+            }
+            LintKind::UnsupportedOpcode { opcode, platform } => {
+                write!(f, "opcode {:04X} is not supported on {}", opcode, platform)
+            }
+            LintKind::ReadPastRomEnd { start, end } => write!(
+                f,
+                "reads memory 0x{:03X}..0x{:03X}, past the end of the ROM",
+                start, end
+            ),
+            LintKind::RunsOffRomEnd => write!(f, "execution runs past the end of the ROM"),
+            LintKind::StackOverflow { depth } => write!(
+                f,
+                "subroutine calls nest {} deep, but the stack only holds {}",
+                depth, STACK_SIZE
+            ),
+            LintKind::Recursion { subroutine } => write!(
+                f,
+                "subroutine 0x{:03X} is recursive, stack depth can't be bounded",
+                subroutine
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LintWarning {
+    /// Address of the instruction the warning is about
+    pub addr: u16,
+    pub kind: LintKind,
+}
+
+/// Deepest estimated nesting of subroutine calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackDepth {
+    Bounded(usize),
+    Unbounded,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintReport {
+    pub warnings: Vec<LintWarning>,
+    pub max_stack_depth: StackDepth,
+}
+
+/// Statically scan the code reachable from the ROM's entry point for suspicious patterns.
+///
+/// Computed jumps (`BNNN`) can't be followed, so code only reachable through them isn't checked.
+pub fn lint(rom: &[u8], platform: Platform) -> LintReport {
+    let rom_end = (ROM_INITIAL_POSITION + rom.len()) as u16;
+    let quirks = Quirks::for_platform(platform);
+    let mut warnings = vec![];
+    let mut warn = |addr, kind| {
+        let warning = LintWarning { addr, kind };
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    };
+
+    // Walk every path from the entry point, remembering what I is known to hold
+    let mut visited = BTreeSet::new();
+    let mut pending = vec![(ROM_INITIAL_POSITION as u16, None, None::<u16>)];
+    while let Some((addr, from, known_i)) = pending.pop() {
+        if addr >= rom_end || addr as usize + 1 >= RAM_SIZE {
+            if let Some(from) = from {
+                warn(from, LintKind::RunsOffRomEnd);
+            }
+            continue;
+        }
+        if !visited.insert(addr) {
+            continue;
+        }
+        let Some(line) = disassemble_at(rom, ROM_INITIAL_POSITION as u16, addr) else {
+            warn(from.unwrap_or(addr), LintKind::RunsOffRomEnd);
+            continue;
+        };
+        let Some(instruction) = Instruction::decode_for(line.opcode, platform)
+            .filter(|instruction| platform.supports(instruction))
+        else {
+            warn(
+                addr,
+                LintKind::UnsupportedOpcode {
+                    opcode: line.opcode,
+                    platform,
+                },
+            );
+            continue;
+        };
+
+        if let Instruction::Jump { nnn } | Instruction::Call { nnn } = instruction {
+            if nnn % 2 == 1 {
+                warn(addr, LintKind::OddTarget { target: nnn });
+            }
+            if nnn < ROM_INITIAL_POSITION as u16 || nnn >= rom_end {
+                warn(addr, LintKind::TargetOutsideRom { target: nnn });
+            }
+        }
+
+        if let Some(i) = known_i {
+            let read_len = match instruction {
+                Instruction::Draw { n, .. } => {
+                    let (width, height) = quirks.sprite_size(n);
+                    (width / 8 * height) as u16
+                }
+                Instruction::LoadRegisters { x } => x as u16 + 1,
+                _ => 0,
+            };
+            if read_len > 0 && i >= ROM_INITIAL_POSITION as u16 && i + read_len > rom_end {
+                warn(
+                    addr,
+                    LintKind::ReadPastRomEnd {
+                        start: i,
+                        end: i + read_len,
+                    },
+                );
+            }
+        }
+        let known_i = match instruction {
+            Instruction::SetIndex { nnn } => Some(nnn),
+            Instruction::AddToIndex { .. }
+            | Instruction::FontCharacter { .. }
+            | Instruction::BinaryCodedDecimal { .. }
+            | Instruction::StoreRegisters { .. }
+            | Instruction::LoadRegisters { .. } => None,
+            _ => known_i,
+        };
+
+        for next in successors(addr, instruction, true) {
+            // targets outside the ROM have already been reported
+            let is_target = matches!(instruction, Instruction::Jump { nnn } | Instruction::Call { nnn } if nnn == next);
+            if is_target && (next < ROM_INITIAL_POSITION as u16 || next >= rom_end) {
+                continue;
+            }
+            pending.push((next, Some(addr), known_i));
+        }
+    }
+
+    let max_stack_depth = estimate_stack_depth(rom, rom_end, &mut warn);
+    if let StackDepth::Bounded(depth) = max_stack_depth {
+        if depth > STACK_SIZE {
+            warn(
+                ROM_INITIAL_POSITION as u16,
+                LintKind::StackOverflow { depth },
+            );
+        }
+    }
+
+    warnings.sort_by_key(|warning| warning.addr);
+    LintReport {
+        warnings,
+        max_stack_depth,
+    }
+}
+
+/// Addresses execution can continue at after `instruction`.
+/// Calls continue at their target, plus after returning when `through_calls` is set.
+pub(crate) fn successors(addr: u16, instruction: Instruction, through_calls: bool) -> Vec<u16> {
+    match instruction {
+        Instruction::Jump { nnn } => vec![nnn],
+        Instruction::Call { nnn } if through_calls => vec![nnn, addr + 2],
+        Instruction::Call { .. } => vec![addr + 2],
+        Instruction::Return | Instruction::JumpWithOffset { .. } => vec![],
+        Instruction::SkipIfEqual { .. }
+        | Instruction::SkipIfNotEqual { .. }
+        | Instruction::SkipIfRegistersEqual { .. }
+        | Instruction::SkipIfRegistersNotEqual { .. }
+        | Instruction::SkipIfKey { .. }
+        | Instruction::SkipIfNotKey { .. }
+        | Instruction::SkipIfSecondKey { .. }
+        | Instruction::SkipIfNotSecondKey { .. } => vec![addr + 2, addr + 4],
+        _ => vec![addr + 2],
+    }
+}
+
+/// Subroutines called from the code reachable from `entry`, without entering them
+fn calls_from(rom: &[u8], rom_end: u16, entry: u16) -> BTreeSet<u16> {
+    let mut calls = BTreeSet::new();
+    let mut visited = BTreeSet::new();
+    let mut pending = vec![entry];
+    while let Some(addr) = pending.pop() {
+        if addr >= rom_end || !visited.insert(addr) {
+            continue;
+        }
+        let Some(instruction) = disassemble_at(rom, ROM_INITIAL_POSITION as u16, addr)
+            .and_then(|line| line.instruction)
+        else {
+            continue;
+        };
+        if let Instruction::Call { nnn } = instruction {
+            calls.insert(nnn);
+        }
+        pending.extend(successors(addr, instruction, false));
+    }
+    calls
+}
+
+fn estimate_stack_depth(
+    rom: &[u8],
+    rom_end: u16,
+    warn: &mut impl FnMut(u16, LintKind),
+) -> StackDepth {
+    fn depth_of(
+        subroutine: u16,
+        graph: &mut BTreeMap<u16, BTreeSet<u16>>,
+        depths: &mut BTreeMap<u16, StackDepth>,
+        in_progress: &mut BTreeSet<u16>,
+        rom: &[u8],
+        rom_end: u16,
+        warn: &mut impl FnMut(u16, LintKind),
+    ) -> StackDepth {
+        if let Some(depth) = depths.get(&subroutine) {
+            return *depth;
+        }
+        if !in_progress.insert(subroutine) {
+            warn(subroutine, LintKind::Recursion { subroutine });
+            return StackDepth::Unbounded;
+        }
+        let callees = graph
+            .entry(subroutine)
+            .or_insert_with(|| calls_from(rom, rom_end, subroutine))
+            .clone();
+        let mut depth = StackDepth::Bounded(0);
+        for callee in callees {
+            depth = match (
+                depth,
+                depth_of(callee, graph, depths, in_progress, rom, rom_end, warn),
+            ) {
+                (StackDepth::Bounded(current), StackDepth::Bounded(nested)) => {
+                    StackDepth::Bounded(current.max(nested + 1))
+                }
+                _ => StackDepth::Unbounded,
+            };
+        }
+        in_progress.remove(&subroutine);
+        depths.insert(subroutine, depth);
+        depth
+    }
+
+    depth_of(
+        ROM_INITIAL_POSITION as u16,
+        &mut BTreeMap::new(),
+        &mut BTreeMap::new(),
+        &mut BTreeSet::new(),
+        rom,
+        rom_end,
+        warn,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_rom_has_no_warnings() {
+        let report = lint(
+            &[
+                0x22, 0x04, // 200: call 204
+                0x12, 0x02, // 202: jump to self
+                0xA2, 0x0A, // 204: I = 20A
+                0xD0, 0x11, // 206: draw 1 row
+                0x00, 0xEE, // 208: return
+                0xFF, // 20A: sprite
+            ],
+            Platform::CosmacVip,
+        );
+
+        assert_eq!(report.warnings, []);
+        assert_eq!(report.max_stack_depth, StackDepth::Bounded(1));
+    }
+
+    #[test]
+    fn suspicious_patterns_are_reported() {
+        let report = lint(
+            &[
+                0x32, 0x00, // 200: skip if V2 == 0
+                0x13, 0x01, // 202: jump to odd address outside the ROM
+                0xA2, 0x08, // 204: I = 208
+                0xD0, 0x15, // 206: draw 5 rows, 2 of which are past the end
+                0x01, 0x23, // 208: machine code routine
+            ],
+            Platform::CosmacVip,
+        );
+
+        assert_eq!(
+            report.warnings,
+            [
+                LintWarning {
+                    addr: 0x202,
+                    kind: LintKind::OddTarget { target: 0x301 }
+                },
+                LintWarning {
+                    addr: 0x202,
+                    kind: LintKind::TargetOutsideRom { target: 0x301 }
+                },
+                LintWarning {
+                    addr: 0x206,
+                    kind: LintKind::ReadPastRomEnd {
+                        start: 0x208,
+                        end: 0x20D
+                    }
+                },
+                LintWarning {
+                    addr: 0x208,
+                    kind: LintKind::UnsupportedOpcode {
+                        opcode: 0x0123,
+                        platform: Platform::CosmacVip
+                    }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn recursion_makes_stack_depth_unbounded() {
+        let report = lint(&[0x22, 0x02, 0x22, 0x02], Platform::CosmacVip);
+
+        assert_eq!(report.max_stack_depth, StackDepth::Unbounded);
+        assert!(report.warnings.contains(&LintWarning {
+            addr: 0x202,
+            kind: LintKind::Recursion { subroutine: 0x202 }
+        }));
+    }
+}
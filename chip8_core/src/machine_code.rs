@@ -0,0 +1,110 @@
+//! Stand-ins for the machine-language routines a few original programs call with 0NNN.
+//! Those ran on the COSMAC VIP's processor itself, so emulating them would mean emulating
+//! the CDP1802; instead, calls to chosen addresses run a built-in handler doing what the
+//! routine was for. Calls to any other address stop the machine with
+//! [`crate::Chip8Error::MachineCode`].
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MachineRoutine {
+    /// Carry on at the next instruction, for routines whose effect the game can do without
+    Ignore,
+    /// Clear the screen like 00E0, which was what many of these routines were for
+    ClearScreen,
+    /// Stay on the call forever, for routines that exit to the VIP's monitor
+    Halt,
+}
+
+impl MachineRoutine {
+    pub const ALL: [MachineRoutine; 3] = [
+        MachineRoutine::Ignore,
+        MachineRoutine::ClearScreen,
+        MachineRoutine::Halt,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MachineRoutine::Ignore => "ignore",
+            MachineRoutine::ClearScreen => "clear",
+            MachineRoutine::Halt => "halt",
+        }
+    }
+
+    /// A routine for an address, as `ADDR=ROUTINE` with the address in hex like `0x2F0=clear`
+    pub fn parse_mapping(s: &str) -> Result<(u16, MachineRoutine), String> {
+        let Some((addr, routine)) = s.split_once('=') else {
+            return Err(format!("'{}' is not ADDR=ROUTINE", s));
+        };
+        let digits = addr.trim_start_matches("0x");
+        let addr = match u16::from_str_radix(digits, 16) {
+            Ok(addr) if (1..0x1000).contains(&addr) => addr,
+            _ => return Err(format!("'{}' is not an address from 0x001 to 0xFFF", addr)),
+        };
+        Ok((addr, routine.parse()?))
+    }
+}
+
+impl fmt::Display for MachineRoutine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for MachineRoutine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MachineRoutine::ALL
+            .into_iter()
+            .find(|routine| routine.name() == s)
+            .ok_or_else(|| {
+                let names = MachineRoutine::ALL.map(MachineRoutine::name);
+                format!(
+                    "unknown machine routine '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Chip8, Chip8Error, MachineRoutine};
+
+    #[test]
+    fn machine_code_calls_run_handlers_or_stop_the_machine() {
+        let rom = [
+            0xA0, 0x50, // 200: I = font character 0
+            0xD0, 0x05, // 202: draw it
+            0x02, 0xF0, // 204: call the machine routine at 0x2F0
+            0x03, 0x00, // 206: call the machine routine at 0x300
+        ];
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&rom);
+        chip8.set_machine_routine(0x2F0, MachineRoutine::ClearScreen);
+        for _ in 0..3 {
+            chip8.step().unwrap();
+        }
+        assert!(chip8.displayed_screen().iter().all(|&on| !on));
+        assert_eq!(
+            chip8.step(),
+            Err(Chip8Error::MachineCode {
+                pc: 0x206,
+                addr: 0x300
+            })
+        );
+
+        chip8.set_machine_routine(0x300, MachineRoutine::Halt);
+        chip8.step().unwrap();
+        assert_eq!(chip8.cpu_state().pc, 0x206);
+        assert_eq!(
+            MachineRoutine::parse_mapping("0x2F0=ignore"),
+            Ok((0x2F0, MachineRoutine::Ignore))
+        );
+        assert!(MachineRoutine::parse_mapping("0x2F0=jump").is_err());
+        assert!(MachineRoutine::parse_mapping("0x1000=halt").is_err());
+    }
+}
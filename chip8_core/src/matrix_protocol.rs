@@ -0,0 +1,99 @@
+//! Framing of screens streamed over a serial line to hardware mirroring the display, like
+//! a microcontroller driving an LED matrix. Each frame is, in order:
+//!
+//! - the magic bytes `C8`
+//! - the protocol version, [`VERSION`]
+//! - a sequence number counting frames, wrapping after 255, to notice dropped ones
+//! - the width and height in pixels, 64 and 32
+//! - the pixels row by row from the top left, 8 to a byte with the leftmost in the high bit
+//! - the XOR of every pixel byte
+//!
+//! Receivers losing track of the frames find the next one by looking for the magic bytes
+//! followed by a valid frame.
+
+use crate::{index_from_point, PIXELS_PER_COLUMN, PIXELS_PER_ROW, PIXELS_PER_SCREEN};
+
+pub const MAGIC: [u8; 2] = *b"C8";
+pub const VERSION: u8 = 1;
+const HEADER_LEN: usize = 6;
+/// Bytes in every frame
+pub const FRAME_LEN: usize = HEADER_LEN + PIXELS_PER_SCREEN / 8 + 1;
+
+pub fn encode_frame(screen: &[bool; PIXELS_PER_SCREEN], sequence: u8) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(FRAME_LEN);
+    frame.extend(MAGIC);
+    frame.extend([
+        VERSION,
+        sequence,
+        PIXELS_PER_ROW as u8,
+        PIXELS_PER_COLUMN as u8,
+    ]);
+    for y in 0..PIXELS_PER_COLUMN {
+        for x in (0..PIXELS_PER_ROW).step_by(8) {
+            let byte = (0..8).fold(0, |byte, bit| {
+                byte << 1 | screen[index_from_point((y, x + bit))] as u8
+            });
+            frame.push(byte);
+        }
+    }
+    let checksum = frame[HEADER_LEN..].iter().fold(0, |sum, byte| sum ^ byte);
+    frame.push(checksum);
+    frame
+}
+
+/// Read back a frame, as its sequence number and screen
+pub fn decode_frame(frame: &[u8]) -> Result<(u8, [bool; PIXELS_PER_SCREEN]), String> {
+    if frame.len() != FRAME_LEN {
+        return Err(format!(
+            "expected {} bytes, found {}",
+            FRAME_LEN,
+            frame.len()
+        ));
+    }
+    let (header, rest) = frame.split_at(HEADER_LEN);
+    let (pixels, checksum) = rest.split_at(rest.len() - 1);
+    if header[..2] != MAGIC {
+        return Err("the frame doesn't start with the magic bytes".to_string());
+    }
+    if header[2] != VERSION {
+        return Err(format!("unsupported protocol version {}", header[2]));
+    }
+    if header[4..] != [PIXELS_PER_ROW as u8, PIXELS_PER_COLUMN as u8] {
+        return Err(format!("unsupported size {}x{}", header[4], header[5]));
+    }
+    if pixels.iter().fold(0, |sum, byte| sum ^ byte) != checksum[0] {
+        return Err("the checksum doesn't match".to_string());
+    }
+    let mut screen = [false; PIXELS_PER_SCREEN];
+    for (index, byte) in pixels.iter().enumerate() {
+        let (y, x) = (index * 8 / PIXELS_PER_ROW, index * 8 % PIXELS_PER_ROW);
+        for bit in 0..8 {
+            screen[index_from_point((y, x + bit))] = byte & 0x80 >> bit != 0;
+        }
+    }
+    Ok((header[3], screen))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_round_trip() {
+        let mut screen = [false; PIXELS_PER_SCREEN];
+        screen[index_from_point((0, 0))] = true;
+        screen[index_from_point((31, 63))] = true;
+        let frame = encode_frame(&screen, 7);
+        assert_eq!(frame.len(), FRAME_LEN);
+        assert_eq!(frame[..HEADER_LEN], [b'C', b'8', 1, 7, 64, 32]);
+        assert_eq!(frame[HEADER_LEN], 0x80);
+        assert_eq!(frame[FRAME_LEN - 2], 0x01);
+        assert_eq!(frame[FRAME_LEN - 1], 0x81);
+        assert_eq!(decode_frame(&frame), Ok((7, screen)));
+
+        let mut corrupted = frame.clone();
+        corrupted[HEADER_LEN + 3] ^= 0x10;
+        assert!(decode_frame(&corrupted).is_err());
+        assert!(decode_frame(&frame[1..]).is_err());
+    }
+}
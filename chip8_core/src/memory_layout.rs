@@ -0,0 +1,184 @@
+//! Checking where a ROM and the data files loaded next to it go in memory. Some homebrew
+//! comes as code plus files of levels or graphics that must sit at fixed addresses; they
+//! mustn't overlap each other or the ROM, and must fit in the memory of the machine the
+//! program was written for, which isn't always the full 4K.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::{RAM_SIZE, ROM_INITIAL_POSITION};
+
+/// A data file to place at `addr` with [`crate::Chip8::load_segment`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overlay {
+    pub name: String,
+    pub addr: usize,
+    pub data: Vec<u8>,
+}
+
+impl Overlay {
+    pub fn range(&self) -> Range<usize> {
+        self.addr..self.addr + self.data.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The file ends past the memory the machine has
+    DoesNotFit {
+        name: String,
+        range: Range<usize>,
+        ram_size: usize,
+    },
+    /// The file starts below 0x200, over the interpreter and its font
+    InInterpreterArea { name: String, addr: usize },
+    /// Two files share the addresses in `range`
+    Overlap {
+        first: String,
+        second: String,
+        range: Range<usize>,
+    },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::DoesNotFit {
+                name,
+                range,
+                ram_size,
+            } => write!(
+                f,
+                "{} takes 0x{:03X}..0x{:03X}, past the end of {} bytes of memory",
+                name, range.start, range.end, ram_size
+            ),
+            LayoutError::InInterpreterArea { name, addr } => write!(
+                f,
+                "{} starts at 0x{:03X}, inside the interpreter area below 0x200",
+                name, addr
+            ),
+            LayoutError::Overlap {
+                first,
+                second,
+                range,
+            } => write!(
+                f,
+                "{} and {} both take 0x{:03X}..0x{:03X}",
+                first, second, range.start, range.end
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// The files placed in memory so far, checked as each is added
+#[derive(Debug, Clone)]
+pub struct MemoryLayout {
+    ram_size: usize,
+    placed: Vec<(String, Range<usize>)>,
+}
+
+impl MemoryLayout {
+    /// An empty layout for a machine with `ram_size` bytes of memory, at most [`RAM_SIZE`]
+    pub fn new(ram_size: usize) -> Self {
+        Self {
+            ram_size: ram_size.min(RAM_SIZE),
+            placed: vec![],
+        }
+    }
+
+    pub fn ram_size(&self) -> usize {
+        self.ram_size
+    }
+
+    /// The largest ROM that fits, without bank switching
+    pub fn max_rom_size(&self) -> usize {
+        self.ram_size.saturating_sub(ROM_INITIAL_POSITION)
+    }
+
+    /// Add the `len` bytes the ROM takes at 0x200
+    pub fn place_rom(&mut self, len: usize) -> Result<(), LayoutError> {
+        self.place("the ROM", ROM_INITIAL_POSITION, len)
+    }
+
+    /// Add the file `name` of `len` bytes at `addr`, unless it's out of memory or overlaps
+    /// something placed before
+    pub fn place(&mut self, name: &str, addr: usize, len: usize) -> Result<(), LayoutError> {
+        let range = addr..addr + len;
+        if addr < ROM_INITIAL_POSITION {
+            return Err(LayoutError::InInterpreterArea {
+                name: name.to_string(),
+                addr,
+            });
+        }
+        if range.end > self.ram_size {
+            return Err(LayoutError::DoesNotFit {
+                name: name.to_string(),
+                range,
+                ram_size: self.ram_size,
+            });
+        }
+        for (other, taken) in &self.placed {
+            let shared = range.start.max(taken.start)..range.end.min(taken.end);
+            if !shared.is_empty() {
+                return Err(LayoutError::Overlap {
+                    first: other.clone(),
+                    second: name.to_string(),
+                    range: shared,
+                });
+            }
+        }
+        if !range.is_empty() {
+            self.placed.push((name.to_string(), range));
+        }
+        Ok(())
+    }
+
+    /// The files placed so far with the addresses they take, in the order they were added
+    pub fn placed(&self) -> &[(String, Range<usize>)] {
+        &self.placed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Chip8;
+
+    #[test]
+    fn overlays_must_fit_without_overlapping() {
+        let mut layout = MemoryLayout::new(2048);
+        assert_eq!(layout.max_rom_size(), 0x600);
+        layout.place_rom(0x100).unwrap();
+        layout.place("levels.bin", 0x400, 0x80).unwrap();
+        assert_eq!(
+            layout.place("tiles.bin", 0x2F0, 0x20),
+            Err(LayoutError::Overlap {
+                first: "the ROM".to_string(),
+                second: "tiles.bin".to_string(),
+                range: 0x2F0..0x300,
+            })
+        );
+        assert!(matches!(
+            layout.place("music.bin", 0x7F0, 0x20),
+            Err(LayoutError::DoesNotFit { ram_size: 2048, .. })
+        ));
+        assert!(matches!(
+            layout.place("font.bin", 0x000, 0x50),
+            Err(LayoutError::InInterpreterArea { addr: 0, .. })
+        ));
+        assert_eq!(layout.placed().len(), 2);
+
+        let overlay = Overlay {
+            name: "levels.bin".to_string(),
+            addr: 0x400,
+            data: vec![0xAB; 0x80],
+        };
+        let mut chip8 = Chip8::new();
+        chip8.load_segment(overlay.addr, &overlay.data).unwrap();
+        chip8.load_rom(&[0x12, 0x00]);
+        assert_eq!(chip8.segments(), [overlay.range()]);
+        assert_eq!(chip8.memory()[0x47F], 0xAB);
+    }
+}
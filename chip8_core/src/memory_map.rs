@@ -0,0 +1,211 @@
+//! How a ROM uses its bytes, found statically: code is what's reachable from the entry
+//! point, data what that code reads through I after pointing it into the ROM with ANNN,
+//! like sprites and register loads. What's left is unreferenced, often padding, or data
+//! only reached through computed jumps or I arithmetic that can't be followed.
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use crate::disasm::disassemble_at;
+use crate::json::Value;
+use crate::lint::successors;
+use crate::{Instruction, Platform, Quirks, ROM_INITIAL_POSITION};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ByteUse {
+    Code,
+    Data,
+    Unreferenced,
+}
+
+impl ByteUse {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ByteUse::Code => "code",
+            ByteUse::Data => "data",
+            ByteUse::Unreferenced => "unreferenced",
+        }
+    }
+}
+
+/// Consecutive bytes used the same way
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub byte_use: ByteUse,
+    pub range: Range<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryMap {
+    /// One entry per ROM byte, from 0x200
+    uses: Vec<ByteUse>,
+}
+
+impl MemoryMap {
+    /// Map the bytes of `rom` as decoded for `platform`. A byte both executed and read is
+    /// code.
+    pub fn analyze(rom: &[u8], platform: Platform) -> Self {
+        let origin = ROM_INITIAL_POSITION as u16;
+        let rom_end = origin + rom.len() as u16;
+        let quirks = Quirks::for_platform(platform);
+        let mut uses = vec![ByteUse::Unreferenced; rom.len()];
+        let mut mark = |range: Range<u16>, byte_use| {
+            let start = range.start.max(origin).min(rom_end);
+            let end = range.end.min(rom_end);
+            for used in &mut uses[(start - origin) as usize..(end.max(start) - origin) as usize] {
+                if *used != ByteUse::Code {
+                    *used = byte_use;
+                }
+            }
+        };
+
+        // Walk every path from the entry point, remembering what I is known to hold
+        let mut visited = BTreeSet::new();
+        let mut pending = vec![(origin, None::<u16>)];
+        while let Some((addr, known_i)) = pending.pop() {
+            if addr >= rom_end || !visited.insert((addr, known_i)) {
+                continue;
+            }
+            let Some(line) = disassemble_at(rom, origin, addr) else {
+                continue;
+            };
+            mark(addr..addr + 2, ByteUse::Code);
+            let Some(instruction) = Instruction::decode_for(line.opcode, platform)
+                .filter(|instruction| platform.supports(instruction))
+            else {
+                continue;
+            };
+
+            let read_len = match instruction {
+                Instruction::Draw { n, .. } => {
+                    let (width, height) = quirks.sprite_size(n);
+                    (width / 8 * height) as u16
+                }
+                Instruction::BinaryCodedDecimal { .. } => 3,
+                Instruction::StoreRegisters { x } | Instruction::LoadRegisters { x } => {
+                    x as u16 + 1
+                }
+                _ => 0,
+            };
+            if let Some(i) = known_i.filter(|_| read_len > 0) {
+                mark(i..i.saturating_add(read_len), ByteUse::Data);
+            }
+            let known_i = match instruction {
+                Instruction::SetIndex { nnn } => Some(nnn),
+                Instruction::AddToIndex { .. }
+                | Instruction::FontCharacter { .. }
+                | Instruction::BinaryCodedDecimal { .. }
+                | Instruction::StoreRegisters { .. }
+                | Instruction::LoadRegisters { .. } => None,
+                _ => known_i,
+            };
+            for next in successors(addr, instruction, true) {
+                pending.push((next, known_i));
+            }
+        }
+        Self { uses }
+    }
+
+    /// How the byte at `addr` is used, `None` outside the ROM
+    pub fn byte_use(&self, addr: u16) -> Option<ByteUse> {
+        let offset = (addr as usize).checked_sub(ROM_INITIAL_POSITION)?;
+        self.uses.get(offset).copied()
+    }
+
+    /// How many of the ROM's bytes are used as `byte_use`
+    pub fn count(&self, byte_use: ByteUse) -> usize {
+        self.uses.iter().filter(|&&used| used == byte_use).count()
+    }
+
+    /// The ROM split into runs of bytes used the same way, in order
+    pub fn regions(&self) -> Vec<Region> {
+        let mut regions: Vec<Region> = vec![];
+        for (offset, &byte_use) in self.uses.iter().enumerate() {
+            let addr = (ROM_INITIAL_POSITION + offset) as u16;
+            match regions.last_mut() {
+                Some(region) if region.byte_use == byte_use => region.range.end = addr + 1,
+                _ => regions.push(Region {
+                    byte_use,
+                    range: addr..addr + 1,
+                }),
+            }
+        }
+        regions
+    }
+
+    /// The map as a JSON object like
+    /// `{"size":6,"code":4,"data":1,"unreferenced":1,"regions":[{"use":"code","start":512,"end":516},...]}`
+    pub fn to_json(&self) -> Value {
+        let regions = self
+            .regions()
+            .into_iter()
+            .map(|region| {
+                Value::Object(vec![
+                    ("use".to_string(), region.byte_use.name().into()),
+                    ("start".to_string(), (region.range.start as u32).into()),
+                    ("end".to_string(), (region.range.end as u32).into()),
+                ])
+            })
+            .collect();
+        let mut members = vec![("size".to_string(), (self.uses.len() as u32).into())];
+        for byte_use in [ByteUse::Code, ByteUse::Data, ByteUse::Unreferenced] {
+            let count = self.count(byte_use) as u32;
+            members.push((byte_use.name().to_string(), count.into()));
+        }
+        members.push(("regions".to_string(), Value::Array(regions)));
+        Value::Object(members)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_data_and_unreferenced_bytes_are_told_apart() {
+        let rom = [
+            0xA2, 0x0C, // 200: I = 20C
+            0xD0, 0x12, // 202: draw 2 rows
+            0x22, 0x0A, // 204: call 20A
+            0x12, 0x06, // 206: jump to self
+            0x00, 0x00, // 208: padding
+            0x00, 0xEE, // 20A: return
+            0x3C, 0x42, // 20C: sprite
+            0xFF, // 20E: unused
+        ];
+        let map = MemoryMap::analyze(&rom, Platform::CosmacVip);
+
+        assert_eq!(
+            map.regions(),
+            [
+                Region {
+                    byte_use: ByteUse::Code,
+                    range: 0x200..0x208,
+                },
+                Region {
+                    byte_use: ByteUse::Unreferenced,
+                    range: 0x208..0x20A,
+                },
+                Region {
+                    byte_use: ByteUse::Code,
+                    range: 0x20A..0x20C,
+                },
+                Region {
+                    byte_use: ByteUse::Data,
+                    range: 0x20C..0x20E,
+                },
+                Region {
+                    byte_use: ByteUse::Unreferenced,
+                    range: 0x20E..0x20F,
+                },
+            ]
+        );
+        assert_eq!(map.byte_use(0x20D), Some(ByteUse::Data));
+        assert_eq!(map.byte_use(0x20F), None);
+        assert_eq!(map.count(ByteUse::Code), 10);
+        assert_eq!(
+            map.to_json().get("unreferenced").and_then(Value::as_f64),
+            Some(3.0)
+        );
+    }
+}
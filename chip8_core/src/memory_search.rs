@@ -0,0 +1,156 @@
+//! Finding where a program keeps a value, like the lives or the score, by narrowing down
+//! memory addresses between snapshots: "lost a life, so it decreased", "nothing happened,
+//! so it's unchanged", and so on until a few addresses remain.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// How a candidate's byte must compare with the previous snapshot to stay a candidate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchFilter {
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+    Equals(u8),
+}
+
+impl SearchFilter {
+    fn keeps(self, before: u8, now: u8) -> bool {
+        match self {
+            Self::Changed => now != before,
+            Self::Unchanged => now == before,
+            Self::Increased => now > before,
+            Self::Decreased => now < before,
+            Self::Equals(value) => now == value,
+        }
+    }
+}
+
+impl FromStr for SearchFilter {
+    type Err = String;
+
+    /// `changed`, `unchanged`, `increased`, `decreased` or a value to equal, like `3` or
+    /// `0x1F`
+    fn from_str(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let value = s.strip_prefix("equals").map(str::trim).unwrap_or(s);
+        let value = match value.strip_prefix("0x") {
+            Some(digits) => u8::from_str_radix(digits, 16).ok(),
+            None => value.parse().ok(),
+        };
+        match (s, value) {
+            ("changed", _) => Ok(Self::Changed),
+            ("unchanged", _) => Ok(Self::Unchanged),
+            ("increased", _) => Ok(Self::Increased),
+            ("decreased", _) => Ok(Self::Decreased),
+            (_, Some(value)) => Ok(Self::Equals(value)),
+            _ => Err(format!("'{}' is not a search filter", s)),
+        }
+    }
+}
+
+impl fmt::Display for SearchFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Changed => f.write_str("changed"),
+            Self::Unchanged => f.write_str("unchanged"),
+            Self::Increased => f.write_str("increased"),
+            Self::Decreased => f.write_str("decreased"),
+            Self::Equals(value) => write!(f, "equals {}", value),
+        }
+    }
+}
+
+/// Addresses still matching every filter applied since the search started, and the memory
+/// they're compared against next
+#[derive(Debug, Clone)]
+pub struct MemorySearch {
+    snapshot: Vec<u8>,
+    candidates: Vec<u16>,
+}
+
+impl MemorySearch {
+    /// Start with every address as a candidate
+    pub fn new(memory: &[u8]) -> Self {
+        Self {
+            snapshot: memory.to_vec(),
+            candidates: (0..memory.len() as u16).collect(),
+        }
+    }
+
+    /// Drop the candidates whose byte in `memory` doesn't pass `filter`, then take `memory`
+    /// as the snapshot for the next filter
+    pub fn filter(&mut self, memory: &[u8], filter: SearchFilter) {
+        let snapshot = &self.snapshot;
+        self.candidates.retain(|&addr| {
+            let addr = addr as usize;
+            filter.keeps(snapshot[addr], memory[addr])
+        });
+        self.snapshot.copy_from_slice(memory);
+    }
+
+    pub fn candidates(&self) -> &[u16] {
+        &self.candidates
+    }
+
+    /// One line like `3 candidates: 0x3F0=2 0x3F4=2 0x3FA=2`, listing at most `limit`
+    /// addresses with their current values
+    pub fn describe(&self, limit: usize) -> String {
+        let mut line = format!("{} candidates", self.candidates.len());
+        if !self.candidates.is_empty() && self.candidates.len() <= limit {
+            line.push(':');
+            for &addr in &self.candidates {
+                line += &format!(" 0x{:03X}={}", addr, self.snapshot[addr as usize]);
+            }
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Chip8;
+
+    #[test]
+    fn filters_narrow_down_a_counter() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[
+            0x60, 0x03, // V0 = 3 lives
+            0xA3, 0x00, // I = 0x300
+            0xF0, 0x55, // store V0 at 0x300
+            0x70, 0xFF, // V0 -= 1
+            0xA3, 0x00, // I = 0x300
+            0xF0, 0x55, // store V0 at 0x300
+            0x12, 0x0C, // loop forever
+        ]);
+        for _ in 0..3 {
+            chip8.step().unwrap();
+        }
+        let mut search = MemorySearch::new(chip8.memory());
+        search.filter(chip8.memory(), SearchFilter::Equals(3));
+        assert!(search.candidates().contains(&0x300));
+
+        for _ in 0..3 {
+            chip8.step().unwrap();
+        }
+        search.filter(chip8.memory(), SearchFilter::Decreased);
+        assert_eq!(search.candidates(), [0x300]);
+        assert_eq!(search.describe(8), "1 candidates: 0x300=2");
+
+        chip8.step().unwrap();
+        search.filter(chip8.memory(), SearchFilter::Changed);
+        assert!(search.candidates().is_empty());
+    }
+
+    #[test]
+    fn filters_parse_from_words_and_numbers() {
+        assert_eq!("changed".parse(), Ok(SearchFilter::Changed));
+        assert_eq!(" decreased ".parse(), Ok(SearchFilter::Decreased));
+        assert_eq!("equals 0x1F".parse(), Ok(SearchFilter::Equals(0x1F)));
+        assert_eq!("7".parse(), Ok(SearchFilter::Equals(7)));
+        assert!("256".parse::<SearchFilter>().is_err());
+        assert!("bigger".parse::<SearchFilter>().is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Interchange with the Octo IDE: its options JSON and its shared cartridge format,
+//! `{"program": <source>, "options": {...}}`
+
+use std::fmt::Write;
+
+use crate::disasm::disassemble;
+use crate::json::{self, JsonError, Value};
+use crate::{Chip8, ClockConfig, Quirks, ZeroHeightSprite, ROM_INITIAL_POSITION};
+
+/// Emulation options as Octo stores them. Fields missing from a file take Octo's defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OctoOptions {
+    /// Instructions per frame
+    pub tickrate: u32,
+    /// `8XY6`/`8XYE` shift VX in place
+    pub shift_quirks: bool,
+    /// `FX55`/`FX65` leave I unchanged
+    pub load_store_quirks: bool,
+    /// VF is written before the result of `8XY4`..`8XYE`
+    pub vf_order_quirks: bool,
+    /// Sprites are clipped at the screen edges instead of wrapping
+    pub clip_quirks: bool,
+    /// `DXYN` waits for the vertical blank
+    pub vblank_quirks: bool,
+    /// `BXNN` jumps to XNN plus VX
+    pub jump_quirks: bool,
+    /// `8XY1`/`8XY2`/`8XY3` reset VF
+    pub logic_quirks: bool,
+    /// Largest ROM Octo lets the program assemble to
+    pub max_size: u32,
+}
+
+impl Default for OctoOptions {
+    fn default() -> Self {
+        Self {
+            tickrate: 20,
+            shift_quirks: false,
+            load_store_quirks: false,
+            vf_order_quirks: false,
+            clip_quirks: false,
+            vblank_quirks: false,
+            jump_quirks: false,
+            logic_quirks: false,
+            max_size: 3584,
+        }
+    }
+}
+
+impl OctoOptions {
+    /// Options describing how this core runs with `quirks` at `clock` speed
+    pub fn from_machine(quirks: Quirks, clock: ClockConfig) -> Self {
+        Self {
+            tickrate: clock.instructions_per_frame,
+            shift_quirks: quirks.shift_in_place,
+            load_store_quirks: !quirks.increment_index,
+            jump_quirks: quirks.jump_with_vx,
+            logic_quirks: quirks.vf_reset,
+            // Octo can't wrap one axis and clip the other, so that counts as clipping
+            clip_quirks: !(quirks.wrap_horizontally && quirks.wrap_vertically),
+            // the core never waits for the vertical blank
+            ..Self::default()
+        }
+    }
+
+    pub fn quirks(&self) -> Quirks {
+        Quirks {
+            shift_in_place: self.shift_quirks,
+            vf_reset: self.logic_quirks,
+            increment_index: !self.load_store_quirks,
+            jump_with_vx: self.jump_quirks,
+            wrap_horizontally: !self.clip_quirks,
+            wrap_vertically: !self.clip_quirks,
+            // Octo always draws DXY0 as a 16x16 sprite
+            zero_height_sprite: ZeroHeightSprite::Large,
+            clipped_rows_collide: false,
+        }
+    }
+
+    pub fn clock(&self) -> ClockConfig {
+        ClockConfig {
+            instructions_per_frame: self.tickrate,
+            ..ClockConfig::default()
+        }
+    }
+
+    /// Names of the options set to behaviour the core can't emulate
+    pub fn unsupported(&self) -> Vec<&'static str> {
+        let mut unsupported = vec![];
+        if self.vf_order_quirks {
+            unsupported.push("vfOrderQuirks");
+        }
+        if self.vblank_quirks {
+            unsupported.push("vBlankQuirks");
+        }
+        unsupported
+    }
+
+    /// Read the options from an Octo options file, or from the `options` of a cartridge
+    pub fn parse(text: &str) -> Result<Self, JsonError> {
+        let document = json::parse(text)?;
+        let options = document.get("options").unwrap_or(&document);
+        let flag = |name: &str, default: bool| {
+            options
+                .get(name)
+                .and_then(Value::as_bool)
+                .unwrap_or(default)
+        };
+        let number = |name: &str, default: u32| {
+            options
+                .get(name)
+                .and_then(Value::as_f64)
+                .map(|value| value as u32)
+                .unwrap_or(default)
+        };
+
+        let defaults = Self::default();
+        Ok(Self {
+            tickrate: number("tickrate", defaults.tickrate),
+            shift_quirks: flag("shiftQuirks", defaults.shift_quirks),
+            load_store_quirks: flag("loadStoreQuirks", defaults.load_store_quirks),
+            vf_order_quirks: flag("vfOrderQuirks", defaults.vf_order_quirks),
+            clip_quirks: flag("clipQuirks", defaults.clip_quirks),
+            vblank_quirks: flag("vBlankQuirks", defaults.vblank_quirks),
+            jump_quirks: flag("jumpQuirks", defaults.jump_quirks),
+            logic_quirks: flag("logicQuirks", defaults.logic_quirks),
+            max_size: number("maxSize", defaults.max_size),
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::Object(vec![
+            ("tickrate".to_string(), self.tickrate.into()),
+            ("shiftQuirks".to_string(), self.shift_quirks.into()),
+            ("loadStoreQuirks".to_string(), self.load_store_quirks.into()),
+            ("vfOrderQuirks".to_string(), self.vf_order_quirks.into()),
+            ("clipQuirks".to_string(), self.clip_quirks.into()),
+            ("vBlankQuirks".to_string(), self.vblank_quirks.into()),
+            ("jumpQuirks".to_string(), self.jump_quirks.into()),
+            ("logicQuirks".to_string(), self.logic_quirks.into()),
+            ("maxSize".to_string(), self.max_size.into()),
+        ])
+    }
+}
+
+/// Export the loaded ROM, as it currently sits in memory, with the machine's options as an
+/// Octo cartridge.
+///
+/// The program is Octo source emitting the ROM's bytes, annotated with their disassembly.
+/// Octo can't resume a running machine, so the registers are recorded in a comment.
+pub fn export_cartridge(chip8: &Chip8) -> String {
+    let rom_info = chip8.rom_info();
+    let state = chip8.cpu_state();
+    let rom = &chip8.memory()[ROM_INITIAL_POSITION..ROM_INITIAL_POSITION + rom_info.size];
+
+    let mut program = String::new();
+    let _ = writeln!(
+        program,
+        "# ROM {:016x} running as {}",
+        rom_info.hash, rom_info.platform
+    );
+    let _ = writeln!(
+        program,
+        "# Machine state when exported: PC 0x{:03X}  I 0x{:03X}  DT {}  ST {}",
+        state.pc, state.i, state.delay_timer, state.sound_timer
+    );
+    let registers = state.v.map(|value| format!("{:02X}", value)).join(" ");
+    let _ = writeln!(program, "# V0-VF {}", registers);
+    let stack = state
+        .stack
+        .iter()
+        .map(|addr| format!("0x{:03X}", addr))
+        .collect::<Vec<_>>();
+    let _ = writeln!(program, "# Stack [{}]", stack.join(" "));
+    let _ = writeln!(program, ": main");
+    for line in disassemble(rom, ROM_INITIAL_POSITION as u16) {
+        let [high, low] = line.opcode.to_be_bytes();
+        let _ = match line.instruction {
+            Some(instruction) => {
+                writeln!(program, "\t0x{:02X} 0x{:02X}  # {}", high, low, instruction)
+            }
+            None => writeln!(program, "\t0x{:02X} 0x{:02X}", high, low),
+        };
+    }
+    if rom.len() % 2 == 1 {
+        let _ = writeln!(program, "\t0x{:02X}", rom[rom.len() - 1]);
+    }
+
+    let options = OctoOptions::from_machine(chip8.quirks(), chip8.clock());
+    Value::Object(vec![
+        ("program".to_string(), program.into()),
+        ("options".to_string(), options.to_json()),
+    ])
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Platform;
+
+    #[test]
+    fn options_map_onto_quirks() {
+        let options = OctoOptions::parse(
+            r#"{"tickrate": 15, "shiftQuirks": true, "loadStoreQuirks": true, "jumpQuirks": true, "clipQuirks": true, "vBlankQuirks": true}"#,
+        )
+        .unwrap();
+
+        assert_eq!(options.quirks(), Quirks::for_platform(Platform::SuperChip));
+        assert_eq!(options.clock().instructions_per_frame, 15);
+        assert_eq!(options.unsupported(), ["vBlankQuirks"]);
+
+        // Octo wraps sprites on both axes unless told to clip them
+        let wrapping = OctoOptions::parse("{}").unwrap().quirks();
+        assert!(wrapping.wrap_horizontally && wrapping.wrap_vertically);
+    }
+
+    #[test]
+    fn cartridges_carry_the_rom_and_options() {
+        let mut chip8 = Chip8::with_platform(Platform::SuperChip);
+        chip8.load_rom(&[0x60, 0x05, 0x12, 0x02, 0xFF]);
+        chip8.tick().unwrap();
+
+        let cartridge = json::parse(&export_cartridge(&chip8)).unwrap();
+        let program = cartridge.get("program").and_then(Value::as_str).unwrap();
+        assert!(program.contains("PC 0x202"));
+        assert!(program
+            .contains(": main\n\t0x60 0x05  # LD V0, 0x05\n\t0x12 0x02  # JP 0x202\n\t0xFF\n"));
+
+        let options = OctoOptions::parse(&cartridge.to_string()).unwrap();
+        assert_eq!(options.quirks(), chip8.quirks());
+        assert_eq!(options.unsupported(), Vec::<&str>::new());
+    }
+}
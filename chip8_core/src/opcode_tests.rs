@@ -0,0 +1,527 @@
+//! One or more tests per opcode, each running a single instruction on a prepared machine
+
+use crate::test_machine::{TestMachine, NEXT, SKIPPED};
+use crate::{
+    BackgroundColor, Chip8, Chip8Error, ForegroundColor, Platform, Quirks, ZeroHeightSprite,
+    FONT_INITIAL_POSITION, PIXELS_PER_SCREEN,
+};
+
+#[test]
+fn scrolls_00cn_00fb_00fc() {
+    let machine = || {
+        TestMachine::new()
+            .with_platform(Platform::SuperChip)
+            .with_pixels(&[(0, 0), (62, 30)])
+    };
+    let down = machine().execute(0x00C2);
+    assert!(down.pixel(0, 2) && !down.pixel(0, 0));
+    assert_eq!(down.screen.iter().filter(|&&on| on).count(), 1);
+
+    let right = machine().execute(0x00FB);
+    assert!(right.pixel(4, 0) && !right.pixel(0, 0));
+    assert_eq!(right.screen.iter().filter(|&&on| on).count(), 1);
+
+    let left = machine().execute(0x00FC);
+    assert!(left.pixel(58, 30));
+    assert_eq!(left.screen.iter().filter(|&&on| on).count(), 1);
+}
+
+#[test]
+fn cycle_background_02a0() {
+    let chip8 = TestMachine::new()
+        .with_platform(Platform::Chip8X)
+        .execute(0x02A0);
+    let colors = chip8.colors().unwrap();
+    assert_eq!(colors.background(), BackgroundColor::Black);
+}
+
+#[test]
+fn zone_colors_bxy0_bxyn() {
+    // Zones 1 and 2 across, rows 4 to 7
+    let chip8 = TestMachine::new()
+        .with_platform(Platform::Chip8X)
+        .with_v(4, 0x11)
+        .with_v(5, 0x01)
+        .with_v(6, 4)
+        .execute(0xB460);
+    let colors = chip8.colors().unwrap();
+    assert_eq!(colors.foreground_at(8, 4), ForegroundColor::Green);
+    assert_eq!(colors.foreground_at(23, 7), ForegroundColor::Green);
+    assert_eq!(colors.foreground_at(24, 4), ForegroundColor::Red);
+    assert_eq!(colors.foreground_at(8, 8), ForegroundColor::Red);
+
+    // Two rows of the zone holding x = 20, from y = 10
+    let chip8 = TestMachine::new()
+        .with_platform(Platform::Chip8X)
+        .with_v(4, 20)
+        .with_v(5, 10)
+        .with_v(6, 7)
+        .execute(0xB462);
+    let colors = chip8.colors().unwrap();
+    assert_eq!(colors.foreground_at(16, 10), ForegroundColor::White);
+    assert_eq!(colors.foreground_at(23, 11), ForegroundColor::White);
+    assert_eq!(colors.foreground_at(16, 12), ForegroundColor::Red);
+}
+
+#[test]
+fn clear_screen_00e0() {
+    let chip8 = TestMachine::new()
+        .with_pixels(&[(0, 0), (63, 31)])
+        .execute(0x00E0);
+    assert_eq!(chip8.screen, [false; PIXELS_PER_SCREEN]);
+    assert!(chip8.should_redraw);
+}
+
+#[test]
+fn return_00ee() {
+    let chip8 = TestMachine::new().with_stack(&[0x340]).execute(0x00EE);
+    assert_eq!(chip8.pc, 0x340);
+    assert!(chip8.stack.is_empty());
+
+    let result = TestMachine::new().try_execute(0x00EE);
+    assert_eq!(result.err(), Some(Chip8Error::StackUnderflow { pc: 0x200 }));
+}
+
+#[test]
+fn jump_1nnn() {
+    assert_eq!(TestMachine::new().execute(0x1ABC).pc, 0xABC);
+}
+
+#[test]
+fn call_2nnn() {
+    let chip8 = TestMachine::new().execute(0x2ABC);
+    assert_eq!(chip8.pc, 0xABC);
+    assert_eq!(chip8.stack.as_slice(), [NEXT]);
+    let chip8 = TestMachine::new().with_pc(0x400).execute(0x2ABC);
+    assert_eq!(chip8.stack.as_slice(), [0x402]);
+
+    let result = TestMachine::new()
+        .with_stack(&[0x300; 16])
+        .try_execute(0x2ABC);
+    assert_eq!(result.err(), Some(Chip8Error::StackOverflow { pc: 0x200 }));
+}
+
+#[test]
+fn skip_if_equal_3xnn() {
+    assert_eq!(
+        TestMachine::new().with_v(4, 0x12).execute(0x3412).pc,
+        SKIPPED
+    );
+    assert_eq!(TestMachine::new().with_v(4, 0x13).execute(0x3412).pc, NEXT);
+}
+
+#[test]
+fn skip_if_not_equal_4xnn() {
+    assert_eq!(TestMachine::new().with_v(4, 0x12).execute(0x4412).pc, NEXT);
+    assert_eq!(
+        TestMachine::new().with_v(4, 0x13).execute(0x4412).pc,
+        SKIPPED
+    );
+}
+
+#[test]
+fn skip_if_registers_equal_5xy0() {
+    let machine = || TestMachine::new().with_v(1, 7);
+    assert_eq!(machine().with_v(2, 7).execute(0x5120).pc, SKIPPED);
+    assert_eq!(machine().with_v(2, 8).execute(0x5120).pc, NEXT);
+}
+
+#[test]
+fn set_6xnn() {
+    let chip8 = TestMachine::new().execute(0x6A42);
+    assert_eq!(chip8.v[0xA], 0x42);
+    assert_eq!(chip8.pc, NEXT);
+}
+
+#[test]
+fn add_7xnn_wraps_without_touching_vf() {
+    let chip8 = TestMachine::new().with_v(3, 0xFF).execute(0x7302);
+    assert_eq!(chip8.v[3], 0x01);
+    assert_eq!(chip8.v[0xF], 0);
+}
+
+#[test]
+fn copy_8xy0() {
+    let chip8 = TestMachine::new().with_v(2, 0x99).execute(0x8120);
+    assert_eq!(chip8.v[1], 0x99);
+}
+
+#[test]
+fn logic_8xy1_8xy2_8xy3() {
+    let machine = || {
+        TestMachine::new()
+            .with_quirks(Quirks::for_platform(Platform::Chip48))
+            .with_v(1, 0b1100)
+            .with_v(2, 0b1010)
+            .with_v(0xF, 5)
+    };
+    let or = machine().execute(0x8121);
+    assert_eq!((or.v[1], or.v[0xF]), (0b1110, 5));
+    let and = machine().execute(0x8122);
+    assert_eq!((and.v[1], and.v[0xF]), (0b1000, 5));
+    let xor = machine().execute(0x8123);
+    assert_eq!((xor.v[1], xor.v[0xF]), (0b0110, 5));
+
+    // The VIP's interpreter clobbers VF
+    let or = machine()
+        .with_quirks(Quirks::for_platform(Platform::CosmacVip))
+        .execute(0x8121);
+    assert_eq!((or.v[1], or.v[0xF]), (0b1110, 0));
+}
+
+#[test]
+fn add_registers_8xy4_sets_carry() {
+    let chip8 = TestMachine::new()
+        .with_v(1, 0xF0)
+        .with_v(2, 0x20)
+        .execute(0x8124);
+    assert_eq!((chip8.v[1], chip8.v[0xF]), (0x10, 1));
+    let chip8 = TestMachine::new()
+        .with_v(1, 0x10)
+        .with_v(2, 0x20)
+        .execute(0x8124);
+    assert_eq!((chip8.v[1], chip8.v[0xF]), (0x30, 0));
+
+    // The flag wins when VF is the destination
+    let chip8 = TestMachine::new()
+        .with_v(0xF, 0xFF)
+        .with_v(2, 1)
+        .execute(0x8F24);
+    assert_eq!(chip8.v[0xF], 1);
+}
+
+#[test]
+fn sub_8xy5_sets_not_borrow() {
+    let chip8 = TestMachine::new()
+        .with_v(1, 0x30)
+        .with_v(2, 0x10)
+        .execute(0x8125);
+    assert_eq!((chip8.v[1], chip8.v[0xF]), (0x20, 1));
+    let chip8 = TestMachine::new()
+        .with_v(1, 0x10)
+        .with_v(2, 0x30)
+        .execute(0x8125);
+    assert_eq!((chip8.v[1], chip8.v[0xF]), (0xE0, 0));
+}
+
+#[test]
+fn sub_reversed_8xy7_sets_not_borrow() {
+    let chip8 = TestMachine::new()
+        .with_v(1, 0x10)
+        .with_v(2, 0x30)
+        .execute(0x8127);
+    assert_eq!((chip8.v[1], chip8.v[0xF]), (0x20, 1));
+    let chip8 = TestMachine::new()
+        .with_v(1, 0x30)
+        .with_v(2, 0x10)
+        .execute(0x8127);
+    assert_eq!((chip8.v[1], chip8.v[0xF]), (0xE0, 0));
+}
+
+#[test]
+fn shifts_8xy6_8xye() {
+    let right = TestMachine::new().with_v(2, 0b0000_0011).execute(0x8126);
+    assert_eq!((right.v[1], right.v[0xF]), (0b0000_0001, 1));
+    let left = TestMachine::new().with_v(2, 0b1000_0001).execute(0x812E);
+    assert_eq!((left.v[1], left.v[0xF]), (0b0000_0010, 1));
+
+    let in_place = || {
+        TestMachine::new()
+            .with_quirks(Quirks::for_platform(Platform::SuperChip))
+            .with_v(1, 0b0100_0000)
+            .with_v(2, 0xFF)
+    };
+    let right = in_place().execute(0x8126);
+    assert_eq!((right.v[1], right.v[0xF]), (0b0010_0000, 0));
+    let left = in_place().execute(0x812E);
+    assert_eq!((left.v[1], left.v[0xF]), (0b1000_0000, 0));
+}
+
+#[test]
+fn skip_if_registers_not_equal_9xy0() {
+    let machine = || TestMachine::new().with_v(1, 7);
+    assert_eq!(machine().with_v(2, 7).execute(0x9120).pc, NEXT);
+    assert_eq!(machine().with_v(2, 8).execute(0x9120).pc, SKIPPED);
+}
+
+#[test]
+fn set_index_annn() {
+    assert_eq!(TestMachine::new().execute(0xA123).i, 0x123);
+}
+
+#[test]
+fn jump_with_offset_bnnn() {
+    let machine = || TestMachine::new().with_v(0, 0x10).with_v(3, 0x20);
+    assert_eq!(machine().execute(0xB300).pc, 0x310);
+
+    let chip8 = machine()
+        .with_quirks(Quirks::for_platform(Platform::SuperChip))
+        .execute(0xB300);
+    assert_eq!(chip8.pc, 0x320);
+}
+
+#[test]
+fn random_cxnn_is_masked() {
+    assert_eq!(TestMachine::new().with_v(5, 0xFF).execute(0xC500).v[5], 0);
+    for _ in 0..32 {
+        assert_eq!(TestMachine::new().execute(0xC50F).v[5] & 0xF0, 0);
+    }
+}
+
+#[test]
+fn draw_dxyn_xors_and_reports_collisions() {
+    let machine = || {
+        TestMachine::new()
+            .with_v(3, 2)
+            .with_v(4, 1)
+            .with_i(0x300)
+            .with_memory(0x300, &[0b1100_0000, 0b0100_0000])
+    };
+    let chip8 = machine().execute(0xD342);
+    assert!(chip8.pixel(2, 1) && chip8.pixel(3, 1) && chip8.pixel(3, 2));
+    assert!(!chip8.pixel(2, 2));
+    assert_eq!(chip8.v[0xF], 0);
+    assert!(chip8.should_redraw);
+
+    let chip8 = machine().with_pixels(&[(3, 2), (10, 10)]).execute(0xD342);
+    assert!(!chip8.pixel(3, 2));
+    assert!(chip8.pixel(10, 10));
+    assert_eq!(chip8.v[0xF], 1);
+}
+
+#[test]
+fn draw_dxyn_wraps_the_start_and_clips_the_rest() {
+    let machine = |x, y| {
+        TestMachine::new()
+            .with_v(0, x)
+            .with_v(1, y)
+            .with_i(0x300)
+            .with_memory(0x300, &[0xFF, 0xFF])
+    };
+    let chip8 = machine(64 + 3, 32 + 4).execute(0xD012);
+    assert!(chip8.pixel(3, 4) && chip8.pixel(10, 5));
+
+    let chip8 = machine(62, 31).execute(0xD012);
+    assert_eq!(chip8.screen.iter().filter(|&&on| on).count(), 2);
+    assert!(chip8.pixel(62, 31) && chip8.pixel(63, 31));
+    assert!(!chip8.pixel(0, 31) && !chip8.pixel(62, 0));
+}
+
+#[test]
+fn draw_dxyn_wraps_each_axis_independently() {
+    let machine = |wrap_horizontally, wrap_vertically| {
+        TestMachine::new()
+            .with_quirks(Quirks {
+                wrap_horizontally,
+                wrap_vertically,
+                ..Quirks::default()
+            })
+            .with_v(0, 62)
+            .with_v(1, 31)
+            .with_i(0x300)
+            .with_memory(0x300, &[0xFF, 0xC0])
+            .execute(0xD012)
+    };
+
+    let chip8 = machine(true, false);
+    assert!(chip8.pixel(62, 31) && chip8.pixel(63, 31));
+    assert!(chip8.pixel(0, 31) && chip8.pixel(5, 31));
+    assert!(!chip8.pixel(62, 0));
+
+    let chip8 = machine(false, true);
+    assert!(chip8.pixel(62, 0) && chip8.pixel(63, 0));
+    assert!(!chip8.pixel(0, 31) && !chip8.pixel(0, 0));
+
+    let chip8 = machine(true, true);
+    assert!(chip8.pixel(5, 31) && chip8.pixel(63, 0));
+    assert_eq!(chip8.screen.iter().filter(|&&on| on).count(), 10);
+}
+
+#[test]
+fn draw_dxyn_counts_clipped_rows_as_collisions_under_the_quirk() {
+    let machine = |clipped_rows_collide, y| {
+        TestMachine::new()
+            .with_quirks(Quirks {
+                clipped_rows_collide,
+                ..Quirks::default()
+            })
+            .with_v(0, 10)
+            .with_v(1, y)
+            .with_i(0x300)
+            .with_memory(0x300, &[0x80, 0x80, 0x80])
+            .execute(0xD013)
+    };
+
+    assert_eq!(machine(false, 30).v[0xF], 0);
+    let chip8 = machine(true, 30);
+    assert_eq!(chip8.v[0xF], 1);
+    assert!(chip8.pixel(10, 30) && chip8.pixel(10, 31));
+    assert_eq!(machine(true, 29).v[0xF], 0);
+}
+
+#[test]
+fn draw_dxy0_follows_the_zero_height_quirk() {
+    let machine = |zero_height_sprite| {
+        let mut sprite = [0; 32];
+        sprite[0] = 0x80; // top-left pixel
+        sprite[1] = 0x01; // 16th pixel of the top row, or the second row of a narrow sprite
+        sprite[31] = 0x01; // bottom-right pixel of a 16x16 sprite
+        TestMachine::new()
+            .with_quirks(Quirks {
+                zero_height_sprite,
+                ..Quirks::default()
+            })
+            .with_v(0, 10)
+            .with_v(1, 4)
+            .with_i(0x300)
+            .with_memory(0x300, &sprite)
+            .execute(0xD010)
+    };
+    let lit = |chip8: &Chip8| chip8.screen.iter().filter(|&&on| on).count();
+
+    assert_eq!(lit(&machine(ZeroHeightSprite::Nothing)), 0);
+
+    let chip8 = machine(ZeroHeightSprite::Tall);
+    assert!(chip8.pixel(10, 4) && chip8.pixel(17, 5));
+    assert_eq!(lit(&chip8), 2);
+
+    let chip8 = machine(ZeroHeightSprite::Large);
+    assert!(chip8.pixel(10, 4) && chip8.pixel(25, 4) && chip8.pixel(25, 19));
+    assert_eq!(lit(&chip8), 3);
+    assert_eq!(chip8.v[0xF], 0);
+
+    assert_eq!(
+        Quirks::for_platform(Platform::SuperChip).zero_height_sprite,
+        ZeroHeightSprite::Large
+    );
+}
+
+#[test]
+fn key_skips_ex9e_exa1() {
+    let machine = || TestMachine::new().with_v(2, 0xB);
+    assert_eq!(machine().with_key(0xB).execute(0xE29E).pc, SKIPPED);
+    assert_eq!(machine().execute(0xE29E).pc, NEXT);
+    assert_eq!(machine().with_key(0xB).execute(0xE2A1).pc, NEXT);
+    assert_eq!(machine().execute(0xE2A1).pc, SKIPPED);
+}
+
+#[test]
+fn second_keypad_skips_exf2_exf5() {
+    let machine = || {
+        TestMachine::new()
+            .with_platform(Platform::Chip8X)
+            .with_v(2, 0xB)
+    };
+    let pressed = || machine().with_second_bank_key(0xB);
+    assert_eq!(pressed().execute(0xE2F2).pc, SKIPPED);
+    assert_eq!(pressed().execute(0xE2F5).pc, NEXT);
+    assert_eq!(pressed().execute(0xE29E).pc, NEXT);
+    assert_eq!(machine().execute(0xE2F2).pc, NEXT);
+    assert_eq!(machine().execute(0xE2F5).pc, SKIPPED);
+}
+
+#[test]
+fn timers_fx07_fx15_fx18() {
+    assert_eq!(
+        TestMachine::new().with_timers(42, 0).execute(0xF307).v[3],
+        42
+    );
+
+    let chip8 = TestMachine::new().with_v(3, 9).execute(0xF315);
+    assert_eq!(chip8.delay_timer, 9);
+    let chip8 = TestMachine::new().with_v(3, 9).execute(0xF318);
+    assert_eq!(chip8.sound_timer, 9);
+    assert!(chip8.is_beeping());
+}
+
+#[test]
+fn wait_for_key_fx0a() {
+    let chip8 = TestMachine::new().execute(0xF50A);
+    assert_eq!(chip8.pc, 0x200);
+
+    let chip8 = TestMachine::new().with_key(0xC).execute(0xF50A);
+    assert_eq!(chip8.pc, NEXT);
+    assert_eq!(chip8.v[5], 0xC);
+}
+
+#[test]
+fn add_to_index_fx1e() {
+    let chip8 = TestMachine::new()
+        .with_i(0x300)
+        .with_v(1, 0x20)
+        .execute(0xF11E);
+    assert_eq!(chip8.i, 0x320);
+}
+
+#[test]
+fn font_character_fx29_uses_the_low_nibble() {
+    let chip8 = TestMachine::new().with_v(1, 0xA).execute(0xF129);
+    assert_eq!(chip8.i as usize, FONT_INITIAL_POSITION + 0xA * 5);
+    let chip8 = TestMachine::new().with_v(1, 0x1A).execute(0xF129);
+    assert_eq!(chip8.i as usize, FONT_INITIAL_POSITION + 0xA * 5);
+}
+
+#[test]
+fn binary_coded_decimal_fx33_leaves_i_alone() {
+    let chip8 = TestMachine::new()
+        .with_i(0x300)
+        .with_v(7, 251)
+        .execute(0xF733);
+    assert_eq!(&chip8.memory[0x300..0x303], [2, 5, 1]);
+    assert_eq!(chip8.i, 0x300);
+}
+
+#[test]
+fn store_and_load_registers_fx55_fx65() {
+    let chip8 = TestMachine::new()
+        .with_i(0x300)
+        .with_v(0, 1)
+        .with_v(1, 2)
+        .with_v(2, 3)
+        .with_v(3, 4)
+        .execute(0xF255);
+    assert_eq!(&chip8.memory[0x300..0x304], [1, 2, 3, 0]);
+    assert_eq!(chip8.i, 0x303);
+
+    let chip8 = TestMachine::new()
+        .with_quirks(Quirks::for_platform(Platform::SuperChip))
+        .with_i(0x300)
+        .with_memory(0x300, &[9, 8, 7])
+        .with_v(2, 0xEE)
+        .execute(0xF165);
+    assert_eq!(&chip8.v[0..3], [9, 8, 0xEE]);
+    assert_eq!(chip8.i, 0x300);
+}
+
+#[test]
+fn unknown_opcodes_fault() {
+    let result = TestMachine::new().try_execute(0x5121);
+    assert_eq!(
+        result.err(),
+        Some(Chip8Error::UnknownOpcode {
+            pc: 0x200,
+            opcode: 0x5121
+        })
+    );
+    let result = TestMachine::new().try_execute(0x00FB);
+    assert!(matches!(result, Err(Chip8Error::UnknownOpcode { .. })));
+    let result = TestMachine::new()
+        .with_platform(Platform::SuperChip)
+        .try_execute(0x00FB);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn index_accesses_past_memory_fault() {
+    // FX1E can move I past the end of memory
+    for opcode in [0xD015, 0xF033, 0xF255, 0xF265] {
+        let result = TestMachine::new().with_i(0xFFE).try_execute(opcode);
+        assert!(
+            matches!(result, Err(Chip8Error::OutOfBounds { addr: 0xFFE, .. })),
+            "{:04X} didn't fault",
+            opcode
+        );
+    }
+    let chip8 = TestMachine::new().with_i(0xFFE).execute(0xF155);
+    assert_eq!(chip8.i, 0x1000);
+}
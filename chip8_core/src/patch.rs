@@ -0,0 +1,363 @@
+//! Applying the IPS and BPS patches the community distributes for bug fixes and
+//! compatibility tweaks to classic ROMs
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::MAX_ROM_SIZE;
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_EOF: &[u8] = b"EOF";
+const BPS_MAGIC: &[u8] = b"BPS1";
+/// Source, target and patch CRC32s at the end of a BPS patch
+const BPS_FOOTER_SIZE: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchFormat {
+    Ips,
+    Bps,
+}
+
+/// A patched ROM and anything suspicious about how the patch applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchedRom {
+    pub rom: Vec<u8>,
+    pub format: PatchFormat,
+    /// ROM ranges written by more than one IPS record, where the last record wins
+    pub conflicts: Vec<Range<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// The data is neither an IPS nor a BPS patch
+    UnknownFormat,
+    /// The patch ended in the middle of a record
+    Truncated,
+    /// A write of `len` bytes at `offset` falls outside the ROM area
+    OutOfRomArea { offset: usize, len: usize },
+    /// A BPS copy reads outside the source or the target
+    InvalidCopy { offset: usize },
+    /// The BPS patch was made for another ROM
+    SourceMismatch { expected: u32, found: u32 },
+    /// Applying the BPS patch didn't produce the ROM it describes
+    TargetMismatch { expected: u32, found: u32 },
+    /// The BPS patch itself is corrupted
+    PatchChecksum { expected: u32, found: u32 },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::UnknownFormat => write!(f, "not an IPS or BPS patch"),
+            PatchError::Truncated => write!(f, "patch is truncated"),
+            PatchError::OutOfRomArea { offset, len } => write!(
+                f,
+                "patch writes {} bytes at ROM offset 0x{:03X}, outside the {} byte ROM area",
+                len, offset, MAX_ROM_SIZE
+            ),
+            PatchError::InvalidCopy { offset } => {
+                write!(f, "patch copies from invalid offset 0x{:03X}", offset)
+            }
+            PatchError::SourceMismatch { expected, found } => write!(
+                f,
+                "patch is for a ROM with CRC32 {:08x}, this one is {:08x}",
+                expected, found
+            ),
+            PatchError::TargetMismatch { expected, found } => write!(
+                f,
+                "patched ROM has CRC32 {:08x}, the patch expects {:08x}",
+                found, expected
+            ),
+            PatchError::PatchChecksum { expected, found } => write!(
+                f,
+                "patch is corrupted: CRC32 {:08x}, expected {:08x}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Apply an IPS or BPS patch to `rom`, telling the formats apart by their magic bytes.
+///
+/// The patched ROM must fit in the ROM area of memory.
+pub fn apply_patch(rom: &[u8], patch: &[u8]) -> Result<PatchedRom, PatchError> {
+    if patch.starts_with(IPS_MAGIC) {
+        apply_ips(rom, &patch[IPS_MAGIC.len()..])
+    } else if patch.starts_with(BPS_MAGIC) {
+        apply_bps(rom, patch)
+    } else {
+        Err(PatchError::UnknownFormat)
+    }
+}
+
+fn check_bounds(offset: usize, len: usize) -> Result<(), PatchError> {
+    if offset.saturating_add(len) > MAX_ROM_SIZE {
+        return Err(PatchError::OutOfRomArea { offset, len });
+    }
+    Ok(())
+}
+
+/// Cursor over the bytes of a patch
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], PatchError> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset.saturating_add(len))
+            .ok_or(PatchError::Truncated)?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn big_endian(&mut self, len: usize) -> Result<usize, PatchError> {
+        let bytes = self.bytes(len)?;
+        Ok(bytes
+            .iter()
+            .fold(0, |value, &byte| value << 8 | byte as usize))
+    }
+
+    /// BPS variable-length number
+    fn number(&mut self) -> Result<usize, PatchError> {
+        let mut value = 0usize;
+        let mut shift = 1usize;
+        loop {
+            let byte = self.bytes(1)?[0];
+            value = value.saturating_add((byte & 0x7F) as usize * shift);
+            if byte & 0x80 != 0 {
+                return Ok(value);
+            }
+            shift = shift.saturating_mul(128);
+            value = value.saturating_add(shift);
+        }
+    }
+}
+
+fn apply_ips(rom: &[u8], records: &[u8]) -> Result<PatchedRom, PatchError> {
+    let mut patched = rom.to_vec();
+    let mut written: Vec<Range<usize>> = vec![];
+    let mut conflicts = vec![];
+    let mut reader = Reader {
+        data: records,
+        offset: 0,
+    };
+    loop {
+        if reader.data[reader.offset..].starts_with(IPS_EOF) {
+            reader.offset += IPS_EOF.len();
+            break;
+        }
+        let offset = reader.big_endian(3)?;
+        let (len, data) = match reader.big_endian(2)? {
+            // Run-length encoded record: a count and the byte to repeat
+            0 => {
+                let len = reader.big_endian(2)?;
+                (len, vec![reader.bytes(1)?[0]; len])
+            }
+            len => (len, reader.bytes(len)?.to_vec()),
+        };
+        check_bounds(offset, len)?;
+
+        let range = offset..offset + len;
+        for previous in &written {
+            let overlap = range.start.max(previous.start)..range.end.min(previous.end);
+            if !overlap.is_empty() {
+                conflicts.push(overlap);
+            }
+        }
+        written.push(range.clone());
+        if patched.len() < range.end {
+            patched.resize(range.end, 0);
+        }
+        patched[range].copy_from_slice(&data);
+    }
+    // Some patches end with the size to truncate the ROM to
+    if let Ok(size) = reader.big_endian(3) {
+        patched.truncate(size);
+    }
+    Ok(PatchedRom {
+        rom: patched,
+        format: PatchFormat::Ips,
+        conflicts,
+    })
+}
+
+fn apply_bps(source: &[u8], patch: &[u8]) -> Result<PatchedRom, PatchError> {
+    if patch.len() < BPS_MAGIC.len() + BPS_FOOTER_SIZE {
+        return Err(PatchError::Truncated);
+    }
+    let footer = &patch[patch.len() - BPS_FOOTER_SIZE..];
+    let checksum = |index: usize| {
+        let bytes = &footer[index * 4..index * 4 + 4];
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    };
+    let (source_crc, target_crc, patch_crc) = (checksum(0), checksum(1), checksum(2));
+    let found = crc32(&patch[..patch.len() - 4]);
+    if found != patch_crc {
+        return Err(PatchError::PatchChecksum {
+            expected: patch_crc,
+            found,
+        });
+    }
+    let found = crc32(source);
+    if found != source_crc {
+        return Err(PatchError::SourceMismatch {
+            expected: source_crc,
+            found,
+        });
+    }
+
+    let mut reader = Reader {
+        data: &patch[..patch.len() - BPS_FOOTER_SIZE],
+        offset: BPS_MAGIC.len(),
+    };
+    let _source_size = reader.number()?;
+    let target_size = reader.number()?;
+    check_bounds(0, target_size)?;
+    let metadata_size = reader.number()?;
+    reader.bytes(metadata_size)?;
+
+    let mut target = Vec::with_capacity(target_size);
+    let mut source_offset = 0usize;
+    let mut target_offset = 0usize;
+    // Moves a copy offset by the signed delta BPS encodes in the low bit and the rest
+    let relative = |offset: usize, delta: usize| {
+        let magnitude = delta >> 1;
+        if delta & 1 == 1 {
+            offset.checked_sub(magnitude)
+        } else {
+            offset.checked_add(magnitude)
+        }
+        .ok_or(PatchError::InvalidCopy { offset })
+    };
+    while reader.offset < reader.data.len() {
+        let action = reader.number()?;
+        let len = (action >> 2) + 1;
+        check_bounds(target.len(), len)?;
+        match action & 0b11 {
+            // Source read: the bytes are unchanged from the source at the same offset
+            0 => {
+                let offset = target.len();
+                let bytes = source
+                    .get(offset..offset + len)
+                    .ok_or(PatchError::InvalidCopy { offset })?;
+                target.extend_from_slice(bytes);
+            }
+            // Target read: new bytes stored in the patch
+            1 => target.extend_from_slice(reader.bytes(len)?),
+            // Source copy: bytes from elsewhere in the source
+            2 => {
+                source_offset = relative(source_offset, reader.number()?)?;
+                let bytes = source.get(source_offset..source_offset + len).ok_or(
+                    PatchError::InvalidCopy {
+                        offset: source_offset,
+                    },
+                )?;
+                target.extend_from_slice(bytes);
+                source_offset += len;
+            }
+            // Target copy: bytes already written, which may overlap what is being written
+            _ => {
+                target_offset = relative(target_offset, reader.number()?)?;
+                for _ in 0..len {
+                    let byte = *target.get(target_offset).ok_or(PatchError::InvalidCopy {
+                        offset: target_offset,
+                    })?;
+                    target.push(byte);
+                    target_offset += 1;
+                }
+            }
+        }
+    }
+
+    let found = crc32(&target);
+    if target.len() != target_size || found != target_crc {
+        return Err(PatchError::TargetMismatch {
+            expected: target_crc,
+            found,
+        });
+    }
+    Ok(PatchedRom {
+        rom: target,
+        format: PatchFormat::Bps,
+        conflicts: vec![],
+    })
+}
+
+/// CRC-32 as used by BPS (IEEE, reflected)
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ips_records_are_applied_and_overlaps_reported() {
+        let rom = [0x00, 0xE0, 0x12, 0x00];
+        let mut patch = b"PATCH".to_vec();
+        // Write two bytes at 2, then a run of three 0xAA at 3, past the end of the ROM
+        patch.extend_from_slice(&[0x00, 0x00, 0x02, 0x00, 0x02, 0x12, 0x02]);
+        patch.extend_from_slice(&[0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x03, 0xAA]);
+        patch.extend_from_slice(b"EOF");
+
+        let patched = apply_patch(&rom, &patch).unwrap();
+        assert_eq!(patched.format, PatchFormat::Ips);
+        assert_eq!(patched.rom, [0x00, 0xE0, 0x12, 0xAA, 0xAA, 0xAA]);
+        assert_eq!(patched.conflicts.len(), 1);
+        assert_eq!(patched.conflicts[0], 3..4);
+
+        let mut outside = b"PATCH".to_vec();
+        outside.extend_from_slice(&[0x00, 0x0D, 0xFF, 0x00, 0x02, 0x00, 0x00]);
+        outside.extend_from_slice(b"EOF");
+        assert_eq!(
+            apply_patch(&rom, &outside),
+            Err(PatchError::OutOfRomArea {
+                offset: 0xDFF,
+                len: 2
+            })
+        );
+    }
+
+    #[test]
+    fn bps_patches_check_their_source() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let source = [0x60, 0x01, 0x12, 0x00];
+        let target = [0x60, 0x02, 0x12, 0x00, 0x12, 0x00];
+        let mut patch = b"BPS1".to_vec();
+        // Sizes 4 and 6 and no metadata
+        patch.extend_from_slice(&[0x84, 0x86, 0x80]);
+        // Source read 1, target read 1 (0x02), source read 2, target copy 2 from offset 2
+        patch.extend_from_slice(&[0x80, 0x81, 0x02, 0x84, 0x87, 0x84]);
+        patch.extend_from_slice(&crc32(&source).to_le_bytes());
+        patch.extend_from_slice(&crc32(&target).to_le_bytes());
+        let patch_crc = crc32(&patch);
+        patch.extend_from_slice(&patch_crc.to_le_bytes());
+
+        let patched = apply_patch(&source, &patch).unwrap();
+        assert_eq!(patched.format, PatchFormat::Bps);
+        assert_eq!(patched.rom, target);
+        assert!(matches!(
+            apply_patch(&target, &patch),
+            Err(PatchError::SourceMismatch { .. })
+        ));
+        assert_eq!(
+            apply_patch(&source, b"nope"),
+            Err(PatchError::UnknownFormat)
+        );
+    }
+}
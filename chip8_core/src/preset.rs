@@ -0,0 +1,117 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Chip8, ClockConfig, Quirks, WriteProtection};
+
+/// A named bundle of the quirks, speed and error handling, for players who'd rather not
+/// tune each one. Every preset emulates the quirks of the machine's platform; they differ
+/// in how fast they run and how they treat programs misbehaving.
+///
+/// What each passes of `chip8-testsuite ROMs/test --preset NAME` is listed below.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Preset {
+    /// The speed of a COSMAC VIP, and writes below 0x200 or over the ROM stop the program
+    /// with an error. Meant for checking ROMs under development: of the test ROMs only
+    /// the two logos pass, the others keep data inside their own image and stop at the
+    /// first write to it.
+    Strict,
+    /// A little faster than a COSMAC VIP, which suits most ROMs, with writes over the
+    /// program reported but let through. Passes the logos, corax+ and flags.
+    #[default]
+    Compatible,
+    /// As fast as the most demanding SUPER-CHIP games want, with no checks at all. Passes
+    /// the same test ROMs as [`Preset::Compatible`].
+    Fast,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Strict, Preset::Compatible, Preset::Fast];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Strict => "strict",
+            Preset::Compatible => "compatible",
+            Preset::Fast => "fast",
+        }
+    }
+
+    pub fn instructions_per_frame(&self) -> u32 {
+        match self {
+            Preset::Strict => 11,
+            Preset::Compatible => 15,
+            Preset::Fast => 100,
+        }
+    }
+
+    pub fn write_protection(&self) -> WriteProtection {
+        match self {
+            Preset::Strict => WriteProtection::Strict,
+            Preset::Compatible => WriteProtection::Warn,
+            Preset::Fast => WriteProtection::Off,
+        }
+    }
+
+    /// Set up `chip8`, once its platform is chosen, the way the preset says. The timer
+    /// frequency is left alone.
+    pub fn apply(&self, chip8: &mut Chip8) {
+        chip8.set_quirks(Quirks::for_platform(chip8.platform()));
+        chip8.set_clock(ClockConfig {
+            instructions_per_frame: self.instructions_per_frame(),
+            ..chip8.clock()
+        });
+        chip8.set_write_protection(self.write_protection());
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Preset::ALL
+            .into_iter()
+            .find(|preset| preset.name() == s)
+            .ok_or_else(|| {
+                let names = Preset::ALL.map(|preset| preset.name());
+                format!(
+                    "unknown preset '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Platform;
+
+    #[test]
+    fn presets_keep_the_platform_quirks_and_timer_frequency() {
+        let mut chip8 = Chip8::with_platform(Platform::SuperChip);
+        chip8.set_quirks(Quirks::default());
+        chip8.set_clock(ClockConfig {
+            instructions_per_frame: 1,
+            timer_frequency: 50,
+            ..ClockConfig::default()
+        });
+        "strict".parse::<Preset>().unwrap().apply(&mut chip8);
+        assert_eq!(chip8.quirks(), Quirks::for_platform(Platform::SuperChip));
+        assert_eq!(
+            chip8.clock(),
+            ClockConfig {
+                instructions_per_frame: 11,
+                timer_frequency: 50,
+                ..ClockConfig::default()
+            }
+        );
+        assert_eq!(chip8.write_protection(), WriteProtection::Strict);
+        assert!("accurate".parse::<Preset>().is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Time spent executing each family of opcodes, those sharing a first hex digit, to find
+//! what's worth optimizing. Timing every instruction costs a little, so it's only compiled
+//! in with the `profiling` feature.
+
+use std::cmp::Reverse;
+use std::fmt;
+use std::time::Duration;
+
+/// Names of the families, by first digit
+const FAMILY_NAMES: [&str; 16] = [
+    "0NNN", "1NNN", "2NNN", "3XNN", "4XNN", "5XY0", "6XNN", "7XNN", "8XYN", "9XY0", "ANNN", "BNNN",
+    "CXNN", "DXYN", "EXNN", "FXNN",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FamilyStats {
+    /// Instructions executed
+    pub count: u64,
+    pub time: Duration,
+}
+
+impl FamilyStats {
+    /// Average time an instruction took
+    pub fn per_instruction(&self) -> Duration {
+        match self.count {
+            0 => Duration::ZERO,
+            count => self.time / count.min(u32::MAX as u64) as u32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpcodeProfile {
+    families: [FamilyStats; 16],
+}
+
+impl OpcodeProfile {
+    pub(crate) fn record(&mut self, opcode: u16, time: Duration) {
+        let family = &mut self.families[(opcode >> 12) as usize];
+        family.count += 1;
+        family.time += time;
+    }
+
+    /// Stats of the opcodes starting with the hex digit `first_digit`
+    pub fn family(&self, first_digit: u8) -> FamilyStats {
+        self.families[first_digit as usize & 0xF]
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.families.iter().map(|family| family.time).sum()
+    }
+
+    /// The families that executed anything, most time-consuming first, with their names
+    pub fn by_time(&self) -> Vec<(&'static str, FamilyStats)> {
+        let mut families: Vec<_> = FAMILY_NAMES
+            .iter()
+            .zip(self.families)
+            .filter(|(_, stats)| stats.count > 0)
+            .map(|(&name, stats)| (name, stats))
+            .collect();
+        families.sort_by_key(|(_, stats)| Reverse(stats.time));
+        families
+    }
+
+    /// Share of the total time `stats` took, from 0 to 100
+    pub fn percent(&self, stats: &FamilyStats) -> f64 {
+        match self.total_time().as_secs_f64() {
+            total if total > 0.0 => 100.0 * stats.time.as_secs_f64() / total,
+            _ => 0.0,
+        }
+    }
+}
+
+/// A table of the families, most time-consuming first
+impl fmt::Display for OpcodeProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<6} {:>12} {:>12} {:>10} {:>6}",
+            "family", "count", "time (ms)", "each (ns)", "share"
+        )?;
+        for (name, stats) in self.by_time() {
+            writeln!(
+                f,
+                "{:<6} {:>12} {:>12.3} {:>10} {:>5.1}%",
+                name,
+                stats.count,
+                stats.time.as_secs_f64() * 1000.0,
+                stats.per_instruction().as_nanos(),
+                self.percent(&stats)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Chip8;
+
+    #[test]
+    fn instructions_are_counted_by_family() {
+        // V0 = 1, draw the font's 0 a few times, then loop
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0x60, 0x01, 0xD0, 0x05, 0xD0, 0x05, 0x12, 0x06]);
+        for _ in 0..10 {
+            chip8.step().unwrap();
+        }
+
+        let profile = chip8.opcode_profile();
+        assert_eq!(profile.family(0x6).count, 1);
+        assert_eq!(profile.family(0xD).count, 2);
+        assert_eq!(profile.family(0x1).count, 7);
+        let names: Vec<_> = profile.by_time().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names.len(), 3);
+        assert!(names.contains(&"DXYN"));
+        assert!(profile.to_string().starts_with("family"));
+    }
+}
@@ -0,0 +1,139 @@
+use std::str::FromStr;
+
+/// What happens when a program writes below 0x200 or over its own code, which is usually a
+/// bug; self-modifying programs need it [`WriteProtection::Off`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteProtection {
+    /// Writes go through unnoticed, as on real hardware
+    #[default]
+    Off,
+    /// Writes go through and are reported as [`crate::Chip8Event::IllegalWrite`], and
+    /// executing at odd addresses or below 0x200 as [`crate::Chip8Event::StrayExecution`]
+    Warn,
+    /// Writes fail with [`crate::Chip8Error::IllegalWrite`], executing at odd addresses
+    /// or below 0x200 with [`crate::Chip8Error::StrayExecution`], and reads of memory
+    /// nothing wrote yet are reported as [`crate::Chip8Event::UninitializedRead`]
+    Strict,
+}
+
+impl FromStr for WriteProtection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(WriteProtection::Off),
+            "warn" => Ok(WriteProtection::Warn),
+            "strict" => Ok(WriteProtection::Strict),
+            _ => Err(format!(
+                "unknown write protection '{}', expected off, warn or strict",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Chip8, Chip8Error, Chip8Event, WriteProtection};
+
+    /// Stores V0 at 0x204, inside the ROM itself
+    const SELF_MODIFYING: &[u8] = &[0xA2, 0x04, 0xF0, 0x55, 0x00, 0x00];
+
+    #[test]
+    fn writes_over_the_rom_are_caught() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(SELF_MODIFYING);
+        chip8.set_write_protection(WriteProtection::Strict);
+        chip8.step().unwrap();
+        assert_eq!(
+            chip8.step(),
+            Err(Chip8Error::IllegalWrite {
+                pc: 0x202,
+                addr: 0x204
+            })
+        );
+        assert_eq!(chip8.cpu_state().pc, 0x202);
+
+        let mut chip8 = Chip8::new();
+        chip8.enable_events();
+        chip8.load_rom(SELF_MODIFYING);
+        chip8.set_write_protection(WriteProtection::Warn);
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+        assert_eq!(
+            chip8.take_events(),
+            [Chip8Event::IllegalWrite {
+                pc: 0x202,
+                addr: 0x204
+            }]
+        );
+
+        // Past the end of the ROM is fair game
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0xA2, 0x06, 0xF0, 0x55, 0x00, 0x00]);
+        chip8.set_write_protection(WriteProtection::Strict);
+        chip8.step().unwrap();
+        assert!(chip8.step().is_ok());
+    }
+
+    #[test]
+    fn segments_are_protected_like_the_rom() {
+        // Stores V0 at 0x300, where a level is loaded apart from the ROM
+        let mut chip8 = Chip8::new();
+        chip8.load_segment(0x300, &[1, 2, 3]).unwrap();
+        chip8.load_rom(&[0xA3, 0x00, 0xF0, 0x55, 0x00, 0x00]);
+        chip8.set_write_protection(WriteProtection::Strict);
+        assert_eq!(chip8.memory()[0x300..0x303], [1, 2, 3]);
+        assert_eq!(chip8.segments().first(), Some(&(0x300..0x303)));
+        chip8.step().unwrap();
+        assert_eq!(
+            chip8.step(),
+            Err(Chip8Error::IllegalWrite {
+                pc: 0x202,
+                addr: 0x300
+            })
+        );
+
+        assert_eq!(
+            chip8.load_segment(0xFFE, &[1, 2, 3]),
+            Err(Chip8Error::OutOfBounds {
+                addr: 0xFFE,
+                len: 3
+            })
+        );
+        chip8.swap_rom(&[0x00, 0xE0]);
+        assert!(chip8.segments().is_empty());
+    }
+
+    #[test]
+    fn stray_jumps_are_caught() {
+        // Jumps to 0x203, then into the font
+        let mut chip8 = Chip8::new();
+        chip8.enable_events();
+        chip8.load_rom(&[0x12, 0x03, 0x00, 0x10, 0x50]);
+        chip8.set_write_protection(WriteProtection::Warn);
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+        // The font's F090 is no instruction, but it's reported before failing to decode
+        assert_eq!(
+            chip8.step(),
+            Err(Chip8Error::UnknownOpcode {
+                pc: 0x050,
+                opcode: 0xF090
+            })
+        );
+        assert_eq!(
+            chip8.take_events(),
+            [
+                Chip8Event::StrayExecution { pc: 0x203 },
+                Chip8Event::StrayExecution { pc: 0x050 }
+            ]
+        );
+
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0x12, 0x03]);
+        chip8.set_write_protection(WriteProtection::Strict);
+        chip8.step().unwrap();
+        assert_eq!(chip8.step(), Err(Chip8Error::StrayExecution { pc: 0x203 }));
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::extension;
+use crate::{Instruction, KeyBank};
+
+/// Interpreter a ROM was written for; each one implies a set of [`Quirks`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Platform {
+    /// The original COSMAC VIP interpreter
+    #[default]
+    CosmacVip,
+    /// CHIP-48 on the HP-48 calculators
+    Chip48,
+    /// SUPER-CHIP 1.1
+    SuperChip,
+    /// The COSMAC VIP's CHIP-8X, with the VP-590 colour board and a second keypad
+    Chip8X,
+}
+
+impl Platform {
+    pub const ALL: [Platform; 4] = [
+        Platform::CosmacVip,
+        Platform::Chip48,
+        Platform::SuperChip,
+        Platform::Chip8X,
+    ];
+
+    /// Whether the platform's interpreter implements `instruction`: those of plain CHIP-8
+    /// and of its [`extension`]s, less the opcodes an extension took over
+    pub fn supports(&self, instruction: &Instruction) -> bool {
+        match instruction {
+            // Only with a stand-in, see [`crate::Chip8::set_machine_routine`]
+            Instruction::CallMachineCode { .. } => false,
+            _ => {
+                let extensions = extension::for_platform(*self);
+                Instruction::decode_with(instruction.opcode(), extensions) == Some(*instruction)
+            }
+        }
+    }
+
+    /// Whether the machine has a second hex keypad for a second player
+    pub fn has_second_keypad(&self) -> bool {
+        *self == Platform::Chip8X
+    }
+
+    /// Keypad a key-testing instruction reads on this platform, `None` for other instructions
+    pub fn key_bank(&self, instruction: &Instruction) -> Option<KeyBank> {
+        match instruction {
+            Instruction::SkipIfKey { .. } | Instruction::SkipIfNotKey { .. } => Some(KeyBank::First),
+            Instruction::SkipIfSecondKey { .. } | Instruction::SkipIfNotSecondKey { .. }
+                if self.has_second_keypad() =>
+            {
+                Some(KeyBank::Second)
+            }
+            _ => None,
+        }
+    }
+
+    /// Name used to select the platform on command lines and in config files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::CosmacVip => "cosmac-vip",
+            Platform::Chip48 => "chip-48",
+            Platform::SuperChip => "super-chip",
+            Platform::Chip8X => "chip-8x",
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Platform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Platform::ALL
+            .into_iter()
+            .find(|platform| platform.name() == s)
+            .ok_or_else(|| {
+                let names = Platform::ALL.map(|platform| platform.name());
+                format!("unknown platform '{}', expected one of: {}", s, names.join(", "))
+            })
+    }
+}
+
+/// Behaviours that differ between CHIP-8 interpreters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    /// `8XY6`/`8XYE` shift VX in place and ignore VY, instead of shifting a copy of VY
+    pub shift_in_place: bool,
+    /// `8XY1`/`8XY2`/`8XY3` reset VF to 0
+    pub vf_reset: bool,
+    /// `FX55`/`FX65` leave I pointing after the last register stored or loaded
+    pub increment_index: bool,
+    /// `BXNN` jumps to XNN plus VX, instead of `BNNN` jumping to NNN plus V0
+    pub jump_with_vx: bool,
+    /// `DXYN` wraps the parts of sprites past the right edge around to the left, instead of
+    /// clipping them
+    pub wrap_horizontally: bool,
+    /// `DXYN` wraps the parts of sprites past the bottom edge around to the top, instead of
+    /// clipping them
+    pub wrap_vertically: bool,
+    /// What `DXY0` draws
+    pub zero_height_sprite: ZeroHeightSprite,
+    /// `DXYN` sets VF when rows of the sprite are clipped off the bottom edge, even if no
+    /// pixel was erased, like the high resolution mode of SUPER-CHIP 1.1. Without vertical
+    /// wrapping only; no platform's defaults turn it on.
+    pub clipped_rows_collide: bool,
+}
+
+/// What a `DXY0`, a sprite with no rows, draws
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZeroHeightSprite {
+    /// Nothing, as on the COSMAC VIP
+    #[default]
+    Nothing,
+    /// An 8x16 sprite from 16 bytes, as some interpreters did
+    Tall,
+    /// A 16x16 sprite from 32 bytes, two per row, as on SUPER-CHIP
+    Large,
+}
+
+impl Quirks {
+    pub fn for_platform(platform: Platform) -> Self {
+        match platform {
+            Platform::CosmacVip | Platform::Chip8X => Self {
+                shift_in_place: false,
+                vf_reset: true,
+                increment_index: true,
+                jump_with_vx: false,
+                wrap_horizontally: false,
+                wrap_vertically: false,
+                zero_height_sprite: ZeroHeightSprite::Nothing,
+                clipped_rows_collide: false,
+            },
+            Platform::Chip48 => Self {
+                shift_in_place: true,
+                vf_reset: false,
+                increment_index: true,
+                jump_with_vx: true,
+                wrap_horizontally: false,
+                wrap_vertically: false,
+                zero_height_sprite: ZeroHeightSprite::Nothing,
+                clipped_rows_collide: false,
+            },
+            Platform::SuperChip => Self {
+                shift_in_place: true,
+                vf_reset: false,
+                increment_index: false,
+                jump_with_vx: true,
+                wrap_horizontally: false,
+                wrap_vertically: false,
+                zero_height_sprite: ZeroHeightSprite::Large,
+                clipped_rows_collide: false,
+            },
+        }
+    }
+
+    /// Width and height in pixels of the sprite `DXYN` draws for `n`
+    pub fn sprite_size(&self, n: u8) -> (usize, usize) {
+        match (n, self.zero_height_sprite) {
+            (0, ZeroHeightSprite::Nothing) => (8, 0),
+            (0, ZeroHeightSprite::Tall) => (8, 16),
+            (0, ZeroHeightSprite::Large) => (16, 16),
+            (n, _) => (8, n as usize),
+        }
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Self::for_platform(Platform::default())
+    }
+}
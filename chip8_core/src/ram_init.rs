@@ -0,0 +1,128 @@
+//! What memory and the V registers hold at power-on
+
+use std::fmt;
+
+use crate::entropy::Entropy;
+
+/// Contents of memory and the V registers before the program writes them. Real machines
+/// didn't always start out zeroed, so other patterns flush out ROMs that rely on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RamPattern {
+    #[default]
+    Zero,
+    /// Every bit set, 0xFF
+    Ones,
+    /// Noise from a generator seeded with the number, the same on every run
+    Random(u64),
+}
+
+impl RamPattern {
+    /// The generator noise is drawn from, seeded once for each machine
+    pub(crate) fn entropy(self) -> Entropy {
+        match self {
+            RamPattern::Random(seed) => Entropy::seeded(seed),
+            _ => Entropy::seeded(0),
+        }
+    }
+
+    /// Fill `bytes` with the pattern, drawing noise from `entropy` so memory and the
+    /// registers each get bytes of their own
+    pub(crate) fn fill(self, bytes: &mut [u8], entropy: &mut Entropy) {
+        match self {
+            RamPattern::Zero => bytes.fill(0),
+            RamPattern::Ones => bytes.fill(0xFF),
+            RamPattern::Random(_) => bytes.fill_with(|| entropy.next_u8()),
+        }
+    }
+}
+
+impl fmt::Display for RamPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RamPattern::Zero => write!(f, "zero"),
+            RamPattern::Ones => write!(f, "ff"),
+            RamPattern::Random(seed) => write!(f, "random:{}", seed),
+        }
+    }
+}
+
+impl std::str::FromStr for RamPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "zero" => Ok(RamPattern::Zero),
+            None if s == "ff" => Ok(RamPattern::Ones),
+            None if s == "random" => Ok(RamPattern::Random(0)),
+            Some(("random", seed)) => seed
+                .parse()
+                .map(RamPattern::Random)
+                .map_err(|_| format!("'{}' is not a seed", seed)),
+            _ => Err(format!(
+                "unknown RAM pattern '{}', expected one of: zero, ff, random, random:SEED",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Chip8, Chip8Event, RamPattern, WriteProtection, FONT_INITIAL_POSITION};
+
+    #[test]
+    fn patterns_fill_memory_and_registers_but_not_the_font() {
+        let mut chip8 = Chip8::new();
+        chip8.set_ram_pattern(RamPattern::Ones);
+        chip8.load_rom(&[0x00, 0xE0]);
+        assert_eq!(chip8.memory()[0x000], 0xFF);
+        assert_eq!(chip8.memory()[FONT_INITIAL_POSITION], 0xF0);
+        assert_eq!(chip8.memory()[0x200..0x203], [0x00, 0xE0, 0xFF]);
+        assert_eq!(chip8.cpu_state().v, [0xFF; 16]);
+
+        let random = |seed| {
+            let mut chip8 = Chip8::new();
+            chip8.set_ram_pattern(RamPattern::Random(seed));
+            chip8.memory()[0x300..0x310].to_vec()
+        };
+        assert_eq!(random(7), random(7));
+        assert_ne!(random(7), random(8));
+
+        // The noise goes on across memory and the registers instead of starting over
+        let mut chip8 = Chip8::new();
+        chip8.set_ram_pattern(RamPattern::Random(7));
+        assert_ne!(chip8.memory()[..16], chip8.cpu_state().v);
+        let font_end = FONT_INITIAL_POSITION + crate::FONT_SET.len();
+        assert_ne!(
+            chip8.memory()[..16],
+            chip8.memory()[font_end..font_end + 16]
+        );
+        assert_eq!("random:7".parse(), Ok(RamPattern::Random(7)));
+        assert!("random:x".parse::<RamPattern>().is_err());
+    }
+
+    #[test]
+    fn strict_machines_report_reads_of_memory_never_written() {
+        let mut chip8 = Chip8::new();
+        chip8.enable_events();
+        chip8.set_write_protection(WriteProtection::Strict);
+        chip8.load_rom(&[
+            0xA3, 0x00, // I = 0x300
+            0xF1, 0x65, // load V0-V1 from 0x300, which nothing wrote
+            0xA3, 0x00, // I = 0x300
+            0xF0, 0x55, // store V0 there
+            0xA3, 0x00, // I = 0x300
+            0xF0, 0x65, // and load it back
+        ]);
+        for _ in 0..6 {
+            chip8.step().unwrap();
+        }
+        assert_eq!(
+            chip8.take_events(),
+            [Chip8Event::UninitializedRead {
+                pc: 0x202,
+                addr: 0x300
+            }]
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Presenting frames on displays that refresh slowly, like e-paper: updates are coalesced
+//! into a few refreshes per second, each limited to the regions of the screen that changed.
+
+use crate::runner::FRAME_RATE;
+use crate::{PIXELS_PER_COLUMN, PIXELS_PER_ROW, PIXELS_PER_SCREEN};
+
+/// Side of the square tiles changes are tracked in, in CHIP-8 pixels
+const TILE_SIZE: usize = 8;
+const TILES_PER_ROW: usize = PIXELS_PER_ROW / TILE_SIZE;
+const TILES_PER_COLUMN: usize = PIXELS_PER_COLUMN / TILE_SIZE;
+
+/// A region of the screen to redraw, in CHIP-8 pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirtyRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Decides when a slow display refreshes and which parts of it
+#[derive(Debug, Clone)]
+pub struct PartialRefresh {
+    /// Frames between refreshes
+    interval: u64,
+    /// Number of the frame last refreshed from
+    last_refresh: Option<u64>,
+    /// What the display shows
+    shown: [bool; PIXELS_PER_SCREEN],
+    /// Whether the next refresh redraws everything, right away
+    full_refresh: bool,
+}
+
+impl PartialRefresh {
+    /// Refresh at most `max_refreshes_per_second` times a second of emulated time
+    pub fn new(max_refreshes_per_second: u32) -> Self {
+        let interval = (FRAME_RATE / max_refreshes_per_second.max(1) as f64).ceil() as u64;
+        Self {
+            interval: interval.max(1),
+            last_refresh: None,
+            shown: [false; PIXELS_PER_SCREEN],
+            full_refresh: false,
+        }
+    }
+
+    /// Regions to redraw to show `screen` from the frame numbered `frame`. Nothing is
+    /// redrawn until a refresh is due, nor when nothing changed since the last refresh;
+    /// changes skipped meanwhile are picked up by the next one.
+    pub fn refresh(&mut self, screen: &[bool; PIXELS_PER_SCREEN], frame: u64) -> Vec<DirtyRect> {
+        if std::mem::take(&mut self.full_refresh) {
+            self.shown = *screen;
+            self.last_refresh = Some(frame);
+            return vec![DirtyRect {
+                x: 0,
+                y: 0,
+                width: PIXELS_PER_ROW,
+                height: PIXELS_PER_COLUMN,
+            }];
+        }
+        let due = self
+            .last_refresh
+            .is_none_or(|last| frame < last || frame - last >= self.interval);
+        if !due {
+            return Vec::new();
+        }
+        let rects = self.dirty_rects(screen);
+        if !rects.is_empty() {
+            self.shown = *screen;
+            self.last_refresh = Some(frame);
+        }
+        rects
+    }
+
+    /// Redraw the whole display on the next call to [`PartialRefresh::refresh`], due or not,
+    /// like e-paper needs now and then to clear the ghosts of earlier pictures; for when
+    /// the screen is cleared, see [`crate::Chip8Event::ScreenCleared`]
+    pub fn request_full_refresh(&mut self) {
+        self.full_refresh = true;
+    }
+
+    /// What the display shows since the last refresh
+    pub fn shown(&self) -> &[bool; PIXELS_PER_SCREEN] {
+        &self.shown
+    }
+
+    /// Runs of changed tiles on each row of tiles, joined with the run below when they
+    /// line up
+    fn dirty_rects(&self, screen: &[bool; PIXELS_PER_SCREEN]) -> Vec<DirtyRect> {
+        let mut rects: Vec<DirtyRect> = Vec::new();
+        for tile_y in 0..TILES_PER_COLUMN {
+            let mut tile_x = 0;
+            while tile_x < TILES_PER_ROW {
+                if !self.tile_changed(screen, tile_x, tile_y) {
+                    tile_x += 1;
+                    continue;
+                }
+                let start = tile_x;
+                while tile_x < TILES_PER_ROW && self.tile_changed(screen, tile_x, tile_y) {
+                    tile_x += 1;
+                }
+                let rect = DirtyRect {
+                    x: start * TILE_SIZE,
+                    y: tile_y * TILE_SIZE,
+                    width: (tile_x - start) * TILE_SIZE,
+                    height: TILE_SIZE,
+                };
+                match rects.iter_mut().find(|above| {
+                    above.x == rect.x
+                        && above.width == rect.width
+                        && above.y + above.height == rect.y
+                }) {
+                    Some(above) => above.height += TILE_SIZE,
+                    None => rects.push(rect),
+                }
+            }
+        }
+        rects
+    }
+
+    fn tile_changed(
+        &self,
+        screen: &[bool; PIXELS_PER_SCREEN],
+        tile_x: usize,
+        tile_y: usize,
+    ) -> bool {
+        (0..TILE_SIZE).any(|row| {
+            let start = (tile_y * TILE_SIZE + row) * PIXELS_PER_ROW + tile_x * TILE_SIZE;
+            screen[start..start + TILE_SIZE] != self.shown[start..start + TILE_SIZE]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refreshes_are_coalesced_and_cover_the_changes() {
+        let mut refresh = PartialRefresh::new(10);
+        let mut screen = [false; PIXELS_PER_SCREEN];
+        assert!(refresh.refresh(&screen, 0).is_empty());
+
+        // A pixel at (3, 2) and a column of them at x = 20 spanning two rows of tiles
+        screen[2 * PIXELS_PER_ROW + 3] = true;
+        for y in 4..12 {
+            screen[y * PIXELS_PER_ROW + 20] = true;
+        }
+        assert_eq!(
+            refresh.refresh(&screen, 1),
+            [
+                DirtyRect {
+                    x: 0,
+                    y: 0,
+                    width: 8,
+                    height: 8
+                },
+                DirtyRect {
+                    x: 16,
+                    y: 0,
+                    width: 8,
+                    height: 16
+                },
+            ]
+        );
+
+        // At 10 refreshes a second, the next one is six frames later
+        screen[0] = true;
+        assert!(refresh.refresh(&screen, 4).is_empty());
+        assert!(!refresh.shown()[0]);
+        assert_eq!(refresh.refresh(&screen, 7).len(), 1);
+        assert!(refresh.shown()[0]);
+
+        // A full refresh doesn't wait for its turn, nor for anything to change
+        refresh.request_full_refresh();
+        assert_eq!(
+            refresh.refresh(&screen, 8),
+            [DirtyRect {
+                x: 0,
+                y: 0,
+                width: 64,
+                height: 32
+            }]
+        );
+        assert!(refresh.refresh(&screen, 20).is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+//! Curated presentation settings for known ROMs, looked up by [`crate::rom_hash`]
+
+/// How a known ROM is best presented
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RomProfile {
+    pub name: &'static str,
+    pub hash: u64,
+    /// Colours to show the monochrome screen in, instead of the frontend's own
+    pub colors: Option<DisplayColors>,
+    /// Swap the background and foreground while the buzzer sounds, for games that flash the
+    /// screen with it
+    pub cycle_palette_on_sound: bool,
+    /// Program counter ranges to run at their own speed, for games whose menus race along
+    /// at the speed their gameplay needs
+    pub speed_zones: &'static [SpeedZone],
+}
+
+/// A stretch of code that runs at its own number of instructions per frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeedZone {
+    /// First address of the zone
+    pub start: u16,
+    /// Address just past the zone
+    pub end: u16,
+    pub instructions_per_frame: u32,
+}
+
+impl SpeedZone {
+    pub fn contains(&self, pc: u16) -> bool {
+        (self.start..self.end).contains(&pc)
+    }
+}
+
+/// RGB colours of an unlit and a lit pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayColors {
+    pub background: [u8; 3],
+    pub foreground: [u8; 3],
+}
+
+const PROFILES: &[RomProfile] = &[
+    RomProfile {
+        name: "CHIP-8 logo (Timendus test suite)",
+        hash: 0xf29e_da10_5324_f103,
+        colors: Some(DisplayColors {
+            background: [0x10, 0x10, 0x10],
+            foreground: [0x33, 0xff, 0x66],
+        }),
+        cycle_palette_on_sound: false,
+        speed_zones: &[],
+    },
+    RomProfile {
+        name: "IBM logo",
+        hash: 0x7ce9_4f81_f0dd_b2f2,
+        colors: Some(DisplayColors {
+            background: [0x00, 0x00, 0x00],
+            foreground: [0x1f, 0x70, 0xc1],
+        }),
+        cycle_palette_on_sound: false,
+        speed_zones: &[],
+    },
+    RomProfile {
+        name: "Beep test (Timendus test suite)",
+        hash: 0x290d_a31d_5016_1491,
+        colors: None,
+        cycle_palette_on_sound: true,
+        speed_zones: &[],
+    },
+];
+
+/// The curated profile of the ROM with `hash`, if it has one
+pub fn lookup(hash: u64) -> Option<&'static RomProfile> {
+    PROFILES.iter().find(|profile| profile.hash == hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_are_found_by_hash() {
+        let rom = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../ROMs/test/2-ibm-logo.ch8"
+        ))
+        .unwrap();
+        assert_eq!(
+            lookup(crate::rom_hash(&rom)).map(|profile| profile.name),
+            Some("IBM logo")
+        );
+        assert_eq!(lookup(0), None);
+        let hashes = PROFILES.iter().map(|profile| profile.hash);
+        assert_eq!(
+            hashes.collect::<std::collections::BTreeSet<_>>().len(),
+            PROFILES.len()
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Cleaning up ROM dumps, which often come padded with zeros to some block size or with
+//! several ROMs one after the other, too large to load as they are
+
+use crate::patch::crc32;
+use crate::rom_hash;
+
+/// Facts about a ROM file for telling dumps apart and spotting padding
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RomStats {
+    pub size: usize,
+    /// The hash save states and per-ROM settings are keyed by
+    pub hash: u64,
+    pub crc32: u32,
+    /// Shannon entropy of the bytes in bits per byte, from 0 to 8: code and sprites are
+    /// around 4 to 6, padding pulls it down and compressed data pushes it up
+    pub entropy: f64,
+    /// Zero bytes at the end, which [`trim`] would remove
+    pub padding: usize,
+}
+
+impl RomStats {
+    pub fn of(rom: &[u8]) -> Self {
+        let mut counts = [0usize; 256];
+        for &byte in rom {
+            counts[byte as usize] += 1;
+        }
+        let entropy = counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / rom.len() as f64;
+                -p * p.log2()
+            })
+            .sum();
+        Self {
+            size: rom.len(),
+            hash: rom_hash(rom),
+            crc32: crc32(rom),
+            entropy,
+            padding: rom.len() - trim(rom).len(),
+        }
+    }
+}
+
+/// `rom` without the zeros padding its end. The length stays even, so an instruction
+/// ending in a zero byte like `1200` keeps it; sprite rows of zeros at the very end are
+/// lost though.
+pub fn trim(rom: &[u8]) -> &[u8] {
+    let end = rom
+        .iter()
+        .rposition(|&byte| byte != 0)
+        .map_or(0, |last| last + 1);
+    &rom[..(end + end % 2).min(rom.len())]
+}
+
+/// `rom` with a zero byte added if its length is odd, so its last instruction is whole
+pub fn pad_to_even(rom: &[u8]) -> Vec<u8> {
+    let mut padded = rom.to_vec();
+    if padded.len() % 2 == 1 {
+        padded.push(0);
+    }
+    padded
+}
+
+/// The ROMs in a dump of several, split where at least `min_gap` zero bytes separate them,
+/// each trimmed. A ROM after a gap starts at its first byte that isn't zero, unless that
+/// looks like the end of a `00NN` instruction.
+pub fn split_at_gaps(dump: &[u8], min_gap: usize) -> Vec<&[u8]> {
+    let mut roms = vec![];
+    let mut start = 0;
+    let mut zeros = 0;
+    for (index, &byte) in dump.iter().enumerate() {
+        if byte == 0 {
+            zeros += 1;
+            continue;
+        }
+        if zeros >= min_gap.max(1) {
+            // Keep an instruction ending in a zero byte whole, like trim does
+            let end = index - zeros;
+            roms.push(trim(&dump[start..end + (end - start) % 2]));
+            start = match byte {
+                // The second byte of 00E0, 00EE or another 00NN instruction, which many
+                // ROMs start with
+                0xC0..=0xCF | 0xE0 | 0xEE | 0xFB..=0xFF => index - 1,
+                _ => index,
+            };
+        }
+        zeros = 0;
+    }
+    roms.push(trim(&dump[start..]));
+    roms.retain(|rom| !rom.is_empty());
+    roms
+}
+
+/// The ROMs in a dump of several padded to `size` bytes each, each trimmed
+pub fn split_every(dump: &[u8], size: usize) -> Vec<&[u8]> {
+    dump.chunks(size.max(1))
+        .map(trim)
+        .filter(|rom| !rom.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dumps_are_trimmed_and_split() {
+        assert_eq!(trim(&[0x12, 0x00, 0x00, 0x00]), [0x12, 0x00]);
+        assert_eq!(
+            trim(&[0x60, 0x01, 0xA2, 0x0A, 0, 0]),
+            [0x60, 0x01, 0xA2, 0x0A]
+        );
+        assert_eq!(trim(&[0, 0]), []);
+        assert_eq!(pad_to_even(&[0x12, 0x00, 0x12]), [0x12, 0x00, 0x12, 0x00]);
+
+        let dump = [
+            0x60, 0x01, 0, 0, 0, 0, 0x12, 0x00, 0, 0, 0x70, 0x01, 0, 0, 0, 0,
+        ];
+        assert_eq!(
+            split_at_gaps(&dump, 4),
+            [&[0x60, 0x01][..], &[0x12, 0x00, 0, 0, 0x70, 0x01]]
+        );
+        assert_eq!(
+            split_every(&[0x60, 0x01, 0, 0, 0x12, 0x00, 0, 0, 0x70, 0x01], 4),
+            [&[0x60, 0x01][..], &[0x12, 0x00], &[0x70, 0x01]]
+        );
+
+        assert_eq!(
+            split_at_gaps(
+                &[0, 0, 0, 0, 0x00, 0xE0, 0x12, 0x00, 0, 0, 0, 0x60, 0x01],
+                4
+            ),
+            [&[0x00, 0xE0, 0x12, 0x00][..], &[0x60, 0x01]]
+        );
+
+        let stats = RomStats::of(&dump);
+        assert_eq!(stats.size, 16);
+        assert_eq!(stats.padding, 4);
+        assert_eq!(RomStats::of(&[7; 8]).entropy, 0.0);
+        assert_eq!(RomStats::of(&[0, 1, 2, 3]).entropy, 2.0);
+    }
+}
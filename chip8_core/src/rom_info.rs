@@ -0,0 +1,188 @@
+use std::collections::BTreeSet;
+
+use crate::disasm::disassemble_at;
+use crate::{rom_db, rom_hash, Instruction, Platform, RomProfile, ROM_INITIAL_POSITION};
+
+/// What the core knows about the loaded ROM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RomInfo {
+    pub hash: u64,
+    pub size: usize,
+    /// Platform whose quirks the ROM runs with
+    pub platform: Platform,
+    pub platform_source: PlatformSource,
+    /// Why the platform was picked, for display to the user
+    pub platform_reasons: Vec<String>,
+    /// Curated presentation settings, for ROMs in the built-in database
+    pub profile: Option<&'static RomProfile>,
+}
+
+/// Where [`RomInfo::platform`] came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlatformSource {
+    /// Guessed by [`detect_platform`]
+    Detected,
+    /// Chosen explicitly with [`crate::Chip8::select_platform`]
+    Selected,
+}
+
+impl RomInfo {
+    pub fn analyze(rom: &[u8]) -> Self {
+        let detection = detect_platform(rom);
+        let hash = rom_hash(rom);
+        Self {
+            hash,
+            size: rom.len(),
+            platform: detection.platform,
+            platform_source: PlatformSource::Detected,
+            platform_reasons: detection.reasons,
+            profile: rom_db::lookup(hash),
+        }
+    }
+}
+
+/// Platform a ROM most likely targets, with the evidence that led to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Detection {
+    pub platform: Platform,
+    pub reasons: Vec<String>,
+}
+
+/// Opcodes only SUPER-CHIP (and later) interpreters implement
+fn is_super_chip_opcode(opcode: u16) -> bool {
+    matches!(opcode, 0x00FB..=0x00FF)
+        || opcode & 0xFFF0 == 0x00C0
+        || opcode & 0xF00F == 0xD000
+        || matches!(opcode & 0xF0FF, 0xF030 | 0xF075 | 0xF085)
+}
+
+/// Guess the platform of a ROM by looking at the opcodes its reachable code uses
+pub fn detect_platform(rom: &[u8]) -> Detection {
+    let origin = ROM_INITIAL_POSITION as u16;
+    let rom_end = origin + rom.len() as u16;
+
+    let mut super_chip = vec![];
+    let mut chip8x = vec![];
+    let mut offset_jumps = vec![];
+    let mut copying_shifts = vec![];
+
+    let mut visited = BTreeSet::new();
+    // Each address comes with the instruction that led to it
+    let mut pending = vec![(origin, None)];
+    while let Some((addr, previous)) = pending.pop() {
+        if addr < origin || addr >= rom_end || !visited.insert(addr) {
+            continue;
+        }
+        let Some(line) = disassemble_at(rom, origin, addr) else {
+            continue;
+        };
+        if is_super_chip_opcode(line.opcode) {
+            super_chip.push(format!(
+                "uses SUPER-CHIP opcode {:04X} at 0x{:03X}",
+                line.opcode, addr
+            ));
+        }
+        // BXYN is CHIP-8X's too, but it can't be told apart from BNNN
+        if matches!(
+            line.instruction,
+            Some(
+                Instruction::CycleBackground
+                    | Instruction::SkipIfSecondKey { .. }
+                    | Instruction::SkipIfNotSecondKey { .. }
+            )
+        ) {
+            chip8x.push(format!(
+                "uses CHIP-8X opcode {:04X} at 0x{:03X}",
+                line.opcode, addr
+            ));
+        }
+
+        let next = match line.instruction {
+            Some(Instruction::Jump { nnn }) => vec![nnn],
+            Some(Instruction::Call { nnn }) => vec![nnn, addr + 2],
+            Some(Instruction::Return) => vec![],
+            Some(Instruction::JumpWithOffset { nnn }) => {
+                // BXNN jumps to XNN + VX on CHIP-48; setting VX right before gives it away
+                let x = (nnn >> 8) as u8;
+                if x != 0 && matches!(previous, Some(Instruction::Set { x: set, .. }) if set == x) {
+                    offset_jumps.push(format!(
+                        "BNNN at 0x{:03X} jumps right after setting V{:X}, like CHIP-48's BXNN",
+                        addr, x
+                    ));
+                }
+                vec![]
+            }
+            Some(
+                Instruction::SkipIfEqual { .. }
+                | Instruction::SkipIfNotEqual { .. }
+                | Instruction::SkipIfRegistersEqual { .. }
+                | Instruction::SkipIfRegistersNotEqual { .. }
+                | Instruction::SkipIfKey { .. }
+                | Instruction::SkipIfNotKey { .. }
+                | Instruction::SkipIfSecondKey { .. }
+                | Instruction::SkipIfNotSecondKey { .. },
+            ) => vec![addr + 2, addr + 4],
+            Some(Instruction::ShiftRight { x, y } | Instruction::ShiftLeft { x, y }) => {
+                if x != y {
+                    copying_shifts.push(format!(
+                        "shift at 0x{:03X} names a separate VY, as COSMAC VIP shifts expect",
+                        addr
+                    ));
+                }
+                vec![addr + 2]
+            }
+            _ => vec![addr + 2],
+        };
+        pending.extend(next.into_iter().map(|next| (next, line.instruction)));
+    }
+
+    let (platform, reasons) = if !super_chip.is_empty() {
+        (Platform::SuperChip, super_chip)
+    } else if !chip8x.is_empty() {
+        (Platform::Chip8X, chip8x)
+    } else if !offset_jumps.is_empty() {
+        (Platform::Chip48, offset_jumps)
+    } else if !copying_shifts.is_empty() {
+        (Platform::CosmacVip, copying_shifts)
+    } else {
+        (
+            Platform::CosmacVip,
+            vec!["no platform-specific instructions found".to_string()],
+        )
+    };
+    Detection { platform, reasons }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn super_chip_opcodes_select_super_chip() {
+        let detection = detect_platform(&[0x00, 0xFF, 0x00, 0xE0, 0x12, 0x02]);
+        assert_eq!(detection.platform, Platform::SuperChip);
+        assert_eq!(detection.reasons, ["uses SUPER-CHIP opcode 00FF at 0x200"]);
+    }
+
+    #[test]
+    fn colour_opcodes_select_chip8x() {
+        let detection = detect_platform(&[0x02, 0xA0, 0x12, 0x02]);
+        assert_eq!(detection.platform, Platform::Chip8X);
+        assert_eq!(detection.reasons, ["uses CHIP-8X opcode 02A0 at 0x200"]);
+    }
+
+    #[test]
+    fn offset_jumps_through_vx_select_chip48() {
+        let detection = detect_platform(&[0x63, 0x02, 0xB3, 0x00]);
+        assert_eq!(detection.platform, Platform::Chip48);
+    }
+
+    #[test]
+    fn plain_roms_default_to_cosmac_vip() {
+        assert_eq!(
+            detect_platform(&[0x80, 0x16, 0x12, 0x00]).platform,
+            Platform::CosmacVip
+        );
+        assert_eq!(detect_platform(&[0x12, 0x00]).platform, Platform::CosmacVip);
+    }
+}
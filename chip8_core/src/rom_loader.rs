@@ -0,0 +1,521 @@
+//! Reading ROMs from plain files and from the gzip and zip archives ROM packs are
+//! distributed in
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::banking::MAX_BANKED_ROM_SIZE;
+use crate::patch::crc32;
+use crate::MAX_ROM_SIZE;
+
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const ZIP_END_MAGIC: &[u8] = b"PK\x05\x06";
+const ZIP_ENTRY_MAGIC: &[u8] = b"PK\x01\x02";
+/// Size of the end of central directory record, without the trailing comment
+const ZIP_END_SIZE: usize = 22;
+/// Extension of the archive member picked as the ROM
+const ROM_EXTENSION: &str = ".ch8";
+
+#[derive(Debug)]
+pub enum RomLoadError {
+    Io(io::Error),
+    /// The file holds no data
+    Empty,
+    /// The ROM is larger than the `max` bytes it may have: those that fit in memory after
+    /// the interpreter area, unless it's loaded with bank switching
+    TooLarge {
+        max: usize,
+    },
+    /// The archive is damaged or truncated; the message says where
+    Corrupted(&'static str),
+    /// The decompressed data doesn't match the checksum stored in the archive
+    ChecksumMismatch {
+        expected: u32,
+        found: u32,
+    },
+    /// The zip archive has no file ending in .ch8
+    NoRomInArchive,
+    /// The zip member is compressed with something other than deflate
+    UnsupportedCompression(u16),
+}
+
+impl fmt::Display for RomLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RomLoadError::Io(err) => write!(f, "{}", err),
+            RomLoadError::Empty => write!(f, "the ROM is empty"),
+            RomLoadError::TooLarge { max } => {
+                write!(f, "the ROM is larger than the {} bytes available", max)
+            }
+            RomLoadError::Corrupted(reason) => write!(f, "the archive is corrupted: {}", reason),
+            RomLoadError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "the archive's checksum is {:08x}, but its contents hash to {:08x}",
+                expected, found
+            ),
+            RomLoadError::NoRomInArchive => {
+                write!(f, "the archive has no {} file in it", ROM_EXTENSION)
+            }
+            RomLoadError::UnsupportedCompression(method) => {
+                write!(
+                    f,
+                    "the archive uses unsupported compression method {}",
+                    method
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RomLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RomLoadError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RomLoadError {
+    fn from(err: io::Error) -> Self {
+        RomLoadError::Io(err)
+    }
+}
+
+/// Read the ROM at `path`, decompressing it if it's a gzip or zip archive
+pub fn load_rom_file(path: &Path) -> Result<Vec<u8>, RomLoadError> {
+    decode_rom(&std::fs::read(path)?)
+}
+
+/// Read the ROM at `path` like [`load_rom_file`], allowing it up to `max_size` bytes, as
+/// bank switching does
+pub fn load_rom_file_up_to(path: &Path, max_size: usize) -> Result<Vec<u8>, RomLoadError> {
+    decode_rom_up_to(&std::fs::read(path)?, max_size)
+}
+
+/// The ROM in `data`, which may be a plain ROM, a gzipped ROM, or a zip archive holding
+/// one. Archives are recognised by their contents, not by their file names.
+pub fn decode_rom(data: &[u8]) -> Result<Vec<u8>, RomLoadError> {
+    decode_rom_up_to(data, MAX_ROM_SIZE)
+}
+
+/// The ROM in `data` like [`decode_rom`], allowing it up to `max_size` bytes
+pub fn decode_rom_up_to(data: &[u8], max_size: usize) -> Result<Vec<u8>, RomLoadError> {
+    let rom = if data.starts_with(GZIP_MAGIC) {
+        gunzip(data)?
+    } else if data.starts_with(ZIP_MAGIC) {
+        unzip_rom(data)?
+    } else {
+        data.to_vec()
+    };
+    match rom.len() {
+        0 => Err(RomLoadError::Empty),
+        len if len > max_size => Err(RomLoadError::TooLarge { max: max_size }),
+        _ => Ok(rom),
+    }
+}
+
+fn gunzip(data: &[u8]) -> Result<Vec<u8>, RomLoadError> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    let truncated = RomLoadError::Corrupted("the gzip header is truncated");
+    let header = data.get(..10).ok_or(truncated)?;
+    if header[2] != 8 {
+        return Err(RomLoadError::UnsupportedCompression(header[2] as u16));
+    }
+    let flags = header[3];
+    let mut offset = 10;
+    if flags & FEXTRA != 0 {
+        let len = read_u16(data, offset)? as usize;
+        offset += 2 + len;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let rest = data.get(offset..).unwrap_or_default();
+            let end = rest.iter().position(|&byte| byte == 0);
+            offset += end.ok_or(RomLoadError::Corrupted(
+                "a gzip header string is unterminated",
+            ))? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        offset += 2;
+    }
+
+    let compressed = data
+        .get(offset..)
+        .ok_or(RomLoadError::Corrupted("the gzip header is truncated"))?;
+    let (rom, used) = inflate(compressed, MAX_BANKED_ROM_SIZE)?;
+    let expected = read_u32(compressed, used)?;
+    check_crc(expected, &rom)?;
+    Ok(rom)
+}
+
+/// Decompress the first member of the zip archive whose name ends in .ch8
+fn unzip_rom(data: &[u8]) -> Result<Vec<u8>, RomLoadError> {
+    let end = (0..=data.len().saturating_sub(ZIP_END_SIZE))
+        .rev()
+        .find(|&offset| data[offset..].starts_with(ZIP_END_MAGIC))
+        .ok_or(RomLoadError::Corrupted("the central directory is missing"))?;
+    let entries = read_u16(data, end + 10)?;
+    let mut offset = read_u32(data, end + 16)? as usize;
+
+    for _ in 0..entries {
+        if !data
+            .get(offset..)
+            .unwrap_or_default()
+            .starts_with(ZIP_ENTRY_MAGIC)
+        {
+            return Err(RomLoadError::Corrupted(
+                "a central directory entry is damaged",
+            ));
+        }
+        let method = read_u16(data, offset + 10)?;
+        let crc = read_u32(data, offset + 16)?;
+        let compressed_size = read_u32(data, offset + 20)? as usize;
+        let name_len = read_u16(data, offset + 28)? as usize;
+        let extra_len = read_u16(data, offset + 30)? as usize;
+        let comment_len = read_u16(data, offset + 32)? as usize;
+        let local_header = read_u32(data, offset + 42)? as usize;
+        let name = data
+            .get(offset + 46..offset + 46 + name_len)
+            .ok_or(RomLoadError::Corrupted("a file name is truncated"))?;
+        offset += 46 + name_len + extra_len + comment_len;
+
+        if !String::from_utf8_lossy(name)
+            .to_ascii_lowercase()
+            .ends_with(ROM_EXTENSION)
+        {
+            continue;
+        }
+        let start = local_header
+            + 30
+            + read_u16(data, local_header + 26)? as usize
+            + read_u16(data, local_header + 28)? as usize;
+        let compressed = data
+            .get(start..start + compressed_size)
+            .ok_or(RomLoadError::Corrupted("the ROM's data is truncated"))?;
+        let rom = match method {
+            0 => compressed.to_vec(),
+            8 => inflate(compressed, MAX_BANKED_ROM_SIZE)?.0,
+            method => return Err(RomLoadError::UnsupportedCompression(method)),
+        };
+        check_crc(crc, &rom)?;
+        return Ok(rom);
+    }
+    Err(RomLoadError::NoRomInArchive)
+}
+
+fn check_crc(expected: u32, data: &[u8]) -> Result<(), RomLoadError> {
+    let found = crc32(data);
+    if found != expected {
+        return Err(RomLoadError::ChecksumMismatch { expected, found });
+    }
+    Ok(())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, RomLoadError> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .ok_or(RomLoadError::Corrupted("a header is truncated"))?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, RomLoadError> {
+    let bytes = data
+        .get(offset..offset + 4)
+        .ok_or(RomLoadError::Corrupted("a header is truncated"))?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Base lengths of the length codes 257..=285, and their extra bits
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distances of the distance codes 0..=29, and their extra bits
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order the code length code lengths of a dynamic block are stored in
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+const MAX_CODE_LENGTH: usize = 15;
+
+/// Decompress a raw DEFLATE stream, returning the data and how many input bytes it took.
+/// Output beyond `max_size` bytes is refused, so archives can't balloon in memory.
+pub(crate) fn inflate(data: &[u8], max_size: usize) -> Result<(Vec<u8>, usize), RomLoadError> {
+    let mut bits = BitReader::new(data);
+    let mut out = Vec::new();
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => {
+                bits.align();
+                let len = bits.read(16)?;
+                let complement = bits.read(16)?;
+                if len != !complement & 0xFFFF {
+                    return Err(RomLoadError::Corrupted("a stored block has a bad length"));
+                }
+                for _ in 0..len {
+                    out.push(bits.read(8)? as u8);
+                }
+            }
+            1 => {
+                let mut lengths = [0; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths)?;
+                let distances = Huffman::new(&[5; 30])?;
+                inflate_block(&mut bits, &mut out, &literals, &distances, max_size)?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_tables(&mut bits)?;
+                inflate_block(&mut bits, &mut out, &literals, &distances, max_size)?;
+            }
+            _ => return Err(RomLoadError::Corrupted("a block has an invalid type")),
+        }
+        if out.len() > max_size {
+            return Err(RomLoadError::TooLarge { max: max_size });
+        }
+        if last {
+            bits.align();
+            return Ok((out, bits.position));
+        }
+    }
+}
+
+fn inflate_block(
+    bits: &mut BitReader,
+    out: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+    max_size: usize,
+) -> Result<(), RomLoadError> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let code = symbol - 257;
+                if code >= LENGTH_BASE.len() {
+                    return Err(RomLoadError::Corrupted("a length code is invalid"));
+                }
+                let len = LENGTH_BASE[code] as usize + bits.read(LENGTH_EXTRA[code])? as usize;
+                let code = distances.decode(bits)? as usize;
+                if code >= DISTANCE_BASE.len() {
+                    return Err(RomLoadError::Corrupted("a distance code is invalid"));
+                }
+                let distance =
+                    DISTANCE_BASE[code] as usize + bits.read(DISTANCE_EXTRA[code])? as usize;
+                if distance > out.len() {
+                    return Err(RomLoadError::Corrupted("a match reaches before the start"));
+                }
+                // Matches may overlap the bytes they produce, so copy one at a time
+                for _ in 0..len {
+                    out.push(out[out.len() - distance]);
+                }
+            }
+        }
+        if out.len() > max_size {
+            return Err(RomLoadError::TooLarge { max: max_size });
+        }
+    }
+}
+
+fn read_dynamic_tables(bits: &mut BitReader) -> Result<(Huffman, Huffman), RomLoadError> {
+    let literal_count = bits.read(5)? as usize + 257;
+    let distance_count = bits.read(5)? as usize + 1;
+    let code_length_count = bits.read(4)? as usize + 4;
+
+    let mut code_lengths = [0; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[index] = bits.read(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_lengths.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths
+                    .last()
+                    .ok_or(RomLoadError::Corrupted("a length repeats nothing"))?;
+                (previous, 3 + bits.read(2)?)
+            }
+            17 => (0, 3 + bits.read(3)?),
+            _ => (0, 11 + bits.read(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err(RomLoadError::Corrupted("code lengths overrun the tables"));
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+/// A canonical Huffman code, decoded one bit at a time
+struct Huffman {
+    /// Number of codes of each length
+    counts: [u16; MAX_CODE_LENGTH + 1],
+    /// Symbols ordered by code
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, RomLoadError> {
+        let mut counts = [0; MAX_CODE_LENGTH + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0; MAX_CODE_LENGTH + 2];
+        for len in 1..=MAX_CODE_LENGTH {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; offsets[MAX_CODE_LENGTH + 1] as usize];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, RomLoadError> {
+        // Codes of each length follow those of the previous length, so walk the lengths
+        // keeping the first code and the index of the first symbol of the current one
+        let (mut code, mut first, mut index) = (0, 0, 0);
+        for len in 1..=MAX_CODE_LENGTH {
+            code |= bits.read(1)?;
+            let count = self.counts[len] as u32;
+            if code < first + count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(RomLoadError::Corrupted("a Huffman code is invalid"))
+    }
+}
+
+/// Reads the least significant bit of each byte first, as DEFLATE packs them
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// Next byte to load
+    position: usize,
+    buffer: u32,
+    buffered: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            position: 0,
+            buffer: 0,
+            buffered: 0,
+        }
+    }
+
+    /// The next `count` bits, the first of them in the lowest bit
+    fn read(&mut self, count: u8) -> Result<u32, RomLoadError> {
+        while self.buffered < count {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or(RomLoadError::Corrupted("the compressed data is truncated"))?;
+            self.buffer |= (byte as u32) << self.buffered;
+            self.buffered += 8;
+            self.position += 1;
+        }
+        let value = self.buffer & ((1u64 << count) - 1) as u32;
+        self.buffer >>= count;
+        self.buffered -= count;
+        Ok(value)
+    }
+
+    /// Drop the rest of the current byte
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.buffered = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_file(name: &str) -> Vec<u8> {
+        let path = format!("{}/../ROMs/test/{}", env!("CARGO_MANIFEST_DIR"), name);
+        std::fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn gzipped_roms_are_decompressed() {
+        let rom = test_file("5-quirks.ch8");
+        assert_eq!(
+            decode_rom(&test_file("archives/5-quirks.ch8.gz")).unwrap(),
+            rom
+        );
+        // Stored without compression
+        assert_eq!(
+            decode_rom(&test_file("archives/5-quirks-stored.ch8.gz")).unwrap(),
+            rom
+        );
+
+        let mut damaged = test_file("archives/5-quirks.ch8.gz");
+        let crc = damaged.len() - 8;
+        damaged[crc] ^= 0xFF;
+        assert!(matches!(
+            decode_rom(&damaged),
+            Err(RomLoadError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn zip_archives_give_their_first_rom() {
+        // The archive holds a readme, the IBM logo deflated and the beep test stored
+        let archive = test_file("archives/roms.zip");
+        assert_eq!(decode_rom(&archive).unwrap(), test_file("2-ibm-logo.ch8"));
+
+        let readme_only = test_file("archives/readme.zip");
+        assert!(matches!(
+            decode_rom(&readme_only),
+            Err(RomLoadError::NoRomInArchive)
+        ));
+    }
+
+    #[test]
+    fn plain_roms_are_validated() {
+        assert_eq!(decode_rom(&[0x12, 0x00]).unwrap(), [0x12, 0x00]);
+        assert!(matches!(decode_rom(&[]), Err(RomLoadError::Empty)));
+        assert!(matches!(
+            decode_rom(&vec![0; MAX_ROM_SIZE + 1]),
+            Err(RomLoadError::TooLarge { max: MAX_ROM_SIZE })
+        ));
+    }
+}
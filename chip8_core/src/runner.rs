@@ -0,0 +1,359 @@
+//! Pieces for frontends that run the machine on its own thread and render on another, so
+//! slow rendering or event handling never delays emulation.
+//!
+//! The emulation thread owns the [`Chip8`] (which is [`Send`] for this reason), paces itself
+//! with a [`FrameTimer`] and publishes a [`Frame`] after each frame through a triple buffer.
+//! The render thread always picks up the newest frame, skipping any it was too slow to show.
+//! Frontends wanting frames delivered differently, as messages or behind a shared handle,
+//! hand the emulation loop another [`FrameSink`].
+
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "native")]
+use std::time::{Duration, Instant};
+
+use crate::disasm::SymbolTable;
+#[cfg(feature = "profiling")]
+use crate::profiling::OpcodeProfile;
+use crate::trace::TraceEntry;
+use crate::{
+    index_from_point, Chip8, ColorAttributes, CpuState, Instruction, KeyPrompt, Keypad, Quirks,
+    RomProfile, PIXELS_PER_SCREEN, RAM_SIZE,
+};
+
+/// Frames per second of every supported platform
+pub const FRAME_RATE: f64 = 60.0;
+/// Latest instructions a [`Frame`] carries
+pub const FRAME_HISTORY_LENGTH: usize = 8;
+
+/// Frames the timer may fall behind before it gives up catching up
+#[cfg(feature = "native")]
+const MAX_FRAMES_BEHIND: u32 = 3;
+/// How long before a deadline the timer stops sleeping and starts spinning
+#[cfg(feature = "native")]
+const SPIN_MARGIN: Duration = Duration::from_millis(1);
+
+// Frontends move the machine to the emulation thread
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<Chip8>();
+};
+
+/// What the render thread needs to show one frame of the machine
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Frames emulated before this one, to tell new frames from old ones
+    pub number: u64,
+    pub screen: [bool; PIXELS_PER_SCREEN],
+    /// Pixel brightness with frame blending applied, see [`Chip8::blended_frame`]
+    pub brightness: [u8; PIXELS_PER_SCREEN],
+    pub colors: Option<ColorAttributes>,
+    pub keypad: Keypad,
+    pub beeping: bool,
+    /// Nothing changes until there is input, see [`Chip8::is_idle`]
+    pub idle: bool,
+    pub cpu_state: CpuState,
+    /// The instruction about to execute
+    pub instruction: Option<Instruction>,
+    /// The last [`FRAME_HISTORY_LENGTH`] instructions executed, oldest first, see
+    /// [`Chip8::instruction_history`]
+    pub history: Vec<TraceEntry>,
+    /// What the program is waiting for, when it's waiting for a key
+    pub key_prompt: Option<KeyPrompt>,
+    /// Curated presentation settings of the running ROM
+    pub profile: Option<&'static RomProfile>,
+    /// Labels of the running ROM, when a symbol file was loaded
+    pub symbols: Option<Arc<SymbolTable>>,
+    pub memory: [u8; RAM_SIZE],
+    pub quirks: Quirks,
+    /// See [`Chip8::opcode_profile`]
+    #[cfg(feature = "profiling")]
+    pub opcode_profile: OpcodeProfile,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self {
+            number: 0,
+            screen: [false; PIXELS_PER_SCREEN],
+            brightness: [0; PIXELS_PER_SCREEN],
+            colors: None,
+            keypad: Keypad::default(),
+            beeping: false,
+            idle: false,
+            cpu_state: CpuState::default(),
+            instruction: None,
+            history: Vec::new(),
+            key_prompt: None,
+            profile: None,
+            symbols: None,
+            memory: [0; RAM_SIZE],
+            quirks: Quirks::default(),
+            #[cfg(feature = "profiling")]
+            opcode_profile: OpcodeProfile::default(),
+        }
+    }
+}
+
+impl Frame {
+    /// Copy the machine's current state into the frame, reusing its storage
+    pub fn capture(&mut self, chip8: &Chip8, number: u64) {
+        self.number = number;
+        self.screen = *chip8.displayed_screen();
+        self.brightness = chip8.blended_frame();
+        self.colors = chip8.colors().cloned();
+        self.keypad = chip8.keypad;
+        self.beeping = chip8.is_beeping();
+        self.idle = chip8.is_idle();
+        self.cpu_state = chip8.cpu_state();
+        self.instruction = chip8.current_instruction();
+        self.history.clear();
+        self.history
+            .extend(chip8.instruction_history(FRAME_HISTORY_LENGTH).copied());
+        self.key_prompt = chip8.key_prompt();
+        self.profile = chip8.rom_info().profile;
+        self.symbols = chip8.symbols.clone();
+        self.memory = chip8.memory;
+        self.quirks = chip8.quirks();
+        #[cfg(feature = "profiling")]
+        {
+            self.opcode_profile = *chip8.opcode_profile();
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        self.screen[index_from_point((y, x))]
+    }
+}
+
+/// Where an emulation loop delivers each finished frame, for another thread to show.
+///
+/// Implemented for the writing end of a [`triple_buffer`], for channel senders and for
+/// frames shared behind an `Arc<Mutex<_>>`.
+pub trait FrameSink: Send {
+    fn push_frame(&mut self, frame: &Frame);
+}
+
+impl FrameSink for BufferWriter<Frame> {
+    fn push_frame(&mut self, frame: &Frame) {
+        self.back.clone_from(frame);
+        self.publish();
+    }
+}
+
+/// Every frame is sent; a receiver that hung up is ignored
+impl FrameSink for Sender<Frame> {
+    fn push_frame(&mut self, frame: &Frame) {
+        let _ = self.send(frame.clone());
+    }
+}
+
+/// The shared frame is replaced by each new one
+impl FrameSink for Arc<Mutex<Frame>> {
+    fn push_frame(&mut self, frame: &Frame) {
+        let mut shared = self.lock().unwrap_or_else(|err| err.into_inner());
+        shared.clone_from(frame);
+    }
+}
+
+impl<S: FrameSink + ?Sized> FrameSink for Box<S> {
+    fn push_frame(&mut self, frame: &Frame) {
+        (**self).push_frame(frame);
+    }
+}
+
+/// Create a triple buffer starting out with `initial` in all three slots.
+///
+/// The writer fills its back buffer and publishes it; the reader swaps in the latest
+/// published buffer whenever it wants one. Neither side ever waits for the other to finish
+/// with a buffer.
+pub fn triple_buffer<T: Clone>(initial: T) -> (BufferWriter<T>, BufferReader<T>) {
+    let shared = Arc::new(Mutex::new(Slot {
+        value: initial.clone(),
+        fresh: false,
+    }));
+    let writer = BufferWriter {
+        back: initial.clone(),
+        shared: Arc::clone(&shared),
+    };
+    let reader = BufferReader {
+        front: initial,
+        shared,
+    };
+    (writer, reader)
+}
+
+/// The buffer between the writer and the reader
+struct Slot<T> {
+    value: T,
+    /// Published and not read yet
+    fresh: bool,
+}
+
+pub struct BufferWriter<T> {
+    back: T,
+    shared: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> BufferWriter<T> {
+    /// The buffer to fill before publishing it; holds an older value
+    pub fn back_mut(&mut self) -> &mut T {
+        &mut self.back
+    }
+
+    /// Hand the back buffer to the reader, replacing any value it hasn't picked up yet
+    pub fn publish(&mut self) {
+        let mut slot = self.shared.lock().unwrap_or_else(|err| err.into_inner());
+        std::mem::swap(&mut self.back, &mut slot.value);
+        slot.fresh = true;
+    }
+}
+
+pub struct BufferReader<T> {
+    front: T,
+    shared: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> BufferReader<T> {
+    /// Take the latest published value, if there is one; returns whether it changed
+    pub fn update(&mut self) -> bool {
+        let mut slot = self.shared.lock().unwrap_or_else(|err| err.into_inner());
+        if !slot.fresh {
+            return false;
+        }
+        std::mem::swap(&mut self.front, &mut slot.value);
+        slot.fresh = false;
+        true
+    }
+
+    pub fn get(&self) -> &T {
+        &self.front
+    }
+}
+
+/// Paces a loop to a fixed period using absolute deadlines, so time spent in the loop body
+/// and oversleeping don't accumulate into drift
+#[cfg(feature = "native")]
+#[derive(Debug, Clone)]
+pub struct FrameTimer {
+    period: Duration,
+    next: Instant,
+}
+
+#[cfg(feature = "native")]
+impl FrameTimer {
+    pub fn new(period: Duration) -> Self {
+        Self {
+            period,
+            next: Instant::now() + period,
+        }
+    }
+
+    /// A timer ticking at [`FRAME_RATE`]
+    pub fn at_frame_rate() -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / FRAME_RATE))
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Change the period, starting from the next deadline
+    pub fn set_period(&mut self, period: Duration) {
+        self.next = self.next - self.period + period;
+        self.period = period;
+    }
+
+    /// Start counting periods from now, after the loop was suspended
+    pub fn reset(&mut self) {
+        self.next = Instant::now() + self.period;
+    }
+
+    /// Block until the next deadline. After a stall of more than a few periods the missed
+    /// deadlines are dropped instead of being rushed through.
+    pub fn wait(&mut self) {
+        let now = Instant::now();
+        if now > self.next + self.period * MAX_FRAMES_BEHIND {
+            self.next = now;
+        }
+        // Sleeping can overshoot, so the last moment is spent yielding instead
+        if let Some(sleep) = self.next.checked_duration_since(now + SPIN_MARGIN) {
+            std::thread::sleep(sleep);
+        }
+        while Instant::now() < self.next {
+            std::thread::yield_now();
+        }
+        self.next += self.period;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_gets_the_latest_published_value() {
+        let (mut writer, mut reader) = triple_buffer(0);
+        assert!(!reader.update());
+        assert_eq!(*reader.get(), 0);
+
+        for value in 1..=3 {
+            *writer.back_mut() = value;
+            writer.publish();
+        }
+        assert!(reader.update());
+        assert_eq!(*reader.get(), 3);
+        assert!(!reader.update());
+        assert_eq!(*reader.get(), 3);
+    }
+
+    #[test]
+    fn sinks_deliver_frames_across_threads() {
+        let mut frame = Frame::default();
+        let shared = Arc::new(Mutex::new(Frame::default()));
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let (writer, mut reader) = triple_buffer(Frame::default());
+        let mut sinks: Vec<Box<dyn FrameSink>> = vec![
+            Box::new(Arc::clone(&shared)),
+            Box::new(sender),
+            Box::new(writer),
+        ];
+
+        let emulation = std::thread::spawn(move || {
+            for number in 1..=2 {
+                frame.number = number;
+                for sink in &mut sinks {
+                    sink.push_frame(&frame);
+                }
+            }
+        });
+        emulation.join().unwrap();
+
+        assert_eq!(shared.lock().unwrap().number, 2);
+        assert_eq!(
+            receiver
+                .iter()
+                .map(|frame| frame.number)
+                .collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert!(reader.update());
+        assert_eq!(reader.get().number, 2);
+    }
+
+    #[test]
+    fn frames_mirror_the_machine() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0x60, 0x05, 0xF0, 0x18]);
+        chip8.set_pixel(3, 2, true);
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+
+        let mut frame = Frame::default();
+        frame.capture(&chip8, 7);
+        assert_eq!(frame.number, 7);
+        assert!(frame.pixel(3, 2));
+        assert!(frame.beeping);
+        assert_eq!(frame.cpu_state.pc, 0x204);
+    }
+}
@@ -0,0 +1,145 @@
+//! Triggers on what the screen shows, to stop at a game state like its "GAME OVER" text
+//! without knowing where the game keeps it in memory:
+//!
+//! ```text
+//! pixel 10 12
+//! pattern 24 8 3C42A59981A5423C
+//! ```
+//!
+//! The first fires when the pixel at (10, 12) turns on, the second when the 8x8 square at
+//! (24, 8) shows exactly the pattern, given as 8 rows of a byte each like sprites are.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{index_from_point, PIXELS_PER_COLUMN, PIXELS_PER_ROW, PIXELS_PER_SCREEN};
+
+/// Side of the square a pattern covers
+pub const PATTERN_SIZE: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenTrigger {
+    PixelOn { x: usize, y: usize },
+    Pattern { x: usize, y: usize, rows: [u8; 8] },
+}
+
+impl ScreenTrigger {
+    /// Whether `screen` shows what the trigger waits for
+    pub fn matches(&self, screen: &[bool; PIXELS_PER_SCREEN]) -> bool {
+        match *self {
+            ScreenTrigger::PixelOn { x, y } => screen[index_from_point((y, x))],
+            ScreenTrigger::Pattern { x, y, rows } => rows.iter().enumerate().all(|(row, bits)| {
+                (0..PATTERN_SIZE).all(|column| {
+                    let on = bits & (0x80 >> column) != 0;
+                    screen[index_from_point((y + row, x + column))] == on
+                })
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ScreenTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScreenTrigger::PixelOn { x, y } => write!(f, "pixel {} {}", x, y),
+            ScreenTrigger::Pattern { x, y, rows } => {
+                write!(f, "pattern {} {} ", x, y)?;
+                rows.iter().try_for_each(|row| write!(f, "{:02X}", row))
+            }
+        }
+    }
+}
+
+impl FromStr for ScreenTrigger {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let coordinate = |value: &str, limit: usize| match value.parse() {
+            Ok(value) if value < limit => Ok(value),
+            _ => Err(format!("'{}' is not a coordinate on the screen", value)),
+        };
+        match words.as_slice() {
+            ["pixel", x, y] => Ok(ScreenTrigger::PixelOn {
+                x: coordinate(x, PIXELS_PER_ROW)?,
+                y: coordinate(y, PIXELS_PER_COLUMN)?,
+            }),
+            ["pattern", x, y, pattern] => {
+                let x = coordinate(x, PIXELS_PER_ROW - PATTERN_SIZE + 1)?;
+                let y = coordinate(y, PIXELS_PER_COLUMN - PATTERN_SIZE + 1)?;
+                let mut rows = [0; 8];
+                let valid = pattern.len() == 2 * rows.len()
+                    && pattern.is_ascii()
+                    && rows.iter_mut().enumerate().all(|(row, bits)| {
+                        u8::from_str_radix(&pattern[2 * row..2 * row + 2], 16)
+                            .map(|value| *bits = value)
+                            .is_ok()
+                    });
+                match valid {
+                    true => Ok(ScreenTrigger::Pattern { x, y, rows }),
+                    false => Err(format!(
+                        "'{}' is not a pattern of 8 rows in 16 hex digits",
+                        pattern
+                    )),
+                }
+            }
+            _ => Err(format!(
+                "unknown trigger '{}', expected 'pixel X Y' or 'pattern X Y ROWS'",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Chip8, Chip8Event, StopReason};
+
+    #[test]
+    fn triggers_fire_when_the_screen_starts_matching() {
+        // Draw the font's 0 at (8, 4), erase it, and draw it again
+        let rom = [
+            0x60, 0x08, // 200: V0 = 8
+            0x61, 0x04, // 202: V1 = 4
+            0xA0, 0x50, // 204: I = font character 0
+            0xD0, 0x15, // 206: draw
+            0xD0, 0x15, // 208: erase
+            0xD0, 0x15, // 20A: draw
+        ];
+        let mut chip8 = Chip8::new();
+        chip8.enable_events();
+        chip8.load_rom(&rom);
+        let zero: ScreenTrigger = "pattern 8 4 F0909090F0000000".parse().unwrap();
+        let pixel: ScreenTrigger = "pixel 11 5".parse().unwrap();
+        chip8.add_screen_trigger(zero);
+        chip8.add_screen_trigger(pixel);
+
+        let mut fired = vec![];
+        for _ in 0..rom.len() / 2 {
+            chip8.step().unwrap();
+            for event in chip8.take_events() {
+                if let Chip8Event::ScreenTriggered { pc, trigger } = event {
+                    fired.push((pc, trigger));
+                }
+            }
+        }
+        assert_eq!(
+            fired,
+            [(0x206, zero), (0x206, pixel), (0x20A, zero), (0x20A, pixel)]
+        );
+
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&rom);
+        chip8.add_screen_trigger(zero);
+        assert_eq!(chip8.run_until_event(100), Ok(StopReason::ScreenTrigger));
+        assert_eq!(chip8.cpu_state().pc, 0x208);
+
+        assert_eq!(zero.to_string(), "pattern 8 4 F0909090F0000000");
+        assert!("pattern 60 0 F0909090F0000000"
+            .parse::<ScreenTrigger>()
+            .is_err());
+        assert!("pattern 0 0 F090".parse::<ScreenTrigger>().is_err());
+        assert!("pixel 64 0".parse::<ScreenTrigger>().is_err());
+    }
+}
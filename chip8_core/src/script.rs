@@ -0,0 +1,269 @@
+//! Scripts of keypresses and expectations for testing ROMs without writing Rust, one step
+//! per line or separated by `;`, each at the frame it happens on:
+//!
+//! ```text
+//! frame 10: press 5; frame 30: release 5
+//! frame 100: expect pixel (10, 12) on   # the paddle moved
+//! frame 100: expect v3 = 0x02
+//! ```
+//!
+//! A step at frame N happens once N frames ran, before the next one; keys are hex digits.
+
+use std::fmt;
+
+use crate::{Chip8, PIXELS_PER_COLUMN, PIXELS_PER_ROW};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Press(u8),
+    Release(u8),
+    ExpectPixel { x: usize, y: usize, on: bool },
+    ExpectRegister { x: usize, value: u8 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    /// 1-based line of the script the step is on
+    pub line: usize,
+    pub frame: u32,
+    pub action: Action,
+}
+
+/// A script that couldn't be read, with the 1-based line of the problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// An expectation of the script the machine didn't meet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectationFailed {
+    pub step: Step,
+    pub message: String,
+}
+
+impl fmt::Display for ExpectationFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frame {} (line {}): {}",
+            self.step.frame, self.step.line, self.message
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+    /// In the order they happen
+    steps: Vec<Step>,
+}
+
+impl Script {
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let mut steps = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| ScriptError {
+                line: index + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or_default();
+            for statement in line.split(';').map(str::trim) {
+                if statement.is_empty() {
+                    continue;
+                }
+                let (frame, action) = statement
+                    .strip_prefix("frame")
+                    .and_then(|rest| rest.split_once(':'))
+                    .ok_or_else(|| {
+                        error(format!("expected 'frame N: ...', found '{}'", statement))
+                    })?;
+                let frame = frame
+                    .trim()
+                    .parse()
+                    .map_err(|_| error(format!("'{}' is not a frame number", frame.trim())))?;
+                let action = parse_action(action.trim()).map_err(error)?;
+                steps.push(Step {
+                    line: index + 1,
+                    frame,
+                    action,
+                });
+            }
+        }
+        // Stable, so steps of the same frame keep their order
+        steps.sort_by_key(|step| step.frame);
+        Ok(Self { steps })
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Frames to run for every step to happen
+    pub fn last_frame(&self) -> u32 {
+        self.steps.last().map_or(0, |step| step.frame)
+    }
+
+    /// Start playing the script on a machine
+    pub fn player(&self) -> ScriptPlayer<'_> {
+        ScriptPlayer {
+            steps: &self.steps,
+            keys: [false; 16],
+        }
+    }
+}
+
+/// Plays a script a frame at a time, holding the keys it pressed
+#[derive(Debug, Clone)]
+pub struct ScriptPlayer<'a> {
+    /// Steps that haven't happened yet
+    steps: &'a [Step],
+    keys: [bool; 16],
+}
+
+impl ScriptPlayer<'_> {
+    /// Take the steps due once `frame` frames ran, then hand `chip8` the held keys. Call it
+    /// before running each frame, and once more after the last.
+    pub fn before_frame(&mut self, frame: u32, chip8: &mut Chip8) -> Result<(), ExpectationFailed> {
+        while let Some((&step, rest)) = self.steps.split_first() {
+            if step.frame > frame {
+                break;
+            }
+            self.steps = rest;
+            let failed = |message: String| ExpectationFailed { step, message };
+            match step.action {
+                Action::Press(key) => self.keys[key as usize] = true,
+                Action::Release(key) => self.keys[key as usize] = false,
+                Action::ExpectPixel { x, y, on } => {
+                    if chip8.pixel(x, y) != on {
+                        let state = |on| if on { "on" } else { "off" };
+                        return Err(failed(format!(
+                            "expected pixel ({}, {}) {}, it's {}",
+                            x,
+                            y,
+                            state(on),
+                            state(!on)
+                        )));
+                    }
+                }
+                Action::ExpectRegister { x, value } => {
+                    let found = chip8.cpu_state().v[x];
+                    if found != value {
+                        return Err(failed(format!(
+                            "expected V{:X} = 0x{:02X}, it's 0x{:02X}",
+                            x, value, found
+                        )));
+                    }
+                }
+            }
+        }
+        chip8.keypad.update_keys(self.keys);
+        Ok(())
+    }
+}
+
+fn parse_action(action: &str) -> Result<Action, String> {
+    let words: Vec<&str> = action.split_whitespace().collect();
+    match words.as_slice() {
+        ["press", key] => Ok(Action::Press(parse_key(key)?)),
+        ["release", key] => Ok(Action::Release(parse_key(key)?)),
+        ["expect", "pixel", ..] => {
+            let rest = action["expect".len()..].trim_start()["pixel".len()..].trim();
+            let (point, state) = rest
+                .strip_prefix('(')
+                .and_then(|rest| rest.split_once(')'))
+                .ok_or_else(|| format!("expected 'expect pixel (x, y) on', found '{}'", action))?;
+            let (x, y) = point
+                .split_once(',')
+                .ok_or_else(|| format!("'({})' is not a point like (10, 12)", point))?;
+            let coordinate = |value: &str, size: usize| match value.trim().parse() {
+                Ok(value) if value < size => Ok(value),
+                _ => Err(format!(
+                    "'{}' is not a coordinate on the screen",
+                    value.trim()
+                )),
+            };
+            let on = match state.trim() {
+                "on" => true,
+                "off" => false,
+                state => return Err(format!("expected on or off, found '{}'", state)),
+            };
+            Ok(Action::ExpectPixel {
+                x: coordinate(x, PIXELS_PER_ROW)?,
+                y: coordinate(y, PIXELS_PER_COLUMN)?,
+                on,
+            })
+        }
+        ["expect", register, "=", value] => {
+            let x = register
+                .strip_prefix(['v', 'V'])
+                .and_then(|digit| usize::from_str_radix(digit, 16).ok())
+                .filter(|&x| x < 16)
+                .ok_or_else(|| format!("'{}' is not a register from v0 to vF", register))?;
+            let value = match value.strip_prefix("0x") {
+                Some(digits) => u8::from_str_radix(digits, 16),
+                None => value.parse(),
+            }
+            .map_err(|_| format!("'{}' is not a byte", value))?;
+            Ok(Action::ExpectRegister { x, value })
+        }
+        _ => Err(format!(
+            "unknown step '{}', expected press, release, expect pixel or expect vX",
+            action
+        )),
+    }
+}
+
+fn parse_key(key: &str) -> Result<u8, String> {
+    match u8::from_str_radix(key, 16) {
+        Ok(key) if key < 16 => Ok(key),
+        _ => Err(format!("'{}' is not a key from 0 to F", key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_press_keys_and_check_the_machine() {
+        // Waits for a key, then draws the font's 0 at (V1, V1) with V1 = 2
+        let rom = [0xF0, 0x0A, 0x61, 0x02, 0xA0, 0x50, 0xD1, 0x15, 0x12, 0x08];
+        let script = Script::parse(
+            "frame 2: press 5  # any key\nframe 4: release 5; frame 10: expect v0 = 0x05\n\
+             frame 10: expect pixel (2, 2) on; frame 10: expect pixel (3, 3) off",
+        )
+        .unwrap();
+        assert_eq!(script.steps().len(), 5);
+        assert_eq!(script.last_frame(), 10);
+
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&rom);
+        let mut player = script.player();
+        for frame in 0..=script.last_frame() {
+            player.before_frame(frame, &mut chip8).unwrap();
+            chip8.run_frame().unwrap();
+        }
+
+        let failing = Script::parse("frame 0: expect pixel (0, 0) on").unwrap();
+        let failed = failing.player().before_frame(0, &mut chip8).unwrap_err();
+        assert_eq!(
+            failed.to_string(),
+            "frame 0 (line 1): expected pixel (0, 0) on, it's off"
+        );
+
+        let error = Script::parse("frame 1: press 5\nframe 2: jump").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert!(Script::parse("frame 1: press 10").is_err());
+        assert!(Script::parse("frame 1: expect pixel (64, 0) on").is_err());
+        assert!(Script::parse("frame x: press 1").is_err());
+    }
+}
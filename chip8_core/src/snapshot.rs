@@ -0,0 +1,243 @@
+//! Screens saved as text, a row of `#` (lit) and `.` (unlit) pixels per line, and the
+//! differences between two of them, for when a screen doesn't match the expected one
+
+use std::fmt;
+
+use crate::bitmap::Bitmap;
+use crate::{index_from_point, Chip8, PIXELS_PER_COLUMN, PIXELS_PER_ROW, PIXELS_PER_SCREEN};
+
+/// Colours of the diff image, as RGB
+const UNCHANGED_ON: [u8; 3] = [0xC0, 0xC0, 0xC0];
+const UNCHANGED_OFF: [u8; 3] = [0x20, 0x20, 0x20];
+const ONLY_EXPECTED: [u8; 3] = [0xE0, 0x30, 0x30];
+const ONLY_ACTUAL: [u8; 3] = [0x30, 0xC0, 0x40];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub screen: [bool; PIXELS_PER_SCREEN],
+}
+
+impl Snapshot {
+    pub fn of(chip8: &Chip8) -> Self {
+        Self {
+            screen: chip8.screen,
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        self.screen[index_from_point((y, x))]
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let rows: Vec<&str> = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|row| !row.is_empty())
+            .collect();
+        if rows.len() != PIXELS_PER_COLUMN {
+            return Err(format!(
+                "expected {} rows of pixels, found {}",
+                PIXELS_PER_COLUMN,
+                rows.len()
+            ));
+        }
+        let mut screen = [false; PIXELS_PER_SCREEN];
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != PIXELS_PER_ROW {
+                return Err(format!(
+                    "row {} should be {} pixels wide",
+                    y + 1,
+                    PIXELS_PER_ROW
+                ));
+            }
+            for (x, pixel) in row.chars().enumerate() {
+                screen[index_from_point((y, x))] = match pixel {
+                    '#' => true,
+                    '.' => false,
+                    _ => return Err(format!("row {} has '{}', not '#' or '.'", y + 1, pixel)),
+                };
+            }
+        }
+        Ok(Self { screen })
+    }
+
+    /// The screen in a screenshot, like those Octo saves, scaled up by any whole factor
+    /// with nothing around it. Each pixel is read at the middle of its square.
+    pub fn from_bitmap(bitmap: &Bitmap) -> Result<Self, String> {
+        let scale = bitmap.width / PIXELS_PER_ROW;
+        if scale == 0
+            || bitmap.width != PIXELS_PER_ROW * scale
+            || bitmap.height != PIXELS_PER_COLUMN * scale
+        {
+            return Err(format!(
+                "the image is {}x{} pixels, not a {}x{} screen scaled up",
+                bitmap.width, bitmap.height, PIXELS_PER_ROW, PIXELS_PER_COLUMN
+            ));
+        }
+        let mut screen = [false; PIXELS_PER_SCREEN];
+        for (index, pixel) in screen.iter_mut().enumerate() {
+            let (y, x) = crate::point_from_index(index);
+            let (x, y) = (x * scale + scale / 2, y * scale + scale / 2);
+            *pixel = bitmap.pixels[y * bitmap.width + x];
+        }
+        Ok(Self { screen })
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..PIXELS_PER_COLUMN {
+            let row: String = (0..PIXELS_PER_ROW)
+                .map(|x| if self.pixel(x, y) { '#' } else { '.' })
+                .collect();
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+/// Pixels lit in only one of two snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    expected: Snapshot,
+    actual: Snapshot,
+}
+
+impl SnapshotDiff {
+    pub fn new(expected: Snapshot, actual: Snapshot) -> Self {
+        Self { expected, actual }
+    }
+
+    /// Points lit only in the expected snapshot, as (x, y)
+    pub fn missing(&self) -> Vec<(usize, usize)> {
+        self.points(|expected, actual| expected && !actual)
+    }
+
+    /// Points lit only in the actual snapshot, as (x, y)
+    pub fn unexpected(&self) -> Vec<(usize, usize)> {
+        self.points(|expected, actual| !expected && actual)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expected == self.actual
+    }
+
+    /// Share of the screen's pixels that differ, from 0 to 100
+    pub fn mismatch_percent(&self) -> f64 {
+        let differing = self.missing().len() + self.unexpected().len();
+        100.0 * differing as f64 / PIXELS_PER_SCREEN as f64
+    }
+
+    /// Smallest rectangle holding every differing pixel, as (left, top, right, bottom)
+    pub fn bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        let points = self.points(|expected, actual| expected != actual);
+        let (&(x, y), rest) = points.split_first()?;
+        Some(
+            rest.iter()
+                .fold((x, y, x, y), |(left, top, right, bottom), &(x, y)| {
+                    (left.min(x), top.min(y), right.max(x), bottom.max(y))
+                }),
+        )
+    }
+
+    /// A line or two telling how many pixels differ and where
+    pub fn summary(&self) -> String {
+        match self.bounds() {
+            None => "the snapshots match".to_string(),
+            Some((left, top, right, bottom)) => format!(
+                "{} pixels differ: {} missing, {} unexpected\nbetween ({}, {}) and ({}, {})",
+                self.missing().len() + self.unexpected().len(),
+                self.missing().len(),
+                self.unexpected().len(),
+                left,
+                top,
+                right,
+                bottom
+            ),
+        }
+    }
+
+    /// 24-bit BMP of the two screens overlaid, each pixel `scale` squares wide: grey where
+    /// they agree, red where only the expected one is lit and green where only the actual
+    /// one is
+    pub fn to_bmp(&self, scale: usize) -> Vec<u8> {
+        let scale = scale.max(1);
+        let width = PIXELS_PER_ROW * scale;
+        let height = PIXELS_PER_COLUMN * scale;
+        // Rows are padded to four bytes, but 64 pixels of three bytes always are already
+        let image_size = width * height * 3;
+        let mut bmp = Vec::with_capacity(54 + image_size);
+        bmp.extend(b"BM");
+        bmp.extend(((54 + image_size) as u32).to_le_bytes());
+        bmp.extend([0; 4]);
+        bmp.extend(54u32.to_le_bytes());
+        bmp.extend(40u32.to_le_bytes());
+        bmp.extend((width as i32).to_le_bytes());
+        bmp.extend((height as i32).to_le_bytes());
+        bmp.extend(1u16.to_le_bytes());
+        bmp.extend(24u16.to_le_bytes());
+        bmp.extend([0; 4]);
+        bmp.extend((image_size as u32).to_le_bytes());
+        bmp.extend([0; 16]);
+        // Bottom row first, each pixel as BGR
+        for y in (0..height).rev() {
+            for x in 0..width {
+                let (x, y) = (x / scale, y / scale);
+                let color = match (self.expected.pixel(x, y), self.actual.pixel(x, y)) {
+                    (true, true) => UNCHANGED_ON,
+                    (false, false) => UNCHANGED_OFF,
+                    (true, false) => ONLY_EXPECTED,
+                    (false, true) => ONLY_ACTUAL,
+                };
+                bmp.extend(color.iter().rev());
+            }
+        }
+        bmp
+    }
+
+    fn points(&self, differ: impl Fn(bool, bool) -> bool) -> Vec<(usize, usize)> {
+        (0..PIXELS_PER_COLUMN)
+            .flat_map(|y| (0..PIXELS_PER_ROW).map(move |x| (x, y)))
+            .filter(|&(x, y)| differ(self.expected.pixel(x, y), self.actual.pixel(x, y)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn differing_pixels_are_found_and_drawn() {
+        let mut chip8 = Chip8::new();
+        chip8.screen[index_from_point((1, 2))] = true;
+        let expected = Snapshot::of(&chip8);
+        assert_eq!(Snapshot::parse(&expected.to_string()), Ok(expected.clone()));
+        assert!(Snapshot::parse("#.\n").is_err());
+
+        chip8.screen[index_from_point((1, 2))] = false;
+        chip8.screen[index_from_point((5, 9))] = true;
+        chip8.screen[index_from_point((6, 9))] = true;
+        let diff = SnapshotDiff::new(expected, Snapshot::of(&chip8));
+        assert_eq!(diff.missing(), [(2, 1)]);
+        assert_eq!(diff.unexpected(), [(9, 5), (9, 6)]);
+        assert_eq!(diff.bounds(), Some((2, 1, 9, 6)));
+        assert!(diff.summary().starts_with("3 pixels differ"));
+
+        assert_eq!(diff.mismatch_percent(), 300.0 / 2048.0);
+
+        // Where only the expected screen is lit the image is dark red, and light green
+        // where only the actual one is, so it reads back as the actual screen
+        let image = Bitmap::decode(&diff.to_bmp(2)).unwrap();
+        assert_eq!((image.width, image.height), (128, 64));
+        assert_eq!(Snapshot::from_bitmap(&image), Ok(Snapshot::of(&chip8)));
+        let image = Bitmap::decode(&diff.to_bmp(1)).unwrap();
+        assert_eq!(Snapshot::from_bitmap(&image), Ok(Snapshot::of(&chip8)));
+        let cropped = Bitmap {
+            width: 64,
+            height: 31,
+            pixels: vec![false; 64 * 31],
+        };
+        assert!(Snapshot::from_bitmap(&cropped).is_err());
+    }
+}
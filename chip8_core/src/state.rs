@@ -0,0 +1,354 @@
+use std::fmt;
+
+use crate::banking::{Banks, BANK_SIZE, MAX_BANKS};
+use crate::{Chip8, Keypad, PIXELS_PER_SCREEN, RAM_SIZE, STACK_SIZE};
+
+const MAGIC: &[u8; 4] = b"C8ST";
+pub const STATE_VERSION: u16 = 3;
+
+/// Size of the header shared by every version: magic, version and ROM hash
+const HEADER_SIZE: usize = MAGIC.len() + 2 + 8;
+const SCREEN_BYTES: usize = PIXELS_PER_SCREEN / 8;
+/// Body of a version 1 state: memory, screen, pc, i, stack, timers and registers
+const V1_BODY_SIZE: usize = RAM_SIZE + SCREEN_BYTES + 2 + 2 + 1 + STACK_SIZE * 2 + 1 + 1 + 16;
+/// Version 2 appends the keypad state so `FX0A` waits survive a reload
+const V2_BODY_SIZE: usize = V1_BODY_SIZE + 2 * 2;
+/// Version 3 appends the number of memory banks, 0 without bank switching, followed by
+/// the mapped bank and the banks themselves
+const V3_BODY_SIZE: usize = V2_BODY_SIZE + 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// The data doesn't start with the save state magic bytes
+    NotASaveState,
+    /// The state was written by a newer (or unknown) version of the core
+    UnsupportedVersion(u16),
+    /// The state was created while running a different ROM
+    RomMismatch { expected: u64, found: u64 },
+    /// The data ended before the whole state could be read
+    Truncated { expected: usize, found: usize },
+    /// A field holds a value the machine can't be in
+    Corrupted(&'static str),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::NotASaveState => write!(f, "data is not a CHIP-8 save state"),
+            StateError::UnsupportedVersion(version) => write!(
+                f,
+                "save state version {} is not supported (this core reads versions 1 to {})",
+                version, STATE_VERSION
+            ),
+            StateError::RomMismatch { expected, found } => write!(
+                f,
+                "save state belongs to ROM {:016x}, but the loaded ROM is {:016x}",
+                found, expected
+            ),
+            StateError::Truncated { expected, found } => write!(
+                f,
+                "save state is truncated: expected {} bytes, found {}",
+                expected, found
+            ),
+            StateError::Corrupted(field) => write!(f, "save state is corrupted: invalid {}", field),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+/// FNV-1a hash used to tie save states (and other per-ROM data) to a ROM
+pub fn rom_hash(rom: &[u8]) -> u64 {
+    rom.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+impl Chip8 {
+    /// Serialize the machine into the current save state format
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_SIZE + V3_BODY_SIZE);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&STATE_VERSION.to_le_bytes());
+        out.extend_from_slice(&self.rom_info.hash.to_le_bytes());
+
+        out.extend_from_slice(&self.memory);
+        out.extend_from_slice(&pack_bits(&self.screen));
+        out.extend_from_slice(&self.pc.to_le_bytes());
+        out.extend_from_slice(&self.i.to_le_bytes());
+        out.push(self.stack.len() as u8);
+        for slot in 0..STACK_SIZE {
+            let addr = self.stack.get(slot).copied().unwrap_or(0);
+            out.extend_from_slice(&addr.to_le_bytes());
+        }
+        out.push(self.delay_timer);
+        out.push(self.sound_timer);
+        out.extend_from_slice(&self.v);
+
+        out.extend_from_slice(&pack_bits(&self.keypad.previous_frame_keys));
+        out.extend_from_slice(&pack_bits(&self.keypad.current_frame_keys));
+
+        match &self.banks {
+            Some(banks) => {
+                out.push(banks.count() as u8);
+                out.push(banks.selected() as u8);
+                for bank in 0..banks.count() {
+                    out.extend_from_slice(banks.bank(bank, &self.memory).unwrap());
+                }
+            }
+            None => out.push(0),
+        }
+        out
+    }
+
+    /// Restore a state produced by [`Chip8::save_state`], migrating older versions.
+    ///
+    /// The ROM must already be loaded: states for a different ROM are rejected.
+    /// On error the machine is left untouched.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", skip_all, err(level = "warn"))
+    )]
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        if data.len() < HEADER_SIZE || &data[..MAGIC.len()] != MAGIC {
+            return Err(StateError::NotASaveState);
+        }
+        let mut reader = Reader::new(&data[MAGIC.len()..]);
+        let version = reader.u16();
+        let hash = reader.u64();
+
+        let body_size = match version {
+            1 => V1_BODY_SIZE,
+            2 => V2_BODY_SIZE,
+            3 => match data.get(HEADER_SIZE + V3_BODY_SIZE - 1) {
+                Some(&banks @ 1..) => V3_BODY_SIZE + 1 + banks as usize * BANK_SIZE,
+                _ => V3_BODY_SIZE,
+            },
+            version => return Err(StateError::UnsupportedVersion(version)),
+        };
+        if data.len() != HEADER_SIZE + body_size {
+            return Err(StateError::Truncated {
+                expected: HEADER_SIZE + body_size,
+                found: data.len(),
+            });
+        }
+        if hash != self.rom_info.hash {
+            return Err(StateError::RomMismatch {
+                expected: self.rom_info.hash,
+                found: hash,
+            });
+        }
+
+        let mut state = self.clone();
+        state.memory.copy_from_slice(reader.bytes(RAM_SIZE));
+        state.screen = unpack_bits(reader.bytes(SCREEN_BYTES));
+        state.pc = reader.u16();
+        state.i = reader.u16();
+        let stack_len = reader.u8() as usize;
+        if stack_len > STACK_SIZE {
+            return Err(StateError::Corrupted("stack length"));
+        }
+        state.stack.clear();
+        for slot in 0..STACK_SIZE {
+            let addr = reader.u16();
+            if slot < stack_len {
+                state.stack.push(addr);
+            }
+        }
+        state.delay_timer = reader.u8();
+        state.sound_timer = reader.u8();
+        state.v.copy_from_slice(reader.bytes(16));
+        if state.pc as usize >= RAM_SIZE - 1 {
+            return Err(StateError::Corrupted("program counter"));
+        }
+
+        state.keypad = match version {
+            // version 1 didn't store the keypad: start with every key released
+            1 => Keypad {
+                previous_frame_keys: [false; 16],
+                current_frame_keys: [false; 16],
+                ..state.keypad
+            },
+            // the second bank isn't saved: it keeps following the host's keys
+            _ => Keypad {
+                previous_frame_keys: unpack_bits(reader.bytes(2)),
+                current_frame_keys: unpack_bits(reader.bytes(2)),
+                ..state.keypad
+            },
+        };
+        // earlier versions predate bank switching: it stays as the machine has it
+        if version >= 3 {
+            state.banks = match reader.u8() as usize {
+                0 => None,
+                count if count > MAX_BANKS => return Err(StateError::Corrupted("bank count")),
+                count => {
+                    let selected = reader.u8() as usize;
+                    if selected >= count {
+                        return Err(StateError::Corrupted("mapped bank"));
+                    }
+                    Some(Banks::restore(selected, reader.bytes(count * BANK_SIZE)))
+                }
+            };
+            state.resolve_extensions();
+        }
+        state.should_redraw = true;
+        state.record_screen_changes(&self.screen);
+        state.show_screen();
+
+        *self = state;
+        #[cfg(feature = "tracing")]
+        tracing::info!(version, "loaded save state");
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn bytes(&mut self, len: usize) -> &'a [u8] {
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        head
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.bytes(1)[0]
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes(self.bytes(2).try_into().unwrap())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.bytes(8).try_into().unwrap())
+    }
+}
+
+fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0, |byte, (bit, &on)| byte | ((on as u8) << (7 - bit)))
+        })
+        .collect()
+}
+
+fn unpack_bits<const N: usize>(bytes: &[u8]) -> [bool; N] {
+    let mut bits = [false; N];
+    for (index, bit) in bits.iter_mut().enumerate() {
+        *bit = (bytes[index / 8] >> (7 - index % 8)) & 0b1 == 1;
+    }
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROM: &[u8] = &[0x60, 0x05, 0x61, 0x0A, 0xA2, 0x0A, 0xD0, 0x15, 0x12, 0x08];
+
+    fn machine_with_rom(rom: &[u8]) -> Chip8 {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(rom);
+        chip8
+    }
+
+    #[test]
+    fn state_round_trips() {
+        let mut chip8 = machine_with_rom(ROM);
+        for _ in 0..4 {
+            chip8.tick().unwrap();
+        }
+        let state = chip8.save_state();
+
+        let mut restored = machine_with_rom(ROM);
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.save_state(), state);
+        assert_eq!(restored.screen, chip8.screen);
+    }
+
+    #[test]
+    fn state_from_another_rom_is_rejected() {
+        let state = machine_with_rom(ROM).save_state();
+        let mut other = machine_with_rom(&[0x12, 0x00]);
+
+        assert_eq!(
+            other.load_state(&state),
+            Err(StateError::RomMismatch {
+                expected: rom_hash(&[0x12, 0x00]),
+                found: rom_hash(ROM),
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_versions_and_garbage_are_rejected() {
+        let mut chip8 = machine_with_rom(ROM);
+        let mut state = chip8.save_state();
+        state[4..6].copy_from_slice(&99u16.to_le_bytes());
+
+        assert_eq!(
+            chip8.load_state(&state),
+            Err(StateError::UnsupportedVersion(99))
+        );
+        assert_eq!(
+            chip8.load_state(b"not a state"),
+            Err(StateError::NotASaveState)
+        );
+        assert!(matches!(
+            chip8.load_state(&chip8.save_state()[..100]),
+            Err(StateError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn version_1_states_are_migrated() {
+        let mut chip8 = machine_with_rom(ROM);
+        chip8.tick().unwrap();
+        chip8.keypad.update_keys([true; 16]);
+        let mut state = chip8.save_state();
+        state.truncate(HEADER_SIZE + V1_BODY_SIZE);
+        state[4..6].copy_from_slice(&1u16.to_le_bytes());
+
+        let mut restored = machine_with_rom(ROM);
+        restored.load_state(&state).unwrap();
+        assert_eq!(restored.v[0], 0x05);
+        assert_eq!(restored.keypad.current_frame_keys, [false; 16]);
+    }
+
+    #[test]
+    fn banks_are_saved_with_the_one_mapped() {
+        let mut rom = vec![0; crate::MAX_ROM_SIZE + 2 * BANK_SIZE];
+        rom[..4].copy_from_slice(&[0x60, 0x02, 0xF0, 0xB0]);
+        let banked = || {
+            let mut chip8 = Chip8::new();
+            chip8.enable_banking();
+            chip8.load_rom(&rom);
+            chip8
+        };
+        let mut chip8 = banked();
+        chip8.memory[0x900] = 0x11;
+        chip8.tick().unwrap();
+        chip8.tick().unwrap();
+        let state = chip8.save_state();
+
+        let mut restored = banked();
+        restored.load_state(&state).unwrap();
+        let banks = restored.banks().unwrap();
+        assert_eq!((banks.count(), banks.selected()), (3, 2));
+        assert_eq!(banks.bank(0, restored.memory()).unwrap()[0x100], 0x11);
+        assert_eq!(restored.save_state(), state);
+        assert!(matches!(
+            restored.load_state(&state[..state.len() - 1]),
+            Err(StateError::Truncated { .. })
+        ));
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::VecDeque;
+
+/// Save states taken before each of the most recently executed instructions, so a debugger
+/// can step backwards
+#[derive(Debug, Clone)]
+pub(crate) struct StepHistory {
+    snapshots: VecDeque<Vec<u8>>,
+    capacity: usize,
+}
+
+impl StepHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, snapshot: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    pub fn pop(&mut self) -> Option<Vec<u8>> {
+        self.snapshots.pop_back()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Chip8;
+
+    #[test]
+    fn stepping_back_undoes_instructions() {
+        let mut chip8 = Chip8::new();
+        chip8.enable_step_history(2);
+        chip8.load_rom(&[
+            0x60, 0x05, // V0 = 5
+            0x70, 0x01, // V0 += 1
+            0xA3, 0x00, // I = 0x300
+            0xF0, 0x55, // store V0 at 0x300
+        ]);
+        for _ in 0..4 {
+            chip8.step().unwrap();
+        }
+        assert_eq!(chip8.memory()[0x300], 6);
+
+        assert!(chip8.step_back());
+        assert_eq!(chip8.cpu_state().pc, 0x206);
+        assert_eq!((chip8.cpu_state().i, chip8.memory()[0x300]), (0x300, 0));
+        assert!(chip8.step_back());
+        assert_eq!((chip8.cpu_state().pc, chip8.cpu_state().i), (0x204, 0));
+        // Only the last two instructions are remembered
+        assert!(!chip8.step_back());
+        assert_eq!(chip8.cpu_state().v[0], 6);
+
+        // Execution carries on from the restored state
+        chip8.step().unwrap();
+        assert_eq!(chip8.cpu_state().i, 0x300);
+    }
+}
@@ -0,0 +1,103 @@
+//! Builder for machines in a given state, so tests can run a single opcode against it
+
+use crate::{index_from_point, Chip8, Chip8Error, Platform, Quirks, ROM_INITIAL_POSITION};
+
+pub(crate) struct TestMachine {
+    chip8: Chip8,
+}
+
+impl TestMachine {
+    /// A COSMAC VIP machine with empty memory and registers, about to execute at 0x200
+    pub fn new() -> Self {
+        Self {
+            chip8: Chip8::new(),
+        }
+    }
+
+    pub fn with_platform(mut self, platform: Platform) -> Self {
+        self.chip8.use_platform(platform);
+        self
+    }
+
+    pub fn with_quirks(mut self, quirks: Quirks) -> Self {
+        self.chip8.quirks = quirks;
+        self
+    }
+
+    pub fn with_v(mut self, x: usize, value: u8) -> Self {
+        self.chip8.v[x] = value;
+        self
+    }
+
+    pub fn with_i(mut self, i: u16) -> Self {
+        self.chip8.i = i;
+        self
+    }
+
+    pub fn with_pc(mut self, pc: u16) -> Self {
+        self.chip8.pc = pc;
+        self
+    }
+
+    pub fn with_memory(mut self, addr: usize, data: &[u8]) -> Self {
+        self.chip8.memory[addr..addr + data.len()].copy_from_slice(data);
+        self
+    }
+
+    pub fn with_stack(mut self, stack: &[u16]) -> Self {
+        self.chip8.stack = stack.iter().copied().collect();
+        self
+    }
+
+    pub fn with_timers(mut self, delay: u8, sound: u8) -> Self {
+        self.chip8.delay_timer = delay;
+        self.chip8.sound_timer = sound;
+        self
+    }
+
+    pub fn with_key(mut self, key: usize) -> Self {
+        self.chip8.keypad.current_frame_keys[key] = true;
+        self
+    }
+
+    /// Press a key of the CHIP-8X second keypad
+    pub fn with_second_bank_key(mut self, key: usize) -> Self {
+        let mut keys = [false; 16];
+        keys[key] = true;
+        self.chip8.keypad.update_second_bank(keys);
+        self
+    }
+
+    /// Light the pixels at the given (x, y) positions
+    pub fn with_pixels(mut self, pixels: &[(usize, usize)]) -> Self {
+        for &(x, y) in pixels {
+            self.chip8.screen[index_from_point((y, x))] = true;
+        }
+        self
+    }
+
+    /// Place `opcode` at the program counter and execute it
+    pub fn try_execute(mut self, opcode: u16) -> Result<Chip8, Chip8Error> {
+        let pc = self.chip8.pc as usize;
+        self.chip8.memory[pc..pc + 2].copy_from_slice(&opcode.to_be_bytes());
+        self.chip8.step()?;
+        Ok(self.chip8)
+    }
+
+    /// Like [`TestMachine::try_execute`], for opcodes that must not fault
+    pub fn execute(self, opcode: u16) -> Chip8 {
+        self.try_execute(opcode)
+            .unwrap_or_else(|err| panic!("{:04X} failed: {}", opcode, err))
+    }
+}
+
+impl Default for TestMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where the instruction after the one at 0x200 starts
+pub(crate) const NEXT: u16 = ROM_INITIAL_POSITION as u16 + 2;
+/// Where the program counter ends up when 0x200 skips
+pub(crate) const SKIPPED: u16 = NEXT + 2;
@@ -0,0 +1,243 @@
+//! A 3x5 pixel font for frontends to draw messages, menus and debug views with, the same
+//! way everywhere, without a font library.
+//!
+//! Text is drawn in capitals, one glyph every [`GLYPH_ADVANCE`] font pixels; characters
+//! without a glyph leave a gap. The Chinese characters of the translations are wide glyphs
+//! instead, twice as wide and drawn at twice the resolution. [`text_pixels`] lists the
+//! squares to light for any kind of canvas, and [`draw_rgba`] draws straight into an RGBA
+//! buffer.
+
+use crate::wide_glyphs::WIDE_GLYPHS;
+
+/// Width of a character including the space after it, in font pixels
+pub const GLYPH_ADVANCE: u32 = 4;
+/// Height of a line including the space below it, in font pixels
+pub const LINE_HEIGHT: u32 = 7;
+
+/// A lit square of a glyph, in half font pixels from the top-left corner of the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Square {
+    pub x: u32,
+    pub y: u32,
+    /// Two for the pixels of 3x5 glyphs, one for those of wide glyphs
+    pub size: u32,
+}
+
+impl Square {
+    /// Left, top, width and height of the square in pixels with font pixels `scale` wide.
+    /// Wide glyphs come out a little bold at odd scales, never with gaps.
+    pub fn scaled(&self, scale: u32) -> (u32, u32, u32, u32) {
+        let (left, top) = (self.x * scale / 2, self.y * scale / 2);
+        let right = ((self.x + self.size) * scale).div_ceil(2);
+        let bottom = ((self.y + self.size) * scale).div_ceil(2);
+        (left, top, right - left, bottom - top)
+    }
+}
+
+/// Whether every character of `text` has a glyph
+pub fn can_draw(text: &str) -> bool {
+    text.chars().all(|character| {
+        character == '\n' || glyph(character).is_some() || wide_glyph(character).is_some()
+    })
+}
+
+/// Width of `text` drawn at `scale`, in pixels, without the space after the last glyph
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    let advance: u32 = text.chars().map(advance).sum();
+    advance.saturating_sub(1) * scale
+}
+
+/// The squares to light for `text`
+pub fn text_pixels(text: &str) -> impl Iterator<Item = Square> + '_ {
+    let mut left = 0;
+    text.chars().flat_map(move |character| {
+        let start = 2 * left;
+        left += advance(character);
+        let squares: Vec<Square> = match (glyph(character), wide_glyph(character)) {
+            (Some(rows), _) => lit(&rows, 3)
+                .map(|(column, row)| Square {
+                    x: start + 2 * column,
+                    y: 2 * row,
+                    size: 2,
+                })
+                .collect(),
+            (None, Some(rows)) => lit(&rows, 16)
+                .map(|(column, row)| Square {
+                    x: start + column,
+                    y: row,
+                    size: 1,
+                })
+                .collect(),
+            (None, None) => vec![],
+        };
+        squares
+    })
+}
+
+/// Column and row of the set bits of `rows`, each `width` bits wide
+fn lit<T: Copy + Into<u32>>(rows: &[T], width: u32) -> impl Iterator<Item = (u32, u32)> + '_ {
+    rows.iter().zip(0..).flat_map(move |(&bits, row)| {
+        (0..width)
+            .filter(move |column| (bits.into() >> (width - 1 - column)) & 0b1 == 1)
+            .map(move |column| (column, row))
+    })
+}
+
+/// Font pixels from the start of `character` to the start of the next one
+fn advance(character: char) -> u32 {
+    match glyph(character).is_none() && wide_glyph(character).is_some() {
+        true => 2 * GLYPH_ADVANCE,
+        false => GLYPH_ADVANCE,
+    }
+}
+
+/// Rows of the 16x16 glyph of a character only the wide glyphs have
+fn wide_glyph(character: char) -> Option<[u16; 16]> {
+    WIDE_GLYPHS
+        .binary_search_by_key(&character, |&(wide, _)| wide)
+        .ok()
+        .map(|index| WIDE_GLYPHS[index].1)
+}
+
+/// Draw `text` in `color` into an RGBA buffer `width` pixels wide, its top-left corner at
+/// (`x`, `y`) and each font pixel `scale` wide. Whatever falls outside the buffer is
+/// clipped.
+pub fn draw_rgba(
+    buffer: &mut [u8],
+    width: usize,
+    text: &str,
+    (x, y): (i32, i32),
+    scale: u32,
+    color: [u8; 4],
+) {
+    let height = buffer.len() / 4 / width.max(1);
+    for square in text_pixels(text) {
+        let (left, top, square_width, square_height) = square.scaled(scale);
+        for dy in 0..square_height {
+            for dx in 0..square_width {
+                let px = x as i64 + (left + dx) as i64;
+                let py = y as i64 + (top + dy) as i64;
+                if px < 0 || py < 0 || px >= width as i64 || py >= height as i64 {
+                    continue;
+                }
+                let offset = (py as usize * width + px as usize) * 4;
+                buffer[offset..offset + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+/// Rows of a 3x5 glyph, most significant of the three bits on the left. Accented letters
+/// are drawn without their accents.
+fn glyph(character: char) -> Option<[u8; 5]> {
+    let rows = match strip_accent(character.to_uppercase().next()?) {
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b100, 0b100],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b101, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '*' => [0b101, 0b010, 0b111, 0b010, 0b101],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        '<' => [0b001, 0b010, 0b100, 0b010, 0b001],
+        '[' => [0b110, 0b100, 0b100, 0b100, 0b110],
+        ']' => [0b011, 0b001, 0b001, 0b001, 0b011],
+        '?' => [0b111, 0b001, 0b010, 0b000, 0b010],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b010, 0b100, 0b100, 0b100, 0b010],
+        ')' => [0b010, 0b001, 0b001, 0b001, 0b010],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+/// The unaccented letter for the accented capitals of Portuguese and French
+fn strip_accent(character: char) -> char {
+    match character {
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' => 'A',
+        'Ç' => 'C',
+        'È' | 'É' | 'Ê' | 'Ë' => 'E',
+        'Ì' | 'Í' | 'Î' | 'Ï' => 'I',
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' => 'O',
+        'Ù' | 'Ú' | 'Û' | 'Ü' => 'U',
+        _ => character,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_is_drawn_in_capitals_and_clipped() {
+        assert!(can_draw("Olá, mundo"));
+        assert!(can_draw("已暂停"));
+        assert!(!can_draw("日本"));
+        assert_eq!(text_width("hi", 2), 14);
+        assert_eq!(text_width("音a", 2), 22);
+        // Wide glyphs are drawn in finer squares after the narrow ones before them
+        let mut wide = text_pixels("a音").filter(|square| square.size == 1);
+        assert!(wide.all(|square| (8..24).contains(&square.x) && square.y < 16));
+        let square = Square {
+            x: 3,
+            y: 1,
+            size: 1,
+        };
+        assert_eq!(square.scaled(3), (4, 1, 2, 2));
+        assert_eq!(
+            text_pixels("i").collect::<Vec<_>>(),
+            text_pixels("I").collect::<Vec<_>>()
+        );
+
+        let (width, height) = (8, 6);
+        let mut buffer = vec![0; width * height * 4];
+        draw_rgba(&mut buffer, width, "-1", (-1, 0), 1, [255; 4]);
+        let lit = |x: usize, y: usize| buffer[(y * width + x) * 4] == 255;
+        // The minus lost its first column to the left edge
+        assert!(lit(0, 2) && lit(1, 2) && !lit(2, 2));
+        assert!(lit(4, 0) && lit(3, 1) && lit(4, 4));
+        assert_eq!(
+            buffer.iter().filter(|&&byte| byte == 255).count(),
+            (2 + 8) * 4
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Measuring how many instructions the machine runs per frame in loops of one kind of
+//! instruction, against numbers measured on real hardware. Reference numbers come from
+//! a JSON object mapping loop names to instructions per frame, e.g. `{"6XNN": 17.5}`.
+
+use crate::json::{self, Value};
+use crate::{Chip8, Chip8Error};
+
+/// Copies of the measured instruction in each loop, before the jump back
+const REPETITIONS: usize = 15;
+
+/// A ROM spending nearly all its time on one kind of instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalibrationLoop {
+    /// Pattern of the measured instruction, like `6XNN`
+    pub name: &'static str,
+    pub rom: Vec<u8>,
+}
+
+impl CalibrationLoop {
+    /// `setup` once per iteration, then the instruction [`REPETITIONS`] times and a jump
+    /// back to the start
+    fn new(name: &'static str, setup: &[u16], opcode: u16) -> Self {
+        let mut opcodes = setup.to_vec();
+        opcodes.extend([opcode; REPETITIONS]);
+        opcodes.push(0x1200);
+        Self {
+            name,
+            rom: opcodes
+                .iter()
+                .flat_map(|opcode| opcode.to_be_bytes())
+                .collect(),
+        }
+    }
+}
+
+/// The loops measured: common instructions, from the cheapest to clearing and drawing
+pub fn calibration_loops() -> Vec<CalibrationLoop> {
+    vec![
+        CalibrationLoop::new("6XNN", &[], 0x6000),
+        CalibrationLoop::new("7XNN", &[], 0x7001),
+        CalibrationLoop::new("8XY4", &[], 0x8014),
+        CalibrationLoop::new("3XNN", &[], 0x3001),
+        CalibrationLoop::new("ANNN", &[], 0xA300),
+        CalibrationLoop::new("FX65", &[0xA300], 0xF365),
+        CalibrationLoop::new("00E0", &[], 0x00E0),
+        // The 5 row font sprite for 0, at the top left
+        CalibrationLoop::new("DXYN", &[0xA050], 0xD005),
+    ]
+}
+
+/// Instructions per frame in one loop, and what the hardware ran
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub name: &'static str,
+    pub instructions_per_frame: f64,
+    pub reference: Option<f64>,
+}
+
+impl Measurement {
+    /// How far the measurement is from the reference, in percent of the reference
+    pub fn deviation(&self) -> Option<f64> {
+        self.reference
+            .filter(|&reference| reference > 0.0)
+            .map(|reference| (self.instructions_per_frame - reference) / reference * 100.0)
+    }
+
+    pub fn to_json(&self) -> Value {
+        let number = |value: Option<f64>| value.map_or(Value::Null, Value::Number);
+        Value::Object(vec![
+            ("name".to_string(), self.name.into()),
+            (
+                "instructions_per_frame".to_string(),
+                Value::Number(self.instructions_per_frame),
+            ),
+            ("reference".to_string(), number(self.reference)),
+            ("deviation_percent".to_string(), number(self.deviation())),
+        ])
+    }
+}
+
+/// Run `calibration` on a copy of `machine`, which has the platform and clock to measure,
+/// for `frames` frames
+pub fn measure(
+    machine: &Chip8,
+    calibration: &CalibrationLoop,
+    frames: u32,
+) -> Result<f64, Chip8Error> {
+    let mut chip8 = machine.clone();
+    chip8.load_rom(&calibration.rom);
+    let start = chip8.instruction_count();
+    for _ in 0..frames {
+        chip8.run_frame()?;
+    }
+    Ok((chip8.instruction_count() - start) as f64 / frames.max(1) as f64)
+}
+
+/// Measure every calibration loop on `machine`, each with its number from `reference`
+pub fn timing_report(
+    machine: &Chip8,
+    reference: &[(String, f64)],
+    frames: u32,
+) -> Result<Vec<Measurement>, Chip8Error> {
+    calibration_loops()
+        .iter()
+        .map(|calibration| {
+            Ok(Measurement {
+                name: calibration.name,
+                instructions_per_frame: measure(machine, calibration, frames)?,
+                reference: reference
+                    .iter()
+                    .find(|(name, _)| name == calibration.name)
+                    .map(|&(_, reference)| reference),
+            })
+        })
+        .collect()
+}
+
+/// Read reference numbers: a JSON object of instructions per frame by loop name
+pub fn parse_reference(text: &str) -> Result<Vec<(String, f64)>, String> {
+    let value = json::parse(text).map_err(|err| err.to_string())?;
+    let Value::Object(members) = value else {
+        return Err("the reference must be a JSON object".to_string());
+    };
+    members
+        .into_iter()
+        .map(|(name, value)| match value.as_f64() {
+            Some(number) if number >= 0.0 => Ok((name, number)),
+            _ => Err(format!("the reference for {} must be a number", name)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClockConfig;
+
+    #[test]
+    fn loops_are_measured_against_the_reference() {
+        let mut machine = Chip8::new();
+        machine.set_clock(ClockConfig {
+            instructions_per_frame: 12,
+            ..ClockConfig::default()
+        });
+        let reference = parse_reference(r#"{"6XNN": 16, "DXYN": 1.5}"#).unwrap();
+        let report = timing_report(&machine, &reference, 10).unwrap();
+        assert_eq!(report.len(), calibration_loops().len());
+        assert!(report
+            .iter()
+            .all(|measurement| measurement.instructions_per_frame == 12.0));
+        assert_eq!(report[0].deviation(), Some(-25.0));
+        assert_eq!(report[1].deviation(), None);
+        assert!(parse_reference(r#"{"6XNN": "fast"}"#).is_err());
+    }
+}
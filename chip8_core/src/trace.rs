@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::json::Value;
+use crate::Instruction;
+
+/// The registers an instruction can change besides the program counter and the stack
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Registers {
+    pub v: [u8; 16],
+    pub i: u16,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+}
+
+/// One of [`Registers`], named the way trace exports name it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    V(u8),
+    I,
+    DelayTimer,
+    SoundTimer,
+}
+
+impl Register {
+    /// Every register, in the order exports list them
+    pub fn all() -> impl Iterator<Item = Register> {
+        (0..16)
+            .map(Register::V)
+            .chain([Register::I, Register::DelayTimer, Register::SoundTimer])
+    }
+
+    pub fn read(&self, registers: &Registers) -> u16 {
+        match *self {
+            Register::V(x) => registers.v[x as usize] as u16,
+            Register::I => registers.i,
+            Register::DelayTimer => registers.delay_timer as u16,
+            Register::SoundTimer => registers.sound_timer as u16,
+        }
+    }
+
+    pub fn bits(&self) -> u32 {
+        match self {
+            Register::I => 16,
+            _ => 8,
+        }
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Register::V(x) => write!(f, "v{:x}", x),
+            Register::I => write!(f, "i"),
+            Register::DelayTimer => write!(f, "dt"),
+            Register::SoundTimer => write!(f, "st"),
+        }
+    }
+}
+
+/// An executed instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// Frames the machine had ended when the instruction ran, see [`crate::Chip8::frame_count`]
+    pub frame: u64,
+    pub pc: u16,
+    pub instruction: Instruction,
+    pub before: Registers,
+    pub after: Registers,
+}
+
+impl TraceEntry {
+    /// Registers the instruction changed, with their old and new values
+    pub fn changes(&self) -> impl Iterator<Item = (Register, u16, u16)> + '_ {
+        Register::all()
+            .map(|register| {
+                let (old, new) = (register.read(&self.before), register.read(&self.after));
+                (register, old, new)
+            })
+            .filter(|(_, old, new)| old != new)
+    }
+
+    /// The entry as a JSON object like
+    /// `{"pc":512,"opcode":24586,"mnemonic":"LD V0, 0x0A","changes":{"v0":[0,10]}}`
+    pub fn to_json(&self) -> Value {
+        let changes = self
+            .changes()
+            .map(|(register, old, new)| {
+                let values = Value::Array(vec![(old as u32).into(), (new as u32).into()]);
+                (register.to_string(), values)
+            })
+            .collect();
+        Value::Object(vec![
+            ("pc".to_string(), (self.pc as u32).into()),
+            (
+                "opcode".to_string(),
+                (self.instruction.opcode() as u32).into(),
+            ),
+            ("mnemonic".to_string(), self.instruction.to_string().into()),
+            ("changes".to_string(), Value::Object(changes)),
+        ])
+    }
+}
+
+/// Ring buffer remembering the most recently executed instructions
+#[derive(Debug, Clone)]
+pub struct Tracer {
+    entries: VecDeque<TraceEntry>,
+    capacity: usize,
+}
+
+impl Tracer {
+    /// Remember up to `capacity` instructions, taking memory for them only as they run, so
+    /// `usize::MAX` keeps everything until [`Tracer::drain`]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn record(&mut self, entry: TraceEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Recorded instructions, oldest first
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &TraceEntry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Take the recorded instructions out, oldest first, to export them as they come
+    pub fn drain(&mut self) -> impl Iterator<Item = TraceEntry> + '_ {
+        self.entries.drain(..)
+    }
+
+    /// The recorded instructions as JSON Lines, one [`TraceEntry::to_json`] object per
+    /// line, for diffing against other emulators' traces
+    pub fn to_json_lines(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{}\n", entry.to_json()))
+            .collect()
+    }
+
+    /// The recorded instructions as a Value Change Dump for waveform viewers like
+    /// GTKWave, one time step per instruction. Each step holds the program counter and
+    /// opcode of the instruction and the registers as it left them.
+    pub fn to_vcd(&self) -> String {
+        let mut vcd = VcdWriter::new(vec![]).unwrap();
+        for entry in &self.entries {
+            vcd.write(entry).unwrap();
+        }
+        String::from_utf8(vcd.into_inner()).unwrap()
+    }
+}
+
+/// Writes instructions as a Value Change Dump like [`Tracer::to_vcd`] as they come, for
+/// traces too long to hold in memory
+#[derive(Debug)]
+pub struct VcdWriter<W> {
+    out: W,
+    /// Time step of the next instruction
+    time: u64,
+    /// Signal values as of the last instruction, to only write the ones that changed
+    last: Option<Vec<u16>>,
+}
+
+impl<W: Write> VcdWriter<W> {
+    /// Start a dump in `out` by declaring its signals
+    pub fn new(mut out: W) -> io::Result<Self> {
+        let mut signals = vec![("pc".to_string(), 16), ("opcode".to_string(), 16)];
+        signals.extend(Register::all().map(|register| (register.to_string(), register.bits())));
+
+        out.write_all(b"$timescale 1 us $end\n$scope module chip8 $end\n")?;
+        for (index, (name, bits)) in signals.iter().enumerate() {
+            writeln!(out, "$var wire {} {} {} $end", bits, vcd_id(index), name)?;
+        }
+        out.write_all(b"$upscope $end\n$enddefinitions $end\n")?;
+        Ok(Self {
+            out,
+            time: 0,
+            last: None,
+        })
+    }
+
+    /// Add the next executed instruction as a time step
+    pub fn write(&mut self, entry: &TraceEntry) -> io::Result<()> {
+        let values = [entry.pc, entry.instruction.opcode()]
+            .into_iter()
+            .chain(Register::all().map(|register| register.read(&entry.after)))
+            .collect::<Vec<_>>();
+        writeln!(self.out, "#{}", self.time)?;
+        for (index, &value) in values.iter().enumerate() {
+            if self.last.as_ref().is_none_or(|last| last[index] != value) {
+                writeln!(self.out, "b{:b} {}", value, vcd_id(index))?;
+            }
+        }
+        self.time += 1;
+        self.last = Some(values);
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Short printable identifier of the `index`th VCD signal
+fn vcd_id(index: usize) -> char {
+    (b'!' + index as u8) as char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pc: u16, instruction: Instruction, before: Registers, after: Registers) -> TraceEntry {
+        TraceEntry {
+            frame: 0,
+            pc,
+            instruction,
+            before,
+            after,
+        }
+    }
+
+    #[test]
+    fn only_the_latest_entries_are_kept() {
+        let mut tracer = Tracer::new(2);
+        for pc in [0x200, 0x202, 0x204] {
+            let registers = Registers::default();
+            tracer.record(entry(pc, Instruction::ClearScreen, registers, registers));
+        }
+
+        let pcs = tracer.entries().map(|entry| entry.pc).collect::<Vec<_>>();
+        assert_eq!(pcs, [0x202, 0x204]);
+        assert_eq!(tracer.drain().count(), 2);
+        assert_eq!(tracer.entries().len(), 0);
+    }
+
+    #[test]
+    fn exports_list_the_registers_each_instruction_changed() {
+        let mut tracer = Tracer::new(2);
+        let start = Registers::default();
+        let mut loaded_v0 = start;
+        loaded_v0.v[0] = 0x0A;
+        let mut loaded_i = loaded_v0;
+        loaded_i.i = 0x300;
+        tracer.record(entry(
+            0x200,
+            Instruction::decode(0x600A).unwrap(),
+            start,
+            loaded_v0,
+        ));
+        tracer.record(entry(
+            0x202,
+            Instruction::decode(0xA300).unwrap(),
+            loaded_v0,
+            loaded_i,
+        ));
+
+        let lines = tracer.to_json_lines();
+        let lines = lines.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        let first = crate::json::parse(lines[0]).unwrap();
+        assert_eq!(first.get("pc").and_then(Value::as_f64), Some(512.0));
+        assert_eq!(
+            first.get("opcode").and_then(Value::as_f64),
+            Some(0x600A as f64)
+        );
+        assert_eq!(
+            first.get("changes").map(ToString::to_string).as_deref(),
+            Some(r#"{"v0":[0,10]}"#)
+        );
+
+        let vcd = tracer.to_vcd();
+        assert!(vcd.contains("$var wire 16 ! pc $end"));
+        assert!(vcd.ends_with("#1\nb1000000010 !\nb1010001100000000 \"\nb1100000000 3\n"));
+    }
+}
@@ -0,0 +1,285 @@
+//! Watch expressions like `v3 + v4` or `mem[i + 1]`, evaluated against the machine while
+//! stepping through a program.
+//!
+//! Operands are numbers (decimal, or hex with `0x`), the registers `v0`..`vf`, `i`, `pc`,
+//! `dt` and `st`, and memory bytes `mem[addr]`. Operators, loosest first, are `|`, `^`,
+//! `&`, `<<` `>>`, `+` `-` and `*` `/` `%`; parentheses group.
+
+use std::fmt;
+
+use crate::CpuState;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchExpression {
+    source: String,
+    expr: Expr,
+}
+
+/// Why an expression couldn't be parsed, with the byte offset of the problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for WatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for WatchError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    V(u8),
+    I,
+    Pc,
+    DelayTimer,
+    SoundTimer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Or,
+    Xor,
+    And,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Number(i64),
+    Operand(Operand),
+    Memory(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+/// Operators of each precedence level, loosest first
+const LEVELS: [&[(&str, Operator)]; 6] = [
+    &[("|", Operator::Or)],
+    &[("^", Operator::Xor)],
+    &[("&", Operator::And)],
+    &[("<<", Operator::ShiftLeft), (">>", Operator::ShiftRight)],
+    &[("+", Operator::Add), ("-", Operator::Sub)],
+    &[
+        ("*", Operator::Mul),
+        ("/", Operator::Div),
+        ("%", Operator::Rem),
+    ],
+];
+
+impl WatchExpression {
+    pub fn parse(source: &str) -> Result<Self, WatchError> {
+        let mut parser = Parser { source, offset: 0 };
+        let expr = parser.expression(0)?;
+        parser.skip_whitespace();
+        if parser.offset < source.len() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(Self {
+            source: source.trim().to_string(),
+            expr,
+        })
+    }
+
+    /// The value of the expression, or `None` when it divides by zero or reads outside
+    /// memory
+    pub fn evaluate(&self, state: &CpuState, memory: &[u8]) -> Option<i64> {
+        evaluate(&self.expr, state, memory)
+    }
+
+    /// One line like `v3 + v4 = 18 (0x12)`
+    pub fn describe(&self, state: &CpuState, memory: &[u8]) -> String {
+        match self.evaluate(state, memory) {
+            Some(value) => format!("{} = {} (0x{:X})", self.source, value, value),
+            None => format!("{} = ?", self.source),
+        }
+    }
+}
+
+impl fmt::Display for WatchExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn evaluate(expr: &Expr, state: &CpuState, memory: &[u8]) -> Option<i64> {
+    match expr {
+        Expr::Number(value) => Some(*value),
+        Expr::Operand(operand) => Some(match operand {
+            Operand::V(x) => state.v[*x as usize] as i64,
+            Operand::I => state.i as i64,
+            Operand::Pc => state.pc as i64,
+            Operand::DelayTimer => state.delay_timer as i64,
+            Operand::SoundTimer => state.sound_timer as i64,
+        }),
+        Expr::Memory(addr) => {
+            let addr = usize::try_from(evaluate(addr, state, memory)?).ok()?;
+            memory.get(addr).map(|&byte| byte as i64)
+        }
+        Expr::Binary(operator, left, right) => {
+            let left = evaluate(left, state, memory)?;
+            let right = evaluate(right, state, memory)?;
+            match operator {
+                Operator::Or => Some(left | right),
+                Operator::Xor => Some(left ^ right),
+                Operator::And => Some(left & right),
+                Operator::ShiftLeft => left.checked_shl(u32::try_from(right).ok()?),
+                Operator::ShiftRight => left.checked_shr(u32::try_from(right).ok()?),
+                Operator::Add => left.checked_add(right),
+                Operator::Sub => left.checked_sub(right),
+                Operator::Mul => left.checked_mul(right),
+                Operator::Div => left.checked_div(right),
+                Operator::Rem => left.checked_rem(right),
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> WatchError {
+        WatchError {
+            offset: self.offset,
+            message: message.to_string(),
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.source[self.offset..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume `token` if it comes next
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(token);
+        if found {
+            self.offset += token.len();
+        }
+        found
+    }
+
+    /// Operators of `level` and tighter
+    fn expression(&mut self, level: usize) -> Result<Expr, WatchError> {
+        let Some(operators) = LEVELS.get(level) else {
+            return self.primary();
+        };
+        let mut left = self.expression(level + 1)?;
+        'operators: loop {
+            for &(token, operator) in operators.iter() {
+                if self.eat(token) {
+                    let right = self.expression(level + 1)?;
+                    left = Expr::Binary(operator, Box::new(left), Box::new(right));
+                    continue 'operators;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, WatchError> {
+        if self.eat("(") {
+            let expr = self.expression(0)?;
+            return match self.eat(")") {
+                true => Ok(expr),
+                false => Err(self.error("expected ')'")),
+            };
+        }
+        self.skip_whitespace();
+        let start = self.offset;
+        let word_len = self
+            .rest()
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(self.rest().len());
+        if word_len == 0 {
+            return Err(self.error("expected a value"));
+        }
+        let word = self.rest()[..word_len].to_ascii_lowercase();
+        self.offset += word_len;
+
+        let number = match word.strip_prefix("0x") {
+            Some(digits) => i64::from_str_radix(digits, 16).ok(),
+            None => word.parse().ok(),
+        };
+        if let Some(number) = number {
+            return Ok(Expr::Number(number));
+        }
+        let operand = match word.as_str() {
+            "i" => Operand::I,
+            "pc" => Operand::Pc,
+            "dt" => Operand::DelayTimer,
+            "st" => Operand::SoundTimer,
+            "mem" => {
+                if !self.eat("[") {
+                    return Err(self.error("expected '[' after mem"));
+                }
+                let addr = self.expression(0)?;
+                if !self.eat("]") {
+                    return Err(self.error("expected ']'"));
+                }
+                return Ok(Expr::Memory(Box::new(addr)));
+            }
+            _ => match word.strip_prefix('v').map(|x| u8::from_str_radix(x, 16)) {
+                Some(Ok(x)) if word.len() == 2 => Operand::V(x),
+                _ => {
+                    self.offset = start;
+                    return Err(self.error(&format!("unknown name '{}'", word)));
+                }
+            },
+        };
+        Ok(Expr::Operand(operand))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expressions_read_registers_and_memory() {
+        let mut state = CpuState {
+            i: 0x300,
+            ..CpuState::default()
+        };
+        state.v[3] = 10;
+        state.v[4] = 8;
+        let mut memory = vec![0; 0x1000];
+        memory[0x301] = 0x42;
+
+        let value = |source| {
+            WatchExpression::parse(source)
+                .unwrap()
+                .evaluate(&state, &memory)
+        };
+        assert_eq!(value("v3 + v4"), Some(18));
+        assert_eq!(value("V3 + v4 * 2"), Some(26));
+        assert_eq!(value("(v3 + v4) * 2"), Some(36));
+        assert_eq!(value("mem[i + 1]"), Some(0x42));
+        assert_eq!(value("v4 >> 1 | 0x100"), Some(0x104));
+        assert_eq!(value("v3 / (v4 - 8)"), None);
+        assert_eq!(value("mem[0x1000]"), None);
+
+        let watch = WatchExpression::parse(" v3 - 1 ").unwrap();
+        assert_eq!(watch.describe(&state, &memory), "v3 - 1 = 9 (0x9)");
+
+        let error = WatchExpression::parse("v3 + vg").unwrap_err();
+        assert_eq!(error.offset, 5);
+        assert!(WatchExpression::parse("mem[i").is_err());
+    }
+}
@@ -0,0 +1,572 @@
+//! 16x16 glyphs for the Chinese of the frontends' translations, so menus and messages can
+//! be shown in it. They come from GNU Unifont 13.0.06, Copyright (C) 1998-2020 Roman
+//! Czyborra, Paul Hardy et al., used under the SIL Open Font License 1.1 found in
+//! `UNIFONT-OFL.txt` next to this crate's manifest.
+//!
+//! Only the characters translations use are here; add the rows of new ones from
+//! `unifont-*.hex` when translating more text.
+
+/// Characters, in order, with their rows from the top, the leftmost column in the most
+/// significant bit
+pub(crate) const WIDE_GLYPHS: &[(char, [u16; 16])] = &[
+    (
+        '。',
+        [
+            0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0C00, 0x1200, 0x1200,
+            0x0C00, 0x0000, 0x0000, 0x0000, 0x0000,
+        ],
+    ),
+    (
+        '上',
+        [
+            0x0200, 0x0200, 0x0200, 0x0200, 0x0200, 0x0200, 0x03F8, 0x0200, 0x0200, 0x0200, 0x0200,
+            0x0200, 0x0200, 0x0200, 0xFFFE, 0x0000,
+        ],
+    ),
+    (
+        '个',
+        [
+            0x0100, 0x0100, 0x0280, 0x0440, 0x0820, 0x1010, 0x2108, 0xC106, 0x0100, 0x0100, 0x0100,
+            0x0100, 0x0100, 0x0100, 0x0100, 0x0100,
+        ],
+    ),
+    (
+        '主',
+        [
+            0x0200, 0x0100, 0x0000, 0x7FFC, 0x0100, 0x0100, 0x0100, 0x0100, 0x3FF8, 0x0100, 0x0100,
+            0x0100, 0x0100, 0x0100, 0xFFFE, 0x0000,
+        ],
+    ),
+    (
+        '了',
+        [
+            0x0000, 0x7FF8, 0x0010, 0x0020, 0x0040, 0x0180, 0x0100, 0x0100, 0x0100, 0x0100, 0x0100,
+            0x0100, 0x0100, 0x0100, 0x0500, 0x0200,
+        ],
+    ),
+    (
+        '从',
+        [
+            0x0820, 0x0820, 0x0820, 0x0820, 0x0820, 0x0820, 0x0820, 0x0820, 0x0850, 0x1450, 0x1250,
+            0x1288, 0x2088, 0x2104, 0x4204, 0x8402,
+        ],
+    ),
+    (
+        '会',
+        [
+            0x0100, 0x0100, 0x0280, 0x0440, 0x0820, 0x3018, 0xCFE6, 0x0000, 0x0000, 0x7FFC, 0x0200,
+            0x0400, 0x0820, 0x1010, 0x3FF8, 0x1008,
+        ],
+    ),
+    (
+        '你',
+        [
+            0x0880, 0x0880, 0x0880, 0x11FE, 0x1102, 0x3204, 0x3420, 0x5020, 0x9128, 0x1124, 0x1224,
+            0x1222, 0x1422, 0x1020, 0x10A0, 0x1040,
+        ],
+    ),
+    (
+        '保',
+        [
+            0x0800, 0x0BF8, 0x0A08, 0x1208, 0x1208, 0x33F8, 0x3040, 0x5040, 0x97FC, 0x10E0, 0x1150,
+            0x1248, 0x1444, 0x1842, 0x1040, 0x1040,
+        ],
+    ),
+    (
+        '停',
+        [
+            0x1080, 0x1040, 0x17FC, 0x2000, 0x23F8, 0x6208, 0x63F8, 0xA000, 0x2FFE, 0x2802, 0x23F8,
+            0x2040, 0x2040, 0x2040, 0x2140, 0x2080,
+        ],
+    ),
+    (
+        '入',
+        [
+            0x0400, 0x0200, 0x0100, 0x0100, 0x0100, 0x0280, 0x0280, 0x0280, 0x0440, 0x0440, 0x0820,
+            0x0820, 0x1010, 0x2010, 0x4008, 0x8006,
+        ],
+    ),
+    (
+        '减',
+        [
+            0x0014, 0x4012, 0x2010, 0x27FE, 0x0410, 0x0410, 0x15D0, 0x1412, 0x2412, 0xE5D4, 0x2554,
+            0x2548, 0x25DA, 0x242A, 0x2846, 0x1082,
+        ],
+    ),
+    (
+        '出',
+        [
+            0x0100, 0x0100, 0x2108, 0x2108, 0x2108, 0x2108, 0x3FF8, 0x0108, 0x0100, 0x0100, 0x4104,
+            0x4104, 0x4104, 0x4104, 0x7FFC, 0x0004,
+        ],
+    ),
+    (
+        '到',
+        [
+            0x0004, 0xFF84, 0x0804, 0x1024, 0x2224, 0x4124, 0xFFA4, 0x08A4, 0x0824, 0x0824, 0x7F24,
+            0x0824, 0x0804, 0x0F84, 0xF814, 0x4008,
+        ],
+    ),
+    (
+        '加',
+        [
+            0x1000, 0x1000, 0x1000, 0x107C, 0xFE44, 0x1244, 0x1244, 0x1244, 0x1244, 0x1244, 0x1244,
+            0x1244, 0x2244, 0x227C, 0x4A44, 0x8400,
+        ],
+    ),
+    (
+        '单',
+        [
+            0x1010, 0x0820, 0x0440, 0x3FF8, 0x2108, 0x2108, 0x3FF8, 0x2108, 0x2108, 0x3FF8, 0x0100,
+            0x0100, 0xFFFE, 0x0100, 0x0100, 0x0100,
+        ],
+    ),
+    (
+        '取',
+        [
+            0x0000, 0xFF80, 0x22FC, 0x2244, 0x3E44, 0x2244, 0x2244, 0x3E44, 0x2228, 0x2228, 0x27A8,
+            0xFA10, 0x4210, 0x0228, 0x0244, 0x0282,
+        ],
+    ),
+    (
+        '后',
+        [
+            0x0010, 0x00F8, 0x1F00, 0x1000, 0x1000, 0x1FFE, 0x1000, 0x1000, 0x1000, 0x17F8, 0x1408,
+            0x2408, 0x2408, 0x4408, 0x87F8, 0x0408,
+        ],
+    ),
+    (
+        '告',
+        [
+            0x0100, 0x1100, 0x1100, 0x1FF8, 0x2100, 0x4100, 0x0100, 0xFFFE, 0x0000, 0x0000, 0x1FF0,
+            0x1010, 0x1010, 0x1010, 0x1FF0, 0x1010,
+        ],
+    ),
+    (
+        '图',
+        [
+            0x0000, 0x7FFC, 0x4204, 0x4204, 0x47E4, 0x4C44, 0x5284, 0x4104, 0x46C4, 0x783C, 0x4304,
+            0x4084, 0x4604, 0x4184, 0x7FFC, 0x4004,
+        ],
+    ),
+    (
+        '在',
+        [
+            0x0200, 0x0200, 0x0400, 0xFFFE, 0x0800, 0x0840, 0x1040, 0x3040, 0x57FC, 0x9040, 0x1040,
+            0x1040, 0x1040, 0x1040, 0x1FFE, 0x1000,
+        ],
+    ),
+    (
+        '地',
+        [
+            0x1020, 0x1020, 0x1020, 0x1120, 0x112C, 0xFD34, 0x1164, 0x13A4, 0x1124, 0x1134, 0x1128,
+            0x1D22, 0xE122, 0x4102, 0x00FE, 0x0000,
+        ],
+    ),
+    (
+        '复',
+        [
+            0x1000, 0x1000, 0x1FFC, 0x2000, 0x5FF0, 0x9010, 0x1FF0, 0x1010, 0x1FF0, 0x0400, 0x0FF0,
+            0x1820, 0x6440, 0x0380, 0x1C70, 0xE00E,
+        ],
+    ),
+    (
+        '外',
+        [
+            0x1040, 0x1040, 0x1040, 0x1040, 0x3E40, 0x2260, 0x4250, 0x4248, 0xA444, 0x1444, 0x0840,
+            0x0840, 0x1040, 0x2040, 0x4040, 0x8040,
+        ],
+    ),
+    (
+        '始',
+        [
+            0x1020, 0x1020, 0x1020, 0x1040, 0xFC48, 0x2484, 0x25FE, 0x2482, 0x2400, 0x48FC, 0x2884,
+            0x1084, 0x2884, 0x4484, 0x80FC, 0x0084,
+        ],
+    ),
+    (
+        '存',
+        [
+            0x0400, 0x0400, 0xFFFE, 0x0800, 0x0800, 0x13F8, 0x1010, 0x3020, 0x5040, 0x97FE, 0x1040,
+            0x1040, 0x1040, 0x1040, 0x1140, 0x1080,
+        ],
+    ),
+    (
+        '学',
+        [
+            0x2208, 0x1108, 0x1110, 0x0020, 0x7FFE, 0x4002, 0x8004, 0x1FE0, 0x0040, 0x0180, 0xFFFE,
+            0x0100, 0x0100, 0x0100, 0x0500, 0x0200,
+        ],
+    ),
+    (
+        '对',
+        [
+            0x0010, 0x0010, 0x0010, 0x7E10, 0x02FE, 0x0210, 0x2410, 0x1490, 0x0850, 0x0850, 0x1410,
+            0x1210, 0x2210, 0x4010, 0x0050, 0x0020,
+        ],
+    ),
+    (
+        '导',
+        [
+            0x0000, 0x3FF0, 0x2010, 0x2010, 0x3FF0, 0x2004, 0x2004, 0x1FFC, 0x0020, 0x0020, 0xFFFE,
+            0x0820, 0x0420, 0x0420, 0x00A0, 0x0040,
+        ],
+    ),
+    (
+        '少',
+        [
+            0x0100, 0x0100, 0x0100, 0x0920, 0x0910, 0x1108, 0x1104, 0x2114, 0x4110, 0x0120, 0x0040,
+            0x0080, 0x0100, 0x0200, 0x0C00, 0x7000,
+        ],
+    ),
+    (
+        '已',
+        [
+            0x0000, 0x3FF0, 0x0010, 0x0010, 0x0010, 0x2010, 0x2010, 0x3FF0, 0x2000, 0x2000, 0x2000,
+            0x2004, 0x2004, 0x2004, 0x1FFC, 0x0000,
+        ],
+    ),
+    (
+        '度',
+        [
+            0x0100, 0x0080, 0x3FFE, 0x2220, 0x2220, 0x3FFC, 0x2220, 0x2220, 0x23E0, 0x2000, 0x2FF0,
+            0x2410, 0x4220, 0x41C0, 0x8630, 0x380E,
+        ],
+    ),
+    (
+        '开',
+        [
+            0x0000, 0x7FFC, 0x0820, 0x0820, 0x0820, 0x0820, 0x0820, 0xFFFE, 0x0820, 0x0820, 0x0820,
+            0x0820, 0x1020, 0x1020, 0x2020, 0x4020,
+        ],
+    ),
+    (
+        '式',
+        [
+            0x0048, 0x0044, 0x0044, 0x0040, 0xFFFE, 0x0040, 0x0040, 0x3E40, 0x0840, 0x0840, 0x0820,
+            0x0822, 0x0F12, 0x780A, 0x2006, 0x0002,
+        ],
+    ),
+    (
+        '态',
+        [
+            0x0100, 0x0100, 0x7FFC, 0x0100, 0x0280, 0x0440, 0x0A20, 0x3118, 0xC006, 0x0100, 0x0888,
+            0x4884, 0x4812, 0x4812, 0x87F0, 0x0000,
+        ],
+    ),
+    (
+        '恢',
+        [
+            0x1040, 0x1040, 0x1040, 0x13FE, 0x1880, 0x5490, 0x5090, 0x5112, 0x9152, 0x1154, 0x1290,
+            0x1228, 0x1428, 0x1044, 0x1084, 0x1102,
+        ],
+    ),
+    (
+        '意',
+        [
+            0x0100, 0x3FF8, 0x0820, 0x0440, 0xFFFE, 0x0000, 0x1FF0, 0x1010, 0x1FF0, 0x1010, 0x1FF0,
+            0x0200, 0x5104, 0x5112, 0x9012, 0x0FF0,
+        ],
+    ),
+    (
+        '截',
+        [
+            0x0820, 0x0828, 0x7F24, 0x0820, 0xFFFE, 0x1420, 0x2224, 0x7FA4, 0xA424, 0x3F28, 0x2428,
+            0x3F10, 0x2412, 0x3FAA, 0x2046, 0x2082,
+        ],
+    ),
+    (
+        '报',
+        [
+            0x1000, 0x11FC, 0x1104, 0x1104, 0xFD14, 0x1108, 0x1100, 0x15FC, 0x1944, 0x3144, 0xD128,
+            0x1128, 0x1110, 0x1128, 0x5144, 0x2182,
+        ],
+    ),
+    (
+        '择',
+        [
+            0x2000, 0x27F8, 0x2208, 0x2110, 0xF0A0, 0x2040, 0x21B0, 0x264E, 0x3040, 0xE3F8, 0x2040,
+            0x2040, 0x27FC, 0x2040, 0xA040, 0x4040,
+        ],
+    ),
+    (
+        '按',
+        [
+            0x1040, 0x1020, 0x1020, 0x13FE, 0xFA02, 0x1444, 0x1040, 0x1BFE, 0x3088, 0xD088, 0x1108,
+            0x10D0, 0x1020, 0x1050, 0x5088, 0x2304,
+        ],
+    ),
+    (
+        '提',
+        [
+            0x1000, 0x11FC, 0x1104, 0x1104, 0xFDFC, 0x1104, 0x1104, 0x15FC, 0x1800, 0x33FE, 0xD020,
+            0x1120, 0x113C, 0x1120, 0x52A0, 0x247E,
+        ],
+    ),
+    (
+        '撞',
+        [
+            0x1020, 0x11FC, 0x1088, 0x1050, 0xFDFE, 0x1000, 0x11FC, 0x1524, 0x19FC, 0x3124, 0xD1FC,
+            0x1020, 0x11FC, 0x1020, 0x53FE, 0x2000,
+        ],
+    ),
+    (
+        '教',
+        [
+            0x0820, 0x0820, 0x7EA0, 0x093E, 0x0A44, 0xFF44, 0x0844, 0x7EA4, 0x2428, 0x4828, 0x8F10,
+            0x7810, 0x0828, 0x0848, 0x2884, 0x1102,
+        ],
+    ),
+    (
+        '新',
+        [
+            0x1000, 0x0804, 0x7F78, 0x0040, 0x2240, 0x1440, 0xFF7E, 0x0848, 0x0848, 0x7F48, 0x0848,
+            0x2A48, 0x4948, 0x8888, 0x2888, 0x1108,
+        ],
+    ),
+    (
+        '方',
+        [
+            0x0200, 0x0100, 0x0100, 0xFFFE, 0x0400, 0x0400, 0x0400, 0x07F0, 0x0410, 0x0410, 0x0410,
+            0x0810, 0x0810, 0x1010, 0x20A0, 0x4040,
+        ],
+    ),
+    (
+        '无',
+        [
+            0x0000, 0x3FF0, 0x0200, 0x0200, 0x0200, 0x0200, 0x7FFC, 0x0480, 0x0480, 0x0480, 0x0880,
+            0x0880, 0x1084, 0x2084, 0x407C, 0x8000,
+        ],
+    ),
+    (
+        '暂',
+        [
+            0x100C, 0xFEF0, 0x2080, 0x4880, 0x7EFE, 0x0888, 0x0E88, 0xF908, 0x4A08, 0x1FF8, 0x1010,
+            0x1010, 0x1FF0, 0x1010, 0x1010, 0x1FF0,
+        ],
+    ),
+    (
+        '束',
+        [
+            0x0100, 0x0100, 0xFFFE, 0x0100, 0x0100, 0x3FF8, 0x2108, 0x2108, 0x2108, 0x3FF8, 0x2388,
+            0x0540, 0x0920, 0x3118, 0xC106, 0x0100,
+        ],
+    ),
+    (
+        '模',
+        [
+            0x1110, 0x1110, 0x17FC, 0x1110, 0xFC00, 0x13F8, 0x3208, 0x3BF8, 0x5608, 0x53F8, 0x9040,
+            0x17FC, 0x10A0, 0x1110, 0x1208, 0x1406,
+        ],
+    ),
+    (
+        '次',
+        [
+            0x0080, 0x4080, 0x2080, 0x20FC, 0x0104, 0x0908, 0x0A40, 0x1440, 0x1040, 0xE0A0, 0x20A0,
+            0x2110, 0x2110, 0x2208, 0x2404, 0x0802,
+        ],
+    ),
+    (
+        '正',
+        [
+            0x0000, 0x7FFC, 0x0100, 0x0100, 0x0100, 0x0100, 0x1100, 0x11F8, 0x1100, 0x1100, 0x1100,
+            0x1100, 0x1100, 0x1100, 0xFFFE, 0x0000,
+        ],
+    ),
+    (
+        '步',
+        [
+            0x0100, 0x0100, 0x11F8, 0x1100, 0x1100, 0x1100, 0xFFFE, 0x0100, 0x0100, 0x1108, 0x1110,
+            0x2120, 0x40C0, 0x0300, 0x1C00, 0xE000,
+        ],
+    ),
+    (
+        '法',
+        [
+            0x0040, 0x2040, 0x1040, 0x1040, 0x87FC, 0x4040, 0x4040, 0x1040, 0x1FFE, 0x2040, 0xE080,
+            0x2100, 0x2210, 0x2408, 0x2FFC, 0x0404,
+        ],
+    ),
+    (
+        '烁',
+        [
+            0x1008, 0x101C, 0x11E0, 0x1100, 0x5520, 0x5920, 0x5120, 0x91FE, 0x1020, 0x1020, 0x10A8,
+            0x28A4, 0x2522, 0x4622, 0x40A0, 0x8040,
+        ],
+    ),
+    (
+        '状',
+        [
+            0x0840, 0x0848, 0x0844, 0x4844, 0x2840, 0x2FFE, 0x0840, 0x0840, 0x1840, 0x28A0, 0xC8A0,
+            0x0890, 0x0910, 0x0908, 0x0A04, 0x0C02,
+        ],
+    ),
+    (
+        '的',
+        [
+            0x1040, 0x1040, 0x2040, 0x7E7C, 0x4284, 0x4284, 0x4304, 0x4244, 0x7E24, 0x4224, 0x4204,
+            0x4204, 0x4204, 0x7E04, 0x4228, 0x0010,
+        ],
+    ),
+    (
+        '碰',
+        [
+            0x0108, 0x0090, 0xF800, 0x23FC, 0x2090, 0x4090, 0x7892, 0x4C92, 0xCA94, 0x4A98, 0x4890,
+            0x4890, 0x7890, 0x4890, 0x07FE, 0x0000,
+        ],
+    ),
+    (
+        '示',
+        [
+            0x0000, 0x3FF8, 0x0000, 0x0000, 0x0000, 0x0000, 0xFFFE, 0x0100, 0x0100, 0x1110, 0x1108,
+            0x2104, 0x4102, 0x8102, 0x0500, 0x0200,
+        ],
+    ),
+    (
+        '离',
+        [
+            0x0200, 0x0100, 0xFFFE, 0x0000, 0x1450, 0x1390, 0x1450, 0x1FF0, 0x0100, 0x7FFC, 0x4204,
+            0x4444, 0x4FE4, 0x4424, 0x4014, 0x4008,
+        ],
+    ),
+    (
+        '结',
+        [
+            0x1020, 0x1020, 0x2020, 0x27FE, 0x4420, 0xF820, 0x11FC, 0x2000, 0x4000, 0xFDFC, 0x4104,
+            0x0104, 0x1D04, 0xE104, 0x41FC, 0x0104,
+        ],
+    ),
+    (
+        '继',
+        [
+            0x1020, 0x1220, 0x2220, 0x2324, 0x4AA8, 0xF220, 0x13FC, 0x2220, 0x4270, 0xFAA8, 0x4324,
+            0x0220, 0x1A20, 0xE220, 0x43FE, 0x0000,
+        ],
+    ),
+    (
+        '续',
+        [
+            0x1020, 0x1020, 0x21FC, 0x2420, 0x4420, 0xFBFE, 0x1002, 0x2094, 0x4050, 0xFD10, 0x4090,
+            0x03FE, 0x1C28, 0xE044, 0x4082, 0x0302,
+        ],
+    ),
+    (
+        '设',
+        [
+            0x0000, 0x21F0, 0x1110, 0x1110, 0x0110, 0x020E, 0xF400, 0x13F8, 0x1108, 0x1110, 0x1090,
+            0x14A0, 0x1840, 0x10A0, 0x0318, 0x0C06,
+        ],
+    ),
+    (
+        '话',
+        [
+            0x0008, 0x203C, 0x11E0, 0x1020, 0x0020, 0x03FE, 0xF020, 0x1020, 0x1020, 0x11FC, 0x1104,
+            0x1104, 0x1504, 0x1904, 0x11FC, 0x0104,
+        ],
+    ),
+    (
+        '读',
+        [
+            0x0020, 0x2020, 0x11FC, 0x1020, 0x0020, 0x03FE, 0xF002, 0x1094, 0x1050, 0x1110, 0x1090,
+            0x13FE, 0x1428, 0x1844, 0x1082, 0x0302,
+        ],
+    ),
+    (
+        '轮',
+        [
+            0x2040, 0x2040, 0x20A0, 0xFCA0, 0x4110, 0x5208, 0x9406, 0xFD10, 0x1120, 0x1140, 0x1D80,
+            0xF100, 0x5104, 0x1104, 0x10FC, 0x1000,
+        ],
+    ),
+    (
+        '载',
+        [
+            0x0820, 0x0828, 0x7F24, 0x0820, 0xFFFE, 0x1020, 0x1024, 0xFF24, 0x2024, 0x4828, 0x7F28,
+            0x0810, 0x0F12, 0xF82A, 0x0846, 0x0882,
+        ],
+    ),
+    (
+        '输',
+        [
+            0x2040, 0x20A0, 0x2110, 0xFA08, 0x25F6, 0x4000, 0x53C4, 0x9254, 0xFA54, 0x13D4, 0x1A54,
+            0xF254, 0x53D4, 0x1244, 0x1254, 0x12C8,
+        ],
+    ),
+    (
+        '退',
+        [
+            0x03F8, 0x2208, 0x1208, 0x13F8, 0x0208, 0x0208, 0xF3F8, 0x1244, 0x1228, 0x1210, 0x1288,
+            0x1304, 0x1204, 0x2800, 0x47FE, 0x0000,
+        ],
+    ),
+    (
+        '选',
+        [
+            0x0040, 0x2240, 0x1240, 0x13F8, 0x0440, 0x0040, 0xF7FC, 0x1120, 0x1120, 0x1120, 0x1224,
+            0x1224, 0x141C, 0x2800, 0x47FE, 0x0000,
+        ],
+    ),
+    (
+        '速',
+        [
+            0x0040, 0x2040, 0x17FC, 0x1040, 0x03F8, 0x0248, 0xF248, 0x13F8, 0x10E0, 0x1150, 0x1248,
+            0x1444, 0x1040, 0x2800, 0x47FE, 0x0000,
+        ],
+    ),
+    (
+        '重',
+        [
+            0x0010, 0x00F8, 0x3F00, 0x0100, 0xFFFE, 0x0100, 0x1FF0, 0x1110, 0x1FF0, 0x1110, 0x1FF0,
+            0x0100, 0x3FF8, 0x0100, 0xFFFE, 0x0000,
+        ],
+    ),
+    (
+        '键',
+        [
+            0x2010, 0x2010, 0x3B7C, 0x2114, 0x41FE, 0x7A14, 0xA27C, 0x2710, 0xF97C, 0x2510, 0x25FE,
+            0x2210, 0x2A10, 0x3500, 0x28FE, 0x0000,
+        ],
+    ),
+    (
+        '闪',
+        [
+            0x2000, 0x17FC, 0x0004, 0x4004, 0x4104, 0x4104, 0x4104, 0x4104, 0x4284, 0x4244, 0x4424,
+            0x4824, 0x4004, 0x4004, 0x4014, 0x4008,
+        ],
+    ),
+    (
+        '音',
+        [
+            0x0200, 0x0100, 0x3FF8, 0x0000, 0x0820, 0x0440, 0xFFFE, 0x0000, 0x1FF0, 0x1010, 0x1010,
+            0x1FF0, 0x1010, 0x1010, 0x1FF0, 0x1010,
+        ],
+    ),
+    (
+        '预',
+        [
+            0x0000, 0xF9FE, 0x0820, 0x5040, 0x21FC, 0x1104, 0xFD24, 0x2524, 0x2924, 0x2124, 0x2124,
+            0x2144, 0x2050, 0x2088, 0xA104, 0x4202,
+        ],
+    ),
+    (
+        '题',
+        [
+            0x0000, 0x3EFE, 0x2210, 0x3E20, 0x227C, 0x3E44, 0x0054, 0xFF54, 0x0854, 0x0854, 0x2F28,
+            0x2844, 0x2882, 0x5800, 0x4FFE, 0x8000,
+        ],
+    ),
+    (
+        '，',
+        [
+            0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000,
+            0x0000, 0x03C0, 0x00C0, 0x00C0, 0x0300,
+        ],
+    ),
+    (
+        '：',
+        [
+            0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x03C0, 0x03C0, 0x0000, 0x0000, 0x0000,
+            0x03C0, 0x03C0, 0x0000, 0x0000, 0x0000,
+        ],
+    ),
+];
@@ -0,0 +1,356 @@
+use std::ops::Range;
+use std::path::PathBuf;
+
+use chip8_core::input_queue::KeyEncoding;
+use chip8_core::watch::WatchExpression;
+use chip8_core::{
+    BlendMode, DisplayBuffering, MachineRoutine, Platform, Preset, RamPattern, RandomSource,
+    TimerOrder, WriteProtection, RAM_SIZE,
+};
+use clap::{Parser, ValueEnum};
+
+use crate::i18n::Lang;
+
+/// CHIP-8 emulator
+#[derive(Parser)]
+pub struct Args {
+    /// ROM to run
+    #[arg(required_unless_present = "dump_caps")]
+    pub rom: Option<PathBuf>,
+    /// Print what the emulator core supports (platforms, instructions, quirks, display
+    /// modes, presets and feature versions) as JSON and exit
+    #[arg(long)]
+    pub dump_caps: bool,
+    /// IPS or BPS patch to apply to the ROM before running it
+    #[arg(long)]
+    pub patch: Option<PathBuf>,
+    /// Octo options JSON (or cartridge) to take quirks and speed from, for ROMs built with Octo
+    #[arg(long)]
+    pub octo_options: Option<PathBuf>,
+    /// Symbol file with the ROM's labels and source lines, to show them in the tutor and in
+    /// crash reports. The format is the emulator's own, with lines like `label NAME ADDR`
+    /// and `line ADDR FILE:LINE`.
+    #[arg(long)]
+    pub symbols: Option<PathBuf>,
+    /// Platform whose quirks to emulate (cosmac-vip, chip-48, super-chip, chip-8x);
+    /// detected from the ROM when omitted
+    #[arg(long)]
+    pub platform: Option<Platform>,
+    /// Speed and error handling in one go: strict runs at COSMAC VIP speed and stops on
+    /// writes over the program, compatible suits most ROMs, fast runs demanding games
+    #[arg(long)]
+    pub preset: Option<Preset>,
+    /// Memory range saved to disk on exit and restored on the next run of the same ROM,
+    /// like a battery-backed cartridge (e.g. 0xE00..0xF00)
+    #[arg(long, value_parser = parse_address_range)]
+    pub battery_ram: Option<Range<usize>>,
+    /// Load a data file at ADDR next to the ROM, e.g. `levels.bin@0x800`, for homebrew
+    /// distributed as code plus data; may be repeated. The files mustn't overlap each
+    /// other or the ROM.
+    #[arg(long, value_name = "FILE@ADDR", value_parser = parse_overlay)]
+    pub overlay: Vec<(PathBuf, usize)>,
+    /// Bytes of memory the ROM and its overlays must fit in, like 2048 for a 2K COSMAC VIP
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = RAM_SIZE,
+        value_parser = parse_ram_size
+    )]
+    pub ram_size: usize,
+    /// Timer decrements per second; 50 reproduces the pacing of PAL machines
+    #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u32).range(1..=1000))]
+    pub timer_hz: u32,
+    /// Whether each frame ticks the timers after its instructions, like the VIP, or before
+    #[arg(long, default_value_t = TimerOrder::AfterInstructions)]
+    pub timer_order: TimerOrder,
+    /// When drawing shows: immediate; frame, at the end of each frame; or sync, when the
+    /// game sets its delay timer, so sprites it erases and redraws over several frames
+    /// never go missing
+    #[arg(long, default_value_t = DisplayBuffering::Immediate, value_name = "WHEN")]
+    pub display_buffering: DisplayBuffering,
+    /// Catch writes below 0x200 or over the ROM's own code, and execution at odd addresses
+    /// or below 0x200: off, warn or strict (stop with an error); off unless the preset says
+    /// otherwise
+    #[arg(long)]
+    pub write_protection: Option<WriteProtection>,
+    /// Remember that this ROM modifies its own code, so write protection skips it on this and
+    /// later runs
+    #[arg(long)]
+    pub self_modifying: bool,
+    /// What memory and the V registers hold at power-on: zero, ff, random or random:SEED;
+    /// with strict write protection, reads of memory nothing wrote are reported too
+    #[arg(long, default_value_t = RamPattern::Zero, value_name = "PATTERN")]
+    pub ram_init: RamPattern,
+    /// Where CXNN's random numbers come from: system, xorshift:SEED for the same numbers on
+    /// every run, or lfsr:SEED for a simple shift register's less random ones
+    #[arg(long, default_value_t = RandomSource::System, value_name = "SOURCE")]
+    pub random: RandomSource,
+    /// Let homebrew larger than memory switch 2K banks into 0x800-0xFFF with FXB0, an
+    /// extension no other interpreter has
+    #[arg(long)]
+    pub banking: bool,
+    /// Set VF when a sprite is clipped at the bottom of the screen, like SUPER-CHIP 1.1's
+    /// high resolution mode, for collision tests telling interpreters apart
+    #[arg(long)]
+    pub clipped_rows_collide: bool,
+    /// Stand in for the machine-language routine at ADDR, which a 0NNN calls, with ignore,
+    /// clear (the screen) or halt, e.g. `0x2F0=clear`; may be repeated. Calls to other
+    /// routines stop the game with an error.
+    #[arg(long, value_name = "ADDR=ROUTINE", value_parser = MachineRoutine::parse_mapping)]
+    pub machine_routine: Vec<(u16, MachineRoutine)>,
+    /// Animate SUPER-CHIP scrolling over a few frames instead of jumping
+    #[arg(long)]
+    pub smooth_scroll: bool,
+    /// Experimental: slide sprites that moved a few pixels from where they were to where
+    /// they are at the display's refresh rate, for games moving them every few frames;
+    /// changes how games look and feel
+    #[arg(long)]
+    pub smooth_motion: bool,
+    /// Blend this many frames together, up to 16, to hide the flicker of sprites redrawn
+    /// every frame
+    #[arg(
+        long,
+        default_value_t = 1,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=16)
+    )]
+    pub blend_frames: usize,
+    /// How to blend frames: or lights pixels lit in any of them, average fades them
+    #[arg(long, default_value = "average")]
+    pub blend_mode: BlendMode,
+    /// While the program waits for a key, name the keys it seems to expect
+    #[arg(long)]
+    pub key_hints: bool,
+    /// Read keys like the VIP's hex keypad, where holding three corners of a rectangle of
+    /// keys also presses the fourth, to test ROMs meant for real hardware
+    #[arg(long)]
+    pub hardware_keypad: bool,
+    /// What to do while the window doesn't have focus. The sound is muted either way.
+    #[arg(long, value_enum, default_value_t = FocusLoss::Pause)]
+    pub focus_loss: FocusLoss,
+    /// SDL audio driver to use (e.g. pulseaudio, alsa, directsound)
+    #[arg(long)]
+    pub audio_driver: Option<String>,
+    /// Audio output device to use instead of the system default
+    #[arg(long)]
+    pub audio_device: Option<String>,
+    /// Audio buffer size in samples, a power of two; lower it if beeps lag,
+    /// raise it if they crackle
+    #[arg(long, value_parser = parse_buffer_size, default_value_t = 512)]
+    pub audio_buffer: u16,
+    /// Whether the beep keeps its pitch when the emulation runs faster or slower than real
+    /// time, or rises and falls with the speed
+    #[arg(long, value_enum, default_value_t = BeepPitch::Constant)]
+    pub beep_pitch: BeepPitch,
+    /// Shortest a beep lasts in milliseconds, so games beeping for a single tick like Pong
+    /// are heard; 0 plays beeps exactly as long as the sound timer runs
+    #[arg(long, value_name = "MS", default_value_t = 50)]
+    pub min_beep: u64,
+    /// How long beeps take to fade in, in milliseconds, so they start without a click
+    #[arg(long, value_name = "MS", default_value_t = 2)]
+    pub beep_attack: u64,
+    /// How long beeps take to fade out, in milliseconds, so they stop without a pop
+    #[arg(long, value_name = "MS", default_value_t = 5)]
+    pub beep_release: u64,
+    /// Rumble game controllers at this percent of their strength when a beep starts
+    #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub rumble: Option<u8>,
+    /// How long each rumble pulse lasts in milliseconds
+    #[arg(long, value_name = "MS", default_value_t = 80)]
+    pub rumble_duration: u32,
+    /// Language of on-screen messages (en, pt-br, zh-cn); taken from the system locale
+    /// when omitted
+    #[arg(long)]
+    pub lang: Option<Lang>,
+    /// Accept commands on this localhost TCP port, or Unix socket path, one per line:
+    /// pause, resume, load-rom PATH, save-state SLOT, load-state SLOT, screenshot PATH,
+    /// set-speed FACTOR, watch EXPRESSION, unwatch, search start|changed|unchanged|
+    /// increased|decreased|VALUE|pin
+    #[arg(long, value_name = "PORT|PATH")]
+    pub control: Option<String>,
+    /// Play taking turns with someone joining on this TCP port: wait for them, then play
+    /// while they watch until they take control (Insert)
+    #[arg(long, value_name = "PORT", conflicts_with = "join_remote")]
+    pub host_remote: Option<u16>,
+    /// Join the remote play hosted at HOST:PORT, watching until taking control (Insert)
+    #[arg(long, value_name = "HOST:PORT")]
+    pub join_remote: Option<String>,
+    /// Expression like `v3 + v4` or `mem[i]` to print whenever its value changes and after
+    /// every tutor step; may be repeated
+    #[arg(long, value_name = "EXPRESSION", value_parser = parse_watch)]
+    pub watch_expr: Vec<WatchExpression>,
+    /// Record every emulated frame to a Y4M video at 60 fps, e.g. for
+    /// `ffmpeg -i out.y4m out.mp4`
+    #[arg(long, value_name = "PATH")]
+    pub record_video: Option<PathBuf>,
+    /// Mirror the screen on hardware like an LED matrix through this serial port, e.g.
+    /// /dev/ttyUSB0, set up beforehand with stty; see chip8_core::matrix_protocol for the
+    /// framing
+    #[arg(long, value_name = "PORT")]
+    pub serial: Option<PathBuf>,
+    /// Save the machine's state this often and on exit, so the next run of the ROM can
+    /// resume where it left off or recover from a crash; 0 turns autosaving off
+    #[arg(long, value_name = "SECONDS", default_value_t = 60)]
+    pub autosave: u32,
+    /// Turn the picture clockwise by 0, 90, 180 or 270 degrees, for displays mounted
+    /// sideways; the keypad's 2, 4, 6 and 8 turn with it
+    #[arg(long, value_name = "DEGREES", default_value_t = 0, value_parser = parse_rotation)]
+    pub rotate: u16,
+    /// Mirror the picture left to right, before turning it
+    #[arg(long)]
+    pub mirror_horizontal: bool,
+    /// Mirror the picture top to bottom, before turning it
+    #[arg(long)]
+    pub mirror_vertical: bool,
+    /// Open the window on this display, 0 being the primary one, instead of where it was
+    /// left last time; for cabinets and other setups with several screens
+    #[arg(long, value_name = "N")]
+    pub monitor: Option<u32>,
+    /// Start in fullscreen, whatever the window was left in last time
+    #[arg(long)]
+    pub fullscreen: bool,
+    /// Open a second window showing the registers, the stack and the code around PC
+    #[arg(long)]
+    pub debug_window: bool,
+    /// Start the ROM over whenever its file changes, keeping the quirks and speed, for
+    /// running each build straight from Octo or an assembler
+    #[arg(long)]
+    pub watch: bool,
+    /// How to draw the window: accelerated uses the GPU, software works everywhere; the GPU
+    /// is tried first when omitted
+    #[arg(long, value_enum)]
+    pub renderer: Option<Renderer>,
+    /// Show the screen like an e-paper display would: at most this many refreshes a second,
+    /// only when something changed
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=60))]
+    pub max_refresh_rate: Option<u32>,
+    /// File remapping the emulator's own hotkeys, one `action = Key` per line; defaults to
+    /// hotkeys.conf in the config directory (~/.config/chip8 on Linux)
+    #[arg(long, value_name = "PATH")]
+    pub hotkeys: Option<PathBuf>,
+    /// How pasted text (paste-text, Home) becomes keypad presses: digits types hex digits,
+    /// ascii types each character's code as two hex digits, or a file with one
+    /// `character = keys` per line, like `A = 4 1`
+    #[arg(long, value_name = "ENCODING", default_value = "digits", value_parser = parse_key_encoding)]
+    pub paste_encoding: KeyEncoding,
+    /// Also play the buzzer as a note on the first MIDI output port whose name contains this
+    #[cfg(feature = "midi")]
+    #[arg(long, value_name = "NAME")]
+    pub midi_port: Option<String>,
+    /// MIDI note of the buzzer, A4 (69) by default like its tone
+    #[cfg(feature = "midi")]
+    #[arg(long, default_value_t = 69, value_parser = clap::value_parser!(u8).range(0..=127))]
+    pub midi_note: u8,
+    /// MIDI channel of the buzzer, from 1 to 16
+    #[cfg(feature = "midi")]
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=16))]
+    pub midi_channel: u8,
+    /// Show the opcode families taking the most time in the top-left corner
+    #[cfg(feature = "profiling")]
+    #[arg(long)]
+    pub perf_hud: bool,
+    /// Print the time spent on each opcode family to stderr when quitting
+    #[cfg(feature = "profiling")]
+    #[arg(long)]
+    pub perf_report: bool,
+    /// Least severe messages to log: error, warn, info, debug or trace (every frame)
+    #[cfg(feature = "tracing")]
+    #[arg(long, default_value_t = tracing::Level::INFO)]
+    pub log_level: tracing::Level,
+    /// Write the log to this file instead of stderr, to attach to bug reports
+    #[cfg(feature = "tracing")]
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FocusLoss {
+    /// Stop emulating until focus comes back
+    Pause,
+    /// Keep emulating at a quarter of the speed
+    Throttle,
+    /// Keep emulating at full speed
+    Ignore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Renderer {
+    Accelerated,
+    Software,
+}
+
+impl Renderer {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Renderer::Accelerated => "accelerated",
+            Renderer::Software => "software",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BeepPitch {
+    /// Always the same tone
+    Constant,
+    /// Scale the tone with the playback speed, like a tape played faster or slower
+    FollowSpeed,
+}
+
+fn parse_address(s: &str) -> Result<usize, String> {
+    let digits = s.trim_start_matches("0x").trim_start_matches("0X");
+    usize::from_str_radix(digits, 16).map_err(|_| format!("'{}' is not a hex address", s))
+}
+
+fn parse_address_range(s: &str) -> Result<Range<usize>, String> {
+    let (start, end) = s
+        .split_once("..")
+        .ok_or_else(|| format!("'{}' is not a range like 0xE00..0xF00", s))?;
+    let range = parse_address(start)?..parse_address(end)?;
+    if range.is_empty() || range.end > RAM_SIZE {
+        return Err(format!(
+            "'{}' must start below its end and end by 0x{:03X}",
+            s, RAM_SIZE
+        ));
+    }
+    Ok(range)
+}
+
+fn parse_overlay(s: &str) -> Result<(PathBuf, usize), String> {
+    let (path, addr) = s
+        .rsplit_once('@')
+        .ok_or_else(|| format!("'{}' is not FILE@ADDR like levels.bin@0x800", s))?;
+    Ok((PathBuf::from(path), parse_address(addr)?))
+}
+
+fn parse_ram_size(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(size) if (0x202..=RAM_SIZE).contains(&size) => Ok(size),
+        _ => Err(format!("'{}' is not from 514 to {} bytes", s, RAM_SIZE)),
+    }
+}
+
+fn parse_watch(s: &str) -> Result<WatchExpression, String> {
+    WatchExpression::parse(s).map_err(|err| err.to_string())
+}
+
+fn parse_rotation(s: &str) -> Result<u16, String> {
+    match s.parse::<u16>() {
+        Ok(degrees) if degrees % 90 == 0 && degrees < 360 => Ok(degrees),
+        _ => Err(format!("'{}' is not one of 0, 90, 180 and 270", s)),
+    }
+}
+
+fn parse_key_encoding(s: &str) -> Result<KeyEncoding, String> {
+    match s.parse() {
+        Ok(encoding) => Ok(encoding),
+        Err(_) => std::fs::read_to_string(s)
+            .map_err(|err| format!("'{}' isn't digits, ascii or a readable file: {}", s, err))
+            .and_then(|text| KeyEncoding::parse_map(&text)),
+    }
+}
+
+fn parse_buffer_size(s: &str) -> Result<u16, String> {
+    match s.parse::<u16>() {
+        Ok(size) if size.is_power_of_two() && size >= 64 => Ok(size),
+        _ => Err(format!("'{}' is not a power of two between 64 and 32768", s)),
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+use sdl2::Sdl;
+
+use chip8_core::audio::{Envelope, ToneSynth};
+
+use crate::args::BeepPitch;
+
+/// Pitch of the buzzer, roughly what the COSMAC VIP's sounded like
+const BEEP_FREQUENCY: f32 = 440.0;
+const VOLUME: f32 = 0.15;
+/// The collision cue, a blip an octave above the buzzer and quieter
+const CUE_FREQUENCY: f32 = 880.0;
+const CUE_VOLUME: f32 = 0.1;
+const CUE_LENGTH: Duration = Duration::from_millis(30);
+const SAMPLE_RATE: i32 = 44_100;
+
+/// How to open the audio device, from the command line
+pub struct AudioConfig<'a> {
+    /// SDL audio backend, e.g. "pulseaudio" or "alsa"; SDL picks one when `None`
+    pub driver: Option<&'a str>,
+    /// Output device name; the system default when `None`
+    pub device: Option<&'a str>,
+    /// Samples per buffer: smaller is lower latency, larger is less prone to crackling
+    pub buffer_size: u16,
+    pub pitch: BeepPitch,
+    /// Shortest a beep lasts, so a sound timer of a tick or two is still heard
+    pub min_beep: Duration,
+    /// Fade in and out of each beep, so starting and stopping it doesn't click
+    pub envelope: Envelope,
+}
+
+/// The tones, clocked by the samples the device consumes so their pitch depends on real
+/// time only and not on how fast frames are emulated
+struct Tone {
+    beep: ToneSynth,
+    cue: ToneSynth,
+}
+
+impl AudioCallback for Tone {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        self.beep.fill(out);
+        self.cue.mix(out);
+    }
+}
+
+/// The buzzer, or silence when no audio device could be opened
+pub struct Beeper {
+    device: Option<AudioDevice<Tone>>,
+    pitch: BeepPitch,
+}
+
+impl Beeper {
+    /// Open the audio device, falling back to silence (with a warning) when there is none
+    pub fn open(sdl_context: &Sdl, config: &AudioConfig) -> Self {
+        match open_device(sdl_context, config) {
+            Ok(device) => {
+                // The tone is silenced sample by sample, so short beeps last their minimum
+                // even if the machine stops them sooner
+                device.resume();
+                Self {
+                    device: Some(device),
+                    pitch: config.pitch,
+                }
+            }
+            Err(err) => {
+                warn!("No audio, running silently: {}", err);
+                if config.driver.is_some() || config.device.is_some() {
+                    print_devices(sdl_context);
+                }
+                Self {
+                    device: None,
+                    pitch: config.pitch,
+                }
+            }
+        }
+    }
+
+    /// Start or stop the tone. Starting it lasts at least the minimum beep duration, so
+    /// call this with `true` as soon as the machine starts a beep, not only once a frame
+    /// shows it.
+    pub fn set_beeping(&mut self, beeping: bool) {
+        let Some(device) = &mut self.device else {
+            return;
+        };
+        device.lock().beep.set_on(beeping);
+    }
+
+    /// Play the short cue for a sprite collision over the buzzer
+    pub fn cue(&mut self) {
+        let Some(device) = &mut self.device else {
+            return;
+        };
+        let mut tone = device.lock();
+        tone.cue.set_on(true);
+        tone.cue.set_on(false);
+    }
+
+    /// Follow the emulation running at `speed` times real time, which shifts the pitch
+    /// when it's configured to follow the speed
+    pub fn set_playback_speed(&mut self, speed: f64) {
+        let Some(device) = &mut self.device else {
+            return;
+        };
+        let frequency = match self.pitch {
+            BeepPitch::Constant => BEEP_FREQUENCY,
+            BeepPitch::FollowSpeed => BEEP_FREQUENCY * speed as f32,
+        };
+        device.lock().beep.set_frequency(frequency);
+    }
+}
+
+fn open_device(sdl_context: &Sdl, config: &AudioConfig) -> Result<AudioDevice<Tone>, String> {
+    if let Some(driver) = config.driver {
+        if !sdl2::audio::drivers().any(|available| available == driver) {
+            return Err(format!("audio driver '{}' isn't available", driver));
+        }
+        sdl2::hint::set("SDL_AUDIODRIVER", driver);
+    }
+    let audio = sdl_context.audio()?;
+    let desired = AudioSpecDesired {
+        freq: Some(SAMPLE_RATE),
+        channels: Some(1),
+        samples: Some(config.buffer_size),
+    };
+    audio.open_playback(config.device, &desired, |spec| {
+        let mut beep = ToneSynth::new(spec.freq as u32, BEEP_FREQUENCY, VOLUME);
+        beep.set_min_beep(config.min_beep);
+        beep.set_envelope(config.envelope);
+        let mut cue = ToneSynth::new(spec.freq as u32, CUE_FREQUENCY, CUE_VOLUME);
+        cue.set_min_beep(CUE_LENGTH);
+        cue.set_envelope(config.envelope);
+        Tone { beep, cue }
+    })
+}
+
+/// List the audio drivers SDL was built with and the output devices of the current one,
+/// to help pick valid `--audio-driver` and `--audio-device` values
+fn print_devices(sdl_context: &Sdl) {
+    let drivers = sdl2::audio::drivers().collect::<Vec<_>>();
+    eprintln!("Available audio drivers: {}", drivers.join(", "));
+    if let Ok(audio) = sdl_context.audio() {
+        eprintln!("Output devices for {}:", audio.current_audio_driver());
+        let count = audio.num_audio_playback_devices().unwrap_or(0);
+        for index in 0..count {
+            if let Ok(name) = audio.audio_playback_device_name(index) {
+                eprintln!("  {}", name);
+            }
+        }
+    }
+}
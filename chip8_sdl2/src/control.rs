@@ -0,0 +1,229 @@
+//! Local control socket for scripts, tests and streaming overlays to drive the emulator.
+//!
+//! Clients send one command per line and get one line back: `ok`, optionally followed by a
+//! message, or `error` and what went wrong.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender, TryIter};
+
+use chip8_core::disasm::SymbolTable;
+use chip8_core::memory_search::SearchFilter;
+use chip8_core::screen_trigger::ScreenTrigger;
+use chip8_core::watch::WatchExpression;
+
+use crate::emulation::SPEED_RANGE;
+
+/// Where the outcome of a command goes: a message for `ok`, or the error
+pub type Reply = Sender<Result<String, String>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    Pause,
+    Resume,
+    LoadRom(PathBuf),
+    SaveState(u8),
+    LoadState(u8),
+    /// Save the screen as a BMP image
+    Screenshot(PathBuf),
+    /// Multiply the frame rate, e.g. 2.0 for double speed
+    SetSpeed(f64),
+    /// Print the expression's value whenever it changes and after every tutor step
+    Watch(WatchExpression),
+    /// Forget every watch expression
+    Unwatch,
+    Search(SearchStep),
+    /// Add a label or note to the ROM's annotations, a line of the symbol file format
+    Annotate(String),
+    /// Pause when the screen starts showing what the trigger waits for
+    BreakOn(ScreenTrigger),
+    /// Forget every screen trigger
+    ClearBreaks,
+    /// Run the next instruction, or the whole subroutine when it's a call, while paused
+    StepOver,
+    /// Run until the current subroutine returns, while paused
+    StepOut,
+}
+
+/// A step of searching memory for where the program keeps a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStep {
+    /// Snapshot memory, with every address a candidate
+    Start,
+    /// Keep the candidates that pass the filter since the last snapshot
+    Filter(SearchFilter),
+    /// Watch the remaining candidates
+    Pin,
+}
+
+impl ControlCommand {
+    fn parse(line: &str) -> Result<Self, String> {
+        let (name, argument) = match line.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, Some(argument.trim())),
+            None => (line, None),
+        };
+        let argument = |usage: &str| argument.ok_or_else(|| format!("usage: {} {}", name, usage));
+        let slot = |usage: &str| {
+            let slot = argument(usage)?;
+            slot.parse()
+                .map_err(|_| format!("'{}' is not a slot number", slot))
+        };
+        match name {
+            "pause" => Ok(Self::Pause),
+            "resume" => Ok(Self::Resume),
+            "load-rom" => Ok(Self::LoadRom(argument("<path>")?.into())),
+            "save-state" => Ok(Self::SaveState(slot("<slot>")?)),
+            "load-state" => Ok(Self::LoadState(slot("<slot>")?)),
+            "screenshot" => Ok(Self::Screenshot(argument("<path>")?.into())),
+            "set-speed" => {
+                let speed = argument("<factor>")?;
+                match speed.parse() {
+                    Ok(speed) if SPEED_RANGE.contains(&speed) => Ok(Self::SetSpeed(speed)),
+                    _ => Err(format!(
+                        "'{}' is not a speed between {} and {}",
+                        speed,
+                        SPEED_RANGE.start(),
+                        SPEED_RANGE.end()
+                    )),
+                }
+            }
+            "watch" => WatchExpression::parse(argument("<expression>")?)
+                .map(Self::Watch)
+                .map_err(|err| err.to_string()),
+            "unwatch" => Ok(Self::Unwatch),
+            "search" => {
+                match argument("start|changed|unchanged|increased|decreased|<value>|pin")? {
+                    "start" => Ok(Self::Search(SearchStep::Start)),
+                    "pin" => Ok(Self::Search(SearchStep::Pin)),
+                    filter => filter
+                        .parse()
+                        .map(|filter| Self::Search(SearchStep::Filter(filter))),
+                }
+            }
+            "annotate" => {
+                let entry = argument("label <name> <addr> | note <addr>[-<end>] <text>")?;
+                match SymbolTable::parse(entry) {
+                    Ok(_) => Ok(Self::Annotate(entry.to_string())),
+                    Err(err) => Err(err.message),
+                }
+            }
+            "break-on" => match argument("pixel <x> <y> | pattern <x> <y> <rows> | clear")? {
+                "clear" => Ok(Self::ClearBreaks),
+                trigger => trigger.parse().map(Self::BreakOn),
+            },
+            "step-over" => Ok(Self::StepOver),
+            "step-out" => Ok(Self::StepOut),
+            _ => Err(format!("unknown command '{}'", name)),
+        }
+    }
+}
+
+/// A command waiting for the main loop, with where to send its outcome
+pub struct Request {
+    pub command: ControlCommand,
+    pub reply: Reply,
+}
+
+/// Accepts connections on its own thread and hands their commands to the main loop
+pub struct ControlServer {
+    requests: Receiver<Request>,
+}
+
+impl ControlServer {
+    /// Listen on `address`: a TCP port on localhost when it's a number, a Unix socket path
+    /// otherwise
+    pub fn start(address: &str) -> io::Result<Self> {
+        let (sender, requests) = mpsc::channel();
+        if let Ok(port) = address.parse::<u16>() {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    serve(stream, sender.clone());
+                }
+            });
+        } else {
+            listen_unix(address, sender)?;
+        }
+        Ok(Self { requests })
+    }
+
+    /// Commands received since the last call
+    pub fn requests(&self) -> TryIter<'_, Request> {
+        self.requests.try_iter()
+    }
+}
+
+#[cfg(unix)]
+fn listen_unix(path: &str, sender: Sender<Request>) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let listener = match UnixListener::bind(path) {
+        // A socket left behind by an emulator that didn't exit cleanly, nobody listens on
+        Err(err)
+            if err.kind() == io::ErrorKind::AddrInUse
+                && std::fs::symlink_metadata(path)
+                    .is_ok_and(|meta| meta.file_type().is_socket())
+                && UnixStream::connect(path).is_err() =>
+        {
+            std::fs::remove_file(path)?;
+            UnixListener::bind(path)?
+        }
+        listener => listener?,
+    };
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            serve(stream, sender.clone());
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn listen_unix(path: &str, _sender: Sender<Request>) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "'{}' isn't a port, and Unix sockets aren't supported here",
+            path
+        ),
+    ))
+}
+
+/// Answer the commands of one client on a thread of its own
+fn serve<S>(stream: S, requests: Sender<Request>)
+where
+    S: Send + 'static,
+    for<'a> &'a S: Read + Write,
+{
+    std::thread::spawn(move || {
+        let mut writer = &stream;
+        for line in BufReader::new(&stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let outcome = ControlCommand::parse(line).and_then(|command| {
+                let (reply, outcome) = mpsc::channel();
+                requests
+                    .send(Request { command, reply })
+                    .map_err(|_| "the emulator is shutting down".to_string())?;
+                outcome
+                    .recv()
+                    .unwrap_or_else(|_| Err("the emulator is shutting down".to_string()))
+            });
+            let response = match outcome {
+                Ok(message) if message.is_empty() => "ok".to_string(),
+                Ok(message) => format!("ok {}", message),
+                Err(err) => format!("error {}", err),
+            };
+            if writeln!(writer, "{}", response).is_err() {
+                break;
+            }
+        }
+    });
+}
@@ -0,0 +1,177 @@
+use sdl2::{pixels::Color, render::Canvas, video::Window, VideoSubsystem};
+
+use chip8_core::disasm::disassemble_at;
+use chip8_core::runner::Frame;
+
+use crate::args::Renderer;
+use crate::overlay::{self, LINE_HEIGHT};
+use crate::video;
+
+const CATPPUCCIN_MOCHA_CRUST: Color = Color::RGB(17, 17, 27);
+const CATPPUCCIN_MOCHA_SUBTEXT0: Color = Color::RGB(166, 173, 200);
+const CATPPUCCIN_MOCHA_TEXT: Color = Color::RGB(205, 214, 244);
+const CATPPUCCIN_MOCHA_YELLOW: Color = Color::RGB(249, 226, 175);
+
+const WINDOW_TITLE: &str = "CHIP-8 Debugger";
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 820;
+const MARGIN: u32 = 12;
+const TEXT_SCALE: u32 = 2;
+/// Instructions listed before and after the one at PC
+const INSTRUCTIONS_BEFORE: u16 = 6;
+const INSTRUCTIONS_AFTER: u16 = 12;
+/// Rows of the memory I points at shown, and bytes in each
+const MEMORY_ROWS: usize = 2;
+const MEMORY_ROW_LEN: usize = 8;
+
+/// A second window showing the registers, the stack, the code around PC, the memory at I
+/// and the latest instructions of the latest frame, next to the game, with the notes the
+/// user left on them
+pub struct DebugWindow {
+    canvas: Canvas<Window>,
+}
+
+impl DebugWindow {
+    pub fn open(
+        video_subsystem: &VideoSubsystem,
+        renderer: Option<Renderer>,
+    ) -> Result<Self, String> {
+        let window = video_subsystem.window(WINDOW_TITLE, WIDTH, HEIGHT);
+        let canvas = video::create_canvas(&window, renderer)?;
+        Ok(Self { canvas })
+    }
+
+    /// ID of the window, to tell its events from the game window's
+    pub fn id(&self) -> u32 {
+        self.canvas.window().id()
+    }
+
+    pub fn draw(&mut self, frame: &Frame) {
+        let canvas = &mut self.canvas;
+        canvas.set_draw_color(CATPPUCCIN_MOCHA_CRUST);
+        canvas.clear();
+        let state = &frame.cpu_state;
+        let mut lines = Lines::new(canvas);
+
+        lines.heading("Registers");
+        lines.text(&format!(
+            "PC {:03X}   I {:03X}   DT {:02X}   ST {:02X}",
+            state.pc, state.i, state.delay_timer, state.sound_timer
+        ));
+        for (row, values) in state.v.chunks(8).enumerate() {
+            let registers = values
+                .iter()
+                .enumerate()
+                .map(|(column, value)| format!("V{:X} {:02X}", row * 8 + column, value))
+                .collect::<Vec<_>>();
+            lines.text(&registers.join("  "));
+        }
+
+        lines.heading("Stack");
+        if state.stack.is_empty() {
+            lines.text("empty");
+        }
+        for (depth, addr) in state.stack.iter().enumerate().rev() {
+            lines.text(&format!("{:X}  {:03X}", depth, addr));
+        }
+
+        lines.heading("Code");
+        let first = state.pc.saturating_sub(2 * INSTRUCTIONS_BEFORE);
+        let addresses = (0..INSTRUCTIONS_BEFORE + 1 + INSTRUCTIONS_AFTER).map(|n| first + 2 * n);
+        for addr in addresses {
+            let Some(instruction) = disassemble_at(&frame.memory, 0, addr) else {
+                break;
+            };
+            let listing = match &frame.symbols {
+                Some(symbols) => instruction.with_symbols(symbols),
+                None => instruction.to_string(),
+            };
+            if addr == state.pc {
+                lines.highlighted(&format!("> {}", listing));
+            } else {
+                lines.text(&format!("  {}", listing));
+            }
+        }
+
+        match &frame.symbols {
+            Some(symbols) => lines.heading(&format!("Memory at {}", symbols.describe(state.i))),
+            None => lines.heading("Memory at I"),
+        }
+        for row in 0..MEMORY_ROWS {
+            let start = state.i as usize + row * MEMORY_ROW_LEN;
+            let Some(bytes) = frame.memory.get(start..start + MEMORY_ROW_LEN) else {
+                break;
+            };
+            let bytes = bytes.iter().map(|byte| format!("{:02X}", byte));
+            lines.text(&format!(
+                "{:03X}  {}",
+                start,
+                bytes.collect::<Vec<_>>().join(" ")
+            ));
+        }
+        let note = frame
+            .symbols
+            .as_ref()
+            .and_then(|symbols| symbols.note(state.i));
+        if let Some((range, note)) = note {
+            lines.highlighted(&format!(
+                "{:03X}-{:03X}  {}",
+                range.start(),
+                range.end(),
+                note
+            ));
+        }
+
+        lines.heading("Recent instructions");
+        for entry in &frame.history {
+            let changes = entry
+                .changes()
+                .map(|(register, _, new)| format!("{}={:X}", register, new))
+                .collect::<Vec<_>>();
+            lines.text(&format!(
+                "{:>6}  {:03X}  {:<16} {}",
+                entry.frame,
+                entry.pc,
+                entry.instruction.to_string(),
+                changes.join(" ").to_uppercase()
+            ));
+        }
+        self.canvas.present();
+    }
+}
+
+/// Draws text one line below the other down the window
+struct Lines<'a> {
+    canvas: &'a mut Canvas<Window>,
+    top: i32,
+}
+
+impl<'a> Lines<'a> {
+    fn new(canvas: &'a mut Canvas<Window>) -> Self {
+        Self {
+            canvas,
+            top: MARGIN as i32,
+        }
+    }
+
+    fn heading(&mut self, text: &str) {
+        if self.top > MARGIN as i32 {
+            self.top += (LINE_HEIGHT * TEXT_SCALE) as i32;
+        }
+        self.draw(text, CATPPUCCIN_MOCHA_SUBTEXT0);
+    }
+
+    fn text(&mut self, text: &str) {
+        self.draw(text, CATPPUCCIN_MOCHA_TEXT);
+    }
+
+    fn highlighted(&mut self, text: &str) {
+        self.draw(text, CATPPUCCIN_MOCHA_YELLOW);
+    }
+
+    fn draw(&mut self, text: &str, color: Color) {
+        self.canvas.set_draw_color(color);
+        overlay::draw_text(self.canvas, text, MARGIN as i32, self.top, TEXT_SCALE);
+        self.top += (LINE_HEIGHT * TEXT_SCALE) as i32;
+    }
+}
@@ -0,0 +1,662 @@
+use std::sync::mpsc::{self, Receiver, Sender, TryIter};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use sdl2::event::EventSender;
+
+use chip8_core::disasm::SymbolTable;
+use chip8_core::input_queue::InputQueue;
+use chip8_core::launcher::Launcher;
+use chip8_core::memory_search::MemorySearch;
+use chip8_core::octo;
+use chip8_core::runner::{triple_buffer, BufferReader, Frame, FrameSink, FrameTimer, FRAME_RATE};
+use chip8_core::screen_trigger::ScreenTrigger;
+use chip8_core::watch::WatchExpression;
+use chip8_core::{Chip8, Chip8Error, Chip8Event, CrashReport, Preset, StopReason, WriteProtection};
+
+use crate::args::FocusLoss;
+use crate::control::{Reply, SearchStep};
+use crate::i18n::Text;
+use crate::palette::Theme;
+use crate::recorder::FrameRecorder;
+use crate::{persistence, tutor};
+
+/// Slowest and fastest the game may run, as a factor of real time; slower speeds
+/// overflow the frame period or as good as freeze the game
+pub const SPEED_RANGE: std::ops::RangeInclusive<f64> = 0.05..=16.0;
+
+/// While throttled in the background, frames are this many times longer
+const THROTTLE_FACTOR: f64 = 4.0;
+
+/// Share of a frame's period its instructions may take; a frame running over leaves the
+/// rest of its instructions to the next ones
+const FRAME_BUDGET_SHARE: f64 = 0.8;
+
+/// Memory searches list their candidates, and pin them as watches, once this few remain
+const SEARCH_RESULTS_SHOWN: usize = 8;
+
+/// Step over and step out give up on subroutines still running after this many instructions
+const STEP_INSTRUCTION_LIMIT: usize = 1_000_000;
+
+/// Instructions tutor mode can step back over
+const STEP_HISTORY_LENGTH: usize = 256;
+
+/// Frames each pasted key is held, then released, long enough for programs polling the keypad
+const PASTED_KEY_FRAMES: u32 = 3;
+
+/// What the render thread asks of the emulation thread
+pub enum Command {
+    /// Keys held on each keypad, applied from the next frame on
+    Keys {
+        first: [bool; 16],
+        second: [bool; 16],
+    },
+    /// Press these keys one after the other, as if typed, on top of the held ones
+    Type(Vec<u8>),
+    /// Tutor mode pauses the game and steps one explained instruction at a time; the render
+    /// thread explains the keys
+    SetTutor(bool),
+    /// How the window lost focus, `None` once it's back
+    SetBackground(Option<FocusLoss>),
+    /// Execute one instruction in tutor mode
+    Step,
+    /// Undo the last instruction in tutor mode
+    StepBack,
+    /// Run the next instruction, or the whole subroutine it calls, while paused or in tutor
+    /// mode
+    StepOver(Reply),
+    /// Run until the current subroutine returns, while paused or in tutor mode
+    StepOut(Reply),
+    /// Pause or resume at the request of a control client or the pause hotkey
+    SetPaused(bool),
+    /// Run this many times faster than normal
+    SetSpeed(f64),
+    /// Switch the running machine to the preset's quirks, speed and checks
+    ApplyPreset(Preset),
+    /// Colour recorded frames of monochrome ROMs with this theme from now on
+    SetTheme(Theme),
+    /// Swap in a machine with another ROM loaded, saving the old one's battery RAM first
+    Replace(Box<Chip8>),
+    /// Like `Replace`, but for a new build of the same ROM: the quirks and clock of the
+    /// running machine carry over
+    Reload(Box<Chip8>),
+    /// Name addresses with these labels and notes from now on
+    SetSymbols(Option<SymbolTable>),
+    SaveState {
+        slot: u8,
+        reply: Reply,
+    },
+    LoadState {
+        slot: u8,
+        reply: Reply,
+    },
+    TogglePixel {
+        x: usize,
+        y: usize,
+    },
+    ExportOcto,
+    /// Load the autosave left by the last run of the ROM
+    ResumeAutosave,
+    /// Print the expression's value whenever it changes and after every tutor step
+    Watch(WatchExpression),
+    ClearWatches,
+    /// Pause when the screen starts showing what the trigger waits for
+    AddScreenTrigger(ScreenTrigger),
+    ClearScreenTriggers,
+    Search {
+        step: SearchStep,
+        reply: Reply,
+    },
+    /// Hand a save state of the machine as it is now to the callback, for remote play
+    Snapshot(Box<dyn FnOnce(Vec<u8>) + Send>),
+    /// Continue from a save state made elsewhere, like by the other player of remote play
+    Restore(Vec<u8>),
+    Quit,
+}
+
+/// Pushed to SDL's event queue when a frame arrives after idling, to wake the render thread
+/// from waiting for events
+pub struct FrameReady;
+
+/// What the emulation thread tells the render thread, besides new frames
+pub enum Notice {
+    /// A message to show for a few seconds
+    Show(Text),
+    /// A message to show until another replaces it
+    ShowPersistent(Text),
+    /// Emulation now runs this many times faster than real time
+    Speed(f64),
+    Event(Chip8Event),
+}
+
+/// The machine running on its own thread at a steady 60 Hz
+pub struct Emulation {
+    commands: Sender<Command>,
+    notices: Receiver<Notice>,
+    frames: BufferReader<Frame>,
+    thread: JoinHandle<()>,
+}
+
+impl Emulation {
+    /// Start the emulation thread; it wakes the render thread through `wake` when it
+    /// resumes after idling. `FrameReady` must be registered as a custom event.
+    pub fn spawn(
+        chip8: Chip8,
+        wake: EventSender,
+        recorder: Option<FrameRecorder>,
+        launcher: Option<Launcher>,
+        autosave_interval: Option<Duration>,
+    ) -> Self {
+        let (commands, command_receiver) = mpsc::channel();
+        let (notice_sender, notices) = mpsc::channel();
+        let (frame_writer, frames) = triple_buffer(Frame::default());
+        let emulator = Emulator {
+            chip8,
+            notices: notice_sender,
+            tutor_mode: false,
+            paused: false,
+            speed: 1.0,
+            background: None,
+            crashed: false,
+            keys: [false; 16],
+            second_keys: [false; 16],
+            typed_keys: InputQueue::new(PASTED_KEY_FRAMES, PASTED_KEY_FRAMES),
+            frame_number: 0,
+            watches: Vec::new(),
+            search: None,
+            recorder,
+            launcher,
+            autosave_frames: autosave_interval
+                .map(|interval| (interval.as_secs_f64() * FRAME_RATE).ceil() as u64),
+            frames_since_autosave: 0,
+        };
+        let thread = std::thread::Builder::new()
+            .name("emulation".to_string())
+            .spawn(move || emulator.run(command_receiver, frame_writer, wake))
+            .unwrap();
+        Self {
+            commands,
+            notices,
+            frames,
+            thread,
+        }
+    }
+
+    pub fn send(&self, command: Command) {
+        // Only fails once the emulation thread is gone, and then there's nobody to tell
+        let _ = self.commands.send(command);
+    }
+
+    pub fn notices(&self) -> TryIter<'_, Notice> {
+        self.notices.try_iter()
+    }
+
+    /// Pick up the newest frame; returns whether there was one
+    pub fn update_frame(&mut self) -> bool {
+        self.frames.update()
+    }
+
+    pub fn frame(&self) -> &Frame {
+        self.frames.get()
+    }
+
+    /// Stop the machine and wait until it has saved what it persists
+    pub fn quit(self) {
+        self.send(Command::Quit);
+        if self.thread.join().is_err() {
+            error!("The emulation thread panicked");
+        }
+    }
+}
+
+/// State of the emulation thread
+struct Emulator {
+    chip8: Chip8,
+    notices: Sender<Notice>,
+    tutor_mode: bool,
+    /// Paused through the control socket or the pause hotkey
+    paused: bool,
+    speed: f64,
+    /// How the window lost focus, `None` while it has it
+    background: Option<FocusLoss>,
+    /// Once the program faults the machine stays frozen for inspection
+    crashed: bool,
+    keys: [bool; 16],
+    second_keys: [bool; 16],
+    /// Keys pasted but not all typed yet
+    typed_keys: InputQueue,
+    frame_number: u64,
+    /// Watch expressions with the value last printed for each
+    watches: Vec<(WatchExpression, Option<i64>)>,
+    search: Option<MemorySearch>,
+    /// Gets every frame the machine runs, but none while it's paused
+    recorder: Option<FrameRecorder>,
+    /// Games to swap in when the running menu picks one
+    launcher: Option<Launcher>,
+    /// Frames run between autosaves, `None` when autosaving is off
+    autosave_frames: Option<u64>,
+    frames_since_autosave: u64,
+}
+
+impl Emulator {
+    fn run(mut self, commands: Receiver<Command>, mut frames: impl FrameSink, wake: EventSender) {
+        let mut timer = FrameTimer::at_frame_rate();
+        let mut frame = Frame::default();
+        let mut was_idle = false;
+        loop {
+            loop {
+                match commands.try_recv() {
+                    Ok(Command::Quit) | Err(mpsc::TryRecvError::Disconnected) => {
+                        self.shut_down();
+                        return;
+                    }
+                    Ok(command) => self.handle(command, &mut timer),
+                    Err(mpsc::TryRecvError::Empty) => break,
+                }
+            }
+
+            self.run_frame();
+            self.frame_number += 1;
+            frame.capture(&self.chip8, self.frame_number);
+            // Games in the background are muted, however they go on running
+            frame.beeping &= self.is_running() && self.background.is_none();
+            // Paused machines can't change on their own either
+            frame.idle |= !self.is_running();
+            let idle = frame.idle;
+            if self.is_running() {
+                self.record(&frame);
+                self.frames_since_autosave += 1;
+                if Some(self.frames_since_autosave) == self.autosave_frames {
+                    self.autosave(false);
+                }
+            }
+            frames.push_frame(&frame);
+            if was_idle {
+                // The render thread may be waiting for events since it saw the last frame
+                let _ = wake.push_custom_event(FrameReady);
+            }
+            was_idle = idle;
+
+            if idle {
+                // Sleep until the frontend asks for something instead of running empty frames
+                match commands.recv() {
+                    Ok(Command::Quit) | Err(_) => {
+                        self.shut_down();
+                        return;
+                    }
+                    Ok(command) => self.handle(command, &mut timer),
+                }
+                timer.reset();
+            } else {
+                timer.wait();
+            }
+        }
+    }
+
+    fn record(&mut self, frame: &Frame) {
+        let Some(recorder) = &mut self.recorder else {
+            return;
+        };
+        if let Err(err) = recorder.write_frame(frame) {
+            warn!("Stopped recording video: {}", err);
+            self.recorder = None;
+        }
+    }
+
+    /// Save the state for the next run to resume from, unless autosaving is off. A crashed
+    /// machine isn't worth resuming, so the last good autosave is kept instead.
+    fn autosave(&mut self, clean_exit: bool) {
+        self.frames_since_autosave = 0;
+        if self.autosave_frames.is_none() || self.crashed {
+            return;
+        }
+        let state = self.chip8.save_state();
+        if let Err(err) = persistence::save_autosave(self.chip8.rom_hash(), &state, clean_exit) {
+            warn!("Couldn't autosave: {}", err);
+        }
+    }
+
+    fn shut_down(&mut self) {
+        self.save_battery_ram();
+        self.autosave(true);
+        if let Some(Err(err)) = self.recorder.take().map(FrameRecorder::finish) {
+            warn!("Failed to finish the video: {}", err);
+        }
+    }
+
+    /// Keep the states tutor mode steps back to only while it's on, since taking them
+    /// slows every instruction down
+    fn update_step_history(&mut self) {
+        match self.tutor_mode {
+            true => self.chip8.enable_step_history(STEP_HISTORY_LENGTH),
+            false => self.chip8.disable_step_history(),
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        !self.tutor_mode
+            && !self.paused
+            && self.background != Some(FocusLoss::Pause)
+            && !self.crashed
+    }
+
+    fn frame_period(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / (FRAME_RATE * self.playback_speed()))
+    }
+
+    /// How much faster than real time frames are emulated
+    fn playback_speed(&self) -> f64 {
+        match self.background {
+            Some(FocusLoss::Throttle) => self.speed / THROTTLE_FACTOR,
+            _ => self.speed,
+        }
+    }
+
+    fn run_frame(&mut self) {
+        let mut keys = self.keys;
+        if self.is_running() {
+            for (key, typed) in keys.iter_mut().zip(self.typed_keys.next_frame()) {
+                *key |= typed;
+            }
+        }
+        self.chip8.keypad.update_keys(keys);
+        self.chip8.keypad.update_second_bank(self.second_keys);
+        if self.is_running() {
+            let deadline = Instant::now() + self.frame_period().mul_f64(FRAME_BUDGET_SHARE);
+            if let Err(err) = self.chip8.run_frame_within(|| Instant::now() >= deadline) {
+                self.report_crash(err);
+            }
+        }
+        let launched = self
+            .launcher
+            .as_ref()
+            .and_then(|launcher| launcher.poll(&mut self.chip8));
+        if let Some(index) = launched {
+            self.chip8.apply_rom_platform();
+            println!(
+                "Launching game {} as {}",
+                index + 1,
+                self.chip8.rom_info().platform
+            );
+        }
+        for event in self.chip8.take_events() {
+            // The rest of the frame still runs, so the pause comes at its end
+            if let Chip8Event::ScreenTriggered { .. } = event {
+                self.paused = true;
+            }
+            self.notify(Notice::Event(event));
+        }
+        self.print_changed_watches();
+    }
+
+    fn handle_watch(&mut self, watch: WatchExpression) {
+        let state = self.chip8.cpu_state();
+        println!("{}", watch.describe(&state, self.chip8.memory()));
+        let value = watch.evaluate(&state, self.chip8.memory());
+        self.watches.push((watch, value));
+    }
+
+    fn print_changed_watches(&mut self) {
+        let state = self.chip8.cpu_state();
+        for (watch, last) in &mut self.watches {
+            let value = watch.evaluate(&state, self.chip8.memory());
+            if value != *last {
+                println!("{}", watch.describe(&state, self.chip8.memory()));
+                *last = value;
+            }
+        }
+    }
+
+    fn handle(&mut self, command: Command, timer: &mut FrameTimer) {
+        match command {
+            Command::Keys { first, second } => {
+                self.keys = first;
+                self.second_keys = second;
+            }
+            Command::Type(keys) => self.typed_keys.push(keys),
+            Command::SetTutor(tutor_mode) => {
+                self.tutor_mode = tutor_mode;
+                self.update_step_history();
+                if tutor_mode {
+                    self.print_tutor_step();
+                }
+            }
+            Command::SetBackground(background) => {
+                self.background = background;
+                timer.set_period(self.frame_period());
+                self.notify(Notice::Speed(self.playback_speed()));
+            }
+            Command::SetPaused(paused) => self.paused = paused,
+            Command::SetSpeed(speed) => {
+                self.speed = speed;
+                timer.set_period(self.frame_period());
+                self.notify(Notice::Speed(self.playback_speed()));
+            }
+            Command::ApplyPreset(preset) => {
+                preset.apply(&mut self.chip8);
+                // Self-modifying ROMs stay exempt from write protection, as on startup
+                if persistence::is_self_modifying(self.chip8.rom_hash()) {
+                    self.chip8.set_write_protection(WriteProtection::Off);
+                }
+            }
+            Command::SetTheme(theme) => {
+                if let Some(recorder) = &mut self.recorder {
+                    recorder.set_theme(theme);
+                }
+            }
+            Command::Replace(chip8) => {
+                self.save_battery_ram();
+                self.chip8 = *chip8;
+                self.crashed = false;
+                self.update_step_history();
+            }
+            Command::Reload(mut chip8) => {
+                chip8.set_quirks(self.chip8.quirks());
+                chip8.set_clock(self.chip8.clock());
+                self.save_battery_ram();
+                self.chip8 = *chip8;
+                self.crashed = false;
+                self.update_step_history();
+            }
+            Command::SetSymbols(symbols) => self.chip8.set_symbols(symbols),
+            Command::SaveState { slot, reply } => {
+                let state = self.chip8.save_state();
+                let outcome = persistence::save_state(self.chip8.rom_hash(), slot, &state)
+                    .map(|path| path.display().to_string())
+                    .map_err(|err| err.to_string());
+                if outcome.is_ok() {
+                    self.notify(Notice::Show(Text::SavedState(slot)));
+                }
+                let _ = reply.send(outcome);
+            }
+            Command::LoadState { slot, reply } => {
+                let outcome = persistence::load_state(self.chip8.rom_hash(), slot)
+                    .map_err(|err| err.to_string())
+                    .and_then(|state| self.chip8.load_state(&state).map_err(|err| err.to_string()));
+                if outcome.is_ok() {
+                    self.crashed = false;
+                    self.notify(Notice::Show(Text::LoadedState(slot)));
+                }
+                let _ = reply.send(outcome.map(|()| String::new()));
+            }
+            Command::Snapshot(send) => send(self.chip8.save_state()),
+            Command::Restore(state) => match self.chip8.load_state(&state) {
+                Ok(()) => self.crashed = false,
+                Err(err) => warn!("Couldn't load the other player's state: {}", err),
+            },
+            Command::ResumeAutosave => {
+                let outcome = persistence::load_autosave(self.chip8.rom_hash())
+                    .map_err(|err| err.to_string())
+                    .and_then(|state| self.chip8.load_state(&state).map_err(|err| err.to_string()));
+                match outcome {
+                    Ok(()) => {
+                        self.crashed = false;
+                        self.notify(Notice::Show(Text::Resumed));
+                    }
+                    Err(err) => warn!("Couldn't resume the last session: {}", err),
+                }
+            }
+            Command::Step if self.tutor_mode && !self.crashed => {
+                if let Err(err) = self.chip8.step() {
+                    self.report_crash(err);
+                }
+                self.print_tutor_step();
+            }
+            Command::Step => {}
+            Command::StepBack if self.tutor_mode && !self.crashed => {
+                if self.chip8.step_back() {
+                    self.print_tutor_step();
+                } else {
+                    println!("No earlier instruction to step back to\n");
+                }
+            }
+            Command::StepBack => {}
+            Command::StepOver(reply) => {
+                let outcome = self.debug_step(|chip8| chip8.step_over(STEP_INSTRUCTION_LIMIT));
+                let _ = reply.send(outcome);
+            }
+            Command::StepOut(reply) => {
+                let outcome = self.debug_step(|chip8| chip8.step_out(STEP_INSTRUCTION_LIMIT));
+                let _ = reply.send(outcome);
+            }
+            Command::TogglePixel { x, y } => {
+                let lit = self.chip8.pixel(x, y);
+                self.chip8.set_pixel(x, y, !lit);
+            }
+            Command::ExportOcto => {
+                let cartridge = octo::export_cartridge(&self.chip8);
+                match persistence::save_octo_cartridge(self.chip8.rom_hash(), &cartridge) {
+                    Ok(path) => {
+                        info!("Octo cartridge saved to {}", path.display());
+                        self.notify(Notice::Show(Text::ExportedOcto));
+                    }
+                    Err(err) => self.notify(Notice::Show(Text::OctoExportFailed(err.to_string()))),
+                }
+            }
+            Command::Watch(watch) => self.handle_watch(watch),
+            Command::ClearWatches => self.watches.clear(),
+            Command::AddScreenTrigger(trigger) => self.chip8.add_screen_trigger(trigger),
+            Command::ClearScreenTriggers => self.chip8.clear_screen_triggers(),
+            Command::Search { step, reply } => {
+                let _ = reply.send(self.search(step));
+            }
+            Command::Quit => {}
+        }
+    }
+
+    /// Step over or out with `step`, describing where the machine stopped
+    fn debug_step(
+        &mut self,
+        step: impl FnOnce(&mut Chip8) -> Result<StopReason, Chip8Error>,
+    ) -> Result<String, String> {
+        if self.crashed {
+            return Err("the program crashed, reset or load a state first".to_string());
+        }
+        if !self.paused && !self.tutor_mode {
+            return Err("pause first".to_string());
+        }
+        let reason = match step(&mut self.chip8) {
+            Ok(reason) => reason,
+            Err(err) => {
+                let message = err.to_string();
+                self.report_crash(err);
+                return Err(message);
+            }
+        };
+        if self.tutor_mode {
+            self.print_tutor_step();
+        }
+        let pc = self.chip8.cpu_state().pc;
+        Ok(match reason {
+            StopReason::Breakpoint => format!("breakpoint at 0x{:03X}", pc),
+            StopReason::Budget => format!(
+                "stopped at 0x{:03X} after {} instructions without returning",
+                pc, STEP_INSTRUCTION_LIMIT
+            ),
+            _ => format!("at 0x{:03X}", pc),
+        })
+    }
+
+    fn search(&mut self, step: SearchStep) -> Result<String, String> {
+        let memory = self.chip8.memory();
+        let search = match (step, &mut self.search) {
+            (SearchStep::Start, search) => search.insert(MemorySearch::new(memory)),
+            (_, None) => return Err("no search started, send 'search start' first".to_string()),
+            (SearchStep::Filter(filter), Some(search)) => {
+                search.filter(memory, filter);
+                search
+            }
+            (SearchStep::Pin, Some(search)) => search,
+        };
+        let description = search.describe(SEARCH_RESULTS_SHOWN);
+        if step != SearchStep::Pin {
+            return Ok(description);
+        }
+        let candidates = search.candidates().to_vec();
+        if candidates.is_empty() {
+            return Err("no candidates left to pin".to_string());
+        }
+        if candidates.len() > SEARCH_RESULTS_SHOWN {
+            return Err(format!(
+                "{}, narrow them down to {} to pin them",
+                description, SEARCH_RESULTS_SHOWN
+            ));
+        }
+        for addr in &candidates {
+            let watch = WatchExpression::parse(&format!("mem[0x{:03X}]", addr)).unwrap();
+            self.handle_watch(watch);
+        }
+        Ok(format!("watching {} addresses", candidates.len()))
+    }
+
+    fn notify(&self, notice: Notice) {
+        // The render thread only hangs up when it's quitting
+        let _ = self.notices.send(notice);
+    }
+
+    fn report_crash(&mut self, error: Chip8Error) {
+        self.crashed = true;
+        let report = CrashReport::new(&self.chip8, error);
+        error!("{}", report.summary());
+        match persistence::save_crash_report(self.chip8.rom_hash(), &report.to_string()) {
+            Ok(path) => {
+                info!("Crash report saved to {}", path.display());
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                self.notify(Notice::ShowPersistent(Text::Crashed {
+                    summary: report.summary(),
+                    report: Some(file_name.into_owned()),
+                }));
+            }
+            Err(err) => {
+                warn!("Couldn't save the crash report: {}\n{}", err, report);
+                self.notify(Notice::ShowPersistent(Text::Crashed {
+                    summary: report.summary(),
+                    report: None,
+                }));
+            }
+        }
+    }
+
+    fn print_tutor_step(&mut self) {
+        let state = self.chip8.cpu_state();
+        let instruction = self.chip8.current_instruction();
+        println!(
+            "{}\n{}",
+            tutor::describe_next(&state, instruction, self.chip8.symbols()),
+            tutor::register_dump(&state, instruction)
+        );
+        for (watch, last) in &mut self.watches {
+            *last = watch.evaluate(&state, self.chip8.memory());
+            println!(" {}", watch.describe(&state, self.chip8.memory()));
+        }
+        println!();
+    }
+
+    fn save_battery_ram(&self) {
+        if let Some(data) = self.chip8.battery_ram() {
+            if let Err(err) = persistence::save_battery_ram(self.chip8.rom_hash(), data) {
+                warn!("Couldn't save battery RAM: {}", err);
+            }
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Keys for the emulator's own controls, remappable through a hotkeys file.
+//!
+//! Each line of the file binds an action to a key by its SDL name, like `pause = P` or
+//! `fast-forward = Left Shift`; `#` starts a comment. Actions left out keep their default
+//! key. A hotkey may not take a key of the CHIP-8 keypads, since the game would stop
+//! seeing it, unless the binding ends with `!` to allow that, like `reset = V!`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use sdl2::keyboard::Scancode;
+
+use crate::overlay;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Unbound unless a hotkeys file binds it; the pause menu and closing the window quit
+    Quit,
+    /// Pauses the game behind a menu of actions and settings
+    Menu,
+    Tutor,
+    Step,
+    StepBack,
+    Keypad,
+    Picker,
+    ExportOcto,
+    SpriteViewer,
+    DebugWindow,
+    /// Turns the picture a quarter clockwise
+    Rotate,
+    /// Switches between the window and fullscreen on its display
+    Fullscreen,
+    ResumeAutosave,
+    Pause,
+    SaveState,
+    LoadState,
+    Screenshot,
+    Reset,
+    /// Runs faster while held
+    FastForward,
+    /// Asks the other player of remote play for control
+    TakeControl,
+    /// Types the clipboard's text on the keypad
+    PasteText,
+}
+
+impl Action {
+    const ALL: [Action; 21] = [
+        Action::Quit,
+        Action::Menu,
+        Action::Tutor,
+        Action::Step,
+        Action::StepBack,
+        Action::Keypad,
+        Action::Picker,
+        Action::ExportOcto,
+        Action::SpriteViewer,
+        Action::DebugWindow,
+        Action::Rotate,
+        Action::Fullscreen,
+        Action::ResumeAutosave,
+        Action::Pause,
+        Action::SaveState,
+        Action::LoadState,
+        Action::Screenshot,
+        Action::Reset,
+        Action::FastForward,
+        Action::TakeControl,
+        Action::PasteText,
+    ];
+
+    /// Name of the action in hotkeys files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Menu => "menu",
+            Action::Tutor => "tutor",
+            Action::Step => "step",
+            Action::StepBack => "step-back",
+            Action::Keypad => "keypad",
+            Action::Picker => "picker",
+            Action::ExportOcto => "export-octo",
+            Action::SpriteViewer => "sprite-viewer",
+            Action::DebugWindow => "debug-window",
+            Action::Rotate => "rotate",
+            Action::Fullscreen => "fullscreen",
+            Action::ResumeAutosave => "resume-autosave",
+            Action::Pause => "pause",
+            Action::SaveState => "save-state",
+            Action::LoadState => "load-state",
+            Action::Screenshot => "screenshot",
+            Action::Reset => "reset",
+            Action::FastForward => "fast-forward",
+            Action::TakeControl => "take-control",
+            Action::PasteText => "paste-text",
+        }
+    }
+
+    fn default_key(&self) -> Option<Scancode> {
+        let key = match self {
+            Action::Quit => return None,
+            Action::Menu => Scancode::Escape,
+            Action::Tutor => Scancode::F1,
+            Action::Step => Scancode::Space,
+            Action::StepBack => Scancode::Backspace,
+            Action::Keypad => Scancode::F2,
+            Action::Picker => Scancode::F3,
+            Action::ExportOcto => Scancode::F4,
+            Action::SpriteViewer => Scancode::F5,
+            Action::DebugWindow => Scancode::F10,
+            Action::Rotate => Scancode::F11,
+            Action::Fullscreen => Scancode::End,
+            Action::ResumeAutosave => Scancode::F6,
+            Action::SaveState => Scancode::F7,
+            Action::LoadState => Scancode::F8,
+            Action::Reset => Scancode::F9,
+            Action::Pause => Scancode::Pause,
+            Action::Screenshot => Scancode::F12,
+            Action::FastForward => Scancode::Tab,
+            Action::TakeControl => Scancode::Insert,
+            Action::PasteText => Scancode::Home,
+        };
+        Some(key)
+    }
+}
+
+/// A hotkeys file that couldn't be used, with the 1-based line of the problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotkeyError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for HotkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for HotkeyError {}
+
+#[derive(Debug, Clone)]
+pub struct Hotkeys {
+    keys: HashMap<Action, Scancode>,
+}
+
+impl Default for Hotkeys {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL
+                .iter()
+                .filter_map(|&action| Some((action, action.default_key()?)))
+                .collect(),
+        }
+    }
+}
+
+impl Hotkeys {
+    /// Hotkeys from the file at `path`, or the defaults if there's no file there
+    pub fn load(path: &Path, game_keys: &[Scancode]) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text, game_keys).map_err(|err| err.to_string()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// Apply the bindings in `text` over the defaults, refusing any that would take one of
+    /// `game_keys` without a `!`, or a key another action is bound to
+    pub fn parse(text: &str, game_keys: &[Scancode]) -> Result<Self, HotkeyError> {
+        let mut hotkeys = Self::default();
+        let mut shadowing = vec![];
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| HotkeyError {
+                line: index + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((name, key)) = line.split_once('=') else {
+                return Err(error(format!("expected 'action = key', found '{}'", line)));
+            };
+            let (name, key) = (name.trim(), key.trim());
+            let action = Action::ALL
+                .into_iter()
+                .find(|action| action.name() == name)
+                .ok_or_else(|| error(format!("unknown action '{}'", name)))?;
+            let (key, allow_shadowing) = match key.strip_suffix('!') {
+                Some(key) => (key.trim_end(), true),
+                None => (key, false),
+            };
+            let scancode =
+                Scancode::from_name(key).ok_or_else(|| error(format!("unknown key '{}'", key)))?;
+            if game_keys.contains(&scancode) && !allow_shadowing {
+                return Err(error(format!(
+                    "{} is a keypad key; write '{} = {}!' to take it from the game anyway",
+                    key, name, key
+                )));
+            }
+            hotkeys.keys.insert(action, scancode);
+            if allow_shadowing {
+                shadowing.push(scancode);
+            }
+        }
+
+        for (&action, &scancode) in &hotkeys.keys {
+            let clash = hotkeys
+                .keys
+                .iter()
+                .find(|&(&other, &key)| other != action && key == scancode);
+            if let Some((other, _)) = clash {
+                return Err(HotkeyError {
+                    line: 0,
+                    message: format!(
+                        "{} and {} are both bound to {}",
+                        action.name(),
+                        other.name(),
+                        scancode.name()
+                    ),
+                });
+            }
+            // A default binding can't shadow a game key either, only explicit ones can
+            if game_keys.contains(&scancode) && !shadowing.contains(&scancode) {
+                return Err(HotkeyError {
+                    line: 0,
+                    message: format!(
+                        "the default key of {}, {}, is a keypad key; bind it elsewhere",
+                        action.name(),
+                        scancode.name()
+                    ),
+                });
+            }
+        }
+        Ok(hotkeys)
+    }
+
+    /// The action bound to `scancode`, if any
+    pub fn action(&self, scancode: Scancode) -> Option<Action> {
+        self.keys
+            .iter()
+            .find(|&(_, &key)| key == scancode)
+            .map(|(&action, _)| action)
+    }
+
+    /// Name of the key bound to `action`, for hints
+    pub fn key_name(&self, action: Action) -> String {
+        match self.keys.get(&action) {
+            Some(&key) => overlay::host_key_name(key),
+            None => "(unbound)".to_string(),
+        }
+    }
+}
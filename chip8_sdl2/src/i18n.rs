@@ -0,0 +1,252 @@
+use clap::ValueEnum;
+
+use crate::overlay;
+use crate::pause_menu::Item;
+
+/// Languages the on-screen messages are translated to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Lang {
+    #[default]
+    En,
+    PtBr,
+    ZhCn,
+}
+
+impl Lang {
+    /// The language of the system locale, from the environment variables POSIX systems use
+    pub fn from_environment() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .map(|locale| Self::from_locale(&locale))
+            .unwrap_or_default()
+    }
+
+    /// The closest supported language to a locale like pt_BR.UTF-8
+    fn from_locale(locale: &str) -> Self {
+        let language = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "pt" => Lang::PtBr,
+            "zh" => Lang::ZhCn,
+            _ => Lang::En,
+        }
+    }
+}
+
+/// A message for the player, translated when it's shown
+#[derive(Debug, Clone)]
+pub enum Text {
+    /// Names of the keys that step, step back and leave tutor mode
+    TutorMode {
+        step: String,
+        back: String,
+        resume: String,
+    },
+    SavedState(u8),
+    LoadedState(u8),
+    /// The slot and why it couldn't be saved
+    SaveStateFailed(u8, String),
+    /// The slot and why it couldn't be loaded
+    LoadStateFailed(u8, String),
+    /// File name of the ROM
+    LoadedRom(String),
+    /// File name of the ROM, which changed on disk
+    ReloadedRom(String),
+    ExportedOcto,
+    OctoExportFailed(String),
+    /// A crash summary and the file name of the report, if it could be saved
+    Crashed {
+        summary: String,
+        report: Option<String>,
+    },
+    /// Suffix of the window title while paused
+    Paused,
+    /// Names of the keys a program waiting for input likely expects
+    ChooseKey(Vec<String>),
+    /// An autosave from the last run can be resumed; `crashed` if that run didn't exit
+    /// cleanly
+    ResumeOffer {
+        crashed: bool,
+        /// Name of the key that resumes it
+        key: String,
+    },
+    Resumed,
+    /// File name of the screenshot
+    SavedScreenshot(String),
+    /// Label of a line of the pause menu
+    MenuItem(Item),
+    /// Remote play passed control to this player
+    TookControl,
+    /// Remote play passed control to the other player
+    GaveControl,
+    /// The other player of remote play disconnected
+    RemoteLeft,
+    /// Number of keys being typed from the clipboard
+    Pasting(usize),
+    /// A screen trigger paused the game
+    ScreenTriggered(String),
+}
+
+impl Text {
+    pub fn localize(&self, lang: Lang) -> String {
+        match (self, lang) {
+            (Text::TutorMode { step, back, resume }, Lang::En) => format!(
+                "Tutor mode: {} to step, {} to go back, {} to resume",
+                step, back, resume
+            ),
+            (Text::TutorMode { step, back, resume }, Lang::PtBr) => format!(
+                "Modo tutor: {} avança, {} volta, {} continua",
+                step, back, resume
+            ),
+            (Text::TutorMode { step, back, resume }, Lang::ZhCn) => {
+                format!("教学模式：{} 单步，{} 后退，{} 继续", step, back, resume)
+            }
+            (Text::SavedState(slot), Lang::En) => format!("Saved state {}", slot),
+            (Text::SavedState(slot), Lang::PtBr) => format!("Estado {} salvo", slot),
+            (Text::SavedState(slot), Lang::ZhCn) => format!("已保存状态 {}", slot),
+            (Text::LoadedState(slot), Lang::En) => format!("Loaded state {}", slot),
+            (Text::LoadedState(slot), Lang::PtBr) => format!("Estado {} carregado", slot),
+            (Text::LoadedState(slot), Lang::ZhCn) => format!("已读取状态 {}", slot),
+            (Text::SaveStateFailed(slot, err), Lang::En) => {
+                format!("Couldn't save state {}: {}", slot, err)
+            }
+            (Text::SaveStateFailed(slot, err), Lang::PtBr) => {
+                format!("Não foi possível salvar o estado {}: {}", slot, err)
+            }
+            (Text::SaveStateFailed(slot, err), Lang::ZhCn) => {
+                format!("无法保存状态 {}：{}", slot, err)
+            }
+            (Text::LoadStateFailed(slot, err), Lang::En) => {
+                format!("Couldn't load state {}: {}", slot, err)
+            }
+            (Text::LoadStateFailed(slot, err), Lang::PtBr) => {
+                format!("Não foi possível carregar o estado {}: {}", slot, err)
+            }
+            (Text::LoadStateFailed(slot, err), Lang::ZhCn) => {
+                format!("无法读取状态 {}：{}", slot, err)
+            }
+            (Text::LoadedRom(name), Lang::En) => format!("Loaded {}", name),
+            (Text::LoadedRom(name), Lang::PtBr) => format!("{} carregado", name),
+            (Text::LoadedRom(name), Lang::ZhCn) => format!("已加载 {}", name),
+            (Text::ReloadedRom(name), Lang::En) => format!("Reloaded {}", name),
+            (Text::ReloadedRom(name), Lang::PtBr) => format!("{} recarregado", name),
+            (Text::ReloadedRom(name), Lang::ZhCn) => format!("已重新加载 {}", name),
+            (Text::ExportedOcto, Lang::En) => "Exported for Octo".to_string(),
+            (Text::ExportedOcto, Lang::PtBr) => "Exportado para o Octo".to_string(),
+            (Text::ExportedOcto, Lang::ZhCn) => "已导出到 Octo".to_string(),
+            (Text::OctoExportFailed(err), Lang::En) => {
+                format!("Couldn't export for Octo: {}", err)
+            }
+            (Text::OctoExportFailed(err), Lang::PtBr) => {
+                format!("Não foi possível exportar para o Octo: {}", err)
+            }
+            (Text::OctoExportFailed(err), Lang::ZhCn) => format!("无法导出到 Octo：{}", err),
+            (Text::Crashed { summary, report }, _) => match report {
+                Some(file_name) => {
+                    let saved = match lang {
+                        Lang::En => format!("Report saved to {}", file_name),
+                        Lang::PtBr => format!("Relatório salvo em {}", file_name),
+                        Lang::ZhCn => format!("报告已保存到 {}", file_name),
+                    };
+                    format!("{}\n{}", summary, saved)
+                }
+                None => summary.clone(),
+            },
+            (Text::Paused, Lang::En) => "paused".to_string(),
+            (Text::Paused, Lang::PtBr) => "pausado".to_string(),
+            (Text::Paused, Lang::ZhCn) => "已暂停".to_string(),
+            (Text::ChooseKey(keys), Lang::En) => format!("Press {} to choose", keys.join("/")),
+            (Text::ChooseKey(keys), Lang::PtBr) => {
+                format!("Pressione {} para escolher", keys.join("/"))
+            }
+            (Text::ChooseKey(keys), Lang::ZhCn) => format!("按 {} 选择", keys.join("/")),
+            (Text::ResumeOffer { crashed, key }, _) => match (crashed, lang) {
+                (false, Lang::En) => format!("Press {} to resume where you left off", key),
+                (false, Lang::PtBr) => format!("Pressione {} para continuar de onde parou", key),
+                (false, Lang::ZhCn) => format!("按 {} 从上次离开的地方继续", key),
+                (true, Lang::En) => format!(
+                    "The last session ended unexpectedly. Press {} to recover it",
+                    key
+                ),
+                (true, Lang::PtBr) => format!(
+                    "A última sessão terminou inesperadamente. Pressione {} para recuperá-la",
+                    key
+                ),
+                (true, Lang::ZhCn) => format!("上次会话意外结束。按 {} 恢复", key),
+            },
+            (Text::Resumed, Lang::En) => "Resumed the last session".to_string(),
+            (Text::Resumed, Lang::PtBr) => "Última sessão retomada".to_string(),
+            (Text::Resumed, Lang::ZhCn) => "已恢复上次会话".to_string(),
+            (Text::SavedScreenshot(name), Lang::En) => format!("Screenshot saved to {}", name),
+            (Text::SavedScreenshot(name), Lang::PtBr) => {
+                format!("Captura de tela salva em {}", name)
+            }
+            (Text::SavedScreenshot(name), Lang::ZhCn) => format!("截图已保存到 {}", name),
+            (Text::TookControl, Lang::En) => "Your turn".to_string(),
+            (Text::TookControl, Lang::PtBr) => "Sua vez".to_string(),
+            (Text::TookControl, Lang::ZhCn) => "轮到你了".to_string(),
+            (Text::GaveControl, Lang::En) => "The other player's turn".to_string(),
+            (Text::GaveControl, Lang::PtBr) => "Vez do outro jogador".to_string(),
+            (Text::GaveControl, Lang::ZhCn) => "轮到对方了".to_string(),
+            (Text::RemoteLeft, Lang::En) => "The other player left".to_string(),
+            (Text::RemoteLeft, Lang::PtBr) => "O outro jogador saiu".to_string(),
+            (Text::RemoteLeft, Lang::ZhCn) => "对方已离开".to_string(),
+            (Text::Pasting(keys), Lang::En) => format!("Typing {} keys", keys),
+            (Text::Pasting(keys), Lang::PtBr) => format!("Digitando {} teclas", keys),
+            (Text::Pasting(keys), Lang::ZhCn) => format!("正在输入 {} 个按键", keys),
+            (Text::ScreenTriggered(trigger), Lang::En) => format!("Paused on {}", trigger),
+            (Text::ScreenTriggered(trigger), Lang::PtBr) => format!("Pausado em {}", trigger),
+            (Text::ScreenTriggered(trigger), Lang::ZhCn) => format!("已暂停：{}", trigger),
+            (Text::MenuItem(item), _) => {
+                let label = match (item, lang) {
+                    (Item::Resume, Lang::En) => "Resume",
+                    (Item::Resume, Lang::PtBr) => "Continuar",
+                    (Item::Resume, Lang::ZhCn) => "继续",
+                    (Item::Restart, Lang::En) => "Restart",
+                    (Item::Restart, Lang::PtBr) => "Reiniciar",
+                    (Item::Restart, Lang::ZhCn) => "重新开始",
+                    (Item::SaveState, Lang::En) => "Save state",
+                    (Item::SaveState, Lang::PtBr) => "Salvar estado",
+                    (Item::SaveState, Lang::ZhCn) => "保存状态",
+                    (Item::LoadState, Lang::En) => "Load state",
+                    (Item::LoadState, Lang::PtBr) => "Carregar estado",
+                    (Item::LoadState, Lang::ZhCn) => "读取状态",
+                    (Item::Speed, Lang::En) => "Speed",
+                    (Item::Speed, Lang::PtBr) => "Velocidade",
+                    (Item::Speed, Lang::ZhCn) => "速度",
+                    (Item::Theme, Lang::En) => "Theme",
+                    (Item::Theme, Lang::PtBr) => "Tema",
+                    (Item::Theme, Lang::ZhCn) => "主题",
+                    (Item::Preset, Lang::En) => "Preset",
+                    (Item::Preset, Lang::PtBr) => "Predefinição",
+                    (Item::Preset, Lang::ZhCn) => "预设",
+                    (Item::ReduceFlashes, Lang::En) => "Reduce flashes",
+                    (Item::ReduceFlashes, Lang::PtBr) => "Reduzir flashes",
+                    (Item::ReduceFlashes, Lang::ZhCn) => "减少闪烁",
+                    (Item::CollisionCue, Lang::En) => "Collision sound",
+                    (Item::CollisionCue, Lang::PtBr) => "Som de colisão",
+                    (Item::CollisionCue, Lang::ZhCn) => "碰撞提示音",
+                    (Item::Quit, Lang::En) => "Quit",
+                    (Item::Quit, Lang::PtBr) => "Sair",
+                    (Item::Quit, Lang::ZhCn) => "退出",
+                };
+                label.to_string()
+            }
+        }
+    }
+
+    /// The translation if the overlay font can draw it, English otherwise, like for
+    /// Chinese characters the font has no wide glyphs for yet
+    pub fn for_overlay(&self, lang: Lang) -> String {
+        let text = self.localize(lang);
+        if overlay::can_draw(&text) {
+            text
+        } else {
+            self.localize(Lang::En)
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! What the emulator reports as it runs. Built with the `tracing` feature, messages go
+//! through `tracing` along with the core's own events, filtered by `--log-level` and
+//! written to stderr or `--log-file`; otherwise they are printed as they are.
+
+/// Report something worth knowing that went as it should
+macro_rules! info {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::info!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        println!($($arg)+);
+    }};
+}
+
+/// Report a problem the emulator carries on despite
+macro_rules! warn {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::warn!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        eprintln!($($arg)+);
+    }};
+}
+
+/// Report a problem the emulator can't carry on after
+macro_rules! error {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::error!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        eprintln!($($arg)+);
+    }};
+}
+
+/// Send the core's and the frontend's events at `level` and above to `file`, or to stderr
+#[cfg(feature = "tracing")]
+pub fn init(level: tracing::Level, file: Option<&std::path::Path>) -> std::io::Result<()> {
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false);
+    match file {
+        Some(path) => {
+            let file = std::fs::File::create(path)?;
+            subscriber.with_writer(std::sync::Mutex::new(file)).init();
+        }
+        None => subscriber.with_writer(std::io::stderr).init(),
+    }
+    Ok(())
+}
@@ -1,67 +1,653 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::mpsc;
 use std::time::Duration;
 
+use clap::Parser;
 use sdl2::{
-    event::Event,
+    event::{Event, WindowEvent},
     keyboard::Scancode,
-    pixels::Color, rect::Rect,
+    mouse::MouseButton,
+    pixels::{Color, PixelFormatEnum},
+    rect::Rect,
+    surface::Surface,
+    video::FullscreenType,
 };
 
-use chip8_core::{point_from_index, Chip8, PIXELS_PER_COLUMN, PIXELS_PER_ROW};
+use chip8_core::audio::Envelope;
+use chip8_core::banking::MAX_BANKED_ROM_SIZE;
+use chip8_core::disasm::SymbolTable;
+use chip8_core::flash_limit::{FlashLimiter, SAFE_FLASHES_PER_SECOND};
+use chip8_core::launcher::Launcher;
+use chip8_core::memory_layout::{MemoryLayout, Overlay};
+use chip8_core::octo::OctoOptions;
+use chip8_core::patch;
+use chip8_core::refresh::PartialRefresh;
+use chip8_core::rom_loader;
+use chip8_core::runner::{Frame, FrameTimer};
+use chip8_core::{
+    index_from_point, point_from_index, BlendConfig, Chip8, Chip8Event, ClockConfig, KeyBank,
+    Quirks, WriteProtection, MAX_ROM_SIZE, PIXELS_PER_COLUMN, PIXELS_PER_ROW,
+};
+
+use args::FocusLoss;
+use control::ControlCommand;
+use emulation::{Command, Notice};
+use hotkeys::{Action, Hotkeys};
+use i18n::{Lang, Text};
+use pause_menu::{Choice, PauseMenu};
+use remote_play::{Message, RemotePlay};
+use rom_watcher::RomWatcher;
+use settings::{Settings, WindowGeometry};
+
+#[macro_use]
+mod log;
+
+mod args;
+mod audio;
+mod control;
+mod debug_window;
+mod emulation;
+mod hotkeys;
+mod i18n;
+#[cfg(feature = "midi")]
+mod midi;
+mod motion_smoothing;
+mod orientation;
+mod osd;
+mod overlay;
+mod palette;
+mod pause_menu;
+mod persistence;
+mod recorder;
+mod remote_play;
+mod rom_watcher;
+mod rumble;
+mod serial_output;
+mod settings;
+mod smooth_scroll;
+mod sprite_viewer;
+mod tutor;
+mod video;
 
 const SQUARE_SIZE: u32 = 20;
 const SCREEN_WIDTH: u32 = PIXELS_PER_ROW as u32 * SQUARE_SIZE;
 const SCREEN_HEIGHT: u32 = PIXELS_PER_COLUMN as u32 * SQUARE_SIZE;
 
 const CATPPUCCIN_MOCHA_BASE: Color = Color::RGB(30, 30, 46);
-const CATPPUCCIN_MOCHA_YELLOW: Color = Color::RGB(249, 226, 175);
+const CATPPUCCIN_MOCHA_RED: Color = Color::RGB(243, 139, 168);
+
+const WINDOW_TITLE: &str = "CHIP-8 Emulator";
+/// Instructions kept for crash reports
+const TRACE_LENGTH: usize = 64;
+/// Longest the window waits for input while the machine is idle
+const IDLE_POLL_INTERVAL_MS: u32 = 250;
+/// How much faster the machine runs while the fast-forward hotkey is held
+const FAST_FORWARD_SPEED: f64 = 4.0;
+/// Save state slot the save and load hotkeys use
+const HOTKEY_STATE_SLOT: u8 = 0;
+
+/// The outcome of a state hotkey on its way back, and the message to show if it failed
+type StateReply = (
+    mpsc::Receiver<Result<String, String>>,
+    fn(u8, String) -> Text,
+);
 
 fn main() {
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-    let window = video_subsystem
-        .window("CHIP-8 Emulator", SCREEN_WIDTH, SCREEN_HEIGHT)
-        .position_centered()
-        .build()
-        .unwrap();
-    let mut canvas = window.into_canvas().build().unwrap();
-
-    
+    let mut args = args::Args::parse();
+    if args.dump_caps {
+        println!("{}", chip8_core::capabilities::capabilities());
+        return;
+    }
+    #[cfg(feature = "tracing")]
+    if let Err(err) = log::init(args.log_level, args.log_file.as_deref()) {
+        fail("Couldn't open the log file", err);
+    }
+    let rom_path = args
+        .rom
+        .clone()
+        .expect("clap requires a ROM without --dump-caps");
+    let lang = args.lang.unwrap_or_else(Lang::from_environment);
+    let mut settings = Settings::load(&persistence::settings_path());
+    // A preset on the command line wins over the one picked in the menu last time
+    match args.preset {
+        Some(preset) => settings.preset = Some(preset),
+        None => args.preset = settings.preset,
+    }
+    let hotkeys_path = args
+        .hotkeys
+        .clone()
+        .unwrap_or_else(persistence::hotkeys_path);
+    let game_keys = [KEYPAD_BINDINGS, SECOND_KEYPAD_BINDINGS].concat();
+    let hotkeys = Hotkeys::load(&hotkeys_path, &game_keys).unwrap_or_else(|err| {
+        warn!("Ignoring hotkeys {}: {}", hotkeys_path.display(), err);
+        Hotkeys::default()
+    });
+    let sdl_context = sdl2::init().unwrap_or_else(|err| fail("Couldn't initialise SDL", err));
+    let video_subsystem = sdl_context
+        .video()
+        .unwrap_or_else(|err| fail("Couldn't start the SDL video subsystem", err));
+    let mut orientation =
+        orientation::Orientation::new(args.rotate, args.mirror_horizontal, args.mirror_vertical);
+    let (window_width, window_height) = orientation.window_size();
+    let (position, (width, height)) = window_placement(
+        &video_subsystem,
+        args.monitor,
+        &settings,
+        (window_width, window_height),
+    );
+    let mut window = video_subsystem.window(WINDOW_TITLE, width, height);
+    window.resizable();
+    match position {
+        Some((x, y)) => window.position(x, y),
+        None => window.position_centered(),
+    };
+    let mut canvas = video::create_canvas(&window, args.renderer).unwrap_or_else(|err| {
+        error!("{}", err);
+        std::process::exit(1);
+    });
+    // The picture is scaled to whatever size the window is given
+    if let Err(err) = canvas.set_logical_size(window_width, window_height) {
+        warn!("Couldn't scale the picture to the window: {}", err);
+    }
+    if args.fullscreen || settings.fullscreen {
+        if let Err(err) = canvas.window_mut().set_fullscreen(FullscreenType::Desktop) {
+            warn!("Couldn't go fullscreen: {}", err);
+        }
+    }
+    let main_window_id = canvas.window().id();
+    // Everything is drawn upright here first, then turned into the window
+    let texture_creator = canvas.texture_creator();
+    let mut picture = texture_creator
+        .create_texture_target(None, SCREEN_WIDTH, SCREEN_HEIGHT)
+        .unwrap_or_else(|err| fail("Couldn't create the screen texture", err));
+
     canvas.set_draw_color(CATPPUCCIN_MOCHA_BASE);
     canvas.clear();
     canvas.present();
-    let mut event_pump = sdl_context.event_pump().unwrap();
-    let mut chip8 = Chip8::new();
-    let instructions_per_frame = 5;
-    let rom =
-            // std::fs::read("/mnt/Demoiselle/游戏/ROMs/CHIP-8/games/Pong (1 player).ch8").unwrap();
-            std::fs::read("./ROMs/test/5-quirks.ch8").unwrap();
-    chip8.load_rom(&rom);
+    let mut event_pump = sdl_context
+        .event_pump()
+        .unwrap_or_else(|err| fail("Couldn't start the SDL event pump", err));
+    let mut beeper = audio::Beeper::open(
+        &sdl_context,
+        &audio::AudioConfig {
+            driver: args.audio_driver.as_deref(),
+            device: args.audio_device.as_deref(),
+            buffer_size: args.audio_buffer,
+            pitch: args.beep_pitch,
+            min_beep: Duration::from_millis(args.min_beep),
+            envelope: Envelope {
+                attack: Duration::from_millis(args.beep_attack),
+                release: Duration::from_millis(args.beep_release),
+            },
+        },
+    );
+    let mut rumble = args.rumble.map(|intensity| {
+        let subsystem = sdl_context
+            .game_controller()
+            .unwrap_or_else(|err| fail("Couldn't start the SDL game controller subsystem", err));
+        rumble::Rumble::new(subsystem, intensity, args.rumble_duration)
+    });
+    #[cfg(feature = "midi")]
+    let mut midi = args.midi_port.as_deref().and_then(|port| {
+        let min_beep = Duration::from_millis(args.min_beep);
+        midi::MidiBuzzer::open(port, args.midi_channel - 1, args.midi_note, min_beep)
+            .map_err(|err| warn!("No MIDI output: {}", err))
+            .ok()
+    });
+    // Bundles made by chip8-launcher start with a menu that swaps the chosen game in
+    let launcher = std::fs::read(&rom_path)
+        .ok()
+        .and_then(|data| Launcher::from_bundle(&data));
+    let loaded = match &launcher {
+        Some(launcher) => Ok(launcher.menu().to_vec()),
+        None => rom_loader::load_rom_file_up_to(&rom_path, max_rom_size(&args)),
+    };
+    let mut rom = match loaded {
+        Ok(rom) => rom,
+        Err(err) => {
+            error!("Couldn't load {}: {}", rom_path.display(), err);
+            std::process::exit(1);
+        }
+    };
+    if let Some(path) = &args.patch {
+        let patch = std::fs::read(path).unwrap_or_else(|err| {
+            error!("Couldn't read {}: {}", path.display(), err);
+            std::process::exit(1);
+        });
+        rom = match patch::apply_patch(&rom, &patch) {
+            Ok(patched) => {
+                for conflict in &patched.conflicts {
+                    warn!(
+                        "Patch records overlap at 0x{:03X}..0x{:03X}; the later one wins",
+                        conflict.start, conflict.end
+                    );
+                }
+                patched.rom
+            }
+            Err(err) => {
+                error!("Couldn't apply {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        };
+    }
+    if args.self_modifying {
+        if let Err(err) = persistence::mark_self_modifying(chip8_core::rom_hash(&rom)) {
+            warn!("Couldn't remember that the ROM is self-modifying: {}", err);
+        }
+    }
+    let overlays = match load_overlays(&args, &rom) {
+        Ok(overlays) => overlays,
+        Err(err) => {
+            error!("Couldn't load {}: {}", rom_path.display(), err);
+            std::process::exit(1);
+        }
+    };
+    let mut chip8 = build_machine(&args, &rom, &overlays);
+    // The symbols describe the ROM given on the command line, not ones loaded later
+    let mut symbols = load_symbols(&args);
+    chip8.set_symbols(annotated(symbols.as_ref(), chip8.rom_hash()));
+    let control = args.control.as_deref().and_then(|address| {
+        control::ControlServer::start(address)
+            .map_err(|err| error!("Couldn't listen for commands on {}: {}", address, err))
+            .ok()
+    });
+    let event_subsystem = sdl_context
+        .event()
+        .unwrap_or_else(|err| fail("Couldn't start the SDL event subsystem", err));
+    event_subsystem
+        .register_custom_event::<emulation::FrameReady>()
+        .unwrap_or_else(|err| fail("Couldn't register SDL's frame event", err));
+    let recorder = args.record_video.as_deref().and_then(|path| {
+        recorder::FrameRecorder::create(path, settings.theme)
+            .map_err(|err| warn!("Not recording {}: {}", path.display(), err))
+            .ok()
+    });
+    let mut serial = args.serial.as_deref().and_then(|path| {
+        serial_output::SerialOutput::open(path)
+            .map_err(|err| warn!("Not mirroring the screen to {}: {}", path.display(), err))
+            .ok()
+    });
+    let autosave_interval = (args.autosave > 0).then(|| Duration::from_secs(args.autosave.into()));
+    // The last run's autosave can be resumed until the next autosave replaces it
+    let last_session = persistence::last_session(chip8.rom_hash()).filter(|_| args.autosave > 0);
+    let mut emulation = emulation::Emulation::spawn(
+        chip8,
+        event_subsystem.event_sender(),
+        recorder,
+        launcher,
+        autosave_interval,
+    );
+    for watch in &args.watch_expr {
+        emulation.send(Command::Watch(watch.clone()));
+    }
+    let mut render_timer = FrameTimer::at_frame_rate();
+    // The reset hotkey starts this ROM over
+    let mut current_rom = rom;
+    let mut current_overlays = overlays;
+    let mut tutor_mode = false;
+    let mut paused = false;
+    let mut remote = match (args.host_remote, &args.join_remote) {
+        (Some(port), _) => {
+            info!("Waiting for the other player to join on port {}", port);
+            Some(RemotePlay::host(port))
+        }
+        (None, Some(address)) => Some(RemotePlay::join(address)),
+        (None, None) => None,
+    }
+    .map(|remote| remote.unwrap_or_else(|err| fail("Couldn't start remote play", err)));
+    // Whoever joins watches the host play first
+    if remote.as_ref().is_some_and(|remote| !remote.in_control()) {
+        paused = true;
+        emulation.send(Command::SetPaused(true));
+    }
+    // Speed set through the control socket, restored when fast-forwarding stops
+    let mut speed = settings.speed;
+    if speed != 1.0 {
+        emulation.send(Command::SetSpeed(speed));
+    }
+    let mut show_keypad = false;
+    // The pixel picker shows the pixel under the mouse and toggles it on click
+    let mut show_picker = false;
+    let mut hovered_pixel = None;
+    // Replaces the screen with a view of memory as sprites while open
+    let mut sprite_viewer: Option<sprite_viewer::SpriteViewer> = None;
+    // Pauses the game while open, whatever `paused` says
+    let mut pause_menu: Option<PauseMenu> = None;
+    let mut debug_window = None;
+    if args.debug_window {
+        debug_window = open_debug_window(&video_subsystem, args.renderer);
+    }
+    let mut osd = osd::Osd::default();
+    let mut resume_offered = false;
+    let mut state_replies: Vec<StateReply> = vec![];
+    if let Some(session) = last_session {
+        let offer = Text::ResumeOffer {
+            crashed: !session.clean_exit,
+            key: hotkeys.key_name(Action::ResumeAutosave),
+        };
+        println!("{}", offer.localize(lang));
+        osd.show(offer.for_overlay(lang));
+        resume_offered = true;
+    }
+    let mut smooth_scroll = smooth_scroll::SmoothScroll::default();
+    let mut motion_smoothing = motion_smoothing::MotionSmoothing::default();
+    let mut keys = [false; 16];
+    let mut second_keys = [false; 16];
+    let mut should_redraw = true;
+    // Instructions already reported for writing to protected memory
+    let mut illegal_writers = HashSet::new();
+    // And for reading memory nothing wrote
+    let mut uninitialized_readers = HashSet::new();
+    // And addresses already reported for being executed at by a stray jump or return
+    let mut stray_executions = HashSet::new();
+    // With --max-refresh-rate, what decides the refreshes and the frame they last showed
+    let mut partial_refresh = args
+        .max_refresh_rate
+        .map(|rate| (PartialRefresh::new(rate), Box::<Frame>::default()));
+    // With flashes reduced, what decides the frames shown and the frame last shown
+    let mut flash_limit = settings.reduce_flashes.then(new_flash_limit);
+    // Event that ended the last wait for input, still to be handled
+    let mut waited_event = None;
+    let mut rom_watcher = args.watch.then(|| RomWatcher::new(rom_path.clone()));
     'running: loop {
         // Parse events
-        let mut new_frame_keys = chip8.keypad.current_frame_keys;
-        for event in event_pump.poll_iter() {
+        let mut new_frame_keys = keys;
+        let mut new_second_keys = second_keys;
+        for event in waited_event.take().into_iter().chain(event_pump.poll_iter()) {
             match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    scancode: Some(Scancode::Escape),
+                Event::Quit { .. } => break 'running,
+                Event::ControllerDeviceAdded { which, .. } => {
+                    if let Some(rumble) = &mut rumble {
+                        rumble.connected(which);
+                    }
+                }
+                Event::ControllerDeviceRemoved { which, .. } => {
+                    if let Some(rumble) = &mut rumble {
+                        rumble.disconnected(which);
+                    }
+                }
+                // With the debug window open, closing either window doesn't quit by itself
+                Event::Window {
+                    window_id,
+                    win_event: WindowEvent::Close,
                     ..
                 } => {
-                    break 'running;
+                    if window_id == main_window_id {
+                        break 'running;
+                    }
+                    if debug_window.as_ref().map(debug_window::DebugWindow::id) == Some(window_id) {
+                        debug_window = None;
+                    }
+                }
+                // The debug window gaining or losing focus says nothing about the game's
+                Event::Window {
+                    window_id,
+                    win_event: WindowEvent::FocusLost,
+                    ..
+                } if window_id == main_window_id => {
+                    emulation.send(Command::SetBackground(Some(args.focus_loss)));
+                    // Key releases won't reach us while unfocused
+                    new_frame_keys = [false; 16];
+                    new_second_keys = [false; 16];
+                    if args.focus_loss == FocusLoss::Pause {
+                        show_paused(&mut canvas, true, lang);
+                    }
+                }
+                Event::Window {
+                    window_id,
+                    win_event: WindowEvent::FocusGained,
+                    ..
+                } if window_id == main_window_id => {
+                    emulation.send(Command::SetBackground(None));
+                    if !tutor_mode && !paused {
+                        canvas.window_mut().set_title(WINDOW_TITLE).unwrap();
+                    }
+                }
+                Event::MouseMotion {
+                    window_id, x, y, ..
+                } if show_picker && window_id == main_window_id => {
+                    hovered_pixel = pixel_at(&orientation, x, y);
+                    should_redraw = true;
+                }
+                Event::MouseButtonDown {
+                    window_id,
+                    mouse_btn: MouseButton::Left,
+                    x,
+                    y,
+                    ..
+                } if show_picker && window_id == main_window_id => {
+                    if let Some((x, y)) = pixel_at(&orientation, x, y) {
+                        emulation.send(Command::TogglePixel { x, y });
+                    }
                 }
                 Event::KeyDown {
+                    window_id,
                     scancode: Some(scancode),
+                    repeat,
                     ..
                 } => {
+                    // The menu takes every key while it's open, and picks some of the actions
+                    let menu_choice =
+                        pause_menu
+                            .as_mut()
+                            .map(|menu| match hotkeys.action(scancode) {
+                                Some(Action::Menu) => Some(Choice::Resume),
+                                _ => menu.handle_key(scancode),
+                            });
+                    let action = match menu_choice {
+                        Some(choice) => {
+                            should_redraw = true;
+                            let choice = match choice {
+                                Some(Choice::Settings(changed)) => {
+                                    apply_settings(&emulation, &settings, &changed);
+                                    if changed.reduce_flashes != settings.reduce_flashes {
+                                        flash_limit = changed.reduce_flashes.then(new_flash_limit);
+                                    }
+                                    speed = changed.speed;
+                                    args.preset = changed.preset;
+                                    settings = changed;
+                                    if let Err(err) = settings.save(&persistence::settings_path()) {
+                                        warn!("Couldn't save the settings: {}", err);
+                                    }
+                                    continue;
+                                }
+                                Some(choice) => choice,
+                                None => continue,
+                            };
+                            pause_menu = None;
+                            emulation.send(Command::SetPaused(paused));
+                            match choice {
+                                Choice::Restart => Some(Action::Reset),
+                                Choice::SaveState => Some(Action::SaveState),
+                                Choice::LoadState => Some(Action::LoadState),
+                                Choice::Quit => Some(Action::Quit),
+                                Choice::Resume | Choice::Settings(_) => continue,
+                            }
+                        }
+                        // Stepping and resuming only take their key while there's something
+                        // to do
+                        None => hotkeys.action(scancode).filter(|action| match action {
+                            Action::Step | Action::StepBack => tutor_mode,
+                            Action::ResumeAutosave => resume_offered,
+                            _ => true,
+                        }),
+                    };
+                    if let Some(action) = action {
+                        // Only steps repeat while their key is held
+                        if repeat && !matches!(action, Action::Step | Action::StepBack) {
+                            continue;
+                        }
+                        match action {
+                            Action::Quit => break 'running,
+                            Action::Menu => {
+                                pause_menu = Some(PauseMenu::new(Settings { speed, ..settings }));
+                                emulation.send(Command::SetPaused(true));
+                            }
+                            Action::Tutor => {
+                                tutor_mode = !tutor_mode;
+                                if tutor_mode {
+                                    let hint = Text::TutorMode {
+                                        step: hotkeys.key_name(Action::Step),
+                                        back: hotkeys.key_name(Action::StepBack),
+                                        resume: hotkeys.key_name(Action::Tutor),
+                                    };
+                                    println!("{}", hint.localize(lang));
+                                    osd.show(hint.for_overlay(lang));
+                                } else {
+                                    show_paused(&mut canvas, paused, lang);
+                                }
+                                emulation.send(Command::SetTutor(tutor_mode));
+                            }
+                            Action::Step => emulation.send(Command::Step),
+                            Action::StepBack => emulation.send(Command::StepBack),
+                            Action::Keypad => show_keypad = !show_keypad,
+                            Action::Picker => show_picker = !show_picker,
+                            Action::ExportOcto => emulation.send(Command::ExportOcto),
+                            Action::SpriteViewer => {
+                                sprite_viewer = match sprite_viewer {
+                                    Some(_) => None,
+                                    None => Some(sprite_viewer::SpriteViewer::new(
+                                        emulation.frame().cpu_state.i,
+                                    )),
+                                };
+                            }
+                            Action::DebugWindow => {
+                                debug_window = match debug_window {
+                                    Some(_) => None,
+                                    None => open_debug_window(&video_subsystem, args.renderer),
+                                };
+                            }
+                            Action::Fullscreen => {
+                                let window = canvas.window_mut();
+                                let fullscreen = match window.fullscreen_state() {
+                                    FullscreenType::Off => FullscreenType::Desktop,
+                                    _ => FullscreenType::Off,
+                                };
+                                if let Err(err) = window.set_fullscreen(fullscreen) {
+                                    warn!("Couldn't switch fullscreen: {}", err);
+                                }
+                            }
+                            Action::Rotate => {
+                                orientation.rotate_clockwise();
+                                let (width, height) = orientation.window_size();
+                                if let Err(err) = canvas.set_logical_size(width, height) {
+                                    warn!("Couldn't scale the picture to the window: {}", err);
+                                }
+                                let window = canvas.window_mut();
+                                if window.fullscreen_state() == FullscreenType::Off {
+                                    if let Err(err) = window.set_size(width, height) {
+                                        warn!("Couldn't resize the window: {}", err);
+                                    }
+                                }
+                                // Held d-pad keys would be released as different keys
+                                new_frame_keys = [false; 16];
+                                new_second_keys = [false; 16];
+                            }
+                            Action::ResumeAutosave => {
+                                emulation.send(Command::ResumeAutosave);
+                                resume_offered = false;
+                            }
+                            Action::Pause => {
+                                paused = !paused;
+                                emulation.send(Command::SetPaused(paused));
+                                show_paused(&mut canvas, paused && !tutor_mode, lang);
+                            }
+                            // Successes show on screen by themselves, failures once the
+                            // reply comes back
+                            Action::SaveState => {
+                                let (reply, outcome) = mpsc::channel();
+                                emulation.send(Command::SaveState {
+                                    slot: HOTKEY_STATE_SLOT,
+                                    reply,
+                                });
+                                state_replies.push((outcome, Text::SaveStateFailed));
+                            }
+                            Action::LoadState => {
+                                let (reply, outcome) = mpsc::channel();
+                                emulation.send(Command::LoadState {
+                                    slot: HOTKEY_STATE_SLOT,
+                                    reply,
+                                });
+                                state_replies.push((outcome, Text::LoadStateFailed));
+                            }
+                            Action::Screenshot => {
+                                let rom_hash = chip8_core::rom_hash(&current_rom);
+                                let saved = persistence::screenshot_path(rom_hash)
+                                    .map_err(|err| err.to_string())
+                                    .and_then(|path| {
+                                        save_screenshot(emulation.frame(), settings.theme, &path)
+                                            .map(|()| path)
+                                    });
+                                match saved {
+                                    Ok(path) => {
+                                        info!("Screenshot saved to {}", path.display());
+                                        let file_name = path.file_name().unwrap_or_default();
+                                        let text = Text::SavedScreenshot(
+                                            file_name.to_string_lossy().into_owned(),
+                                        );
+                                        osd.show(text.for_overlay(lang));
+                                    }
+                                    Err(err) => warn!("Couldn't save a screenshot: {}", err),
+                                }
+                            }
+                            Action::Reset => {
+                                let mut chip8 =
+                                    build_machine(&args, &current_rom, &current_overlays);
+                                chip8.set_symbols(annotated(symbols.as_ref(), chip8.rom_hash()));
+                                emulation.send(Command::Replace(Box::new(chip8)));
+                            }
+                            Action::FastForward => {
+                                emulation.send(Command::SetSpeed(FAST_FORWARD_SPEED));
+                            }
+                            Action::TakeControl => {
+                                if let Some(remote) = remote.as_ref().filter(|r| !r.in_control()) {
+                                    remote.send(Message::RequestControl);
+                                }
+                            }
+                            Action::PasteText => {
+                                match video_subsystem.clipboard().clipboard_text() {
+                                    Ok(text) => {
+                                        let keys = args.paste_encoding.encode(&text);
+                                        osd.show(Text::Pasting(keys.len()).for_overlay(lang));
+                                        emulation.send(Command::Type(keys));
+                                    }
+                                    Err(err) => warn!("Couldn't read the clipboard: {}", err),
+                                }
+                            }
+                        }
+                        should_redraw = true;
+                        continue;
+                    }
+                    // Hotkeys work in every window, the game only gets keys from its own
+                    if window_id != main_window_id {
+                        continue;
+                    }
+                    if let Some(viewer) = &mut sprite_viewer {
+                        if viewer.handle_key(scancode) {
+                            should_redraw = true;
+                            continue;
+                        }
+                    }
                     if let Some(key) = get_keypad_button_from_scancode(scancode) {
-                        new_frame_keys[key] = true;
+                        new_frame_keys[orientation.keypad_key(key)] = true;
+                    }
+                    if let Some(key) = second_keypad_button_from_scancode(scancode) {
+                        new_second_keys[orientation.keypad_key(key)] = true;
                     }
                 }
                 Event::KeyUp {
                     scancode: Some(scancode),
                     ..
                 } => {
+                    if hotkeys.action(scancode) == Some(Action::FastForward) {
+                        emulation.send(Command::SetSpeed(speed));
+                    }
                     if let Some(key) = get_keypad_button_from_scancode(scancode) {
-                        new_frame_keys[key] = false;
+                        new_frame_keys[orientation.keypad_key(key)] = false;
+                    }
+                    if let Some(key) = second_keypad_button_from_scancode(scancode) {
+                        new_second_keys[orientation.keypad_key(key)] = false;
                     }
                 }
                 _ => {}
@@ -69,42 +655,771 @@ fn main() {
         }
 
         // Update keys
-        chip8.keypad.update_keys(new_frame_keys);
+        if new_frame_keys != keys || new_second_keys != second_keys {
+            keys = new_frame_keys;
+            second_keys = new_second_keys;
+            emulation.send(Command::Keys {
+                first: keys,
+                second: second_keys,
+            });
+        }
+
+        for notice in emulation.notices() {
+            match notice {
+                Notice::Show(text) => osd.show(text.for_overlay(lang)),
+                Notice::ShowPersistent(text) => osd.show_persistent(text.for_overlay(lang)),
+                Notice::Speed(speed) => beeper.set_playback_speed(speed),
+                Notice::Event(Chip8Event::Scrolled { dx, dy }) if args.smooth_scroll => {
+                    smooth_scroll.scrolled(dx, dy)
+                }
+                Notice::Event(Chip8Event::Scrolled { .. }) => {}
+                // E-paper keeps ghosts of what it showed until it's fully refreshed
+                Notice::Event(Chip8Event::ScreenCleared { .. }) => {
+                    if let Some((refresh, _)) = &mut partial_refresh {
+                        refresh.request_full_refresh();
+                    }
+                }
+                Notice::Event(Chip8Event::SpriteDrawn {
+                    addr,
+                    x,
+                    y,
+                    width,
+                    height,
+                    collided,
+                }) => {
+                    if args.smooth_motion {
+                        motion_smoothing.sprite_drawn(addr, x, y, width, height);
+                    }
+                    if collided && settings.collision_cue {
+                        beeper.cue();
+                    }
+                }
+                // Beeps can start and stop between two frames; the edges still reach the
+                // buzzer, which holds them long enough to hear
+                Notice::Event(Chip8Event::SoundStarted { .. }) => {
+                    beeper.set_beeping(true);
+                    if let Some(rumble) = &mut rumble {
+                        rumble.pulse();
+                    }
+                    #[cfg(feature = "midi")]
+                    if let Some(midi) = &mut midi {
+                        midi.set_beeping(true);
+                    }
+                }
+                Notice::Event(Chip8Event::SoundStopped) => {}
+                // Frames carry the prompt itself
+                Notice::Event(Chip8Event::WaitingForKey { .. }) => {}
+                Notice::Event(Chip8Event::IllegalWrite { pc, addr }) => {
+                    if illegal_writers.insert(pc) {
+                        warn!(
+                            "Instruction at 0x{:03X} wrote to protected address 0x{:03X}; \
+                             pass --self-modifying if the ROM changes its own code",
+                            pc, addr
+                        );
+                    }
+                }
+                Notice::Event(Chip8Event::StrayExecution { pc }) => {
+                    if stray_executions.insert(pc) {
+                        warn!(
+                            "Executing at 0x{:03X}, {}; a jump or return likely went astray",
+                            pc,
+                            if pc % 2 == 1 { "an odd address" } else { "below the program" }
+                        );
+                    }
+                }
+                // The emulation thread paused itself already
+                Notice::Event(Chip8Event::ScreenTriggered { pc, trigger }) => {
+                    info!("Screen trigger '{}' fired at 0x{:03X}", trigger, pc);
+                    paused = true;
+                    show_paused(&mut canvas, !tutor_mode, lang);
+                    osd.show(Text::ScreenTriggered(trigger.to_string()).for_overlay(lang));
+                }
+                Notice::Event(Chip8Event::UninitializedRead { pc, addr }) => {
+                    if uninitialized_readers.insert(pc) {
+                        warn!(
+                            "Instruction at 0x{:03X} read 0x{:03X} before anything wrote it",
+                            pc, addr
+                        );
+                    }
+                }
+            }
+            should_redraw = true;
+        }
+        state_replies.retain(|(outcome, failed)| match outcome.try_recv() {
+            Ok(Err(err)) => {
+                osd.show(failed(HOTKEY_STATE_SLOT, err).for_overlay(lang));
+                false
+            }
+            Ok(Ok(_)) | Err(mpsc::TryRecvError::Disconnected) => false,
+            Err(mpsc::TryRecvError::Empty) => true,
+        });
+
+        // Frames the emulation thread finished since the last look; older ones are skipped.
+        // Slow displays only show them on their next refresh.
+        if emulation.update_frame() {
+            if args.smooth_motion {
+                motion_smoothing.frame_finished(emulation.frame().number);
+            }
+            if partial_refresh.is_none() {
+                should_redraw = true;
+            }
+        }
+        if let Some(serial) = &mut serial {
+            serial.show(&emulation.frame().screen);
+        }
+
+        if let Some(playing) = &mut remote {
+            let messages = playing.messages().collect::<Vec<_>>();
+            let mut left = false;
+            for message in messages {
+                match message {
+                    Message::State(state) if !playing.in_control() => {
+                        emulation.send(Command::Restore(state));
+                    }
+                    Message::RequestControl if playing.in_control() => {
+                        playing.set_in_control(false);
+                        paused = true;
+                        emulation.send(Command::SetPaused(true));
+                        let sender = playing.sender();
+                        emulation.send(Command::Snapshot(Box::new(move |state| {
+                            let _ = sender.send(Message::Handover(state));
+                        })));
+                        osd.show(Text::GaveControl.for_overlay(lang));
+                    }
+                    Message::State(_) | Message::RequestControl => {}
+                    Message::Handover(state) => {
+                        emulation.send(Command::Restore(state));
+                        playing.set_in_control(true);
+                        paused = false;
+                        emulation.send(Command::SetPaused(false));
+                        osd.show(Text::TookControl.for_overlay(lang));
+                    }
+                    Message::Left => {
+                        left = true;
+                        warn!("The other player left");
+                        osd.show(Text::RemoteLeft.for_overlay(lang));
+                        if !playing.in_control() {
+                            paused = false;
+                            emulation.send(Command::SetPaused(false));
+                        }
+                    }
+                }
+            }
+            if left {
+                remote = None;
+            } else if playing.push_due() {
+                let sender = playing.sender();
+                emulation.send(Command::Snapshot(Box::new(move |state| {
+                    let _ = sender.send(Message::State(state));
+                })));
+            }
+        }
+
+        let rom_changed = rom_watcher.as_mut().is_some_and(RomWatcher::changed);
+        if let Some(watcher) = rom_watcher.as_ref().filter(|_| rom_changed) {
+            let path = watcher.path();
+            let loaded = rom_loader::load_rom_file_up_to(path, max_rom_size(&args))
+                .map_err(|err| err.to_string())
+                .and_then(|rom| check_layout(&args, &rom, &current_overlays).map(|_| rom));
+            match loaded {
+                Ok(rom) => {
+                    // The symbols were likely rebuilt along with the ROM
+                    if path == rom_path {
+                        symbols = load_symbols(&args);
+                    }
+                    let mut chip8 = build_machine(&args, &rom, &current_overlays);
+                    chip8.set_symbols(annotated(symbols.as_ref(), chip8.rom_hash()));
+                    emulation.send(Command::Reload(Box::new(chip8)));
+                    current_rom = rom;
+                    info!("Reloaded {}", path.display());
+                    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                    osd.show(Text::ReloadedRom(file_name.into_owned()).for_overlay(lang));
+                }
+                // The machine built before goes on running
+                Err(err) => warn!("Couldn't reload {}: {}", path.display(), err),
+            }
+        }
+
+        for request in control.iter().flat_map(|control| control.requests()) {
+            let outcome = match request.command {
+                ControlCommand::Pause => {
+                    paused = true;
+                    emulation.send(Command::SetPaused(true));
+                    Ok(String::new())
+                }
+                ControlCommand::Resume => {
+                    paused = false;
+                    emulation.send(Command::SetPaused(false));
+                    Ok(String::new())
+                }
+                ControlCommand::SetSpeed(new_speed) => {
+                    speed = new_speed;
+                    emulation.send(Command::SetSpeed(speed));
+                    Ok(String::new())
+                }
+                ControlCommand::LoadRom(path) => {
+                    match rom_loader::load_rom_file_up_to(&path, max_rom_size(&args)) {
+                        Ok(rom) => {
+                            // The overlays belong to the ROM given on the command line
+                            let mut chip8 = build_machine(&args, &rom, &[]);
+                            chip8.set_symbols(annotated(None, chip8.rom_hash()));
+                            emulation.send(Command::Replace(Box::new(chip8)));
+                            current_rom = rom;
+                            current_overlays = vec![];
+                            symbols = None;
+                            if let Some(watcher) = &mut rom_watcher {
+                                *watcher = RomWatcher::new(path.clone());
+                            }
+                            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                            osd.show(Text::LoadedRom(file_name.into_owned()).for_overlay(lang));
+                            Ok(String::new())
+                        }
+                        Err(err) => Err(format!("couldn't read {}: {}", path.display(), err)),
+                    }
+                }
+                // The emulation thread owns the machine, so it answers these itself
+                ControlCommand::SaveState(slot) => {
+                    emulation.send(Command::SaveState {
+                        slot,
+                        reply: request.reply,
+                    });
+                    continue;
+                }
+                ControlCommand::LoadState(slot) => {
+                    emulation.send(Command::LoadState {
+                        slot,
+                        reply: request.reply,
+                    });
+                    continue;
+                }
+                ControlCommand::Screenshot(path) => {
+                    save_screenshot(emulation.frame(), settings.theme, &path)
+                        .map(|()| path.display().to_string())
+                }
+                ControlCommand::Watch(watch) => {
+                    emulation.send(Command::Watch(watch));
+                    Ok(String::new())
+                }
+                ControlCommand::Unwatch => {
+                    emulation.send(Command::ClearWatches);
+                    Ok(String::new())
+                }
+                ControlCommand::Search(step) => {
+                    emulation.send(Command::Search {
+                        step,
+                        reply: request.reply,
+                    });
+                    continue;
+                }
+                ControlCommand::StepOver => {
+                    emulation.send(Command::StepOver(request.reply));
+                    continue;
+                }
+                ControlCommand::StepOut => {
+                    emulation.send(Command::StepOut(request.reply));
+                    continue;
+                }
+                ControlCommand::BreakOn(trigger) => {
+                    emulation.send(Command::AddScreenTrigger(trigger));
+                    Ok(String::new())
+                }
+                ControlCommand::ClearBreaks => {
+                    emulation.send(Command::ClearScreenTriggers);
+                    Ok(String::new())
+                }
+                ControlCommand::Annotate(entry) => {
+                    let rom_hash = chip8_core::rom_hash(&current_rom);
+                    match persistence::add_annotation(rom_hash, &entry) {
+                        Ok(path) => {
+                            let symbols = annotated(symbols.as_ref(), rom_hash);
+                            emulation.send(Command::SetSymbols(symbols));
+                            Ok(path.display().to_string())
+                        }
+                        Err(err) => Err(format!("couldn't save the annotation: {}", err)),
+                    }
+                }
+            };
+            let _ = request.reply.send(outcome);
+        }
+        beeper.set_beeping(emulation.frame().beeping);
+        #[cfg(feature = "midi")]
+        if let Some(midi) = &mut midi {
+            midi.set_beeping(emulation.frame().beeping);
+        }
+        let frame = match &mut partial_refresh {
+            Some((refresh, shown)) => {
+                let latest = emulation.frame();
+                if !refresh.refresh(&latest.screen, latest.number).is_empty() {
+                    (**shown).clone_from(latest);
+                    should_redraw = true;
+                }
+                shown
+            }
+            None => emulation.frame(),
+        };
+        // Strobing games are slowed down to safe flashes, the screen catching up in between
+        let mut held_back = false;
+        let frame = match &mut flash_limit {
+            Some((limiter, shown)) if frame.number != shown.number => {
+                if limiter.allow(&frame.brightness, frame.number) {
+                    (**shown).clone_from(frame);
+                    should_redraw = true;
+                } else {
+                    held_back = true;
+                }
+                shown
+            }
+            Some((_, shown)) => shown,
+            None => frame,
+        };
+
+        let sliding = motion_smoothing.is_animating();
+        if smooth_scroll.is_animating() || sliding {
+            should_redraw = true;
+        }
 
-        
-        // Tick emulator
-        for _ in 0..instructions_per_frame {
-            chip8.tick();
+        // Redraw while messages come and go
+        if osd.update() || osd.is_visible() {
+            should_redraw = true;
+        }
+
+        // Nothing will change on screen until there is input
+        let idle = frame.idle
+            && !held_back
+            && !smooth_scroll.is_animating()
+            && !sliding
+            && !osd.is_visible();
+
+        if let Some(debug_window) = debug_window.as_mut().filter(|_| should_redraw) {
+            debug_window.draw(frame);
         }
 
         // Draw screen if needed
-        if chip8.should_redraw {
-            // Clear screen
-            canvas.set_draw_color(CATPPUCCIN_MOCHA_BASE);
-            canvas.clear();
-
-            // Draw pixels
-            canvas.set_draw_color(CATPPUCCIN_MOCHA_YELLOW);
-            chip8
-                .screen
-                .into_iter()
-                .enumerate()
-                .filter(|(_, is_on)| *is_on)
-                .for_each(|(index, _)| {
-                    let rect = get_rect_dimensions_from_index(index);
-                    canvas.fill_rect(rect).unwrap();
-                });
+        if should_redraw {
+            let drawn = canvas.with_texture_canvas(&mut picture, |canvas| {
+                if let Some(viewer) = &sprite_viewer {
+                    viewer.draw(canvas, &frame.memory);
+                    if let Some(menu) = &pause_menu {
+                        menu.draw(canvas, lang);
+                    }
+                    osd.draw(canvas);
+                    return;
+                }
+                // Clear screen
+                canvas.set_draw_color(palette::background(frame, settings.theme));
+                canvas.clear();
+
+                // Draw pixels
+                let (offset_x, offset_y) = smooth_scroll.next_offset();
+                let offset_x = (offset_x * SQUARE_SIZE as f32) as i32;
+                let offset_y = (offset_y * SQUARE_SIZE as f32) as i32;
+                frame
+                    .brightness
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, brightness)| *brightness > 0)
+                    .for_each(|(index, brightness)| {
+                        let (y, x) = point_from_index(index);
+                        let color = palette::pixel(frame, settings.theme, x, y, brightness);
+                        canvas.set_draw_color(color);
+                        let mut rect = get_rect_dimensions_from_index(index);
+                        let (slide_x, slide_y) = motion_smoothing.offset_at(x, y);
+                        rect.offset(
+                            offset_x + (slide_x * SQUARE_SIZE as f32) as i32,
+                            offset_y + (slide_y * SQUARE_SIZE as f32) as i32,
+                        );
+                        canvas.fill_rect(rect).unwrap();
+                    });
 
-            // Don't draw again until requested 
-            chip8.should_redraw = false;
+                // Outline what the next instruction is going to draw on
+                if tutor_mode {
+                    canvas
+                        .window_mut()
+                        .set_title(&tutor::describe_next(
+                            &frame.cpu_state,
+                            frame.instruction,
+                            frame.symbols.as_deref(),
+                        ))
+                        .unwrap();
+                    if let Some(region) = tutor::affected_screen_region(
+                        &frame.cpu_state,
+                        frame.instruction,
+                        frame.quirks,
+                    ) {
+                        canvas.set_draw_color(CATPPUCCIN_MOCHA_RED);
+                        canvas.draw_rect(region).unwrap();
+                    }
+                }
+
+                if show_keypad {
+                    overlay::draw_keypad(canvas, &frame.keypad.current_frame_keys, KeyBank::First);
+                    if let Some(keys) = &frame.keypad.second_bank {
+                        overlay::draw_keypad(canvas, keys, KeyBank::Second);
+                    }
+                }
+                #[cfg(feature = "profiling")]
+                if args.perf_hud {
+                    overlay::draw_perf_hud(canvas, &frame.opcode_profile);
+                }
+                if let Some((x, y)) = hovered_pixel.filter(|_| show_picker) {
+                    canvas.set_draw_color(CATPPUCCIN_MOCHA_RED);
+                    canvas
+                        .draw_rect(get_rect_dimensions_from_index(index_from_point((y, x))))
+                        .unwrap();
+                    overlay::draw_pixel_info(canvas, x, y, frame.pixel(x, y));
+                }
+                let prompt = frame
+                    .key_prompt
+                    .as_ref()
+                    .filter(|prompt| !prompt.choices.is_empty());
+                if let Some(prompt) = prompt.filter(|_| args.key_hints) {
+                    // The host key that presses each choice once the d-pad is turned
+                    let keys = prompt
+                        .choices
+                        .iter()
+                        .filter_map(|&key| {
+                            (0..16).find(|&host| orientation.keypad_key(host) == key as usize)
+                        })
+                        .map(|host| overlay::host_key_name(KEYPAD_BINDINGS[host]))
+                        .collect();
+                    overlay::draw_hint(canvas, &Text::ChooseKey(keys).for_overlay(lang));
+                }
+                if let Some(menu) = &pause_menu {
+                    menu.draw(canvas, lang);
+                }
+                osd.draw(canvas);
+            });
+            if let Err(err) = drawn {
+                warn!("Couldn't draw the screen: {}", err);
+            }
+
+            // Don't draw again until requested
+            should_redraw = false;
+        }
+        canvas.set_draw_color(CATPPUCCIN_MOCHA_BASE);
+        canvas.clear();
+        if let Err(err) = orientation.copy(&mut canvas, &picture) {
+            warn!("Couldn't show the screen: {}", err);
         }
-        
+
         // Present canvas
         canvas.present();
 
-        // Sleep
-        std::thread::sleep(Duration::from_secs_f64(1.0 / 60.0));
+        if idle {
+            // Wait for input instead of redrawing the same picture 60 times per second; the
+            // timeout keeps the control socket responsive
+            waited_event = event_pump.wait_event_timeout(IDLE_POLL_INTERVAL_MS);
+            render_timer.reset();
+        } else {
+            // Rendering runs at the frame rate too, but a late frame here no longer slows the game
+            render_timer.wait();
+        }
+    }
+
+    #[cfg(feature = "profiling")]
+    if args.perf_report {
+        eprint!("{}", emulation.frame().opcode_profile);
+    }
+    save_window_placement(canvas.window());
+    emulation.quit();
+}
+
+/// Where to open the window and how large: centred on the display `monitor` if given,
+/// otherwise where it was left last time if that's still on a display, or centred on the
+/// display it was on. The size is the one it was left at, `size` the first time.
+fn window_placement(
+    video: &sdl2::VideoSubsystem,
+    monitor: Option<u32>,
+    settings: &Settings,
+    size: (u32, u32),
+) -> (Option<(i32, i32)>, (u32, u32)) {
+    let (width, height) = settings
+        .window
+        .map_or(size, |window| (window.width, window.height));
+    let centred_on = |index: u32| {
+        let bounds = video
+            .display_bounds(index as i32)
+            .map_err(|err| warn!("Couldn't find display {}: {}", index, err))
+            .ok()?;
+        Some((
+            bounds.x() + (bounds.width() as i32 - width as i32) / 2,
+            bounds.y() + (bounds.height() as i32 - height as i32) / 2,
+        ))
+    };
+    if let Some(display) = monitor {
+        return (centred_on(display), (width, height));
+    }
+    let displays = video.num_video_displays().unwrap_or(0);
+    let on_a_display = |window: &WindowGeometry| {
+        (0..displays).any(|display| {
+            video
+                .display_bounds(display)
+                .is_ok_and(|bounds| bounds.contains_point((window.x, window.y)))
+        })
+    };
+    match settings.window.filter(on_a_display) {
+        Some(window) => (Some((window.x, window.y)), (width, height)),
+        None => (settings.monitor.and_then(centred_on), (width, height)),
+    }
+}
+
+/// Remember where the window was left for the next run. The other settings are kept as the
+/// file has them, not as the command line changed them for this run.
+fn save_window_placement(window: &sdl2::video::Window) {
+    let path = persistence::settings_path();
+    let mut settings = Settings::load(&path);
+    settings.fullscreen = window.fullscreen_state() != FullscreenType::Off;
+    if !settings.fullscreen {
+        let ((x, y), (width, height)) = (window.position(), window.size());
+        settings.window = Some(WindowGeometry {
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+    settings.monitor = window.display_index().ok().map(|display| display as u32);
+    if let Err(err) = settings.save(&path) {
+        warn!("Couldn't remember where the window was: {}", err);
+    }
+}
+
+/// `symbols` with the labels and notes the user annotated the ROM with on top
+fn annotated(symbols: Option<&SymbolTable>, rom_hash: u64) -> Option<SymbolTable> {
+    let annotations = match persistence::load_annotations(rom_hash) {
+        Ok(Some(text)) => SymbolTable::parse(&text)
+            .map_err(|err| warn!("Ignoring the ROM's annotations: {}", err))
+            .ok(),
+        Ok(None) => None,
+        Err(err) => {
+            warn!("Couldn't read the ROM's annotations: {}", err);
+            None
+        }
+    };
+    match (symbols.cloned(), annotations) {
+        (Some(mut symbols), Some(annotations)) => {
+            symbols.merge(annotations);
+            Some(symbols)
+        }
+        (symbols, annotations) => symbols.or(annotations),
+    }
+}
+
+/// A flash limiter and the frame it last let through, blank to begin with
+fn new_flash_limit() -> (FlashLimiter, Box<Frame>) {
+    (
+        FlashLimiter::new(SAFE_FLASHES_PER_SECOND),
+        Box::<Frame>::default(),
+    )
+}
+
+/// Tell the emulation thread about the settings changed from `old` in the pause menu. A
+/// preset switched off only takes effect on the next restart.
+fn apply_settings(emulation: &emulation::Emulation, old: &Settings, new: &Settings) {
+    if new.speed != old.speed {
+        emulation.send(Command::SetSpeed(new.speed));
+    }
+    if new.theme != old.theme {
+        emulation.send(Command::SetTheme(new.theme));
+    }
+    if let Some(preset) = new.preset.filter(|_| new.preset != old.preset) {
+        emulation.send(Command::ApplyPreset(preset));
+    }
+}
+
+/// Report that SDL couldn't set something the emulator needs up and exit
+fn fail(what: &str, err: impl std::fmt::Display) -> ! {
+    error!("{}: {}", what, err);
+    std::process::exit(1);
+}
+
+/// Open the debug window next to the game's, or explain why it couldn't be
+fn open_debug_window(
+    video_subsystem: &sdl2::VideoSubsystem,
+    renderer: Option<args::Renderer>,
+) -> Option<debug_window::DebugWindow> {
+    debug_window::DebugWindow::open(video_subsystem, renderer)
+        .map_err(|err| warn!("Couldn't open the debug window: {}", err))
+        .ok()
+}
+
+/// Mark the window title as paused, or restore it
+fn show_paused(canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, paused: bool, lang: Lang) {
+    let title = match paused {
+        true => format!("{} ({})", WINDOW_TITLE, Text::Paused.localize(lang)),
+        false => WINDOW_TITLE.to_string(),
+    };
+    canvas.window_mut().set_title(&title).unwrap();
+}
+
+/// Largest ROM the options let the machine load
+fn max_rom_size(args: &args::Args) -> usize {
+    match args.banking {
+        true => MAX_BANKED_ROM_SIZE,
+        false => MemoryLayout::new(args.ram_size).max_rom_size(),
+    }
+}
+
+/// Read the files given with --overlay, checking that they and `rom` fit in memory
+/// without overlapping
+fn load_overlays(args: &args::Args, rom: &[u8]) -> Result<Vec<Overlay>, String> {
+    let mut overlays = vec![];
+    for (path, addr) in &args.overlay {
+        let data = std::fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        overlays.push(Overlay {
+            name: path.display().to_string(),
+            addr: *addr,
+            data,
+        });
+    }
+    check_layout(args, rom, &overlays)?;
+    Ok(overlays)
+}
+
+/// Check that `rom` and `overlays` fit in memory without overlapping
+fn check_layout(args: &args::Args, rom: &[u8], overlays: &[Overlay]) -> Result<(), String> {
+    let mut layout = MemoryLayout::new(args.ram_size);
+    layout
+        .place_rom(rom.len().min(MAX_ROM_SIZE))
+        .map_err(|err| err.to_string())?;
+    for overlay in overlays {
+        layout
+            .place(&overlay.name, overlay.addr, overlay.data.len())
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+/// The symbols given with --symbols, if they can be read
+fn load_symbols(args: &args::Args) -> Option<SymbolTable> {
+    let path = args.symbols.as_ref()?;
+    std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| SymbolTable::parse(&text).map_err(|err| err.to_string()))
+        .map_err(|err| warn!("Ignoring symbols {}: {}", path.display(), err))
+        .ok()
+}
+
+/// A machine running `rom` with the platform, speed and other options from the command line
+fn build_machine(args: &args::Args, rom: &[u8], overlays: &[Overlay]) -> Chip8 {
+    let mut chip8 = Chip8::new();
+    chip8.enable_tracer(TRACE_LENGTH);
+    // Scrolling, sound and write protection are reported as events
+    chip8.enable_events();
+    chip8.set_clock(ClockConfig {
+        instructions_per_frame: 5,
+        timer_frequency: args.timer_hz,
+        timer_order: args.timer_order,
+    });
+    if args.blend_frames > 1 {
+        chip8.set_frame_blending(Some(BlendConfig {
+            mode: args.blend_mode,
+            frames: args.blend_frames,
+        }));
+    }
+    chip8.set_ram_pattern(args.ram_init);
+    chip8.set_random_source(args.random);
+    chip8.set_display_buffering(args.display_buffering);
+    if args.banking {
+        chip8.enable_banking();
+    }
+    for &(addr, routine) in &args.machine_routine {
+        chip8.set_machine_routine(addr, routine);
+    }
+    for overlay in overlays {
+        if let Err(err) = chip8.load_segment(overlay.addr, &overlay.data) {
+            warn!("Couldn't load {}: {}", overlay.name, err);
+        }
+    }
+    chip8.load_rom(rom);
+    match args.platform {
+        Some(platform) => chip8.select_platform(platform),
+        None => chip8.apply_rom_platform(),
+    }
+    if let Some(preset) = args.preset {
+        preset.apply(&mut chip8);
+    }
+    if let Some(path) = &args.octo_options {
+        match std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| OctoOptions::parse(&text).map_err(|err| err.to_string()))
+        {
+            Ok(options) => {
+                chip8.set_quirks(options.quirks());
+                chip8.set_clock(ClockConfig {
+                    timer_frequency: args.timer_hz,
+                    timer_order: args.timer_order,
+                    ..options.clock()
+                });
+                for name in options.unsupported() {
+                    warn!("Octo option {} isn't supported and will be ignored", name);
+                }
+            }
+            Err(err) => warn!("Ignoring Octo options {}: {}", path.display(), err),
+        }
+    }
+    if args.clipped_rows_collide {
+        chip8.set_quirks(Quirks {
+            clipped_rows_collide: true,
+            ..chip8.quirks()
+        });
+    }
+    let rom_info = chip8.rom_info();
+    let reasons = rom_info.platform_reasons.iter().take(3).cloned();
+    info!(
+        "Running as {} ({})",
+        rom_info.platform,
+        reasons.collect::<Vec<_>>().join("; ")
+    );
+    if let Some(profile) = rom_info.profile {
+        info!("Recognised {}", profile.name);
+    }
+    if let Some(range) = args.battery_ram.clone() {
+        if let Err(err) = chip8.set_battery_ram(Some(range)) {
+            error!("Couldn't set the battery RAM up: {}", err);
+            std::process::exit(1);
+        }
+        match persistence::load_battery_ram(chip8.rom_hash()) {
+            Ok(Some(data)) => {
+                if let Err(err) = chip8.restore_battery_ram(&data) {
+                    warn!("Ignoring saved battery RAM: {}", err);
+                }
+            }
+            Ok(None) => {}
+            Err(err) => warn!("Couldn't read saved battery RAM: {}", err),
+        }
+    }
+    let write_protection = match persistence::is_self_modifying(chip8.rom_hash()) {
+        true => WriteProtection::Off,
+        false => args
+            .write_protection
+            .or(args.preset.map(|preset| preset.write_protection()))
+            .unwrap_or_default(),
     };
+    chip8.set_write_protection(write_protection);
+    chip8.keypad.ghosting = args.hardware_keypad;
+    chip8
+}
+
+/// Save the frame as a BMP image at the window's scale, without any overlays
+fn save_screenshot(frame: &Frame, theme: palette::Theme, path: &Path) -> Result<(), String> {
+    let mut image = palette::render_rgb(frame, theme, SQUARE_SIZE as usize);
+    let surface = Surface::from_data(
+        &mut image,
+        SCREEN_WIDTH,
+        SCREEN_HEIGHT,
+        SCREEN_WIDTH * 3,
+        PixelFormatEnum::RGB24,
+    )?;
+    surface.save_bmp(path)
+}
+
+/// CHIP-8 pixel under the window coordinates `x` and `y`, as (x, y)
+fn pixel_at(orientation: &orientation::Orientation, x: i32, y: i32) -> Option<(usize, usize)> {
+    let (x, y) = orientation.picture_point(x, y);
+    let x = usize::try_from(x).ok()? / SQUARE_SIZE as usize;
+    let y = usize::try_from(y).ok()? / SQUARE_SIZE as usize;
+    (x < PIXELS_PER_ROW && y < PIXELS_PER_COLUMN).then_some((x, y))
 }
 
 fn get_rect_dimensions_from_index(index: usize) -> Rect {
@@ -118,25 +1433,57 @@ fn get_rect_dimensions_from_index(index: usize) -> Rect {
     )
 }
 
+/// Host keys for each CHIP-8 key, indexed by the CHIP-8 key
+const KEYPAD_BINDINGS: [Scancode; 16] = [
+    Scancode::X,
+    Scancode::Num1,
+    Scancode::Num2,
+    Scancode::Num3,
+    Scancode::Q,
+    Scancode::W,
+    Scancode::E,
+    Scancode::A,
+    Scancode::S,
+    Scancode::D,
+    Scancode::Z,
+    Scancode::C,
+    Scancode::Num4,
+    Scancode::R,
+    Scancode::F,
+    Scancode::V,
+];
+
 fn get_keypad_button_from_scancode(scancode: Scancode) -> Option<usize> {
-    let key = match scancode {
-        Scancode::Num1 => 0x1,
-        Scancode::Num2 => 0x2,
-        Scancode::Num3 => 0x3,
-        Scancode::Num4 => 0xC,
-        Scancode::Q => 0x4,
-        Scancode::W => 0x5,
-        Scancode::E => 0x6,
-        Scancode::R => 0xD,
-        Scancode::A => 0x7,
-        Scancode::S => 0x8,
-        Scancode::D => 0x9,
-        Scancode::F => 0xE,
-        Scancode::Z => 0xA,
-        Scancode::X => 0x0,
-        Scancode::C => 0xB,
-        Scancode::V => 0xF,
-        _ => return None,
-    };
-    Some(key)
+    KEYPAD_BINDINGS.iter().position(|&bound| bound == scancode)
+}
+
+/// Host keys for player 2's keypad, on the numpad
+const SECOND_KEYPAD_BINDINGS: [Scancode; 16] = [
+    Scancode::Kp0,
+    Scancode::Kp1,
+    Scancode::Kp2,
+    Scancode::Kp3,
+    Scancode::Kp4,
+    Scancode::Kp5,
+    Scancode::Kp6,
+    Scancode::Kp7,
+    Scancode::Kp8,
+    Scancode::Kp9,
+    Scancode::KpDivide,
+    Scancode::KpMultiply,
+    Scancode::KpMinus,
+    Scancode::KpPlus,
+    Scancode::KpEnter,
+    Scancode::KpPeriod,
+];
+
+fn second_keypad_button_from_scancode(scancode: Scancode) -> Option<usize> {
+    SECOND_KEYPAD_BINDINGS.iter().position(|&bound| bound == scancode)
+}
+
+fn keypad_button_scancode(bank: KeyBank, key: usize) -> Scancode {
+    match bank {
+        KeyBank::First => KEYPAD_BINDINGS[key],
+        KeyBank::Second => SECOND_KEYPAD_BINDINGS[key],
+    }
 }
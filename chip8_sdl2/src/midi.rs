@@ -0,0 +1,89 @@
+//! The buzzer played as a MIDI note, for routing the beep through synthesizers: the note
+//! starts when the sound timer does and stops with it, once it lasted the minimum beep
+
+use std::time::{Duration, Instant};
+
+use midir::{MidiOutput, MidiOutputConnection};
+
+const CLIENT_NAME: &str = "chip8";
+const NOTE_ON: u8 = 0x90;
+const NOTE_OFF: u8 = 0x80;
+const VELOCITY: u8 = 100;
+
+pub struct MidiBuzzer {
+    connection: MidiOutputConnection,
+    /// 0 to 15, one less than the channel number musicians use
+    channel: u8,
+    note: u8,
+    /// Shortest a note lasts, like the beeps of the audio output
+    min_beep: Duration,
+    /// When the note playing started
+    started: Option<Instant>,
+    /// A message failed to send, which was reported; a port that went away fails them all
+    failed: bool,
+}
+
+impl MidiBuzzer {
+    /// Connect to the first output port whose name contains `port`
+    pub fn open(port: &str, channel: u8, note: u8, min_beep: Duration) -> Result<Self, String> {
+        let output = MidiOutput::new(CLIENT_NAME).map_err(|err| err.to_string())?;
+        let ports = output.ports();
+        let names: Vec<String> = ports
+            .iter()
+            .map(|port| output.port_name(port).unwrap_or_default())
+            .collect();
+        let Some(index) = names.iter().position(|name| name.contains(port)) else {
+            return Err(format!(
+                "no MIDI output port matches '{}', there are: {}",
+                port,
+                names.join(", ")
+            ));
+        };
+        let connection = output
+            .connect(&ports[index], "buzzer")
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            connection,
+            channel: channel & 0xF,
+            note: note & 0x7F,
+            min_beep,
+            started: None,
+            failed: false,
+        })
+    }
+
+    /// Start or stop the note when the buzzer does. A note stopped before it lasted the
+    /// minimum beep goes on until a call after that, so call this every frame.
+    pub fn set_beeping(&mut self, beeping: bool) {
+        match (beeping, self.started) {
+            (true, None) => {
+                self.send([NOTE_ON | self.channel, self.note, VELOCITY]);
+                self.started = Some(Instant::now());
+            }
+            (false, Some(started)) if started.elapsed() >= self.min_beep => self.stop(),
+            _ => {}
+        }
+    }
+
+    fn stop(&mut self) {
+        if self.started.take().is_some() {
+            self.send([NOTE_OFF | self.channel, self.note, 0]);
+        }
+    }
+
+    fn send(&mut self, message: [u8; 3]) {
+        if let Err(err) = self.connection.send(&message) {
+            if !self.failed {
+                warn!("Couldn't send a MIDI note: {}", err);
+                self.failed = true;
+            }
+        }
+    }
+}
+
+impl Drop for MidiBuzzer {
+    /// Leave no note hanging on the synthesizer
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
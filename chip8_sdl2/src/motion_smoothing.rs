@@ -0,0 +1,122 @@
+//! Motion smoothing, an experiment: a sprite that moved a few pixels is drawn sliding from
+//! where it was to where it is at the display's refresh rate, over as long as the game took
+//! between its last two moves, instead of jumping there.
+//!
+//! Sprites are told apart by their address in memory. One drawn once in a frame, or erased
+//! where it was and drawn again, moved; sprites drawn all over, like tiles, are left alone.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chip8_core::runner::FRAME_RATE;
+
+/// Longest move in CHIP-8 pixels that slides; farther ones are teleports
+const MAX_STEP: i32 = 8;
+/// Most frames between moves a slide is spread over
+const MAX_MOVE_FRAMES: u64 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Draw {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+struct Slide {
+    addr: u16,
+    /// Where the sprite is drawn in the frame
+    to: Draw,
+    /// Offset from there to where the slide starts, in CHIP-8 pixels
+    from: (f32, f32),
+    start: Instant,
+    duration: Duration,
+}
+
+impl Slide {
+    fn offset(&self) -> (f32, f32) {
+        let left = 1.0 - (self.start.elapsed().as_secs_f32() / self.duration.as_secs_f32());
+        let left = left.max(0.0);
+        (self.from.0 * left, self.from.1 * left)
+    }
+}
+
+#[derive(Default)]
+pub struct MotionSmoothing {
+    /// Sprites drawn since the last frame, by address
+    draws: HashMap<u16, Vec<Draw>>,
+    /// Where each sprite was last drawn and the frame it moved there in
+    positions: HashMap<u16, (Draw, u64)>,
+    slides: Vec<Slide>,
+}
+
+impl MotionSmoothing {
+    pub fn sprite_drawn(&mut self, addr: u16, x: u8, y: u8, width: u8, height: u8) {
+        let draw = Draw {
+            x: x.into(),
+            y: y.into(),
+            width: width.into(),
+            height: height.into(),
+        };
+        self.draws.entry(addr).or_default().push(draw);
+    }
+
+    /// Slide the sprites that moved in the frames up to `frame_number`
+    pub fn frame_finished(&mut self, frame_number: u64) {
+        for (addr, draws) in self.draws.drain() {
+            let Some(&last) = draws.last() else {
+                continue;
+            };
+            let Some(&(before, moved_in)) = self.positions.get(&addr) else {
+                self.positions.insert(addr, (last, frame_number));
+                continue;
+            };
+            let erased_first = draws.len() == 2 && draws[0] == before;
+            if draws.len() > 2 || (draws.len() == 2 && !erased_first) {
+                self.positions.remove(&addr);
+                continue;
+            }
+            // Only erased, to be drawn elsewhere in a later frame
+            if last == before {
+                continue;
+            }
+            let (dx, dy) = (last.x - before.x, last.y - before.y);
+            self.positions.insert(addr, (last, frame_number));
+            let slid = self.slides.iter().position(|slide| slide.addr == addr);
+            let (from_x, from_y) = slid.map_or((0.0, 0.0), |index| {
+                let slide = self.slides.swap_remove(index);
+                slide.offset()
+            });
+            if dx.abs() > MAX_STEP || dy.abs() > MAX_STEP || last.width != before.width {
+                continue;
+            }
+            let frames = (frame_number - moved_in).clamp(1, MAX_MOVE_FRAMES);
+            self.slides.push(Slide {
+                addr,
+                to: last,
+                from: (from_x - dx as f32, from_y - dy as f32),
+                start: Instant::now(),
+                duration: Duration::from_secs_f64(frames as f64 / FRAME_RATE),
+            });
+        }
+    }
+
+    /// Whether a slide is still going, forgetting finished ones
+    pub fn is_animating(&mut self) -> bool {
+        self.slides
+            .retain(|slide| slide.start.elapsed() < slide.duration);
+        !self.slides.is_empty()
+    }
+
+    /// How far from its place to draw the pixel at (`x`, `y`), in CHIP-8 pixels
+    pub fn offset_at(&self, x: usize, y: usize) -> (f32, f32) {
+        let (x, y) = (x as i32, y as i32);
+        self.slides
+            .iter()
+            .find(|slide| {
+                let to = slide.to;
+                (to.x..to.x + to.width).contains(&x) && (to.y..to.y + to.height).contains(&y)
+            })
+            .map_or((0.0, 0.0), Slide::offset)
+    }
+}
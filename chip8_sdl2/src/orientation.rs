@@ -0,0 +1,109 @@
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture};
+use sdl2::video::Window;
+
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Keypad keys games commonly use as a d-pad, with the direction each points in
+const DIRECTION_KEYS: [(usize, (i32, i32)); 4] =
+    [(0x2, (0, -1)), (0x4, (-1, 0)), (0x6, (1, 0)), (0x8, (0, 1))];
+
+/// How the picture is turned and mirrored in the window, for displays mounted sideways or
+/// cocktail cabinets with a player on each side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Orientation {
+    /// Clockwise quarter turns, applied after mirroring
+    quarter_turns: u8,
+    pub mirror_horizontally: bool,
+    pub mirror_vertically: bool,
+}
+
+impl Orientation {
+    /// `degrees` is one of 0, 90, 180 and 270
+    pub fn new(degrees: u16, mirror_horizontally: bool, mirror_vertically: bool) -> Self {
+        Self {
+            quarter_turns: (degrees / 90 % 4) as u8,
+            mirror_horizontally,
+            mirror_vertically,
+        }
+    }
+
+    pub fn rotate_clockwise(&mut self) {
+        self.quarter_turns = (self.quarter_turns + 1) % 4;
+    }
+
+    pub fn degrees(&self) -> u16 {
+        self.quarter_turns as u16 * 90
+    }
+
+    /// Size of the window that fits the picture
+    pub fn window_size(&self) -> (u32, u32) {
+        if self.quarter_turns % 2 == 1 {
+            (SCREEN_HEIGHT, SCREEN_WIDTH)
+        } else {
+            (SCREEN_WIDTH, SCREEN_HEIGHT)
+        }
+    }
+
+    /// Copy `picture`, drawn the right way up, to the window turned and mirrored
+    pub fn copy(&self, canvas: &mut Canvas<Window>, picture: &Texture) -> Result<(), String> {
+        let (width, height) = self.window_size();
+        // Rotation turns the destination around its centre, which is the window's
+        let destination = Rect::new(
+            (width as i32 - SCREEN_WIDTH as i32) / 2,
+            (height as i32 - SCREEN_HEIGHT as i32) / 2,
+            SCREEN_WIDTH,
+            SCREEN_HEIGHT,
+        );
+        canvas.copy_ex(
+            picture,
+            None,
+            destination,
+            self.degrees().into(),
+            None,
+            self.mirror_horizontally,
+            self.mirror_vertically,
+        )
+    }
+
+    /// Point of the upright picture shown at window coordinates `x` and `y`
+    pub fn picture_point(&self, x: i32, y: i32) -> (i32, i32) {
+        let (width, height) = self.window_size();
+        // Doubled offsets of the pixel's centre from the window's, to stay in integers
+        let (dx, dy) = self.unturn(2 * x + 1 - width as i32, 2 * y + 1 - height as i32);
+        (
+            (dx + SCREEN_WIDTH as i32 - 1) / 2,
+            (dy + SCREEN_HEIGHT as i32 - 1) / 2,
+        )
+    }
+
+    /// The keypad key a host key for `key` presses: d-pad-style keys point the way they
+    /// look on the turned picture, the others stay as they are
+    pub fn keypad_key(&self, key: usize) -> usize {
+        let Some(&(_, (x, y))) = DIRECTION_KEYS.iter().find(|(bound, _)| *bound == key) else {
+            return key;
+        };
+        let direction = self.unturn(x, y);
+        DIRECTION_KEYS
+            .iter()
+            .find(|(_, bound)| *bound == direction)
+            .map_or(key, |&(key, _)| key)
+    }
+
+    /// Undo the rotation, then the mirroring, of a vector in window coordinates
+    fn unturn(&self, x: i32, y: i32) -> (i32, i32) {
+        let (mut x, mut y) = match self.quarter_turns {
+            1 => (y, -x),
+            2 => (-x, -y),
+            3 => (-y, x),
+            _ => (x, y),
+        };
+        if self.mirror_horizontally {
+            x = -x;
+        }
+        if self.mirror_vertically {
+            y = -y;
+        }
+        (x, y)
+    }
+}
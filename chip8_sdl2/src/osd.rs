@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+use sdl2::{pixels::Color, rect::Rect, render::Canvas, video::Window};
+
+use crate::overlay::{draw_text, text_width, LINE_HEIGHT};
+
+const CATPPUCCIN_MOCHA_CRUST: Color = Color::RGB(17, 17, 27);
+const CATPPUCCIN_MOCHA_TEXT: Color = Color::RGB(205, 214, 244);
+
+const MESSAGE_DURATION: Duration = Duration::from_secs(3);
+const TEXT_SCALE: u32 = 2;
+const PADDING: u32 = 8;
+
+/// On-screen display for short messages drawn over the game
+#[derive(Default)]
+pub struct Osd {
+    message: Option<String>,
+    /// When the message disappears, `None` if it stays until replaced
+    expires_at: Option<Instant>,
+}
+
+impl Osd {
+    /// Show a message for a few seconds
+    pub fn show(&mut self, message: impl Into<String>) {
+        self.message = Some(message.into());
+        self.expires_at = Some(Instant::now() + MESSAGE_DURATION);
+    }
+
+    /// Show a message until another one replaces it
+    pub fn show_persistent(&mut self, message: impl Into<String>) {
+        self.message = Some(message.into());
+        self.expires_at = None;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.message.is_some()
+    }
+
+    /// Forget the message once it has expired; returns true if it just went away
+    pub fn update(&mut self) -> bool {
+        let expired = self
+            .expires_at
+            .is_some_and(|expires_at| Instant::now() >= expires_at);
+        if expired {
+            self.message = None;
+            self.expires_at = None;
+        }
+        expired
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>) {
+        let Some(message) = &self.message else {
+            return;
+        };
+        let lines = message.lines().collect::<Vec<_>>();
+        let longest = lines
+            .iter()
+            .map(|line| text_width(line, TEXT_SCALE))
+            .max()
+            .unwrap_or(0);
+        let width = longest + 2 * PADDING;
+        let height = lines.len() as u32 * LINE_HEIGHT * TEXT_SCALE + 2 * PADDING;
+
+        canvas.set_draw_color(CATPPUCCIN_MOCHA_CRUST);
+        canvas.fill_rect(Rect::new(0, 0, width, height)).unwrap();
+        canvas.set_draw_color(CATPPUCCIN_MOCHA_TEXT);
+        for (index, line) in lines.iter().enumerate() {
+            let y = PADDING + index as u32 * LINE_HEIGHT * TEXT_SCALE;
+            draw_text(canvas, line, PADDING as i32, y as i32, TEXT_SCALE);
+        }
+    }
+}
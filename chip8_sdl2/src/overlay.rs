@@ -0,0 +1,182 @@
+use sdl2::{
+    keyboard::{Keycode, Scancode},
+    pixels::Color,
+    rect::Rect,
+    render::Canvas,
+    video::Window,
+};
+
+#[cfg(feature = "profiling")]
+use chip8_core::profiling::OpcodeProfile;
+use chip8_core::text;
+use chip8_core::{index_from_point, KeyBank};
+
+use crate::{keypad_button_scancode, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+pub use chip8_core::text::{can_draw, text_width, GLYPH_ADVANCE, LINE_HEIGHT};
+
+const CATPPUCCIN_MOCHA_SURFACE0: Color = Color::RGB(49, 50, 68);
+const CATPPUCCIN_MOCHA_TEXT: Color = Color::RGB(205, 214, 244);
+const CATPPUCCIN_MOCHA_CRUST: Color = Color::RGB(17, 17, 27);
+const CATPPUCCIN_MOCHA_YELLOW: Color = Color::RGB(249, 226, 175);
+
+/// The hex keypad as laid out on the COSMAC VIP
+const KEYPAD_LAYOUT: [[usize; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xC],
+    [0x4, 0x5, 0x6, 0xD],
+    [0x7, 0x8, 0x9, 0xE],
+    [0xA, 0x0, 0xB, 0xF],
+];
+
+const CELL_SIZE: u32 = 40;
+const CELL_GAP: u32 = 4;
+const MARGIN: u32 = 12;
+
+/// Draw a hex keypad in the bottom-right corner, highlighting pressed keys.
+/// Player 2's keypad goes to the left of player 1's.
+pub fn draw_keypad(canvas: &mut Canvas<Window>, keys: &[bool; 16], bank: KeyBank) {
+    let size = 4 * CELL_SIZE + 5 * CELL_GAP;
+    let slot = match bank {
+        KeyBank::First => 1,
+        KeyBank::Second => 2,
+    };
+    let left = (SCREEN_WIDTH - slot * (size + MARGIN)) as i32;
+    let top = (SCREEN_HEIGHT - size - MARGIN) as i32;
+
+    canvas.set_draw_color(CATPPUCCIN_MOCHA_CRUST);
+    canvas.fill_rect(Rect::new(left, top, size, size)).unwrap();
+
+    for (row, keys_in_row) in KEYPAD_LAYOUT.iter().enumerate() {
+        for (column, &key) in keys_in_row.iter().enumerate() {
+            let x = left + (CELL_GAP + column as u32 * (CELL_SIZE + CELL_GAP)) as i32;
+            let y = top + (CELL_GAP + row as u32 * (CELL_SIZE + CELL_GAP)) as i32;
+            let (background, foreground) = if keys[key] {
+                (CATPPUCCIN_MOCHA_YELLOW, CATPPUCCIN_MOCHA_CRUST)
+            } else {
+                (CATPPUCCIN_MOCHA_SURFACE0, CATPPUCCIN_MOCHA_TEXT)
+            };
+            canvas.set_draw_color(background);
+            canvas
+                .fill_rect(Rect::new(x, y, CELL_SIZE, CELL_SIZE))
+                .unwrap();
+
+            // CHIP-8 key in the top-left corner, host key in the bottom-right one
+            canvas.set_draw_color(foreground);
+            let chip8_label = format!("{:X}", key);
+            draw_text(canvas, &chip8_label, x + 4, y + 4, 3);
+            let name = host_key_name(keypad_button_scancode(bank, key));
+            let host_label = name.trim_start_matches("Keypad ");
+            let length = host_label.chars().count() as u32;
+            // Longer names like "Enter" only fit at the smallest size
+            let scale = if length > 1 { 1 } else { 2 };
+            let label_width = (length * GLYPH_ADVANCE).saturating_sub(1) * scale;
+            draw_text(
+                canvas,
+                host_label,
+                x + (CELL_SIZE - 4 - label_width) as i32,
+                y + CELL_SIZE as i32 - 4 - 5 * scale as i32,
+                scale,
+            );
+        }
+    }
+}
+
+/// Name of the key at `scancode` on the current keyboard layout, so AZERTY keyboards show A
+/// where QWERTY ones show Q
+pub fn host_key_name(scancode: Scancode) -> String {
+    Keycode::from_scancode(scancode)
+        .map(Keycode::name)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| scancode.name().to_string())
+}
+
+/// Describe the pixel at column `x` and row `y` in the bottom-left corner
+pub fn draw_pixel_info(canvas: &mut Canvas<Window>, x: usize, y: usize, lit: bool) {
+    let text = format!(
+        "X {} Y {} INDEX {} {}",
+        x,
+        y,
+        index_from_point((y, x)),
+        if lit { "ON" } else { "OFF" }
+    );
+    let scale = 2;
+    let width = text.len() as u32 * GLYPH_ADVANCE * scale + 2 * MARGIN;
+    let height = LINE_HEIGHT * scale + 2 * MARGIN;
+    let top = (SCREEN_HEIGHT - height) as i32;
+
+    canvas.set_draw_color(CATPPUCCIN_MOCHA_CRUST);
+    canvas.fill_rect(Rect::new(0, top, width, height)).unwrap();
+    canvas.set_draw_color(CATPPUCCIN_MOCHA_TEXT);
+    draw_text(canvas, &text, MARGIN as i32, top + MARGIN as i32, scale);
+}
+
+/// Families of opcodes the perf HUD lists
+#[cfg(feature = "profiling")]
+const PERF_HUD_FAMILIES: usize = 4;
+
+/// List the opcode families taking the most time in the top-left corner, with their share
+/// of it and how long each instruction takes
+#[cfg(feature = "profiling")]
+pub fn draw_perf_hud(canvas: &mut Canvas<Window>, profile: &OpcodeProfile) {
+    let lines: Vec<String> = profile
+        .by_time()
+        .iter()
+        .take(PERF_HUD_FAMILIES)
+        .map(|(name, stats)| {
+            format!(
+                // The font has no percent sign
+                "{} {:>3.0} PCT {}NS",
+                name,
+                profile.percent(stats),
+                stats.per_instruction().as_nanos()
+            )
+        })
+        .collect();
+    if lines.is_empty() {
+        return;
+    }
+    let scale = 2;
+    let longest = lines.iter().map(String::len).max().unwrap_or_default() as u32;
+    let width = longest * GLYPH_ADVANCE * scale + 2 * MARGIN;
+    let height = lines.len() as u32 * LINE_HEIGHT * scale + 2 * MARGIN;
+
+    canvas.set_draw_color(CATPPUCCIN_MOCHA_CRUST);
+    canvas.fill_rect(Rect::new(0, 0, width, height)).unwrap();
+    canvas.set_draw_color(CATPPUCCIN_MOCHA_TEXT);
+    for (row, line) in lines.iter().enumerate() {
+        let top = MARGIN + row as u32 * LINE_HEIGHT * scale;
+        draw_text(canvas, line, MARGIN as i32, top as i32, scale);
+    }
+}
+
+/// Show `text` centered above the bottom of the screen
+pub fn draw_hint(canvas: &mut Canvas<Window>, text: &str) {
+    let scale = 2;
+    let width = text_width(text, scale) + 2 * MARGIN;
+    let height = LINE_HEIGHT * scale + 2 * MARGIN;
+    let left = (SCREEN_WIDTH.saturating_sub(width) / 2) as i32;
+    let top = (SCREEN_HEIGHT - height - MARGIN) as i32;
+
+    canvas.set_draw_color(CATPPUCCIN_MOCHA_CRUST);
+    canvas
+        .fill_rect(Rect::new(left, top, width, height))
+        .unwrap();
+    canvas.set_draw_color(CATPPUCCIN_MOCHA_YELLOW);
+    draw_text(
+        canvas,
+        text,
+        left + MARGIN as i32,
+        top + MARGIN as i32,
+        scale,
+    );
+}
+
+/// Draw text with the current draw color using the shared pixel font, each font pixel
+/// `scale` wide. Characters without a glyph are skipped.
+pub fn draw_text(canvas: &mut Canvas<Window>, text: &str, x: i32, y: i32, scale: u32) {
+    for square in text::text_pixels(text) {
+        let (left, top, width, height) = square.scaled(scale);
+        let rect = Rect::new(x + left as i32, y + top as i32, width, height);
+        canvas.fill_rect(rect).unwrap();
+    }
+}
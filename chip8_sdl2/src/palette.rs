@@ -0,0 +1,218 @@
+use std::fmt;
+use std::str::FromStr;
+
+use sdl2::pixels::Color;
+
+use chip8_core::runner::Frame;
+use chip8_core::{
+    point_from_index, BackgroundColor, ColorAttributes, ForegroundColor, PIXELS_PER_COLUMN,
+    PIXELS_PER_ROW,
+};
+
+const CATPPUCCIN_MOCHA_CRUST: Color = Color::RGB(17, 17, 27);
+const CATPPUCCIN_MOCHA_BASE: Color = Color::RGB(30, 30, 46);
+const CATPPUCCIN_MOCHA_TEXT: Color = Color::RGB(205, 214, 244);
+const CATPPUCCIN_MOCHA_RED: Color = Color::RGB(243, 139, 168);
+const CATPPUCCIN_MOCHA_BLUE: Color = Color::RGB(137, 180, 250);
+const CATPPUCCIN_MOCHA_MAUVE: Color = Color::RGB(203, 166, 247);
+const CATPPUCCIN_MOCHA_GREEN: Color = Color::RGB(166, 227, 161);
+const CATPPUCCIN_MOCHA_YELLOW: Color = Color::RGB(249, 226, 175);
+const CATPPUCCIN_MOCHA_TEAL: Color = Color::RGB(148, 226, 213);
+const CATPPUCCIN_LATTE_BASE: Color = Color::RGB(239, 241, 245);
+const CATPPUCCIN_LATTE_TEXT: Color = Color::RGB(76, 79, 105);
+const BLACK: Color = Color::RGB(0, 0, 0);
+const WHITE: Color = Color::RGB(255, 255, 255);
+// Okabe and Ito's colours, told apart with every common kind of colour blindness
+const OKABE_ITO_VERMILLION: Color = Color::RGB(213, 94, 0);
+const OKABE_ITO_BLUE: Color = Color::RGB(0, 114, 178);
+const OKABE_ITO_REDDISH_PURPLE: Color = Color::RGB(204, 121, 167);
+const OKABE_ITO_BLUISH_GREEN: Color = Color::RGB(0, 158, 115);
+const OKABE_ITO_YELLOW: Color = Color::RGB(240, 228, 66);
+const OKABE_ITO_SKY_BLUE: Color = Color::RGB(86, 180, 233);
+const OKABE_ITO_GREY: Color = Color::RGB(153, 153, 153);
+
+/// Colours of monochrome screens, for ROMs without curated ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    /// Yellow on dark blue-grey
+    #[default]
+    Mocha,
+    /// Green on near black, like a phosphor monitor
+    Phosphor,
+    /// Dark grey on white
+    Latte,
+    /// White on black, even for ROMs with colours of their own
+    HighContrast,
+    /// Yellow on black, with colours safe for colour blindness for ROMs that have them
+    Colorblind,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 5] = [
+        Theme::Mocha,
+        Theme::Phosphor,
+        Theme::Latte,
+        Theme::HighContrast,
+        Theme::Colorblind,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Mocha => "mocha",
+            Theme::Phosphor => "phosphor",
+            Theme::Latte => "latte",
+            Theme::HighContrast => "high-contrast",
+            Theme::Colorblind => "colorblind",
+        }
+    }
+
+    /// Accessibility themes are enforced: they replace the colours curated for ROMs and
+    /// those CHIP-8X programs pick, and don't swap with the buzzer
+    fn is_enforced(&self) -> bool {
+        matches!(self, Theme::HighContrast | Theme::Colorblind)
+    }
+
+    /// Unlit and lit colours
+    fn colors(&self) -> (Color, Color) {
+        match self {
+            Theme::Mocha => (CATPPUCCIN_MOCHA_BASE, CATPPUCCIN_MOCHA_YELLOW),
+            Theme::Phosphor => (CATPPUCCIN_MOCHA_CRUST, CATPPUCCIN_MOCHA_GREEN),
+            Theme::Latte => (CATPPUCCIN_LATTE_BASE, CATPPUCCIN_LATTE_TEXT),
+            Theme::HighContrast => (BLACK, WHITE),
+            Theme::Colorblind => (BLACK, OKABE_ITO_YELLOW),
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Theme::ALL
+            .into_iter()
+            .find(|theme| theme.name() == s)
+            .ok_or_else(|| {
+                let names = Theme::ALL.map(|theme| theme.name());
+                format!(
+                    "unknown theme '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Colour behind the pixels
+pub fn background(frame: &Frame, theme: Theme) -> Color {
+    let colors = frame.colors.as_ref().filter(|_| !theme.is_enforced());
+    match colors.map(ColorAttributes::background) {
+        None => monochrome(frame, theme).0,
+        Some(BackgroundColor::Blue) => CATPPUCCIN_MOCHA_BLUE,
+        Some(BackgroundColor::Black) => CATPPUCCIN_MOCHA_CRUST,
+        Some(BackgroundColor::Green) => CATPPUCCIN_MOCHA_GREEN,
+        Some(BackgroundColor::Red) => CATPPUCCIN_MOCHA_RED,
+    }
+}
+
+/// Colour of the lit pixel at column `x` and row `y`
+pub fn foreground(frame: &Frame, theme: Theme, x: usize, y: usize) -> Color {
+    let color = frame
+        .colors
+        .as_ref()
+        .map(|colors| colors.foreground_at(x, y));
+    match color {
+        None => monochrome(frame, theme).1,
+        Some(color) if theme == Theme::Colorblind => colorblind_safe(color),
+        Some(_) if theme.is_enforced() => monochrome(frame, theme).1,
+        Some(ForegroundColor::Black) => CATPPUCCIN_MOCHA_CRUST,
+        Some(ForegroundColor::Red) => CATPPUCCIN_MOCHA_RED,
+        Some(ForegroundColor::Blue) => CATPPUCCIN_MOCHA_BLUE,
+        Some(ForegroundColor::Violet) => CATPPUCCIN_MOCHA_MAUVE,
+        Some(ForegroundColor::Green) => CATPPUCCIN_MOCHA_GREEN,
+        Some(ForegroundColor::Yellow) => CATPPUCCIN_MOCHA_YELLOW,
+        Some(ForegroundColor::Aqua) => CATPPUCCIN_MOCHA_TEAL,
+        Some(ForegroundColor::White) => CATPPUCCIN_MOCHA_TEXT,
+    }
+}
+
+/// Colour of the pixel at column `x` and row `y` lit at `brightness` out of 255, fading from
+/// the background into the foreground
+pub fn pixel(frame: &Frame, theme: Theme, x: usize, y: usize, brightness: u8) -> Color {
+    let background = background(frame, theme);
+    let foreground = foreground(frame, theme, x, y);
+    let mix = |from: u8, to: u8| {
+        let (from, to, brightness) = (from as u32, to as u32, brightness as u32);
+        ((from * (255 - brightness) + to * brightness) / 255) as u8
+    };
+    Color::RGB(
+        mix(background.r, foreground.r),
+        mix(background.g, foreground.g),
+        mix(background.b, foreground.b),
+    )
+}
+
+/// The frame as packed RGB24 rows, each CHIP-8 pixel a `scale` by `scale` square, without
+/// any overlays
+pub fn render_rgb(frame: &Frame, theme: Theme, scale: usize) -> Vec<u8> {
+    let width = PIXELS_PER_ROW * scale;
+    let mut image = vec![0; width * PIXELS_PER_COLUMN * scale * 3];
+    for (index, &brightness) in frame.brightness.iter().enumerate() {
+        let (y, x) = point_from_index(index);
+        let color = pixel(frame, theme, x, y, brightness);
+        for row in y * scale..(y + 1) * scale {
+            let start = (row * width + x * scale) * 3;
+            for rgb in image[start..start + scale * 3].chunks_exact_mut(3) {
+                rgb.copy_from_slice(&[color.r, color.g, color.b]);
+            }
+        }
+    }
+    image
+}
+
+/// A CHIP-8X colour as one of Okabe and Ito's, on the black background of
+/// [`Theme::Colorblind`]
+fn colorblind_safe(color: ForegroundColor) -> Color {
+    match color {
+        // Black wouldn't show on black
+        ForegroundColor::Black => OKABE_ITO_GREY,
+        ForegroundColor::Red => OKABE_ITO_VERMILLION,
+        ForegroundColor::Blue => OKABE_ITO_BLUE,
+        ForegroundColor::Violet => OKABE_ITO_REDDISH_PURPLE,
+        ForegroundColor::Green => OKABE_ITO_BLUISH_GREEN,
+        ForegroundColor::Yellow => OKABE_ITO_YELLOW,
+        ForegroundColor::Aqua => OKABE_ITO_SKY_BLUE,
+        ForegroundColor::White => WHITE,
+    }
+}
+
+/// Unlit and lit colours of a monochrome screen: the ROM's curated ones if it has them,
+/// the theme's otherwise, swapped while the buzzer sounds if its profile asks for that.
+/// Enforced themes always use their own.
+fn monochrome(frame: &Frame, theme: Theme) -> (Color, Color) {
+    if theme.is_enforced() {
+        return theme.colors();
+    }
+    let (background, foreground) = match frame.profile.and_then(|profile| profile.colors) {
+        Some(colors) => (rgb(colors.background), rgb(colors.foreground)),
+        None => theme.colors(),
+    };
+    let flash = frame.beeping
+        && frame
+            .profile
+            .is_some_and(|profile| profile.cycle_palette_on_sound);
+    if flash {
+        (foreground, background)
+    } else {
+        (background, foreground)
+    }
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> Color {
+    Color::RGB(r, g, b)
+}
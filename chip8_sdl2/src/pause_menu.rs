@@ -0,0 +1,200 @@
+use sdl2::{keyboard::Scancode, pixels::Color, rect::Rect, render::Canvas, video::Window};
+
+use chip8_core::text::text_width;
+use chip8_core::Preset;
+
+use crate::i18n::{Lang, Text};
+use crate::overlay::{draw_text, LINE_HEIGHT};
+use crate::palette::Theme;
+use crate::settings::Settings;
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const CATPPUCCIN_MOCHA_CRUST: Color = Color::RGB(17, 17, 27);
+const CATPPUCCIN_MOCHA_TEXT: Color = Color::RGB(205, 214, 244);
+const CATPPUCCIN_MOCHA_YELLOW: Color = Color::RGB(249, 226, 175);
+
+const TEXT_SCALE: u32 = 3;
+const PADDING: u32 = 16;
+const LINE_GAP: u32 = 6;
+/// Speeds the menu steps through
+const SPEEDS: [f64; 8] = [0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 4.0];
+const PRESETS: [Option<Preset>; 4] = [
+    None,
+    Some(Preset::Strict),
+    Some(Preset::Compatible),
+    Some(Preset::Fast),
+];
+
+/// A line of the menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Item {
+    Resume,
+    Restart,
+    SaveState,
+    LoadState,
+    Speed,
+    Theme,
+    Preset,
+    ReduceFlashes,
+    CollisionCue,
+    Quit,
+}
+
+const ITEMS: [Item; 10] = [
+    Item::Resume,
+    Item::Restart,
+    Item::SaveState,
+    Item::LoadState,
+    Item::Speed,
+    Item::Theme,
+    Item::Preset,
+    Item::ReduceFlashes,
+    Item::CollisionCue,
+    Item::Quit,
+];
+
+/// What the player picked in the menu
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Choice {
+    Resume,
+    Restart,
+    SaveState,
+    LoadState,
+    Quit,
+    /// A setting was changed to these settings; the menu stays open
+    Settings(Settings),
+}
+
+/// Menu over the paused game, moved through with the arrow keys: Enter picks a line,
+/// left and right change a setting and Escape goes back to the game
+pub struct PauseMenu {
+    selected: usize,
+    settings: Settings,
+}
+
+impl PauseMenu {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            selected: 0,
+            settings,
+        }
+    }
+
+    /// React to a key press; every key goes to the menu while it's open
+    pub fn handle_key(&mut self, scancode: Scancode) -> Option<Choice> {
+        let item = ITEMS[self.selected];
+        match scancode {
+            Scancode::Escape => Some(Choice::Resume),
+            Scancode::Up => {
+                self.selected = (self.selected + ITEMS.len() - 1) % ITEMS.len();
+                None
+            }
+            Scancode::Down => {
+                self.selected = (self.selected + 1) % ITEMS.len();
+                None
+            }
+            Scancode::Left => self.change(item, -1),
+            Scancode::Right => self.change(item, 1),
+            Scancode::Return | Scancode::KpEnter => match item {
+                Item::Resume => Some(Choice::Resume),
+                Item::Restart => Some(Choice::Restart),
+                Item::SaveState => Some(Choice::SaveState),
+                Item::LoadState => Some(Choice::LoadState),
+                Item::Quit => Some(Choice::Quit),
+                Item::Speed
+                | Item::Theme
+                | Item::Preset
+                | Item::ReduceFlashes
+                | Item::CollisionCue => self.change(item, 1),
+            },
+            _ => None,
+        }
+    }
+
+    /// Move the setting on the `item` line by `step` options
+    fn change(&mut self, item: Item, step: isize) -> Option<Choice> {
+        let settings = &mut self.settings;
+        match item {
+            Item::Speed => {
+                // Speeds from the command line or the control socket may fall between
+                let nearest = (0..SPEEDS.len())
+                    .min_by(|&a, &b| {
+                        let distance = |index: usize| (SPEEDS[index] - settings.speed).abs();
+                        distance(a).total_cmp(&distance(b))
+                    })
+                    .unwrap_or_default();
+                let index = nearest.saturating_add_signed(step).min(SPEEDS.len() - 1);
+                settings.speed = SPEEDS[index];
+            }
+            Item::Theme => settings.theme = cycle(&Theme::ALL, settings.theme, step),
+            Item::Preset => settings.preset = cycle(&PRESETS, settings.preset, step),
+            Item::ReduceFlashes => settings.reduce_flashes = !settings.reduce_flashes,
+            Item::CollisionCue => settings.collision_cue = !settings.collision_cue,
+            _ => return None,
+        }
+        Some(Choice::Settings(*settings))
+    }
+
+    fn line(&self, item: Item, lang: Lang) -> String {
+        let label = Text::MenuItem(item).for_overlay(lang);
+        match item {
+            Item::Speed => format!("{}: < {}x >", label, self.settings.speed),
+            Item::Theme => format!("{}: < {} >", label, self.settings.theme),
+            Item::Preset => {
+                let preset = self.settings.preset.map_or("none", |preset| preset.name());
+                format!("{}: < {} >", label, preset)
+            }
+            Item::ReduceFlashes => {
+                format!("{}: < {} >", label, on_off(self.settings.reduce_flashes))
+            }
+            Item::CollisionCue => format!("{}: < {} >", label, on_off(self.settings.collision_cue)),
+            _ => label,
+        }
+    }
+
+    /// Draw the menu in the middle of the screen, over whatever is there
+    pub fn draw(&self, canvas: &mut Canvas<Window>, lang: Lang) {
+        let lines = ITEMS.map(|item| self.line(item, lang));
+        let longest = lines
+            .iter()
+            .map(|line| text_width(line, TEXT_SCALE))
+            .max()
+            .unwrap_or(0);
+        let line_height = LINE_HEIGHT * TEXT_SCALE + LINE_GAP;
+        let width = longest + 2 * PADDING;
+        let height = ITEMS.len() as u32 * line_height - LINE_GAP + 2 * PADDING;
+        let left = (SCREEN_WIDTH.saturating_sub(width) / 2) as i32;
+        let top = (SCREEN_HEIGHT.saturating_sub(height) / 2) as i32;
+
+        canvas.set_draw_color(CATPPUCCIN_MOCHA_CRUST);
+        canvas
+            .fill_rect(Rect::new(left, top, width, height))
+            .unwrap();
+        for (index, line) in lines.iter().enumerate() {
+            let color = if index == self.selected {
+                CATPPUCCIN_MOCHA_YELLOW
+            } else {
+                CATPPUCCIN_MOCHA_TEXT
+            };
+            canvas.set_draw_color(color);
+            let y = top + (PADDING + index as u32 * line_height) as i32;
+            draw_text(canvas, line, left + PADDING as i32, y, TEXT_SCALE);
+        }
+    }
+}
+
+/// The option `step` places after `current` in `options`, wrapping around
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, step: isize) -> T {
+    let index = options
+        .iter()
+        .position(|&option| option == current)
+        .unwrap_or_default();
+    options[(index as isize + step).rem_euclid(options.len() as isize) as usize]
+}
+
+fn on_off(on: bool) -> &'static str {
+    match on {
+        true => "on",
+        false => "off",
+    }
+}
@@ -0,0 +1,214 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Directory holding everything the emulator persists for the ROM with the given hash
+pub fn rom_data_dir(rom_hash: u64) -> PathBuf {
+    data_dir().join(format!("{:016x}", rom_hash))
+}
+
+fn data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME") {
+        return PathBuf::from(dir).join("chip8");
+    }
+    if let Some(dir) = std::env::var_os("APPDATA") {
+        return PathBuf::from(dir).join("chip8");
+    }
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".local/share/chip8"),
+        None => PathBuf::from(".chip8"),
+    }
+}
+
+/// Directory holding the emulator's settings, which apply to every ROM
+fn config_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME") {
+        return PathBuf::from(dir).join("chip8");
+    }
+    if let Some(dir) = std::env::var_os("APPDATA") {
+        return PathBuf::from(dir).join("chip8");
+    }
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".config/chip8"),
+        None => PathBuf::from(".chip8"),
+    }
+}
+
+/// Where the emulator's hotkeys are remapped unless another file is given
+pub fn hotkeys_path() -> PathBuf {
+    config_dir().join("hotkeys.conf")
+}
+
+/// Where the pause menu keeps the settings changed in it
+pub fn settings_path() -> PathBuf {
+    config_dir().join("settings.conf")
+}
+
+/// Battery-backed RAM saved by a previous run, if there is any
+pub fn load_battery_ram(rom_hash: u64) -> io::Result<Option<Vec<u8>>> {
+    match std::fs::read(rom_data_dir(rom_hash).join("battery.bin")) {
+        Ok(data) => Ok(Some(data)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+pub fn save_battery_ram(rom_hash: u64, data: &[u8]) -> io::Result<()> {
+    let dir = rom_data_dir(rom_hash);
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("battery.bin"), data)
+}
+
+/// Marks a ROM that writes over its own code, so write protection leaves it alone
+fn self_modifying_marker(rom_hash: u64) -> PathBuf {
+    rom_data_dir(rom_hash).join("self-modifying")
+}
+
+pub fn is_self_modifying(rom_hash: u64) -> bool {
+    self_modifying_marker(rom_hash).exists()
+}
+
+pub fn mark_self_modifying(rom_hash: u64) -> io::Result<()> {
+    std::fs::create_dir_all(rom_data_dir(rom_hash))?;
+    std::fs::write(self_modifying_marker(rom_hash), "")
+}
+
+fn annotations_path(rom_hash: u64) -> PathBuf {
+    rom_data_dir(rom_hash).join("annotations.sym")
+}
+
+/// Labels and notes the user left on the ROM, in the symbol file format
+pub fn load_annotations(rom_hash: u64) -> io::Result<Option<String>> {
+    match std::fs::read_to_string(annotations_path(rom_hash)) {
+        Ok(text) => Ok(Some(text)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Add a line of the symbol file format to the ROM's annotations, returning their path
+pub fn add_annotation(rom_hash: u64, entry: &str) -> io::Result<PathBuf> {
+    let path = annotations_path(rom_hash);
+    std::fs::create_dir_all(rom_data_dir(rom_hash))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    writeln!(file, "{}", entry)?;
+    Ok(path)
+}
+
+fn state_path(rom_hash: u64, slot: u8) -> PathBuf {
+    rom_data_dir(rom_hash)
+        .join("states")
+        .join(format!("slot-{}.state", slot))
+}
+
+pub fn save_state(rom_hash: u64, slot: u8, data: &[u8]) -> io::Result<PathBuf> {
+    let path = state_path(rom_hash, slot);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, data)?;
+    Ok(path)
+}
+
+pub fn load_state(rom_hash: u64, slot: u8) -> io::Result<Vec<u8>> {
+    std::fs::read(state_path(rom_hash, slot))
+}
+
+/// How the last run of a ROM ended, from the session file written with each autosave
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Session {
+    /// The emulator quit normally, rather than crashing or being killed
+    pub clean_exit: bool,
+    /// Unix time of the last autosave
+    pub saved_at: u64,
+}
+
+fn session_path(rom_hash: u64) -> PathBuf {
+    rom_data_dir(rom_hash).join("session")
+}
+
+fn autosave_path(rom_hash: u64) -> PathBuf {
+    rom_data_dir(rom_hash).join("states").join("autosave.state")
+}
+
+/// Save `data` as the ROM's autosave, recording whether this is the save made on exit
+pub fn save_autosave(rom_hash: u64, data: &[u8], clean_exit: bool) -> io::Result<()> {
+    let path = autosave_path(rom_hash);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, data)?;
+    let saved_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    std::fs::write(
+        session_path(rom_hash),
+        format!("clean_exit={}\nsaved_at={}\n", clean_exit, saved_at),
+    )
+}
+
+pub fn load_autosave(rom_hash: u64) -> io::Result<Vec<u8>> {
+    std::fs::read(autosave_path(rom_hash))
+}
+
+/// The session the ROM's autosave comes from, if it has one
+pub fn last_session(rom_hash: u64) -> Option<Session> {
+    if !autosave_path(rom_hash).exists() {
+        return None;
+    }
+    let text = std::fs::read_to_string(session_path(rom_hash)).ok()?;
+    let value = |key: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+    };
+    Some(Session {
+        clean_exit: value("clean_exit")?.parse().ok()?,
+        saved_at: value("saved_at")?.parse().ok()?,
+    })
+}
+
+/// Write a crash report for the ROM, returning where it was saved
+pub fn save_crash_report(rom_hash: u64, report: &str) -> io::Result<PathBuf> {
+    save_timestamped(rom_hash, "crashes", "crash", "txt", report)
+}
+
+/// Write an Octo cartridge exported from the ROM, returning where it was saved
+pub fn save_octo_cartridge(rom_hash: u64, cartridge: &str) -> io::Result<PathBuf> {
+    save_timestamped(rom_hash, "octo", "cartridge", "json", cartridge)
+}
+
+/// A new path for a screenshot of the ROM, creating its directory
+pub fn screenshot_path(rom_hash: u64) -> io::Result<PathBuf> {
+    timestamped_path(rom_hash, "screenshots", "screenshot", "bmp")
+}
+
+/// Write `contents` to `<subdir>/<prefix>-<unix time>.<extension>` in the ROM's data directory
+fn save_timestamped(
+    rom_hash: u64,
+    subdir: &str,
+    prefix: &str,
+    extension: &str,
+    contents: &str,
+) -> io::Result<PathBuf> {
+    let path = timestamped_path(rom_hash, subdir, prefix, extension)?;
+    std::fs::write(&path, contents)?;
+    Ok(path)
+}
+
+fn timestamped_path(
+    rom_hash: u64,
+    subdir: &str,
+    prefix: &str,
+    extension: &str,
+) -> io::Result<PathBuf> {
+    let dir = rom_data_dir(rom_hash).join(subdir);
+    std::fs::create_dir_all(&dir)?;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    Ok(dir.join(format!("{}-{}.{}", prefix, timestamp, extension)))
+}
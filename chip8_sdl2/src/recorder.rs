@@ -0,0 +1,88 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use chip8_core::runner::Frame;
+use chip8_core::{PIXELS_PER_COLUMN, PIXELS_PER_ROW};
+
+use crate::palette::{self, Theme};
+use crate::SQUARE_SIZE;
+
+/// Writes every emulated frame, upscaled like the window, to a YUV4MPEG2 (`.y4m`) file at
+/// 60 fps, which ffmpeg and most players read as is.
+///
+/// Chroma is stored at half resolution (4:2:0). Each CHIP-8 pixel covers whole 2x2 blocks,
+/// so no colour bleeds between pixels.
+pub struct FrameRecorder {
+    out: BufWriter<File>,
+    /// Y, then U, then V plane of the frame being written
+    planes: Vec<u8>,
+    theme: Theme,
+}
+
+impl FrameRecorder {
+    const WIDTH: usize = PIXELS_PER_ROW * SQUARE_SIZE as usize;
+    const HEIGHT: usize = PIXELS_PER_COLUMN * SQUARE_SIZE as usize;
+
+    pub fn create(path: &Path, theme: Theme) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(
+            out,
+            "YUV4MPEG2 W{} H{} F60:1 Ip A1:1 C420jpeg",
+            Self::WIDTH,
+            Self::HEIGHT
+        )?;
+        Ok(Self {
+            out,
+            planes: Vec::with_capacity(Self::WIDTH * Self::HEIGHT * 3 / 2),
+            theme,
+        })
+    }
+
+    /// Colour the following frames of monochrome ROMs with `theme`
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let rgb = palette::render_rgb(frame, self.theme, SQUARE_SIZE as usize);
+        let pixel = |x: usize, y: usize| {
+            let start = (y * Self::WIDTH + x) * 3;
+            let [r, g, b] = [rgb[start], rgb[start + 1], rgb[start + 2]].map(f32::from);
+            (r, g, b)
+        };
+
+        // Full-range BT.601, as the JPEG flavour of 4:2:0 expects
+        self.planes.clear();
+        for y in 0..Self::HEIGHT {
+            for x in 0..Self::WIDTH {
+                let (r, g, b) = pixel(x, y);
+                self.planes
+                    .push((0.299 * r + 0.587 * g + 0.114 * b).round() as u8);
+            }
+        }
+        let chroma = |weights: [f32; 3]| {
+            move |(r, g, b): (f32, f32, f32)| {
+                (128.0 + weights[0] * r + weights[1] * g + weights[2] * b).round() as u8
+            }
+        };
+        let planes = [
+            chroma([-0.168_736, -0.331_264, 0.5]),
+            chroma([0.5, -0.418_688, -0.081_312]),
+        ];
+        for plane in planes {
+            for y in (0..Self::HEIGHT).step_by(2) {
+                for x in (0..Self::WIDTH).step_by(2) {
+                    self.planes.push(plane(pixel(x, y)));
+                }
+            }
+        }
+
+        self.out.write_all(b"FRAME\n")?;
+        self.out.write_all(&self.planes)
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
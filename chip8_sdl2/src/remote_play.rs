@@ -0,0 +1,149 @@
+//! "Pass the controller" play between two emulators over TCP. The player in control runs
+//! the game and sends save states to the other, who watches; either can ask for control,
+//! which passes with a final state. Both must run the same ROM.
+
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender, TryIter};
+use std::time::{Duration, Instant};
+
+/// How often the player in control sends the watching one a state
+pub const PUSH_INTERVAL: Duration = Duration::from_secs(1);
+/// Largest message accepted, well above the size of a save state
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+const STATE: u8 = 0;
+const REQUEST_CONTROL: u8 = 1;
+const HANDOVER: u8 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// The game as the player in control has it now
+    State(Vec<u8>),
+    /// The watching player wants control
+    RequestControl,
+    /// The last state of the player giving up control, who watches from now on
+    Handover(Vec<u8>),
+    /// The other player disconnected; never sent
+    Left,
+}
+
+impl Message {
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        let (tag, payload): (u8, &[u8]) = match self {
+            Message::State(state) => (STATE, state),
+            Message::RequestControl => (REQUEST_CONTROL, &[]),
+            Message::Handover(state) => (HANDOVER, state),
+            Message::Left => return Ok(()),
+        };
+        writer.write_all(&[tag])?;
+        writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+        writer.write_all(payload)?;
+        writer.flush()
+    }
+
+    fn read(reader: &mut impl Read) -> io::Result<Self> {
+        let mut header = [0; 5];
+        reader.read_exact(&mut header)?;
+        let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        if len > MAX_MESSAGE_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("message of {} bytes is too large", len),
+            ));
+        }
+        let mut payload = vec![0; len];
+        reader.read_exact(&mut payload)?;
+        match header[0] {
+            STATE => Ok(Message::State(payload)),
+            REQUEST_CONTROL => Ok(Message::RequestControl),
+            HANDOVER => Ok(Message::Handover(payload)),
+            tag => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown message {}", tag),
+            )),
+        }
+    }
+}
+
+/// The connection to the other player and whose turn it is
+pub struct RemotePlay {
+    outgoing: Sender<Message>,
+    incoming: Receiver<Message>,
+    in_control: bool,
+    last_push: Instant,
+}
+
+impl RemotePlay {
+    /// Wait for the other player to connect to `port`, then start in control
+    pub fn host(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        let (stream, _) = listener.accept()?;
+        Self::start(stream, true)
+    }
+
+    /// Connect to the player hosting at `address`, then watch until given control
+    pub fn join(address: &str) -> io::Result<Self> {
+        Self::start(TcpStream::connect(address)?, false)
+    }
+
+    fn start(stream: TcpStream, in_control: bool) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        let (outgoing, to_send) = mpsc::channel::<Message>();
+        let (received, incoming) = mpsc::channel();
+        let mut writer = BufWriter::new(stream.try_clone()?);
+        std::thread::spawn(move || {
+            for message in to_send {
+                if message.write(&mut writer).is_err() {
+                    break;
+                }
+            }
+        });
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(stream);
+            while let Ok(message) = Message::read(&mut reader) {
+                if received.send(message).is_err() {
+                    return;
+                }
+            }
+            let _ = received.send(Message::Left);
+        });
+        Ok(Self {
+            outgoing,
+            incoming,
+            in_control,
+            last_push: Instant::now(),
+        })
+    }
+
+    pub fn in_control(&self) -> bool {
+        self.in_control
+    }
+
+    pub fn set_in_control(&mut self, in_control: bool) {
+        self.in_control = in_control;
+    }
+
+    /// Messages received since the last call
+    pub fn messages(&self) -> TryIter<'_, Message> {
+        self.incoming.try_iter()
+    }
+
+    pub fn send(&self, message: Message) {
+        let _ = self.outgoing.send(message);
+    }
+
+    /// Where to send messages from other threads, like states from the emulation thread
+    pub fn sender(&self) -> Sender<Message> {
+        self.outgoing.clone()
+    }
+
+    /// Whether the player in control is due to send a state, restarting the wait if so
+    pub fn push_due(&mut self) -> bool {
+        if !self.in_control || self.last_push.elapsed() < PUSH_INTERVAL {
+            return false;
+        }
+        self.last_push = Instant::now();
+        true
+    }
+}
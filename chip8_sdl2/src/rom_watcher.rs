@@ -0,0 +1,62 @@
+//! Noticing when the ROM file changes on disk, for --watch: after each assemble in Octo or
+//! another tool the game starts over with the new code
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the file's modification time is looked at
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct RomWatcher {
+    path: PathBuf,
+    /// Modification time of the version last reported or loaded
+    modified: Option<SystemTime>,
+    /// A newer modification time seen once, reported when the next poll sees it again so a
+    /// file still being written isn't loaded half-way
+    pending: Option<SystemTime>,
+    last_poll: Instant,
+}
+
+impl RomWatcher {
+    /// Watch `path`, as loaded now
+    pub fn new(path: PathBuf) -> Self {
+        let modified = modification_time(&path);
+        Self {
+            path,
+            modified,
+            pending: None,
+            last_poll: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file changed since it was last reported, once it stopped changing.
+    /// Cheap to call every frame: the file is only looked at every [`POLL_INTERVAL`].
+    pub fn changed(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+        let modified = modification_time(&self.path);
+        if modified.is_none() || modified == self.modified {
+            self.pending = None;
+            return false;
+        }
+        if self.pending != modified {
+            self.pending = modified;
+            return false;
+        }
+        self.modified = modified;
+        self.pending = None;
+        true
+    }
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
@@ -0,0 +1,52 @@
+//! Game controllers rumbling with the buzzer, for feeling beeps on handhelds and gamepads:
+//! each beep starts a short pulse on every connected controller that can rumble
+
+use sdl2::controller::GameController;
+use sdl2::GameControllerSubsystem;
+
+pub struct Rumble {
+    subsystem: GameControllerSubsystem,
+    controllers: Vec<GameController>,
+    /// Strength of both motors, from 0 to `u16::MAX`
+    strength: u16,
+    duration_ms: u32,
+}
+
+impl Rumble {
+    /// Pulses at `intensity` percent of the motors' strength lasting `duration_ms`.
+    /// Controllers are opened as SDL reports them, including those already connected.
+    pub fn new(subsystem: GameControllerSubsystem, intensity: u8, duration_ms: u32) -> Self {
+        Self {
+            subsystem,
+            controllers: Vec::new(),
+            strength: (u16::MAX as u32 * intensity.min(100) as u32 / 100) as u16,
+            duration_ms,
+        }
+    }
+
+    /// Open the controller SDL reported at `joystick_index`
+    pub fn connected(&mut self, joystick_index: u32) {
+        match self.subsystem.open(joystick_index) {
+            Ok(controller) if controller.has_rumble() => {
+                info!("Rumbling {} with the buzzer", controller.name());
+                self.controllers.push(controller);
+            }
+            Ok(_) => {}
+            Err(err) => warn!("Couldn't open game controller {}: {}", joystick_index, err),
+        }
+    }
+
+    /// Forget the controller SDL reported gone, by its instance ID
+    pub fn disconnected(&mut self, instance_id: u32) {
+        self.controllers
+            .retain(|controller| controller.instance_id() != instance_id);
+    }
+
+    /// Start a pulse, for a beep starting
+    pub fn pulse(&mut self) {
+        for controller in &mut self.controllers {
+            // Some controllers report rumble they can't do over their connection
+            let _ = controller.set_rumble(self.strength, self.strength, self.duration_ms);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Mirroring the screen on hardware over a serial port, in the framing of
+//! [`chip8_core::matrix_protocol`]. The port is opened as a file, so its speed is whatever
+//! it was set to beforehand, e.g. with `stty -F /dev/ttyUSB0 115200 raw`.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chip8_core::matrix_protocol;
+use chip8_core::PIXELS_PER_SCREEN;
+
+/// How often an unchanged screen is sent again, for receivers that start listening late
+const RESEND_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Sends screens from a thread of its own, so a slow port only drops frames instead of
+/// holding up the window
+pub struct SerialOutput {
+    frames: SyncSender<Vec<u8>>,
+    last: Option<[bool; PIXELS_PER_SCREEN]>,
+    last_sent: Instant,
+    sequence: u8,
+}
+
+impl SerialOutput {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut port = OpenOptions::new().write(true).open(path)?;
+        let (frames, pending) = mpsc::sync_channel::<Vec<u8>>(1);
+        let name = path.display().to_string();
+        thread::spawn(move || {
+            for frame in pending {
+                if let Err(err) = port.write_all(&frame).and_then(|()| port.flush()) {
+                    warn!("Stopped writing to {}: {}", name, err);
+                    return;
+                }
+            }
+        });
+        Ok(Self {
+            frames,
+            last: None,
+            last_sent: Instant::now(),
+            sequence: 0,
+        })
+    }
+
+    /// Send `screen` if it changed since the last one sent or that was long ago, unless
+    /// the port is still busy with an earlier frame
+    pub fn show(&mut self, screen: &[bool; PIXELS_PER_SCREEN]) {
+        if self.last.as_ref() == Some(screen) && self.last_sent.elapsed() < RESEND_INTERVAL {
+            return;
+        }
+        let frame = matrix_protocol::encode_frame(screen, self.sequence);
+        match self.frames.try_send(frame) {
+            Ok(()) => {
+                self.last = Some(*screen);
+                self.last_sent = Instant::now();
+                self.sequence = self.sequence.wrapping_add(1);
+            }
+            Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
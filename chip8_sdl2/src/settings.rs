@@ -0,0 +1,175 @@
+//! Settings changed from the pause menu, kept for later runs in a file of `name = value`
+//! lines like `speed = 1.5`, `theme = phosphor`, `preset = fast` or `reduce-flashes = true`,
+//! along with where the window was left, like `window = 100 80 1280 640`, `monitor = 1` and
+//! `fullscreen = true`.
+
+use std::io;
+use std::path::Path;
+
+use chip8_core::Preset;
+
+use crate::emulation::SPEED_RANGE;
+use crate::palette::Theme;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// How many times faster than real time the game runs
+    pub speed: f64,
+    pub theme: Theme,
+    /// `None` leaves the speed and checks to the platform and the command line
+    pub preset: Option<Preset>,
+    /// Hold back frames that would flash the screen more than a few times a second
+    pub reduce_flashes: bool,
+    /// Play a short sound whenever a sprite collides with something
+    pub collision_cue: bool,
+    /// Where the window was when the emulator last quit, outside fullscreen
+    pub window: Option<WindowGeometry>,
+    /// Index of the display the window was on
+    pub monitor: Option<u32>,
+    pub fullscreen: bool,
+}
+
+/// Position and size of a window, in desktop coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowGeometry {
+    /// Read `X Y WIDTH HEIGHT`
+    fn parse(s: &str) -> Result<Self, String> {
+        let numbers: Vec<&str> = s.split_whitespace().collect();
+        let geometry = match numbers.as_slice() {
+            [x, y, width, height] => (|| {
+                Some(Self {
+                    x: x.parse().ok()?,
+                    y: y.parse().ok()?,
+                    width: width.parse().ok().filter(|&width| width > 0)?,
+                    height: height.parse().ok().filter(|&height| height > 0)?,
+                })
+            })(),
+            _ => None,
+        };
+        geometry.ok_or_else(|| format!("'{}' is not 'X Y WIDTH HEIGHT'", s))
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            theme: Theme::default(),
+            preset: None,
+            reduce_flashes: false,
+            collision_cue: false,
+            window: None,
+            monitor: None,
+            fullscreen: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Settings from the file at `path`, the defaults for any it doesn't have or that don't
+    /// make sense
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text, |line, err| {
+                warn!("Ignoring line {} of {}: {}", line, path.display(), err)
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                warn!("Couldn't read {}: {}", path.display(), err);
+                Self::default()
+            }
+        }
+    }
+
+    /// Read the settings in `text`, telling `skipped` about each line that can't be used
+    fn parse(text: &str, mut skipped: impl FnMut(usize, String)) -> Self {
+        let mut settings = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((name, value)) = line.split_once('=') else {
+                skipped(
+                    index + 1,
+                    format!("expected 'name = value', found '{}'", line),
+                );
+                continue;
+            };
+            let value = value.trim();
+            let outcome = match name.trim() {
+                "speed" => match value.parse::<f64>() {
+                    Ok(speed) if SPEED_RANGE.contains(&speed) => {
+                        settings.speed = speed;
+                        Ok(())
+                    }
+                    _ => Err(format!(
+                        "'{}' is not a speed from {} to {}",
+                        value,
+                        SPEED_RANGE.start(),
+                        SPEED_RANGE.end()
+                    )),
+                },
+                "theme" => value.parse().map(|theme| settings.theme = theme),
+                "preset" if value == "none" => {
+                    settings.preset = None;
+                    Ok(())
+                }
+                "preset" => value.parse().map(|preset| settings.preset = Some(preset)),
+                "reduce-flashes" => parse_bool(value).map(|on| settings.reduce_flashes = on),
+                "collision-cue" => parse_bool(value).map(|on| settings.collision_cue = on),
+                "window" => {
+                    WindowGeometry::parse(value).map(|window| settings.window = Some(window))
+                }
+                "monitor" => match value.parse() {
+                    Ok(monitor) => {
+                        settings.monitor = Some(monitor);
+                        Ok(())
+                    }
+                    Err(_) => Err(format!("'{}' is not a display number", value)),
+                },
+                "fullscreen" => parse_bool(value).map(|on| settings.fullscreen = on),
+                name => Err(format!("unknown setting '{}'", name)),
+            };
+            if let Err(err) = outcome {
+                skipped(index + 1, err);
+            }
+        }
+        settings
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let preset = self.preset.map_or("none", |preset| preset.name());
+        let mut text = format!(
+            "speed = {}\ntheme = {}\npreset = {}\nreduce-flashes = {}\ncollision-cue = {}\n",
+            self.speed, self.theme, preset, self.reduce_flashes, self.collision_cue
+        );
+        if let Some(window) = self.window {
+            text += &format!(
+                "window = {} {} {} {}\n",
+                window.x, window.y, window.width, window.height
+            );
+        }
+        if let Some(monitor) = self.monitor {
+            text += &format!("monitor = {}\n", monitor);
+        }
+        text += &format!("fullscreen = {}\n", self.fullscreen);
+        std::fs::write(path, text)
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{}' is not true or false", value))
+}
@@ -0,0 +1,36 @@
+/// Frames a scroll is spread over
+const SCROLL_FRAMES: u32 = 4;
+
+/// Slides the picture from where it was before a scroll to where it is now over a few
+/// frames, instead of jumping there at once
+#[derive(Default)]
+pub struct SmoothScroll {
+    /// How far, in CHIP-8 pixels, the picture is still drawn from its real position
+    offset: (f32, f32),
+    frames_left: u32,
+}
+
+impl SmoothScroll {
+    pub fn scrolled(&mut self, dx: i8, dy: i8) {
+        self.offset.0 -= dx as f32;
+        self.offset.1 -= dy as f32;
+        self.frames_left = SCROLL_FRAMES;
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.frames_left > 0
+    }
+
+    /// Offset to draw the current frame at, in CHIP-8 pixels; moves the animation along
+    pub fn next_offset(&mut self) -> (f32, f32) {
+        if self.frames_left == 0 {
+            self.offset = (0.0, 0.0);
+            return self.offset;
+        }
+        let offset = self.offset;
+        let remaining = (self.frames_left - 1) as f32 / self.frames_left as f32;
+        self.offset = (self.offset.0 * remaining, self.offset.1 * remaining);
+        self.frames_left -= 1;
+        offset
+    }
+}
@@ -0,0 +1,128 @@
+use sdl2::{keyboard::Scancode, pixels::Color, rect::Rect, render::Canvas, video::Window};
+
+use chip8_core::{decode_sprite, RAM_SIZE};
+
+use crate::overlay::{self, LINE_HEIGHT};
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const CATPPUCCIN_MOCHA_CRUST: Color = Color::RGB(17, 17, 27);
+const CATPPUCCIN_MOCHA_SURFACE0: Color = Color::RGB(49, 50, 68);
+const CATPPUCCIN_MOCHA_TEXT: Color = Color::RGB(205, 214, 244);
+const CATPPUCCIN_MOCHA_YELLOW: Color = Color::RGB(249, 226, 175);
+
+/// Sprites per row of the grid
+const COLUMNS: usize = 16;
+/// Size of a sprite pixel on screen
+const SPRITE_SCALE: u32 = 6;
+const CELL_GAP: u32 = 16;
+const MARGIN: u32 = 12;
+/// Scale of the address labels and the header
+const TEXT_SCALE: u32 = 2;
+const LARGEST_HEIGHT: usize = 16;
+
+/// Debug panel showing memory as a grid of 8xN sprites, for browsing a ROM's graphics
+pub struct SpriteViewer {
+    /// Address of the first sprite shown
+    addr: u16,
+    /// Rows per sprite
+    height: usize,
+}
+
+impl SpriteViewer {
+    /// Start browsing at `addr`, typically where I points
+    pub fn new(addr: u16) -> Self {
+        Self { addr, height: 8 }
+    }
+
+    /// Move through memory or resize the sprites if `scancode` is one of the viewer's keys:
+    /// the arrows move by a byte or a sprite, Page Up and Page Down by a screenful, and
+    /// minus and plus change the sprite height. Returns whether the key was used.
+    pub fn handle_key(&mut self, scancode: Scancode) -> bool {
+        let page = (COLUMNS * self.rows() * self.height) as i32;
+        let offset = match scancode {
+            Scancode::Left => -1,
+            Scancode::Right => 1,
+            Scancode::Up => -(self.height as i32),
+            Scancode::Down => self.height as i32,
+            Scancode::PageUp => -page,
+            Scancode::PageDown => page,
+            Scancode::Minus => {
+                self.height = (self.height - 1).max(1);
+                return true;
+            }
+            Scancode::Equals => {
+                self.height = (self.height + 1).min(LARGEST_HEIGHT);
+                return true;
+            }
+            _ => return false,
+        };
+        self.addr = (self.addr as i32 + offset).rem_euclid(RAM_SIZE as i32) as u16;
+        true
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, memory: &[u8]) {
+        canvas.set_draw_color(CATPPUCCIN_MOCHA_CRUST);
+        canvas.clear();
+        canvas.set_draw_color(CATPPUCCIN_MOCHA_YELLOW);
+        let header = format!(
+            "Sprites 8x{} from 0x{:03X}   arrows, PgUp, PgDn: move   - +: height",
+            self.height, self.addr
+        );
+        overlay::draw_text(canvas, &header, MARGIN as i32, MARGIN as i32, TEXT_SCALE);
+
+        let (cell_width, cell_height) = self.cell_size();
+        for (index, addr) in (0..COLUMNS * self.rows())
+            .map(|sprite| (self.addr as usize + sprite * self.height) % RAM_SIZE)
+            .enumerate()
+        {
+            let left = (MARGIN + (index % COLUMNS) as u32 * cell_width) as i32;
+            let top = (Self::grid_top() + (index / COLUMNS) as u32 * cell_height) as i32;
+            canvas.set_draw_color(CATPPUCCIN_MOCHA_TEXT);
+            overlay::draw_text(canvas, &format!("{:03X}", addr), left, top, TEXT_SCALE);
+
+            let top = top + (LINE_HEIGHT * TEXT_SCALE) as i32;
+            let sprite_size = 8 * SPRITE_SCALE;
+            canvas.set_draw_color(CATPPUCCIN_MOCHA_SURFACE0);
+            canvas
+                .fill_rect(Rect::new(
+                    left,
+                    top,
+                    sprite_size,
+                    self.height as u32 * SPRITE_SCALE,
+                ))
+                .unwrap();
+            canvas.set_draw_color(CATPPUCCIN_MOCHA_YELLOW);
+            for (y, row) in decode_sprite(memory, addr as u16, self.height)
+                .iter()
+                .enumerate()
+            {
+                for x in (0..8).filter(|&x| row[x]) {
+                    let rect = Rect::new(
+                        left + (x as u32 * SPRITE_SCALE) as i32,
+                        top + (y as u32 * SPRITE_SCALE) as i32,
+                        SPRITE_SCALE,
+                        SPRITE_SCALE,
+                    );
+                    canvas.fill_rect(rect).unwrap();
+                }
+            }
+        }
+    }
+
+    fn grid_top() -> u32 {
+        2 * MARGIN + LINE_HEIGHT * TEXT_SCALE
+    }
+
+    /// Space taken by a sprite with its label, including the gap to the next one
+    fn cell_size(&self) -> (u32, u32) {
+        let width = (SCREEN_WIDTH - 2 * MARGIN) / COLUMNS as u32;
+        let height = LINE_HEIGHT * TEXT_SCALE + self.height as u32 * SPRITE_SCALE + CELL_GAP;
+        (width, height)
+    }
+
+    /// Rows of sprites that fit on the screen
+    fn rows(&self) -> usize {
+        let (_, cell_height) = self.cell_size();
+        ((SCREEN_HEIGHT - Self::grid_top()) / cell_height).max(1) as usize
+    }
+}
@@ -0,0 +1,90 @@
+use sdl2::rect::Rect;
+
+use chip8_core::disasm::SymbolTable;
+use chip8_core::{CpuState, Instruction, Quirks, PIXELS_PER_COLUMN, PIXELS_PER_ROW};
+
+use crate::SQUARE_SIZE;
+
+/// One line describing the instruction the machine is about to execute, with label names
+/// and the source line when the ROM's symbols are known
+pub fn describe_next(
+    state: &CpuState,
+    instruction: Option<Instruction>,
+    symbols: Option<&SymbolTable>,
+) -> String {
+    let location = match symbols {
+        Some(symbols) => symbols.describe(state.pc),
+        None => format!("0x{:03X}", state.pc),
+    };
+    let mut line = match instruction {
+        Some(instruction) => format!(
+            "{}  {:<16} {}",
+            location,
+            match symbols {
+                Some(symbols) => symbols.instruction(&instruction),
+                None => instruction.to_string(),
+            },
+            instruction.explain(state)
+        ),
+        None => format!("{}  unknown instruction", location),
+    };
+    if let Some(source) = symbols.and_then(|symbols| symbols.source_line(state.pc)) {
+        line = format!("{}  ({})", line, source);
+    }
+    line
+}
+
+/// Register file with the registers touched by `instruction` in brackets
+pub fn register_dump(state: &CpuState, instruction: Option<Instruction>) -> String {
+    let highlighted = instruction.map(|instruction| instruction.registers()).unwrap_or_default();
+    let registers = state
+        .v
+        .iter()
+        .enumerate()
+        .map(|(x, value)| {
+            if highlighted.contains(&(x as u8)) {
+                format!("[V{:X}={:02X}]", x, value)
+            } else {
+                format!(" V{:X}={:02X} ", x, value)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("");
+    format!(
+        "{}\n I={:03X}  DT={:02X}  ST={:02X}  stack={:03X?}",
+        registers, state.i, state.delay_timer, state.sound_timer, state.stack
+    )
+}
+
+/// Area of the window the instruction is about to draw on, if any
+pub fn affected_screen_region(
+    state: &CpuState,
+    instruction: Option<Instruction>,
+    quirks: Quirks,
+) -> Option<Rect> {
+    match instruction? {
+        Instruction::ClearScreen => Some(Rect::new(
+            0,
+            0,
+            PIXELS_PER_ROW as u32 * SQUARE_SIZE,
+            PIXELS_PER_COLUMN as u32 * SQUARE_SIZE,
+        )),
+        Instruction::Draw { x, y, n } => {
+            let column = (state.v[x as usize] as usize % PIXELS_PER_ROW) as u32;
+            let row = (state.v[y as usize] as usize % PIXELS_PER_COLUMN) as u32;
+            let (width, height) = quirks.sprite_size(n);
+            let width = (width as u32).min(PIXELS_PER_ROW as u32 - column);
+            let height = (height as u32).min(PIXELS_PER_COLUMN as u32 - row);
+            if height == 0 {
+                return None;
+            }
+            Some(Rect::new(
+                (column * SQUARE_SIZE) as i32,
+                (row * SQUARE_SIZE) as i32,
+                width * SQUARE_SIZE,
+                height * SQUARE_SIZE,
+            ))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,33 @@
+use sdl2::render::Canvas;
+use sdl2::video::{Window, WindowBuilder};
+
+use crate::args::Renderer;
+
+/// Build the window and its canvas with `renderer`. Without one the GPU is tried first and
+/// the software renderer used if it isn't available, as in most VMs and CI machines.
+pub fn create_canvas(
+    window: &WindowBuilder,
+    renderer: Option<Renderer>,
+) -> Result<Canvas<Window>, String> {
+    match renderer {
+        Some(renderer) => build(window, renderer),
+        None => build(window, Renderer::Accelerated).or_else(|err| {
+            warn!("{}; falling back to the software renderer", err);
+            build(window, Renderer::Software)
+        }),
+    }
+}
+
+fn build(window: &WindowBuilder, renderer: Renderer) -> Result<Canvas<Window>, String> {
+    let window = window
+        .build()
+        .map_err(|err| format!("Couldn't create a window: {}", err))?;
+    let canvas = window.into_canvas();
+    let canvas = match renderer {
+        Renderer::Accelerated => canvas.accelerated(),
+        Renderer::Software => canvas.software(),
+    };
+    canvas
+        .build()
+        .map_err(|err| format!("Couldn't create the {} renderer: {}", renderer.name(), err))
+}
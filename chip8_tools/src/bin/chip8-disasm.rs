@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use chip8_core::control_flow::ControlFlowGraph;
+use chip8_core::disasm::{disassemble, SymbolTable};
+use chip8_core::rom_loader;
+use chip8_core::{Platform, ROM_INITIAL_POSITION};
+
+/// Disassemble a CHIP-8 ROM, as a listing or as a control-flow graph
+#[derive(Parser)]
+struct Args {
+    rom: PathBuf,
+    /// Platform the ROM is meant to run on, for following its code in the graph
+    #[arg(long, default_value_t = Platform::CosmacVip)]
+    platform: Platform,
+    /// Print the control-flow graph of the reachable code in Graphviz's dot language,
+    /// e.g. for `dot -Tsvg`, instead of a listing
+    #[arg(long)]
+    dot: bool,
+    /// Symbol file of `label NAME ADDR` and `line ADDR FILE:LINE` lines, to name the addresses
+    #[arg(long)]
+    symbols: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let rom = match rom_loader::load_rom_file(&args.rom) {
+        Ok(rom) => rom,
+        Err(err) => {
+            eprintln!("couldn't load {}: {}", args.rom.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let symbols = match &args.symbols {
+        Some(path) => {
+            let symbols = std::fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| SymbolTable::parse(&text).map_err(|err| err.to_string()));
+            match symbols {
+                Ok(symbols) => Some(symbols),
+                Err(err) => {
+                    eprintln!("couldn't read {}: {}", path.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        None => None,
+    };
+
+    if args.dot {
+        let graph = ControlFlowGraph::build(&rom, args.platform);
+        print!("{}", graph.to_dot(symbols.as_ref()));
+        return ExitCode::SUCCESS;
+    }
+    for line in disassemble(&rom, ROM_INITIAL_POSITION as u16) {
+        match &symbols {
+            Some(symbols) => println!("{}", line.with_symbols(symbols)),
+            None => println!("{}", line),
+        }
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use chip8_core::snapshot::{Snapshot, SnapshotDiff};
+
+/// Compare two screen snapshots, like those chip8-testsuite saves with --save-screens,
+/// and draw where they differ
+#[derive(Parser)]
+struct Args {
+    expected: PathBuf,
+    actual: PathBuf,
+    /// Write a BMP of both screens overlaid: grey where they agree, red where only the
+    /// expected one is lit, green where only the actual one is
+    #[arg(long, value_name = "PATH")]
+    image: Option<PathBuf>,
+    /// Width in image pixels of each screen pixel
+    #[arg(long, default_value_t = 8)]
+    scale: usize,
+    /// List every differing pixel
+    #[arg(long)]
+    verbose: bool,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let read = |path: &Path| {
+        std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| Snapshot::parse(&text))
+            .map_err(|err| eprintln!("couldn't read {}: {}", path.display(), err))
+    };
+    let (Ok(expected), Ok(actual)) = (read(&args.expected), read(&args.actual)) else {
+        return ExitCode::FAILURE;
+    };
+    let diff = SnapshotDiff::new(expected, actual);
+
+    println!("{}", diff.summary());
+    if args.verbose {
+        for (x, y) in diff.missing() {
+            println!("missing ({}, {})", x, y);
+        }
+        for (x, y) in diff.unexpected() {
+            println!("unexpected ({}, {})", x, y);
+        }
+    }
+    if let Some(path) = &args.image {
+        if let Err(err) = std::fs::write(path, diff.to_bmp(args.scale)) {
+            eprintln!("couldn't write {}: {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    }
+    if diff.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
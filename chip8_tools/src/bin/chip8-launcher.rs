@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use chip8_core::launcher::Launcher;
+use chip8_core::rom_loader;
+
+/// Bundle several CHIP-8 ROMs behind a menu ROM that starts the one picked on the keypad.
+///
+/// The menu numbers the games from 1 in the order given. Emulators that understand
+/// bundles swap the chosen game in; others can't run them.
+#[derive(Parser)]
+struct Args {
+    /// ROMs to offer, at most 15
+    #[arg(required = true)]
+    roms: Vec<PathBuf>,
+    /// Where to write the bundle
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut games = Vec::with_capacity(args.roms.len());
+    for path in &args.roms {
+        match rom_loader::load_rom_file(path) {
+            Ok(rom) => games.push(rom),
+            Err(err) => {
+                eprintln!("error: couldn't load {}: {}", path.display(), err);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let launcher = match Launcher::new(games) {
+        Ok(launcher) => launcher,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(err) = std::fs::write(&args.output, launcher.to_bundle()) {
+        eprintln!("error: couldn't write {}: {}", args.output.display(), err);
+        return ExitCode::FAILURE;
+    }
+
+    for (number, path) in (1..).zip(&args.roms) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        println!("key {:X}: {}", number, name);
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use chip8_core::diagnostic::{Diagnostic, ErrorFormat};
+use chip8_core::disasm::{disassemble_at, SymbolTable};
+use chip8_core::lint::{lint, StackDepth};
+use chip8_core::rom_loader;
+use chip8_core::{Platform, ROM_INITIAL_POSITION};
+
+/// Statically scan a CHIP-8 ROM for suspicious patterns
+#[derive(Parser)]
+struct Args {
+    rom: PathBuf,
+    /// Platform the ROM is meant to run on
+    #[arg(long, default_value_t = Platform::CosmacVip)]
+    platform: Platform,
+    /// Instructions of context to show around each warning
+    #[arg(long, default_value_t = 2)]
+    context: u16,
+    /// Symbol file of `label NAME ADDR` and `line ADDR FILE:LINE` lines, to name the
+    /// addresses in the listings
+    #[arg(long)]
+    symbols: Option<PathBuf>,
+    /// How to print warnings and errors: human, or json for one object per line
+    #[arg(long, default_value_t = ErrorFormat::Human)]
+    error_format: ErrorFormat,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let rom = match rom_loader::load_rom_file(&args.rom) {
+        Ok(rom) => rom,
+        Err(err) => {
+            Diagnostic::error(format!("couldn't load {}: {}", args.rom.display(), err))
+                .emit(args.error_format, None);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let symbols = match args
+        .symbols
+        .as_ref()
+        .map(|path| (path, std::fs::read_to_string(path)))
+    {
+        Some((path, Ok(text))) => match SymbolTable::parse(&text) {
+            Ok(symbols) => Some(symbols),
+            Err(err) => {
+                Diagnostic::from(&err)
+                    .in_file(path.display().to_string())
+                    .emit(args.error_format, Some(&text));
+                return ExitCode::FAILURE;
+            }
+        },
+        Some((path, Err(err))) => {
+            Diagnostic::error(format!("couldn't read symbols: {}", err))
+                .in_file(path.display().to_string())
+                .emit(args.error_format, None);
+            return ExitCode::FAILURE;
+        }
+        None => None,
+    };
+
+    let report = lint(&rom, args.platform);
+    let file = args.rom.display().to_string();
+    if args.error_format == ErrorFormat::Json {
+        for warning in &report.warnings {
+            Diagnostic::from(warning)
+                .in_file(file.clone())
+                .emit(args.error_format, None);
+        }
+        return match report.warnings.is_empty() {
+            true => ExitCode::SUCCESS,
+            false => ExitCode::FAILURE,
+        };
+    }
+    let origin = ROM_INITIAL_POSITION as u16;
+    let rom_end = origin.saturating_add(rom.len() as u16).saturating_sub(1);
+    let context = args.context.saturating_mul(2);
+    for warning in &report.warnings {
+        let mut diagnostic = Diagnostic::from(warning).in_file(file.clone());
+        if let Some(symbols) = &symbols {
+            diagnostic.message = format!("{}: {}", symbols.describe(warning.addr), warning.kind);
+        }
+        print!("{}", diagnostic.render(None));
+        let first = warning.addr.saturating_sub(context).max(origin);
+        let last = warning.addr.saturating_add(context).min(rom_end);
+        for addr in (first..=last).step_by(2) {
+            if let Some(line) = disassemble_at(&rom, origin, addr) {
+                let marker = if addr == warning.addr { ">" } else { " " };
+                match &symbols {
+                    Some(symbols) => println!("  {} {}", marker, line.with_symbols(symbols)),
+                    None => println!("  {} {}", marker, line),
+                }
+            }
+        }
+        println!();
+    }
+
+    match report.max_stack_depth {
+        StackDepth::Bounded(depth) => println!("estimated maximum stack depth: {}", depth),
+        StackDepth::Unbounded => println!("estimated maximum stack depth: unbounded"),
+    }
+    println!("{} warning(s)", report.warnings.len());
+
+    if report.warnings.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,90 @@
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use chip8_core::memory_map::{ByteUse, MemoryMap};
+use chip8_core::rom_loader;
+use chip8_core::{Platform, ROM_INITIAL_POSITION};
+
+/// Bytes shown on each row of the map
+const BYTES_PER_ROW: usize = 64;
+
+/// Report how a CHIP-8 ROM uses its bytes: code reachable from the entry point, data the
+/// code reads through I, like sprites, and bytes nothing refers to
+#[derive(Parser)]
+struct Args {
+    rom: PathBuf,
+    /// Platform the ROM is meant to run on, for following its code
+    #[arg(long, default_value_t = Platform::CosmacVip)]
+    platform: Platform,
+    /// Print the regions as JSON instead of a map
+    #[arg(long)]
+    json: bool,
+    /// Leave the map uncoloured even on a terminal
+    #[arg(long)]
+    no_color: bool,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let rom = match rom_loader::load_rom_file(&args.rom) {
+        Ok(rom) => rom,
+        Err(err) => {
+            eprintln!("couldn't load {}: {}", args.rom.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let map = MemoryMap::analyze(&rom, args.platform);
+    if args.json {
+        println!("{}", map.to_json());
+        return ExitCode::SUCCESS;
+    }
+
+    let color = !args.no_color && std::io::stdout().is_terminal();
+    for row_start in (0..rom.len()).step_by(BYTES_PER_ROW) {
+        let addr = (ROM_INITIAL_POSITION + row_start) as u16;
+        let row_end = (row_start + BYTES_PER_ROW).min(rom.len());
+        let cells: String = (addr..addr + (row_end - row_start) as u16)
+            .filter_map(|addr| map.byte_use(addr))
+            .map(|byte_use| cell(byte_use, color))
+            .collect();
+        println!("0x{:03X}  {}", addr, cells);
+    }
+    println!();
+    for byte_use in [ByteUse::Code, ByteUse::Data, ByteUse::Unreferenced] {
+        let count = map.count(byte_use);
+        println!(
+            "{} {:<12} {:>5} bytes {:>5.1}%",
+            cell(byte_use, color),
+            byte_use.name(),
+            count,
+            100.0 * count as f64 / rom.len().max(1) as f64
+        );
+    }
+    println!();
+    for region in map.regions() {
+        println!(
+            "0x{:03X}..0x{:03X}  {:>5} bytes  {}",
+            region.range.start,
+            region.range.end,
+            region.range.len(),
+            region.byte_use.name()
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+/// One byte of the map: a letter, on a background of its own colour on terminals
+fn cell(byte_use: ByteUse, color: bool) -> String {
+    let (letter, background) = match byte_use {
+        ByteUse::Code => ('C', 42),
+        ByteUse::Data => ('D', 43),
+        ByteUse::Unreferenced => ('.', 100),
+    };
+    match color {
+        true => format!("\x1b[30;{}m{}\x1b[0m", background, letter),
+        false => letter.to_string(),
+    }
+}
@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use chip8_core::bitmap::Bitmap;
+use chip8_core::rom_loader;
+use chip8_core::snapshot::{Snapshot, SnapshotDiff};
+use chip8_core::{Chip8, ClockConfig, Platform};
+
+/// Run a ROM and compare its screen against reference screenshots taken in Octo, to check
+/// drawing and quirks against the community's reference interpreter
+#[derive(Parser)]
+struct Args {
+    rom: PathBuf,
+    /// Directory of PNG or BMP screenshots, each named after the frame it shows, like
+    /// 120.png or frame-0120.png
+    references: PathBuf,
+    /// Platform whose quirks to emulate; detected from the ROM when omitted
+    #[arg(long)]
+    platform: Option<Platform>,
+    /// Instructions per frame, Octo's "tickrate"; the platform's when omitted
+    #[arg(long, value_name = "N")]
+    ipf: Option<u32>,
+    /// Read dark pixels as lit, for screenshots of palettes with a light background
+    #[arg(long)]
+    invert: bool,
+    /// Share of pixels in percent a frame may differ by and still pass
+    #[arg(long, value_name = "PERCENT", default_value_t = 0.0)]
+    tolerance: f64,
+    /// Directory to write a diff image of every failing frame to, see chip8-framediff
+    #[arg(long, value_name = "DIR")]
+    diffs: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let rom = match rom_loader::load_rom_file(&args.rom) {
+        Ok(rom) => rom,
+        Err(err) => {
+            eprintln!("couldn't load {}: {}", args.rom.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let references = match load_references(&args.references, args.invert) {
+        Ok(references) if references.is_empty() => {
+            eprintln!("no screenshots in {}", args.references.display());
+            return ExitCode::FAILURE;
+        }
+        Ok(references) => references,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut chip8 = Chip8::new();
+    chip8.load_rom(&rom);
+    match args.platform {
+        Some(platform) => chip8.select_platform(platform),
+        None => chip8.apply_rom_platform(),
+    }
+    if let Some(ipf) = args.ipf {
+        chip8.set_clock(ClockConfig {
+            instructions_per_frame: ipf,
+            ..chip8.clock()
+        });
+    }
+
+    let mut failed = 0;
+    let mut frame = 0;
+    for (reference_frame, expected) in &references {
+        while frame < *reference_frame {
+            if let Err(err) = chip8.run_frame() {
+                eprintln!("frame {}: {}", frame, err);
+                return ExitCode::FAILURE;
+            }
+            frame += 1;
+        }
+        let diff = SnapshotDiff::new(expected.clone(), Snapshot::of(&chip8));
+        let mismatch = diff.mismatch_percent();
+        let passed = mismatch <= args.tolerance;
+        println!(
+            "frame {:>6}: {:6.2}% differ{}",
+            frame,
+            mismatch,
+            if passed { "" } else { "  FAIL" }
+        );
+        if passed {
+            continue;
+        }
+        failed += 1;
+        if let Some(directory) = &args.diffs {
+            let path = directory.join(format!("frame-{}.bmp", frame));
+            if let Err(err) = std::fs::write(&path, diff.to_bmp(8)) {
+                eprintln!("couldn't write {}: {}", path.display(), err);
+            }
+        }
+    }
+    println!(
+        "{} of {} frames match",
+        references.len() - failed,
+        references.len()
+    );
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// The screenshots in `directory` with the frames they show, in order
+fn load_references(directory: &Path, invert: bool) -> Result<Vec<(u32, Snapshot)>, String> {
+    let entries = std::fs::read_dir(directory)
+        .map_err(|err| format!("couldn't read {}: {}", directory.display(), err))?;
+    let mut references = vec![];
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        if !matches!(extension, Some("png" | "bmp")) {
+            continue;
+        }
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let digits: String = stem.chars().filter(char::is_ascii_digit).collect();
+        let Ok(frame) = digits.parse() else {
+            return Err(format!("{} isn't named after a frame", path.display()));
+        };
+        let snapshot = std::fs::read(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|data| Bitmap::decode(&data).map_err(|err| err.to_string()))
+            .and_then(|mut bitmap| {
+                if invert {
+                    bitmap.invert();
+                }
+                Snapshot::from_bitmap(&bitmap)
+            })
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        references.push((frame, snapshot));
+    }
+    references.sort_by_key(|(frame, _)| *frame);
+    Ok(references)
+}
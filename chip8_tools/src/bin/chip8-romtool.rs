@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+use chip8_core::rom_dump::{self, RomStats};
+use chip8_core::MAX_ROM_SIZE;
+
+/// Clean up ROM dumps: trim their padding, fix odd lengths, and split dumps holding
+/// several ROMs
+#[derive(Parser)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Remove the zeros padding the end of the ROM
+    Trim {
+        rom: PathBuf,
+        /// Where to write the result; the ROM is overwritten by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Add a zero byte to a ROM of odd length, so its last instruction is whole
+    Pad {
+        rom: PathBuf,
+        /// Where to write the result; the ROM is overwritten by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the size, hashes and entropy of ROMs, and whether they fit in memory
+    Stats { roms: Vec<PathBuf> },
+    /// Split a dump of several ROMs into NAME-1.ch8, NAME-2.ch8 and so on, each trimmed
+    Split {
+        dump: PathBuf,
+        /// Cut the dump into ROMs of this many bytes instead of at runs of zeros
+        #[arg(long, value_name = "BYTES")]
+        size: Option<usize>,
+        /// Fewest zero bytes that separate two ROMs
+        #[arg(long, value_name = "BYTES", default_value_t = 32)]
+        min_gap: usize,
+        /// Directory for the ROMs; the dump's by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+fn main() -> ExitCode {
+    let outcome = match Args::parse().command {
+        Command::Trim { rom, output } => rewrite(&rom, output.as_deref(), |data| {
+            let trimmed = rom_dump::trim(data);
+            println!("removed {} bytes of padding", data.len() - trimmed.len());
+            trimmed.to_vec()
+        }),
+        Command::Pad { rom, output } => rewrite(&rom, output.as_deref(), rom_dump::pad_to_even),
+        Command::Stats { roms } => roms.iter().try_for_each(|rom| {
+            let stats = RomStats::of(&read(rom)?);
+            print_stats(rom, &stats);
+            Ok(())
+        }),
+        Command::Split {
+            dump,
+            size,
+            min_gap,
+            output,
+        } => split(&dump, size, min_gap, output),
+    };
+    match outcome {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|err| format!("couldn't read {}: {}", path.display(), err))
+}
+
+fn write(path: &Path, data: &[u8]) -> Result<(), String> {
+    std::fs::write(path, data).map_err(|err| format!("couldn't write {}: {}", path.display(), err))
+}
+
+/// Write `change` of the ROM at `rom` to `output`, or back to `rom`
+fn rewrite(
+    rom: &Path,
+    output: Option<&Path>,
+    change: impl FnOnce(&[u8]) -> Vec<u8>,
+) -> Result<(), String> {
+    let changed = change(&read(rom)?);
+    write(output.unwrap_or(rom), &changed)
+}
+
+fn print_stats(path: &Path, stats: &RomStats) {
+    println!("{}", path.display());
+    println!("  size     {} bytes", stats.size);
+    println!("  hash     {:016x}", stats.hash);
+    println!("  crc32    {:08x}", stats.crc32);
+    println!("  entropy  {:.2} bits per byte", stats.entropy);
+    println!("  padding  {} bytes", stats.padding);
+    let trimmed = stats.size - stats.padding;
+    if stats.size > MAX_ROM_SIZE && trimmed <= MAX_ROM_SIZE {
+        println!("  too large to load, but fits once trimmed");
+    } else if stats.size > MAX_ROM_SIZE {
+        println!("  too large to load, likely several ROMs: try split");
+    }
+}
+
+fn split(
+    dump: &Path,
+    size: Option<usize>,
+    min_gap: usize,
+    output: Option<PathBuf>,
+) -> Result<(), String> {
+    let data = read(dump)?;
+    let roms = match size {
+        Some(size) => rom_dump::split_every(&data, size),
+        None => rom_dump::split_at_gaps(&data, min_gap),
+    };
+    let directory = output
+        .or_else(|| dump.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    let name = dump.file_stem().unwrap_or_default().to_string_lossy();
+    for (index, rom) in roms.iter().enumerate() {
+        let path = directory.join(format!("{}-{}.ch8", name, index + 1));
+        write(&path, rom)?;
+        let warning = match rom.len() > MAX_ROM_SIZE {
+            true => ", still too large to load",
+            false => "",
+        };
+        println!("{}: {} bytes{}", path.display(), rom.len(), warning);
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+
+use chip8_core::bitmap::{Bitmap, SPRITE_WIDTH};
+
+/// Bytes per line of the text formats
+const BYTES_PER_LINE: usize = 8;
+
+/// Convert a PNG or BMP image into CHIP-8 sprite data, a sprite for every 8 pixel wide
+/// column of it
+#[derive(Parser)]
+struct Args {
+    image: PathBuf,
+    /// Light pixels are lit by default; invert for dark art on a light background
+    #[arg(long)]
+    invert: bool,
+    /// list: bytes separated by commas; octo: labelled Octo source; binary: raw bytes
+    #[arg(long, value_enum, default_value_t = Format::List)]
+    format: Format,
+    /// Name of the sprites, numbered by column; the image's file name by default
+    #[arg(long)]
+    name: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    List,
+    Octo,
+    Binary,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let decoded = std::fs::read(&args.image)
+        .map_err(|err| err.to_string())
+        .and_then(|data| Bitmap::decode(&data).map_err(|err| err.to_string()));
+    let mut bitmap = match decoded {
+        Ok(bitmap) => bitmap,
+        Err(err) => {
+            eprintln!("couldn't read {}: {}", args.image.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    if args.invert {
+        bitmap.invert();
+    }
+    let name = args.name.unwrap_or_else(|| {
+        let stem = args.image.file_stem().unwrap_or_default();
+        stem.to_string_lossy().into_owned()
+    });
+
+    let columns = bitmap.sprite_columns();
+    if args.format == Format::Binary {
+        let bytes = columns.concat();
+        return match std::io::stdout().write_all(&bytes) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("couldn't write the sprites: {}", err);
+                ExitCode::FAILURE
+            }
+        };
+    }
+    let (indent, separator) = match args.format {
+        Format::Octo => ("\t", " "),
+        _ => ("", ", "),
+    };
+    for (column, sprite) in columns.iter().enumerate() {
+        let left = column * SPRITE_WIDTH;
+        let right = (left + SPRITE_WIDTH).min(bitmap.width) - 1;
+        match args.format {
+            Format::Octo => println!(": {}-{}", name, column),
+            _ => println!(
+                "; {} {}: x {}-{}, {} rows",
+                name,
+                column,
+                left,
+                right,
+                sprite.len()
+            ),
+        }
+        for line in sprite.chunks(BYTES_PER_LINE) {
+            let bytes: Vec<String> = line.iter().map(|byte| format!("0x{:02X}", byte)).collect();
+            println!("{}{}", indent, bytes.join(separator));
+        }
+    }
+    ExitCode::SUCCESS
+}
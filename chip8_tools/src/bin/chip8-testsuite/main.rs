@@ -0,0 +1,427 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{Parser, ValueEnum};
+
+use chip8_core::diagnostic::Diagnostic;
+use chip8_core::rom_loader;
+use chip8_core::json::{self, Value};
+use chip8_core::limits::{LimitGuard, Limits};
+use chip8_core::script::Script;
+use chip8_core::snapshot::Snapshot;
+use chip8_core::{rom_hash, Chip8, Platform, Preset};
+
+use report::{Method, Status, TestResult};
+
+mod ocr;
+mod report;
+
+/// Exit status when a ROM tripped a limit, apart from failures (1) and usage errors (2)
+const LIMIT_EXIT_CODE: u8 = 3;
+
+/// Run a directory of test ROMs headlessly and report which ones pass. Exits with 0 when
+/// they all do, 1 when any fails and 3 when any trips a limit.
+#[derive(Parser)]
+struct Args {
+    /// Directory holding the `.ch8` ROMs
+    dir: PathBuf,
+    /// Expectations for each ROM [default: <dir>/expectations.json]
+    #[arg(long)]
+    expectations: Option<PathBuf>,
+    /// Frames to run ROMs without an expectation for
+    #[arg(long, default_value_t = 300)]
+    frames: u32,
+    #[arg(long, value_enum, default_value_t = Format::Json)]
+    format: Format,
+    /// Write the report here instead of to stdout
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Record the current screens as the expected ones
+    #[arg(long)]
+    bless: bool,
+    /// Run the ROMs with this accuracy preset (strict, compatible, fast) instead of the
+    /// machine's defaults
+    #[arg(long)]
+    preset: Option<Preset>,
+    /// Save every ROM's last screen to this directory as `<rom>.screen`, to compare with
+    /// chip8-framediff
+    #[arg(long, value_name = "DIR")]
+    save_screens: Option<PathBuf>,
+    /// Stop a ROM once it ran for this long in real time
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+    /// Stop a ROM once it executed more instructions than this
+    #[arg(long, value_name = "N")]
+    max_instructions: Option<u64>,
+    /// Stop a ROM once it wrote more bytes than this to memory in a single frame
+    #[arg(long, value_name = "BYTES")]
+    max_writes_per_frame: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Json,
+    Junit,
+}
+
+/// How to run a ROM and what it should show at the end, from the expectations file:
+///
+/// ```json
+/// {"5-quirks.ch8": {"platform": "super-chip", "memory": {"0x1FF": 2}, "frames": 600, "screen_hash": "…"}}
+/// ```
+///
+/// A `script` next to the ROMs presses keys and checks the machine along the way, see
+/// [`chip8_core::script`]; the run lasts at least until its last step. Without a
+/// `screen_hash` or a `script`, the ROM passes if it draws checkmarks and no crosses.
+#[derive(Debug, Clone, Default)]
+struct Expectation {
+    frames: Option<u32>,
+    platform: Option<Platform>,
+    /// Bytes poked into memory before running, e.g. to skip a test ROM's menu
+    memory: Vec<(usize, u8)>,
+    screen_hash: Option<u64>,
+    /// File name of the script, in the ROM's directory
+    script: Option<String>,
+}
+
+impl Expectation {
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let platform = match value.get("platform").and_then(Value::as_str) {
+            Some(name) => Some(name.parse()?),
+            None => None,
+        };
+        let memory = match value.get("memory") {
+            Some(Value::Object(members)) => members
+                .iter()
+                .map(|(addr, byte)| {
+                    let digits = addr.trim_start_matches("0x").trim_start_matches("0X");
+                    let addr = usize::from_str_radix(digits, 16)
+                        .map_err(|_| format!("'{}' is not a hex address", addr))?;
+                    let byte = byte
+                        .as_f64()
+                        .filter(|byte| (0.0..=255.0).contains(byte))
+                        .ok_or_else(|| format!("memory at {:#05X} must be a byte", addr))?;
+                    Ok((addr, byte as u8))
+                })
+                .collect::<Result<_, String>>()?,
+            _ => vec![],
+        };
+        let screen_hash = match value.get("screen_hash").and_then(Value::as_str) {
+            Some(hash) => Some(
+                u64::from_str_radix(hash, 16)
+                    .map_err(|_| format!("'{}' is not a screen hash", hash))?,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            frames: value
+                .get("frames")
+                .and_then(Value::as_f64)
+                .map(|frames| frames as u32),
+            platform,
+            memory,
+            screen_hash,
+            script: value
+                .get("script")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        })
+    }
+
+    /// One line of the expectations file
+    fn to_json(&self) -> Value {
+        let mut members = vec![];
+        if let Some(frames) = self.frames {
+            members.push(("frames".to_string(), frames.into()));
+        }
+        if let Some(platform) = self.platform {
+            members.push(("platform".to_string(), platform.name().into()));
+        }
+        if !self.memory.is_empty() {
+            let memory = self
+                .memory
+                .iter()
+                .map(|&(addr, byte)| (format!("{:#05X}", addr), (byte as u32).into()))
+                .collect();
+            members.push(("memory".to_string(), Value::Object(memory)));
+        }
+        if let Some(hash) = self.screen_hash {
+            members.push(("screen_hash".to_string(), format!("{:016x}", hash).into()));
+        }
+        if let Some(script) = &self.script {
+            members.push(("script".to_string(), script.as_str().into()));
+        }
+        Value::Object(members)
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let expectations_path = args
+        .expectations
+        .clone()
+        .unwrap_or_else(|| args.dir.join("expectations.json"));
+    let mut expectations = match load_expectations(&expectations_path) {
+        Ok(expectations) => expectations,
+        Err(err) => {
+            eprint!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut roms = match std::fs::read_dir(&args.dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "ch8"))
+            .collect::<Vec<_>>(),
+        Err(err) => {
+            eprintln!("error: couldn't read {}: {}", args.dir.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    roms.sort();
+
+    let limits = Limits {
+        wall_clock: args.timeout.map(Duration::from_secs),
+        instructions: args.max_instructions,
+        writes_per_frame: args.max_writes_per_frame,
+    };
+    let results = roms
+        .iter()
+        .map(|path| {
+            let name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let expectation = expectations.get(&name).cloned().unwrap_or_default();
+            run_test(path, name, &expectation, args.frames, args.preset, limits)
+        })
+        .collect::<Vec<_>>();
+
+    let report = match args.format {
+        Format::Json => report::to_json(&results),
+        Format::Junit => report::to_junit(&results),
+    };
+    match &args.output {
+        Some(path) => {
+            if let Err(err) = std::fs::write(path, report) {
+                eprintln!("error: couldn't write {}: {}", path.display(), err);
+                return ExitCode::FAILURE;
+            }
+        }
+        None => println!("{}", report),
+    }
+    if let Some(dir) = &args.save_screens {
+        for result in &results {
+            let Some(screen) = &result.screen else {
+                continue;
+            };
+            let path = dir.join(format!("{}.screen", result.rom));
+            if let Err(err) = std::fs::write(&path, screen.to_string()) {
+                eprintln!("error: couldn't write {}: {}", path.display(), err);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if args.bless {
+        for result in results
+            .iter()
+            .filter(|result| !matches!(result.status, Status::Error | Status::Limit))
+        {
+            let expectation = expectations.entry(result.rom.clone()).or_default();
+            expectation.frames.get_or_insert(result.frames);
+            expectation.screen_hash = Some(result.screen_hash);
+        }
+        if let Err(err) = save_expectations(&expectations_path, &expectations) {
+            eprintln!(
+                "error: couldn't write {}: {}",
+                expectations_path.display(),
+                err
+            );
+            return ExitCode::FAILURE;
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    if results.iter().any(|result| result.status == Status::Limit) {
+        ExitCode::from(LIMIT_EXIT_CODE)
+    } else if results.iter().all(|result| result.status == Status::Pass) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn run_test(
+    path: &Path,
+    rom: String,
+    expectation: &Expectation,
+    default_frames: u32,
+    preset: Option<Preset>,
+    limits: Limits,
+) -> TestResult {
+    let mut frames = expectation.frames.unwrap_or(default_frames);
+    let method = match (expectation.screen_hash, &expectation.script) {
+        (Some(_), _) => Method::ScreenHash,
+        (None, Some(_)) => Method::Script,
+        (None, None) => Method::Ocr,
+    };
+    let mut result = TestResult {
+        rom,
+        status: Status::Error,
+        method,
+        detail: String::new(),
+        frames,
+        screen_hash: 0,
+        screen: None,
+    };
+
+    let data = match rom_loader::load_rom_file(path) {
+        Ok(data) => data,
+        Err(err) => {
+            result.detail = format!("couldn't load the ROM: {}", err);
+            return result;
+        }
+    };
+    let script = match &expectation.script {
+        Some(name) => {
+            let script_path = path.with_file_name(name);
+            let script = std::fs::read_to_string(&script_path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| Script::parse(&text).map_err(|err| err.to_string()));
+            match script {
+                Ok(script) => Some(script),
+                Err(err) => {
+                    result.detail = format!("couldn't read {}: {}", script_path.display(), err);
+                    return result;
+                }
+            }
+        }
+        None => None,
+    };
+    if let Some(script) = &script {
+        frames = frames.max(script.last_frame());
+        result.frames = frames;
+    }
+    let mut player = script.as_ref().map(Script::player);
+    let mut chip8 = Chip8::new();
+    chip8.load_rom(&data);
+    match expectation.platform {
+        Some(platform) => chip8.select_platform(platform),
+        None => chip8.apply_rom_platform(),
+    }
+    if let Some(preset) = preset {
+        preset.apply(&mut chip8);
+    }
+    for &(addr, byte) in &expectation.memory {
+        if let Err(err) = chip8.write_memory(addr, &[byte]) {
+            result.detail = err.to_string();
+            return result;
+        }
+    }
+    let mut guard = LimitGuard::start(limits, &chip8);
+    for frame in 0..=frames {
+        if let Some(player) = &mut player {
+            if let Err(failed) = player.before_frame(frame, &mut chip8) {
+                result.status = Status::Fail;
+                result.detail = failed.to_string();
+                return result;
+            }
+        }
+        // The script's steps at the last frame only check how the run ended
+        if frame == frames {
+            break;
+        }
+        if let Err(err) = chip8.run_frame() {
+            result.detail = format!("frame {}: {}", frame, err);
+            return result;
+        }
+        if let Err(exceeded) = guard.check(&chip8) {
+            result.status = Status::Limit;
+            result.detail = format!("frame {}: {}", frame, exceeded);
+            return result;
+        }
+    }
+
+    let screen = chip8.screen.map(|on| on as u8);
+    result.screen_hash = rom_hash(&screen);
+    result.screen = Some(Snapshot::of(&chip8));
+    let passed = match (expectation.screen_hash, &script) {
+        (Some(expected), _) => {
+            result.detail = format!(
+                "expected screen {:016x}, got {:016x}",
+                expected, result.screen_hash
+            );
+            expected == result.screen_hash
+        }
+        (None, Some(script)) => {
+            result.detail = format!("met the script's {} steps", script.steps().len());
+            true
+        }
+        (None, None) => {
+            let marks = ocr::read_marks(&chip8);
+            result.detail = format!(
+                "{} checkmark(s), {} cross(es)",
+                marks.checkmarks, marks.crosses
+            );
+            marks.checkmarks > 0 && marks.crosses == 0
+        }
+    };
+    result.status = if passed { Status::Pass } else { Status::Fail };
+    result
+}
+
+/// The expectations in the file at `path`, or the diagnostic to print about it
+fn load_expectations(path: &Path) -> Result<BTreeMap<String, Expectation>, String> {
+    let file = path.display().to_string();
+    let fail = |message: String| {
+        Diagnostic::error(message)
+            .in_file(file.clone())
+            .render(None)
+    };
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(fail(err.to_string())),
+    };
+    let document = json::parse(&text).map_err(|err| {
+        Diagnostic::from(&err)
+            .in_file(file.clone())
+            .render(Some(&text))
+    })?;
+    match document {
+        Value::Object(members) => members
+            .iter()
+            .map(|(rom, value)| {
+                let expectation = Expectation::from_json(value)
+                    .map_err(|err| fail(format!("{}: {}", rom, err)))?;
+                Ok((rom.clone(), expectation))
+            })
+            .collect(),
+        _ => Err(fail(
+            "expected an object with one member per ROM".to_string(),
+        )),
+    }
+}
+
+fn save_expectations(
+    path: &Path,
+    expectations: &BTreeMap<String, Expectation>,
+) -> std::io::Result<()> {
+    // One ROM per line keeps diffs of blessed results readable
+    let lines = expectations
+        .iter()
+        .map(|(rom, expectation)| {
+            format!(
+                "  {}: {}",
+                Value::String(rom.clone()),
+                expectation.to_json()
+            )
+        })
+        .collect::<Vec<_>>();
+    std::fs::write(path, format!("{{\n{}\n}}\n", lines.join(",\n")))
+}
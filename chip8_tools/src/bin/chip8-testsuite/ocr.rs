@@ -0,0 +1,45 @@
+//! Reading the pass and fail marks test ROMs like Timendus' suite draw next to each check
+
+use chip8_core::{Chip8, PIXELS_PER_COLUMN, PIXELS_PER_ROW};
+
+/// 3x3 glyphs, one row per entry, most significant of the three bits on the left
+const CHECKMARK: [u8; 3] = [0b101, 0b110, 0b100];
+const CROSS: [u8; 3] = [0b101, 0b010, 0b101];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Marks {
+    pub checkmarks: usize,
+    pub crosses: usize,
+}
+
+/// Count the checkmarks and crosses standing on their own on the screen
+pub fn read_marks(chip8: &Chip8) -> Marks {
+    let lit = |x: isize, y: isize| {
+        (0..PIXELS_PER_ROW as isize).contains(&x)
+            && (0..PIXELS_PER_COLUMN as isize).contains(&y)
+            && chip8.pixel(x as usize, y as usize)
+    };
+    // Whether `glyph` is drawn with its top-left corner at (x, y), surrounded by unlit pixels
+    let matches = |glyph: &[u8; 3], x: isize, y: isize| {
+        (-1..4).all(|dy| {
+            (-1..4).all(|dx| {
+                let inside = (0..3).contains(&dx) && (0..3).contains(&dy);
+                let expected = inside && (glyph[dy as usize] >> (2 - dx)) & 0b1 == 1;
+                lit(x + dx, y + dy) == expected
+            })
+        })
+    };
+
+    let mut marks = Marks::default();
+    for y in 0..PIXELS_PER_COLUMN as isize {
+        for x in 0..PIXELS_PER_ROW as isize {
+            if matches(&CHECKMARK, x, y) {
+                marks.checkmarks += 1;
+            }
+            if matches(&CROSS, x, y) {
+                marks.crosses += 1;
+            }
+        }
+    }
+    marks
+}
@@ -0,0 +1,137 @@
+use std::fmt::Write;
+
+use chip8_core::json::Value;
+use chip8_core::snapshot::Snapshot;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail,
+    /// The ROM couldn't be run to the end, e.g. because it crashed the core
+    Error,
+    /// The ROM was stopped for going over a limit, like running too long
+    Limit,
+}
+
+impl Status {
+    fn name(&self) -> &'static str {
+        match self {
+            Status::Pass => "pass",
+            Status::Fail => "fail",
+            Status::Error => "error",
+            Status::Limit => "limit",
+        }
+    }
+}
+
+/// How a ROM was judged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// The screen was compared against an expected hash
+    ScreenHash,
+    /// Pass and fail marks were read off the screen
+    Ocr,
+    /// A script pressed keys and checked the machine
+    Script,
+}
+
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    /// File name of the ROM
+    pub rom: String,
+    pub status: Status,
+    pub method: Method,
+    /// Why the ROM passed or failed
+    pub detail: String,
+    pub frames: u32,
+    pub screen_hash: u64,
+    /// The screen at the end, unless the ROM couldn't run
+    pub screen: Option<Snapshot>,
+}
+
+pub fn to_json(results: &[TestResult]) -> String {
+    let count = |status| {
+        results
+            .iter()
+            .filter(|result| result.status == status)
+            .count() as u32
+    };
+    let results = results
+        .iter()
+        .map(|result| {
+            Value::Object(vec![
+                ("rom".to_string(), result.rom.as_str().into()),
+                ("status".to_string(), result.status.name().into()),
+                (
+                    "method".to_string(),
+                    match result.method {
+                        Method::ScreenHash => "screen_hash",
+                        Method::Ocr => "ocr",
+                        Method::Script => "script",
+                    }
+                    .into(),
+                ),
+                ("detail".to_string(), result.detail.as_str().into()),
+                ("frames".to_string(), result.frames.into()),
+                (
+                    "screen_hash".to_string(),
+                    format!("{:016x}", result.screen_hash).into(),
+                ),
+            ])
+        })
+        .collect();
+    Value::Object(vec![
+        ("passed".to_string(), count(Status::Pass).into()),
+        ("failed".to_string(), count(Status::Fail).into()),
+        ("errors".to_string(), count(Status::Error).into()),
+        ("limits".to_string(), count(Status::Limit).into()),
+        ("results".to_string(), Value::Array(results)),
+    ])
+    .to_string()
+}
+
+pub fn to_junit(results: &[TestResult]) -> String {
+    let count = |status| {
+        results
+            .iter()
+            .filter(|result| result.status == status)
+            .count()
+    };
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuite name=\"chip8-testsuite\" tests=\"{}\" failures=\"{}\" errors=\"{}\">",
+        results.len(),
+        count(Status::Fail),
+        count(Status::Error) + count(Status::Limit)
+    );
+    for result in results {
+        let _ = write!(
+            xml,
+            "  <testcase classname=\"chip8-testsuite\" name=\"{}\"",
+            escape(&result.rom)
+        );
+        let _ = match result.status {
+            Status::Pass => writeln!(xml, "/>"),
+            Status::Fail => writeln!(
+                xml,
+                ">\n    <failure message=\"{}\"/>\n  </testcase>",
+                escape(&result.detail)
+            ),
+            Status::Error | Status::Limit => writeln!(
+                xml,
+                ">\n    <error message=\"{}\"/>\n  </testcase>",
+                escape(&result.detail)
+            ),
+        };
+    }
+    xml.push_str("</testsuite>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+
+use chip8_core::json::Value;
+use chip8_core::timing::{parse_reference, timing_report};
+use chip8_core::{Chip8, Platform, Preset};
+
+/// Measure the instructions the emulator runs per frame in loops of one kind of
+/// instruction, and how far that is from reference hardware
+#[derive(Parser)]
+struct Args {
+    /// Instructions per frame measured on the hardware, as a JSON object by loop name,
+    /// e.g. {"6XNN": 17.5, "DXYN": 1}
+    #[arg(long)]
+    reference: Option<PathBuf>,
+    /// Platform whose interpreter is emulated
+    #[arg(long, default_value_t = Platform::CosmacVip)]
+    platform: Platform,
+    /// Accuracy preset (strict, compatible, fast) to measure instead of the defaults
+    #[arg(long)]
+    preset: Option<Preset>,
+    /// Frames to run each loop for
+    #[arg(long, default_value_t = 600)]
+    frames: u32,
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let reference = match &args.reference {
+        Some(path) => match std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| parse_reference(&text))
+        {
+            Ok(reference) => reference,
+            Err(err) => {
+                eprintln!("couldn't read {}: {}", path.display(), err);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => Vec::new(),
+    };
+
+    let mut machine = Chip8::with_platform(args.platform);
+    if let Some(preset) = args.preset {
+        preset.apply(&mut machine);
+    }
+    let report = match timing_report(&machine, &reference, args.frames) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("a calibration loop failed: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match args.format {
+        Format::Json => {
+            let measurements = report.iter().map(|measurement| measurement.to_json());
+            println!("{}", Value::Array(measurements.collect()));
+        }
+        Format::Text => {
+            let number = |value: Option<f64>, suffix| match value {
+                Some(value) => format!("{:.2}{}", value, suffix),
+                None => "-".to_string(),
+            };
+            println!(
+                "{:<6} {:>12} {:>12} {:>10}",
+                "loop", "per frame", "reference", "deviation"
+            );
+            for measurement in &report {
+                println!(
+                    "{:<6} {:>12.2} {:>12} {:>10}",
+                    measurement.name,
+                    measurement.instructions_per_frame,
+                    number(measurement.reference, ""),
+                    number(measurement.deviation(), "%")
+                );
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+
+use chip8_core::rom_loader;
+use chip8_core::{Chip8, Platform, Preset, TraceEntry, VcdWriter};
+
+/// Run a CHIP-8 ROM without a window and export every instruction it executes, to diff
+/// against other emulators or inspect in a waveform viewer
+#[derive(Parser)]
+struct Args {
+    rom: PathBuf,
+    /// File to write the trace to; the format follows its extension unless --format is given
+    #[arg(short, long)]
+    output: PathBuf,
+    /// jsonl for one JSON object per instruction, vcd for waveform viewers like GTKWave
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+    /// Frames to run, at 60 per emulated second
+    #[arg(long, default_value_t = 60)]
+    frames: u32,
+    /// Platform whose quirks to emulate; detected from the ROM when omitted
+    #[arg(long)]
+    platform: Option<Platform>,
+    /// Speed and error handling preset (strict, compatible, fast); the machine's own
+    /// defaults when omitted, as in chip8-testsuite and the emulator
+    #[arg(long)]
+    preset: Option<Preset>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Jsonl,
+    Vcd,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let format = match args.format {
+        Some(format) => format,
+        None => match args.output.extension().and_then(|ext| ext.to_str()) {
+            Some("vcd") => Format::Vcd,
+            Some("jsonl") | Some("json") => Format::Jsonl,
+            _ => {
+                eprintln!(
+                    "error: can't tell the format from the output's extension, pass --format"
+                );
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    let rom = match rom_loader::load_rom_file(&args.rom) {
+        Ok(rom) => rom,
+        Err(err) => {
+            eprintln!("error: couldn't load {}: {}", args.rom.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut chip8 = Chip8::new();
+    chip8.load_rom(&rom);
+    match args.platform {
+        Some(platform) => chip8.select_platform(platform),
+        None => chip8.apply_rom_platform(),
+    }
+    if let Some(preset) = args.preset {
+        preset.apply(&mut chip8);
+    }
+    // Whatever a frame runs, speed zones and instructions carried over included, is
+    // written out after it, so the trace only ever holds one frame
+    chip8.enable_tracer(usize::MAX);
+
+    let mut status = ExitCode::SUCCESS;
+    let written = TraceFile::create(&args.output, format).and_then(|mut file| {
+        for frame in 0..args.frames {
+            let outcome = chip8.run_frame();
+            if let Some(tracer) = chip8.tracer_mut() {
+                for entry in tracer.drain() {
+                    file.write(&entry)?;
+                }
+            }
+            if let Err(err) = outcome {
+                eprintln!("error: frame {}: {}", frame, err);
+                status = ExitCode::FAILURE;
+                break;
+            }
+        }
+        file.finish()
+    });
+    match written {
+        Ok(count) => println!(
+            "{} instructions written to {}",
+            count,
+            args.output.display()
+        ),
+        Err(err) => {
+            eprintln!("error: couldn't write {}: {}", args.output.display(), err);
+            return ExitCode::FAILURE;
+        }
+    }
+    status
+}
+
+/// The output file, written one instruction at a time
+struct TraceFile {
+    out: Output,
+    /// Instructions written so far
+    count: usize,
+}
+
+enum Output {
+    Jsonl(BufWriter<File>),
+    Vcd(VcdWriter<BufWriter<File>>),
+}
+
+impl TraceFile {
+    fn create(path: &Path, format: Format) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        let out = match format {
+            Format::Jsonl => Output::Jsonl(file),
+            Format::Vcd => Output::Vcd(VcdWriter::new(file)?),
+        };
+        Ok(Self { out, count: 0 })
+    }
+
+    fn write(&mut self, entry: &TraceEntry) -> io::Result<()> {
+        match &mut self.out {
+            Output::Jsonl(file) => writeln!(file, "{}", entry.to_json())?,
+            Output::Vcd(vcd) => vcd.write(entry)?,
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Flush the file, returning how many instructions it holds
+    fn finish(self) -> io::Result<usize> {
+        let mut file = match self.out {
+            Output::Jsonl(file) => file,
+            Output::Vcd(vcd) => vcd.into_inner(),
+        };
+        file.flush()?;
+        Ok(self.count)
+    }
+}
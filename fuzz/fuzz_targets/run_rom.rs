@@ -0,0 +1,56 @@
+//! Runs arbitrary bytes as a ROM with arbitrary keys held, checking the core reports every
+//! fault as a `Chip8Error` instead of panicking.
+//!
+//! Input layout: a configuration byte (platform in the low two bits, then the horizontal
+//! and vertical wrap quirks, then two bits for what DXY0 draws), two bytes of keys held,
+//! then the ROM.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use chip8_core::{Chip8, Platform, ZeroHeightSprite, MAX_ROM_SIZE, RAM_SIZE, STACK_SIZE};
+
+/// Enough frames for most programs to get past their setup
+const FRAMES: usize = 120;
+
+fuzz_target!(|data: &[u8]| {
+    let [config, keys_high, keys_low, rom @ ..] = data else {
+        return;
+    };
+    let rom = &rom[..rom.len().min(MAX_ROM_SIZE)];
+    let keys = u16::from_be_bytes([*keys_high, *keys_low]);
+
+    let mut chip8 = Chip8::with_platform(Platform::ALL[(config & 0b11) as usize]);
+    let mut quirks = chip8.quirks();
+    quirks.wrap_horizontally = config & 0b100 != 0;
+    quirks.wrap_vertically = config & 0b1000 != 0;
+    quirks.zero_height_sprite = match config >> 4 & 0b11 {
+        0 => ZeroHeightSprite::Nothing,
+        1 => ZeroHeightSprite::Tall,
+        _ => ZeroHeightSprite::Large,
+    };
+    quirks.clipped_rows_collide = config & 0b100_0000 != 0;
+    chip8.set_quirks(quirks);
+    chip8.enable_step_history(8);
+    chip8.load_rom(rom);
+
+    for frame in 0..FRAMES {
+        // Hold a different subset of the keys each frame
+        for key in 0..16 {
+            match keys.rotate_left(frame as u32) & (1 << key) != 0 {
+                true => chip8.press_key(key).unwrap(),
+                false => chip8.release_key(key).unwrap(),
+            }
+        }
+        if chip8.run_frame().is_err() {
+            break;
+        }
+        let state = chip8.cpu_state();
+        assert!((state.pc as usize) < RAM_SIZE);
+        assert!(state.stack.len() <= STACK_SIZE);
+        if frame % 16 == 15 {
+            chip8.step_back();
+        }
+    }
+});